@@ -0,0 +1,17 @@
+use std::fmt;
+
+/// An entry of the `BootstrapMethods` class attribute, used by `invokedynamic`
+/// and by dynamically-computed constants
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BootstrapMethod {
+    /// Index in the constant pool of a `MethodHandle` entry
+    pub method_ref: u16,
+    /// Indexes in the constant pool of the static arguments
+    pub arguments: Vec<u16>,
+}
+
+impl fmt::Display for BootstrapMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} {:?}", self.method_ref, self.arguments)
+    }
+}
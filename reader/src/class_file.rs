@@ -1,6 +1,6 @@
 use std::fmt;
 
-use crate::{class_access_flags::ClassAccessFlags, class_file_field::ClassFileField, class_file_method::ClassFileMethod, class_file_version::ClassFileVersion, constant_pool::ConstantPool};
+use crate::{bootstrap_method::BootstrapMethod, class_access_flags::ClassAccessFlags, class_file_field::ClassFileField, class_file_method::ClassFileMethod, class_file_version::ClassFileVersion, constant_pool::ConstantPool};
 
 
 
@@ -16,6 +16,7 @@ pub struct ClassFile {
     pub methods: Vec<ClassFileMethod>,
     pub deprecated: bool,
     pub source_file: Option<String>,
+    pub bootstrap_methods: Vec<BootstrapMethod>,
 }
 
 impl fmt::Display for ClassFile {
@@ -40,6 +41,12 @@ impl fmt::Display for ClassFile {
         for method in self.methods.iter() {
             writeln!(f, "  - {method}")?;
         }
+        if !self.bootstrap_methods.is_empty() {
+            writeln!(f, "bootstrap methods:")?;
+            for (index, bootstrap_method) in self.bootstrap_methods.iter().enumerate() {
+                writeln!(f, "  {index}: {bootstrap_method}")?;
+            }
+        }
         Ok(())
     }
 }
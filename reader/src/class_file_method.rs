@@ -49,6 +49,10 @@ impl ClassFileMethod {
         self.flags.contains(MethodFlags::NATIVE)
     }
 
+    pub fn is_abstract(&self) -> bool {
+        self.flags.contains(MethodFlags::ABSTRACT)
+    }
+
     pub fn is_void(&self) -> bool {
         self.parsed_type_descriptor.return_type.is_none()
     }
@@ -1,6 +1,7 @@
 
 use crate::{
     attribute::Attribute,
+    bootstrap_method::BootstrapMethod,
     buffer::Buffer,
     class_access_flags::ClassAccessFlags,
     class_file::ClassFile,
@@ -8,7 +9,7 @@ use crate::{
     class_file_method::{ClassFileMethod, ClassFileMethodCode},
     class_file_version::ClassFileVersion,
     class_reader_error::{ClassReaderError, Result},
-    constant_pool::{ConstantPool, ConstantPoolEntry},
+    constant_pool::{ConstantPool, ConstantPoolEntry, MethodHandleKind},
     exception_table::{ExceptionTable, ExceptionTableEntry},
     field_flags::FieldFlags,
     field_type::FieldType,
@@ -28,7 +29,7 @@ struct ClassFileReader<'a> {
 }
 
 impl<'a> ClassFileReader<'a> {
-    fn new(data: &[u8]) -> ClassFileReader {
+    fn new(data: &[u8]) -> ClassFileReader<'_> {
         ClassFileReader {
             buffer: Buffer::new(data),
             class_file: Default::default(),
@@ -91,7 +92,12 @@ impl<'a> ClassFileReader<'a> {
                 10 => self.read_method_reference_constant()?,
                 11 => self.read_interface_method_reference_constant()?,
                 12 => self.read_name_and_type_constant()?,
-                // For newer versions of java, there are more constant types
+                15 => self.read_method_handle_constant()?,
+                16 => self.read_method_type_constant()?,
+                17 => self.read_dynamic_constant()?,
+                18 => self.read_invoke_dynamic_constant()?,
+                19 => self.read_module_constant()?,
+                20 => self.read_package_constant()?,
                 _ => {
                     warn!("invalid entry in constant pool at index {} tag {}", i, tag);
                     return Err(ClassReaderError::invalid_class_data(format!(
@@ -189,6 +195,50 @@ impl<'a> ClassFileReader<'a> {
         ))
     }
 
+    fn read_method_handle_constant(&mut self) -> Result<ConstantPoolEntry> {
+        let reference_kind = self.buffer.read_u8()?;
+        let reference_index = self.buffer.read_u16()?;
+        match MethodHandleKind::from(reference_kind) {
+            Some(kind) => Ok(ConstantPoolEntry::MethodHandle(kind, reference_index)),
+            None => Err(ClassReaderError::invalid_class_data(format!(
+                "invalid method handle reference kind: {reference_kind}"
+            ))),
+        }
+    }
+
+    fn read_method_type_constant(&mut self) -> Result<ConstantPoolEntry> {
+        let descriptor_index = self.buffer.read_u16()?;
+        Ok(ConstantPoolEntry::MethodType(descriptor_index))
+    }
+
+    fn read_dynamic_constant(&mut self) -> Result<ConstantPoolEntry> {
+        let bootstrap_method_index = self.buffer.read_u16()?;
+        let name_and_type = self.buffer.read_u16()?;
+        Ok(ConstantPoolEntry::Dynamic(
+            bootstrap_method_index,
+            name_and_type,
+        ))
+    }
+
+    fn read_invoke_dynamic_constant(&mut self) -> Result<ConstantPoolEntry> {
+        let bootstrap_method_index = self.buffer.read_u16()?;
+        let name_and_type = self.buffer.read_u16()?;
+        Ok(ConstantPoolEntry::InvokeDynamic(
+            bootstrap_method_index,
+            name_and_type,
+        ))
+    }
+
+    fn read_module_constant(&mut self) -> Result<ConstantPoolEntry> {
+        let name_index = self.buffer.read_u16()?;
+        Ok(ConstantPoolEntry::Module(name_index))
+    }
+
+    fn read_package_constant(&mut self) -> Result<ConstantPoolEntry> {
+        let name_index = self.buffer.read_u16()?;
+        Ok(ConstantPoolEntry::Package(name_index))
+    }
+
     fn read_access_flags(&mut self) -> Result<()> {
         let num = self.buffer.read_u16()?;
         match ClassAccessFlags::from_bits(num) {
@@ -464,9 +514,41 @@ impl<'a> ClassFileReader<'a> {
         let raw_attributes = self.read_raw_attributes()?;
         self.class_file.deprecated = self.search_deprecated_attribute(&raw_attributes);
         self.class_file.source_file = self.search_source_file_attribute(&raw_attributes)?;
+        self.class_file.bootstrap_methods =
+            self.extract_bootstrap_methods(&raw_attributes)?;
         Ok(())
     }
 
+    fn extract_bootstrap_methods(
+        &self,
+        raw_attributes: &[Attribute],
+    ) -> Result<Vec<BootstrapMethod>> {
+        raw_attributes
+            .iter()
+            .find(|attr| attr.name == "BootstrapMethods")
+            .map(|attr| {
+                let mut buf = Buffer::new(&attr.bytes);
+                let num_bootstrap_methods = buf.read_u16()?.into_usize_safe();
+                let mut bootstrap_methods = Vec::with_capacity(num_bootstrap_methods);
+                for _ in 0..num_bootstrap_methods {
+                    let method_ref = buf.read_u16()?;
+                    self.class_file.constants.get_method_handle(method_ref)?;
+
+                    let num_arguments = buf.read_u16()?.into_usize_safe();
+                    let mut arguments = Vec::with_capacity(num_arguments);
+                    for _ in 0..num_arguments {
+                        arguments.push(buf.read_u16()?);
+                    }
+                    bootstrap_methods.push(BootstrapMethod {
+                        method_ref,
+                        arguments,
+                    });
+                }
+                Ok(bootstrap_methods)
+            })
+            .unwrap_or(Ok(Vec::new()))
+    }
+
     fn search_source_file_attribute(&self, raw_attributes: &[Attribute]) -> Result<Option<String>> {
         raw_attributes
             .iter()
@@ -15,6 +15,43 @@ pub enum ConstantPoolEntry {
     MethodReference(u16, u16),
     InterfaceMethodReference(u16, u16),
     NameAndTypeDescriptor(u16, u16),
+    MethodHandle(MethodHandleKind, u16),
+    MethodType(u16),
+    Dynamic(u16, u16),
+    InvokeDynamic(u16, u16),
+    Module(u16),
+    Package(u16),
+}
+
+/// The `reference_kind` of a `CONSTANT_MethodHandle` entry, see JVMS 5.4.3.5
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+pub enum MethodHandleKind {
+    GetField,
+    GetStatic,
+    PutField,
+    PutStatic,
+    InvokeVirtual,
+    InvokeStatic,
+    InvokeSpecial,
+    NewInvokeSpecial,
+    InvokeInterface,
+}
+
+impl MethodHandleKind {
+    pub fn from(reference_kind: u8) -> Option<MethodHandleKind> {
+        match reference_kind {
+            1 => Some(MethodHandleKind::GetField),
+            2 => Some(MethodHandleKind::GetStatic),
+            3 => Some(MethodHandleKind::PutField),
+            4 => Some(MethodHandleKind::PutStatic),
+            5 => Some(MethodHandleKind::InvokeVirtual),
+            6 => Some(MethodHandleKind::InvokeStatic),
+            7 => Some(MethodHandleKind::InvokeSpecial),
+            8 => Some(MethodHandleKind::NewInvokeSpecial),
+            9 => Some(MethodHandleKind::InvokeInterface),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -59,6 +96,15 @@ impl ConstantPool {
         }
     }
 
+    /// Number of slots, including the second slot of long and double entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
     pub fn get(
         &self,
         input_index: u16,
@@ -127,6 +173,34 @@ impl ConstantPool {
                     self.fmt_entry(j)?
                 )
             }
+            ConstantPoolEntry::MethodHandle(kind, n) => {
+                format!("MethodHandle: {}, {} => ({})", kind, n, self.fmt_entry(*n)?)
+            }
+            ConstantPoolEntry::MethodType(n) => {
+                format!("MethodType: {} => ({})", n, self.fmt_entry(*n)?)
+            }
+            ConstantPoolEntry::Dynamic(i, j) => {
+                format!(
+                    "Dynamic: bootstrap method {}, {} => ({})",
+                    i,
+                    j,
+                    self.fmt_entry(*j)?
+                )
+            }
+            ConstantPoolEntry::InvokeDynamic(i, j) => {
+                format!(
+                    "InvokeDynamic: bootstrap method {}, {} => ({})",
+                    i,
+                    j,
+                    self.fmt_entry(*j)?
+                )
+            }
+            ConstantPoolEntry::Module(n) => {
+                format!("Module: {} => ({})", n, self.fmt_entry(*n)?)
+            }
+            ConstantPoolEntry::Package(n) => {
+                format!("Package: {} => ({})", n, self.fmt_entry(*n)?)
+            }
         };
         Ok(text)
     }
@@ -153,9 +227,77 @@ impl ConstantPool {
             ConstantPoolEntry::NameAndTypeDescriptor(i, j) => {
                 format!("{}: {}", self.text_of(*i)?, self.text_of(*j)?)
             }
+            ConstantPoolEntry::MethodHandle(kind, n) => {
+                format!("{} {}", kind, self.text_of(*n)?)
+            }
+            ConstantPoolEntry::MethodType(n) => self.text_of(*n)?,
+            ConstantPoolEntry::Dynamic(i, j) => format!("#{}:{}", i, self.text_of(*j)?),
+            ConstantPoolEntry::InvokeDynamic(i, j) => format!("#{}:{}", i, self.text_of(*j)?),
+            ConstantPoolEntry::Module(n) => self.text_of(*n)?,
+            ConstantPoolEntry::Package(n) => self.text_of(*n)?,
         };
         Ok(text)
     }
+
+    /// Returns the kind and the referenced field or method of a `CONSTANT_MethodHandle` entry
+    pub fn get_method_handle(
+        &self,
+        idx: u16,
+    ) -> Result<(MethodHandleKind, u16), InvalidConstantPoolIndexError> {
+        match self.get(idx)? {
+            &ConstantPoolEntry::MethodHandle(kind, reference_index) => Ok((kind, reference_index)),
+            _ => Err(InvalidConstantPoolIndexError::new(idx)),
+        }
+    }
+
+    /// Returns the index of the descriptor of a `CONSTANT_MethodType` entry
+    pub fn get_method_type(&self, idx: u16) -> Result<u16, InvalidConstantPoolIndexError> {
+        match self.get(idx)? {
+            &ConstantPoolEntry::MethodType(descriptor_index) => Ok(descriptor_index),
+            _ => Err(InvalidConstantPoolIndexError::new(idx)),
+        }
+    }
+
+    /// Returns the bootstrap method attribute index and the name and type index
+    /// of a `CONSTANT_Dynamic` entry
+    pub fn get_dynamic(&self, idx: u16) -> Result<(u16, u16), InvalidConstantPoolIndexError> {
+        match self.get(idx)? {
+            &ConstantPoolEntry::Dynamic(bootstrap_method_index, name_and_type_index) => {
+                Ok((bootstrap_method_index, name_and_type_index))
+            }
+            _ => Err(InvalidConstantPoolIndexError::new(idx)),
+        }
+    }
+
+    /// Returns the bootstrap method attribute index and the name and type index
+    /// of a `CONSTANT_InvokeDynamic` entry
+    pub fn get_invoke_dynamic(
+        &self,
+        idx: u16,
+    ) -> Result<(u16, u16), InvalidConstantPoolIndexError> {
+        match self.get(idx)? {
+            &ConstantPoolEntry::InvokeDynamic(bootstrap_method_index, name_and_type_index) => {
+                Ok((bootstrap_method_index, name_and_type_index))
+            }
+            _ => Err(InvalidConstantPoolIndexError::new(idx)),
+        }
+    }
+
+    /// Returns the index of the name of a `CONSTANT_Module` entry
+    pub fn get_module(&self, idx: u16) -> Result<u16, InvalidConstantPoolIndexError> {
+        match self.get(idx)? {
+            &ConstantPoolEntry::Module(name_index) => Ok(name_index),
+            _ => Err(InvalidConstantPoolIndexError::new(idx)),
+        }
+    }
+
+    /// Returns the index of the name of a `CONSTANT_Package` entry
+    pub fn get_package(&self, idx: u16) -> Result<u16, InvalidConstantPoolIndexError> {
+        match self.get(idx)? {
+            &ConstantPoolEntry::Package(name_index) => Ok(name_index),
+            _ => Err(InvalidConstantPoolIndexError::new(idx)),
+        }
+    }
 }
 
 
@@ -164,7 +306,7 @@ impl fmt::Display for ConstantPool {
         writeln!(f, "Constant pool: (size: {})", self.entries.len())?;
         for (raw_idx, _) in self.entries.iter().enumerate() {
             let index = (raw_idx+1) as u16;
-            let entry_text = self.fmt_entry(index).map_err(|_|fmt::Error::default())?;
+            let entry_text = self.fmt_entry(index).map_err(|_|fmt::Error)?;
             writeln!(f, "    {}, {}", index, entry_text)?;
         }
         Ok(())
@@ -173,7 +315,9 @@ impl fmt::Display for ConstantPool {
 
 #[cfg(test)]
 mod tests {
-    use crate::constant_pool::{ConstantPool, ConstantPoolEntry, InvalidConstantPoolIndexError};
+    use crate::constant_pool::{
+        ConstantPool, ConstantPoolEntry, InvalidConstantPoolIndexError, MethodHandleKind,
+    };
 
     #[test]
     fn constant_pool_works() {
@@ -239,4 +383,61 @@ mod tests {
         assert_eq!("hey.joe", cp.text_of(13).unwrap());
         assert_eq!("hey: joe", cp.text_of(14).unwrap());
     }
+
+    #[test]
+    fn constant_pool_supports_dynamic_entries() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::Utf8("rjvm/Lambda".to_string()));
+        cp.add(ConstantPoolEntry::ClassReference(1));
+        cp.add(ConstantPoolEntry::Utf8("run".to_string()));
+        cp.add(ConstantPoolEntry::Utf8("()V".to_string()));
+        cp.add(ConstantPoolEntry::NameAndTypeDescriptor(3, 4));
+        cp.add(ConstantPoolEntry::MethodReference(2, 5));
+        cp.add(ConstantPoolEntry::MethodHandle(
+            MethodHandleKind::InvokeStatic,
+            6,
+        ));
+        cp.add(ConstantPoolEntry::MethodType(4));
+        cp.add(ConstantPoolEntry::InvokeDynamic(0, 5));
+        cp.add(ConstantPoolEntry::Dynamic(1, 5));
+        cp.add(ConstantPoolEntry::Module(1));
+        cp.add(ConstantPoolEntry::Package(1));
+
+        assert_eq!(
+            Ok((MethodHandleKind::InvokeStatic, 6)),
+            cp.get_method_handle(7)
+        );
+        assert_eq!(Ok(4), cp.get_method_type(8));
+        assert_eq!(Ok((0, 5)), cp.get_invoke_dynamic(9));
+        assert_eq!(Ok((1, 5)), cp.get_dynamic(10));
+        assert_eq!(Ok(1), cp.get_module(11));
+        assert_eq!(Ok(1), cp.get_package(12));
+        assert_eq!(
+            Err(InvalidConstantPoolIndexError::new(8)),
+            cp.get_method_handle(8)
+        );
+        assert_eq!(
+            Err(InvalidConstantPoolIndexError::new(9)),
+            cp.get_dynamic(9)
+        );
+
+        assert_eq!(
+            "InvokeStatic rjvm/Lambda.run: ()V",
+            cp.text_of(7).unwrap()
+        );
+        assert_eq!("()V", cp.text_of(8).unwrap());
+        assert_eq!("#0:run: ()V", cp.text_of(9).unwrap());
+        assert_eq!("#1:run: ()V", cp.text_of(10).unwrap());
+        assert_eq!("rjvm/Lambda", cp.text_of(11).unwrap());
+        assert_eq!("rjvm/Lambda", cp.text_of(12).unwrap());
+
+        assert_eq!(
+            "MethodType: 4 => (String: \"()V\")",
+            cp.fmt_entry(8).unwrap()
+        );
+        assert_eq!(
+            "InvokeDynamic: bootstrap method 0, 5 => (NameAndTypeDescriptor: 3, 4 => (String: \"run\"), (String: \"()V\"))",
+            cp.fmt_entry(9).unwrap()
+        );
+    }
 }
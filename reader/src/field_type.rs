@@ -52,6 +52,15 @@ impl FieldType {
             _ => return Err(InvalidTypeDescriptor(type_descriptor.to_string())),
         })
     }
+
+    /// Returns the descriptor of this type, i.e. the inverse of `parse`
+    pub fn descriptor(&self) -> String {
+        match self {
+            FieldType::Base(base_type) => base_type.descriptor().to_string(),
+            FieldType::Object(class_name) => format!("L{class_name};"),
+            FieldType::Array(component_type) => format!("[{}", component_type.descriptor()),
+        }
+    }
 }
 
 impl fmt::Display for FieldType {
@@ -77,6 +86,21 @@ pub enum BaseType {
     Boolean,
 }
 
+impl BaseType {
+    pub fn descriptor(&self) -> char {
+        match self {
+            BaseType::Byte => 'B',
+            BaseType::Char => 'C',
+            BaseType::Double => 'D',
+            BaseType::Float => 'F',
+            BaseType::Int => 'I',
+            BaseType::Long => 'J',
+            BaseType::Short => 'S',
+            BaseType::Boolean => 'Z',
+        }
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -161,6 +185,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_generate_descriptors() {
+        for descriptor in ["J", "Ljava/lang/String;", "[[D", "[Lrjvm/Test;"] {
+            assert_eq!(
+                descriptor,
+                FieldType::parse(descriptor).unwrap().descriptor()
+            );
+        }
+    }
+
     #[test]
     fn can_format_base_type() {
         assert_eq!("Long", format!("{}", FieldType::parse("J").unwrap()));
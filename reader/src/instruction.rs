@@ -1,7 +1,7 @@
 use crate::class_reader_error::ClassReaderError;
 
 #[allow(non_camel_case_types)]
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Instruction {
     Aaload,
     Aastore,
@@ -88,7 +88,7 @@ pub enum Instruction {
     Getfield(u16),
     Getstatic(u16),
     Goto(u16),
-    Goto_w,
+    Goto_w(u16),
     I2b,
     I2c,
     I2d,
@@ -151,7 +151,7 @@ pub enum Instruction {
     Iushr,
     Ixor,
     Jsr(u16),
-    Jsr_w,
+    Jsr_w(u16),
     L2d,
     L2f,
     L2i,
@@ -173,7 +173,7 @@ pub enum Instruction {
     Lload_3,
     Lmul,
     Lneg,
-    Lookupswitch,
+    Lookupswitch(LookupSwitch),
     Lor,
     Lrem,
     Lreturn,
@@ -203,8 +203,63 @@ pub enum Instruction {
     Sastore,
     Sipush(i16),
     Swap,
-    Tableswitch,
-    Wide,
+    Tableswitch(TableSwitch),
+    Wide(WideInstruction),
+}
+
+/// Jump table of a `tableswitch`. All addresses are absolute, like the ones of the other
+/// jump instructions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TableSwitch {
+    pub default: u16,
+    pub low: i32,
+    pub high: i32,
+    pub jumps: Vec<u16>,
+}
+
+impl TableSwitch {
+    pub fn jump_address(&self, value: i32) -> u16 {
+        if value < self.low || value > self.high {
+            self.default
+        } else {
+            self.jumps[(value as i64 - self.low as i64) as usize]
+        }
+    }
+}
+
+/// Match-offset pairs of a `lookupswitch`, sorted by key as required by the spec.
+/// All addresses are absolute.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LookupSwitch {
+    pub default: u16,
+    pub pairs: Vec<(i32, u16)>,
+}
+
+impl LookupSwitch {
+    pub fn jump_address(&self, value: i32) -> u16 {
+        match self.pairs.binary_search_by_key(&value, |(key, _)| *key) {
+            Ok(index) => self.pairs[index].1,
+            Err(_) => self.default,
+        }
+    }
+}
+
+/// The instructions that can be modified by the `wide` prefix, with their extended operands
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WideInstruction {
+    Iload(u16),
+    Lload(u16),
+    Fload(u16),
+    Dload(u16),
+    Aload(u16),
+    Istore(u16),
+    Lstore(u16),
+    Fstore(u16),
+    Dstore(u16),
+    Astore(u16),
+    Ret(u16),
+    Iinc(u16, i16),
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -309,7 +364,11 @@ impl Instruction {
             0xb4 => Instruction::Getfield(Self::read_u16(raw_code, &mut address)?),
             0xb2 => Instruction::Getstatic(Self::read_u16(raw_code, &mut address)?),
             0xa7 => Instruction::Goto(Self::read_offset(raw_code, &mut address)?),
-            0xc8 => todo!("OpCode::Goto_w"),
+            0xc8 => Instruction::Goto_w(Self::read_wide_offset(
+                raw_code,
+                address - 1,
+                &mut address,
+            )?),
             0x91 => Instruction::I2b,
             0x92 => Instruction::I2c,
             0x87 => Instruction::I2d,
@@ -392,7 +451,11 @@ impl Instruction {
             0x7c => Instruction::Iushr,
             0x82 => Instruction::Ixor,
             0xa8 => Instruction::Jsr(Self::read_offset(raw_code, &mut address)?),
-            0xc9 => todo!("OpCode::Jsr_w"),
+            0xc9 => Instruction::Jsr_w(Self::read_wide_offset(
+                raw_code,
+                address - 1,
+                &mut address,
+            )?),
             0x8a => Instruction::L2d,
             0x89 => Instruction::L2f,
             0x88 => Instruction::L2i,
@@ -414,7 +477,7 @@ impl Instruction {
             0x21 => Instruction::Lload_3,
             0x69 => Instruction::Lmul,
             0x75 => Instruction::Lneg,
-            0xab => todo!("OpCode::Lookupswitch"),
+            0xab => Instruction::Lookupswitch(Self::read_lookupswitch(raw_code, &mut address)?),
             0x81 => Instruction::Lor,
             0x71 => Instruction::Lrem,
             0xad => Instruction::Lreturn,
@@ -465,8 +528,8 @@ impl Instruction {
             0x56 => Instruction::Sastore,
             0x11 => Instruction::Sipush(Self::read_i16(raw_code, &mut address)?),
             0x5f => Instruction::Swap,
-            0xaa => todo!("OpCode::Tableswitch"),
-            0xc4 => todo!("OpCode::Wide"),
+            0xaa => Instruction::Tableswitch(Self::read_tableswitch(raw_code, &mut address)?),
+            0xc4 => Instruction::Wide(Self::read_wide(raw_code, &mut address)?),
             _ => {
                 return Err(ClassReaderError::invalid_class_data(format!(
                     "invalid op code: {op_byte:#04x} at address {address}"
@@ -513,7 +576,7 @@ impl Instruction {
 
     fn read_i8(raw_code: &[u8], address: &mut usize) -> Result<i8, ClassReaderError> {
         let value = Self::read_u8(raw_code, address)?;
-        Ok(unsafe { std::mem::transmute(value) })
+        Ok(value as i8)
     }
 
     fn read_u16(raw_code: &[u8], address: &mut usize) -> Result<u16, ClassReaderError> {
@@ -524,7 +587,13 @@ impl Instruction {
 
     fn read_i16(raw_code: &[u8], address: &mut usize) -> Result<i16, ClassReaderError> {
         let value = Self::read_u16(raw_code, address)?;
-        Ok(unsafe { std::mem::transmute(value) })
+        Ok(value as i16)
+    }
+
+    fn read_i32(raw_code: &[u8], address: &mut usize) -> Result<i32, ClassReaderError> {
+        let high = Self::read_u16(raw_code, address)? as u32;
+        let low = Self::read_u16(raw_code, address)? as u32;
+        Ok(((high << 16) | low) as i32)
     }
 
     fn read_offset(raw_code: &[u8], address: &mut usize) -> Result<u16, ClassReaderError> {
@@ -537,4 +606,236 @@ impl Instruction {
             ))
         })
     }
+
+    fn read_wide_offset(
+        raw_code: &[u8],
+        instruction_address: usize,
+        address: &mut usize,
+    ) -> Result<u16, ClassReaderError> {
+        let offset = Self::read_i32(raw_code, address)?;
+        let jump_address = (instruction_address as i64) + (offset as i64);
+        u16::try_from(jump_address).map_err(|_| {
+            ClassReaderError::invalid_class_data(format!(
+                "invalid jump offset at address {address}"
+            ))
+        })
+    }
+
+    /// The operands of `tableswitch` and `lookupswitch` start at an address that is
+    /// a multiple of four, relative to the start of the method's code
+    fn skip_switch_padding(address: &mut usize) {
+        while !address.is_multiple_of(4) {
+            *address += 1;
+        }
+    }
+
+    fn read_tableswitch(
+        raw_code: &[u8],
+        address: &mut usize,
+    ) -> Result<TableSwitch, ClassReaderError> {
+        let instruction_address = *address - 1;
+        Self::skip_switch_padding(address);
+        let default = Self::read_wide_offset(raw_code, instruction_address, address)?;
+        let low = Self::read_i32(raw_code, address)?;
+        let high = Self::read_i32(raw_code, address)?;
+        if low > high {
+            return Err(ClassReaderError::invalid_class_data(format!(
+                "invalid tableswitch bounds {low}..{high} at address {instruction_address}"
+            )));
+        }
+
+        let jumps = (low..=high)
+            .map(|_| Self::read_wide_offset(raw_code, instruction_address, address))
+            .collect::<Result<Vec<u16>, ClassReaderError>>()?;
+        Ok(TableSwitch {
+            default,
+            low,
+            high,
+            jumps,
+        })
+    }
+
+    fn read_lookupswitch(
+        raw_code: &[u8],
+        address: &mut usize,
+    ) -> Result<LookupSwitch, ClassReaderError> {
+        let instruction_address = *address - 1;
+        Self::skip_switch_padding(address);
+        let default = Self::read_wide_offset(raw_code, instruction_address, address)?;
+        let npairs = Self::read_i32(raw_code, address)?;
+        if npairs < 0 {
+            return Err(ClassReaderError::invalid_class_data(format!(
+                "invalid lookupswitch pairs count {npairs} at address {instruction_address}"
+            )));
+        }
+
+        let mut pairs: Vec<(i32, u16)> = Vec::with_capacity(npairs as usize);
+        for _ in 0..npairs {
+            let key = Self::read_i32(raw_code, address)?;
+            let jump_address = Self::read_wide_offset(raw_code, instruction_address, address)?;
+            if let Some((previous_key, _)) = pairs.last() {
+                if *previous_key >= key {
+                    return Err(ClassReaderError::invalid_class_data(format!(
+                        "lookupswitch keys are not sorted at address {instruction_address}"
+                    )));
+                }
+            }
+            pairs.push((key, jump_address));
+        }
+        Ok(LookupSwitch { default, pairs })
+    }
+
+    fn read_wide(
+        raw_code: &[u8],
+        address: &mut usize,
+    ) -> Result<WideInstruction, ClassReaderError> {
+        let op_byte = Self::read_u8(raw_code, address)?;
+        let wide_instruction = match op_byte {
+            0x15 => WideInstruction::Iload(Self::read_u16(raw_code, address)?),
+            0x16 => WideInstruction::Lload(Self::read_u16(raw_code, address)?),
+            0x17 => WideInstruction::Fload(Self::read_u16(raw_code, address)?),
+            0x18 => WideInstruction::Dload(Self::read_u16(raw_code, address)?),
+            0x19 => WideInstruction::Aload(Self::read_u16(raw_code, address)?),
+            0x36 => WideInstruction::Istore(Self::read_u16(raw_code, address)?),
+            0x37 => WideInstruction::Lstore(Self::read_u16(raw_code, address)?),
+            0x38 => WideInstruction::Fstore(Self::read_u16(raw_code, address)?),
+            0x39 => WideInstruction::Dstore(Self::read_u16(raw_code, address)?),
+            0x3a => WideInstruction::Astore(Self::read_u16(raw_code, address)?),
+            0xa9 => WideInstruction::Ret(Self::read_u16(raw_code, address)?),
+            0x84 => WideInstruction::Iinc(
+                Self::read_u16(raw_code, address)?,
+                Self::read_i16(raw_code, address)?,
+            ),
+            _ => {
+                return Err(ClassReaderError::invalid_class_data(format!(
+                    "invalid op code after wide: {op_byte:#04x} at address {address}"
+                )))
+            }
+        };
+        Ok(wide_instruction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instruction::{Instruction, LookupSwitch, TableSwitch, WideInstruction};
+
+    #[test]
+    fn can_parse_tableswitch_with_padding() {
+        let code = vec![
+            0x00, // nop
+            0xaa, // tableswitch
+            0x00, 0x00, // padding
+            0x00, 0x00, 0x00, 0x1f, // default: +31
+            0x00, 0x00, 0x00, 0x01, // low: 1
+            0x00, 0x00, 0x00, 0x03, // high: 3
+            0x00, 0x00, 0x00, 0x1b, // 1: +27
+            0x00, 0x00, 0x00, 0x1c, // 2: +28
+            0x00, 0x00, 0x00, 0x1d, // 3: +29
+        ];
+        let (instruction, next_address) = Instruction::parse(&code, 1).unwrap();
+        assert_eq!(
+            Instruction::Tableswitch(TableSwitch {
+                default: 32,
+                low: 1,
+                high: 3,
+                jumps: vec![28, 29, 30],
+            }),
+            instruction
+        );
+        assert_eq!(code.len(), next_address);
+    }
+
+    #[test]
+    fn can_lookup_tableswitch_jump_address() {
+        let table_switch = TableSwitch {
+            default: 32,
+            low: -1,
+            high: 1,
+            jumps: vec![28, 29, 30],
+        };
+        assert_eq!(28, table_switch.jump_address(-1));
+        assert_eq!(30, table_switch.jump_address(1));
+        assert_eq!(32, table_switch.jump_address(2));
+        assert_eq!(32, table_switch.jump_address(i32::MIN));
+    }
+
+    #[test]
+    fn can_parse_lookupswitch_without_padding() {
+        let code = vec![
+            0x00, 0x00, // nop nop
+            0xab, // lookupswitch
+            0x00, // padding
+            0x00, 0x00, 0x00, 0x18, // default: +24
+            0x00, 0x00, 0x00, 0x02, // npairs: 2
+            0xff, 0xff, 0xff, 0xff, // key: -1
+            0x00, 0x00, 0x00, 0x16, // offset: +22
+            0x00, 0x00, 0x03, 0xe8, // key: 1000
+            0xff, 0xff, 0xff, 0xfe, // offset: -2
+        ];
+        let (instruction, next_address) = Instruction::parse(&code, 2).unwrap();
+        let expected = LookupSwitch {
+            default: 26,
+            pairs: vec![(-1, 24), (1000, 0)],
+        };
+        assert_eq!(Instruction::Lookupswitch(expected.clone()), instruction);
+        assert_eq!(code.len(), next_address);
+
+        assert_eq!(24, expected.jump_address(-1));
+        assert_eq!(0, expected.jump_address(1000));
+        assert_eq!(26, expected.jump_address(0));
+    }
+
+    #[test]
+    fn cannot_parse_lookupswitch_with_unsorted_keys() {
+        let code = vec![
+            0xab, // lookupswitch
+            0x00, 0x00, 0x00, // padding
+            0x00, 0x00, 0x00, 0x18, // default
+            0x00, 0x00, 0x00, 0x02, // npairs: 2
+            0x00, 0x00, 0x00, 0x02, // key: 2
+            0x00, 0x00, 0x00, 0x10, // offset
+            0x00, 0x00, 0x00, 0x01, // key: 1
+            0x00, 0x00, 0x00, 0x10, // offset
+        ];
+        assert!(Instruction::parse(&code, 0).is_err());
+    }
+
+    #[test]
+    fn can_parse_wide_instructions() {
+        let code = vec![
+            0xc4, 0x15, 0x01, 0x02, // wide iload 258
+            0xc4, 0x84, 0x01, 0x00, 0xff, 0xfe, // wide iinc 256 -2
+        ];
+        assert_eq!(
+            Ok((Instruction::Wide(WideInstruction::Iload(258)), 4)),
+            Instruction::parse(&code, 0)
+        );
+        assert_eq!(
+            Ok((Instruction::Wide(WideInstruction::Iinc(256, -2)), 10)),
+            Instruction::parse(&code, 4)
+        );
+    }
+
+    #[test]
+    fn can_parse_wide_jumps() {
+        let code = vec![
+            0x00, // nop
+            0xc8, 0x00, 0x00, 0x00, 0x0a, // goto_w +10
+            0xc9, 0xff, 0xff, 0xff, 0xfa, // jsr_w -6
+            0xa9, 0x03, // ret 3
+        ];
+        assert_eq!(
+            Ok((Instruction::Goto_w(11), 6)),
+            Instruction::parse(&code, 1)
+        );
+        assert_eq!(Ok((Instruction::Jsr_w(0), 11)), Instruction::parse(&code, 6));
+        assert_eq!(Ok((Instruction::Ret(3), 13)), Instruction::parse(&code, 11));
+    }
+
+    #[test]
+    fn cannot_parse_wide_of_invalid_instruction() {
+        let code = vec![0xc4, 0x60];
+        assert!(Instruction::parse(&code, 0).is_err());
+    }
 }
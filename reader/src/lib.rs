@@ -2,6 +2,7 @@
 extern crate bitflags;
 
 mod attribute;
+pub mod bootstrap_method;
 mod buffer;
 pub mod class_access_flags;
 pub mod class_file;
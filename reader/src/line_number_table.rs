@@ -41,7 +41,7 @@ pub struct LineNumberTableEntry {
 
 impl PartialOrd for LineNumberTableEntry {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.program_counter.partial_cmp(&other.program_counter)
+        Some(self.cmp(other))
     }
 }
 
@@ -0,0 +1,51 @@
+extern crate rjvm_reader;
+
+use rjvm_reader::{
+    bootstrap_method::BootstrapMethod, class_file_version::ClassFileVersion,
+    constant_pool::MethodHandleKind,
+};
+
+use crate::utils;
+use utils::read_class_from_bytes;
+
+#[test_log::test]
+fn can_read_modern_constant_pool_entries() {
+    let class =
+        read_class_from_bytes(include_bytes!("../resources/rjvm/modern/Lambdas.class"));
+    assert_eq!(ClassFileVersion::Jdk17, class.version);
+
+    assert_eq!(Ok((0, 8)), class.constants.get_invoke_dynamic(7));
+    assert_eq!(
+        "#0:get: (Ljava/lang/String;)Ljava/util/function/Supplier;",
+        class.constants.text_of(7).unwrap()
+    );
+    assert_eq!(
+        "#1:makeConcatWithConstants: (Ljava/lang/String;)Ljava/lang/String;",
+        class.constants.text_of(18).unwrap()
+    );
+
+    let (kind, _) = class.constants.get_method_handle(31).unwrap();
+    assert_eq!(MethodHandleKind::InvokeStatic, kind);
+    assert_eq!(
+        "InvokeStatic java/lang/invoke/LambdaMetafactory.metafactory: (Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodHandle;Ljava/lang/invoke/MethodType;)Ljava/lang/invoke/CallSite;",
+        class.constants.text_of(31).unwrap()
+    );
+
+    assert_eq!(Ok(15), class.constants.get_method_type(38));
+    assert_eq!("()Ljava/lang/Object;", class.constants.text_of(38).unwrap());
+
+    assert_eq!(
+        vec![
+            BootstrapMethod {
+                method_ref: 31,
+                arguments: vec![38, 39, 42],
+            },
+            BootstrapMethod {
+                method_ref: 44,
+                arguments: vec![50],
+            },
+        ],
+        class.bootstrap_methods
+    );
+    assert_eq!("Hello, \u{1}!", class.constants.text_of(50).unwrap());
+}
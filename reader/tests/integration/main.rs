@@ -2,5 +2,6 @@ mod assertions;
 mod constants_class_test;
 mod deprecated_class_test;
 mod exceptions;
+mod lambdas_class_test;
 mod pojo_class_test;
 mod utils;
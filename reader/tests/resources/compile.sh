@@ -1,2 +1,3 @@
 #!/usr/bin/env sh
 javac -source 6 -target 6 rjvm/*.java
+javac --release 17 rjvm/modern/*.java
@@ -0,0 +1,10 @@
+package rjvm.modern;
+
+import java.util.function.Supplier;
+
+public class Lambdas {
+    public static String greet(String name) {
+        Supplier<String> supplier = () -> "Hello, " + name + "!";
+        return supplier.get();
+    }
+}
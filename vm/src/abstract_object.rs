@@ -10,6 +10,7 @@ use crate::{
     alloc_entry::AllocEntry,
     array::Array,
     array_entry_type::ArrayEntryType,
+    call_stack::ThreadId,
     class::{Class, ClassId, ClassRef},
     object::Object,
     value::Value,
@@ -83,6 +84,19 @@ pub(crate) struct AllocHeader {
     pub(crate) size: usize,
 }
 
+/// The monitor of an object, stored right after its `AllocHeader` so that it is moved
+/// with the object by the garbage collector
+#[bitfield(u64)]
+struct MonitorHeader {
+    /// The thread owning the monitor, or zero if it is not owned
+    #[bits(32)]
+    owner: u32,
+
+    /// The number of times the owner has entered the monitor without exiting it
+    #[bits(32)]
+    entry_count: u32,
+}
+
 #[repr(transparent)]
 struct ObjectHeader {
     class_id: ClassId,
@@ -100,7 +114,8 @@ const fn align_to_8_bytes(required_size: usize) -> usize {
     }
 }
 
-pub(crate) const ALLOC_HEADER_SIZE: usize = align_to_8_bytes(size_of::<AllocHeader>());
+pub(crate) const ALLOC_HEADER_SIZE: usize =
+    align_to_8_bytes(size_of::<AllocHeader>() + size_of::<MonitorHeader>());
 pub(crate) const OBJECT_HEADER_SIZE: usize = align_to_8_bytes(size_of::<ObjectHeader>());
 pub(crate) const ARRAY_HEADER_SIZE: usize = align_to_8_bytes(size_of::<ArrayHeader>());
 
@@ -171,7 +186,8 @@ impl<'a> AbstractObject<'a> {
                 .with_identity_hash_code(identity_hash_code(alloc_entry.ptr))
                 .with_size(alloc_entry.alloc_size),
         );
-        next_ptr.add(1) as *mut u8
+        std::ptr::write(next_ptr.add(1) as *mut MonitorHeader, MonitorHeader::new());
+        alloc_entry.ptr.add(ALLOC_HEADER_SIZE)
     }
 
     pub(crate) fn from_raw_ptr(ptr: *mut u8) -> Self {
@@ -200,6 +216,80 @@ impl<'a> AbstractObject<'a> {
     pub fn alloc_size(&self) -> usize {
         self.alloc_header().size()
     }
+
+    fn monitor_header(&self) -> *mut MonitorHeader {
+        unsafe { (self.data as *mut AllocHeader).add(1) as *mut MonitorHeader }
+    }
+
+    /// Enters the monitor of this object, which the given thread may already own.
+    /// Returns false if the monitor is owned by another thread.
+    pub(crate) fn try_enter_monitor(&self, thread_id: ThreadId) -> bool {
+        unsafe {
+            let monitor = &mut *self.monitor_header();
+            if monitor.owner() == 0 {
+                monitor.set_owner(thread_id.as_u32());
+                monitor.set_entry_count(1);
+                true
+            } else if monitor.owner() == thread_id.as_u32() {
+                monitor.set_entry_count(monitor.entry_count() + 1);
+                true
+            } else {
+                false
+            }
+        }
+    }
+
+    /// Enters the monitor of this object the given number of times, if it is not owned.
+    /// Used by a thread to take back a monitor released by `Object.wait`.
+    pub(crate) fn try_reenter_monitor(&self, thread_id: ThreadId, entry_count: u32) -> bool {
+        unsafe {
+            let monitor = &mut *self.monitor_header();
+            if monitor.owner() != 0 {
+                return false;
+            }
+            monitor.set_owner(thread_id.as_u32());
+            monitor.set_entry_count(entry_count);
+            true
+        }
+    }
+
+    /// Releases the monitor of this object regardless of how many times the owner entered
+    /// it, returning that number
+    pub(crate) fn release_monitor(&self, thread_id: ThreadId) -> Result<u32, VmError> {
+        unsafe {
+            let monitor = &mut *self.monitor_header();
+            if monitor.owner() != thread_id.as_u32() {
+                return Err(VmError::IllegalMonitorStateException);
+            }
+            let entry_count = monitor.entry_count();
+            monitor.set_owner(0);
+            monitor.set_entry_count(0);
+            Ok(entry_count)
+        }
+    }
+
+    /// Returns the thread owning the monitor of this object, if any
+    pub(crate) fn monitor_owner(&self) -> Option<ThreadId> {
+        let owner = unsafe { (*self.monitor_header()).owner() };
+        (owner != 0).then(|| ThreadId::new(owner))
+    }
+
+    /// Exits the monitor of this object, releasing it when the owner has exited it as many
+    /// times as it entered it
+    pub(crate) fn exit_monitor(&self, thread_id: ThreadId) -> Result<(), VmError> {
+        unsafe {
+            let monitor = &mut *self.monitor_header();
+            if monitor.owner() != thread_id.as_u32() {
+                return Err(VmError::IllegalMonitorStateException);
+            }
+            let entry_count = monitor.entry_count() - 1;
+            monitor.set_entry_count(entry_count);
+            if entry_count == 0 {
+                monitor.set_owner(0);
+            }
+            Ok(())
+        }
+    }
 }
 
 fn hash(data: u64) -> u64 {
@@ -211,7 +301,7 @@ fn identity_hash_code(ptr: *mut u8) -> i32 {
 
     let hash = (hash & ((1 << 30) - 1)) as u32;
 
-    unsafe { std::mem::transmute(hash) }
+    hash as i32
 }
 
 unsafe fn write_value(ptr: *mut u8, value: Value) {
@@ -220,7 +310,10 @@ unsafe fn write_value(ptr: *mut u8, value: Value) {
         Value::Long(long) => std::ptr::write(ptr as *mut i64, long),
         Value::Float(float) => std::ptr::write(ptr as *mut f32, float),
         Value::Double(double) => std::ptr::write(ptr as *mut f64, double),
-        Value::Uninitialized | Value::Null => std::ptr::write(ptr as *mut u64, 0),
+        // Return addresses cannot be stored in fields or arrays
+        Value::Uninitialized | Value::Null | Value::ReturnAddress(_) => {
+            std::ptr::write(ptr as *mut u64, 0)
+        }
         Value::Object(obj) => std::ptr::write(ptr as *mut AbstractObject, obj),
     }
 }
@@ -252,7 +345,7 @@ unsafe fn read_value2<'a>(ptr: *const u8, field_type: &ArrayEntryType) -> Value<
         ArrayEntryType::Base(BaseType::Long) => Value::Long(std::ptr::read(ptr as *const i64)),
         ArrayEntryType::Base(BaseType::Float) => Value::Float(std::ptr::read(ptr as *const f32)),
         ArrayEntryType::Base(BaseType::Double) => Value::Double(std::ptr::read(ptr as *const f64)),
-        ArrayEntryType::Object(_) | ArrayEntryType::Array => {
+        ArrayEntryType::Object(_) | ArrayEntryType::Array(..) => {
             match std::ptr::read(ptr as *const i64) {
                 0 => Value::Null,
                 _ => Value::Object(std::ptr::read(ptr as *const AbstractObject)),
@@ -329,7 +422,7 @@ impl<'a> Array<'a> for AbstractObject<'a> {
 
     fn set_element(&self, index: usize, value: Value<'a>) -> Result<(), VmError> {
         if index >= self.len().into_usize_safe() {
-            Err(VmError::ArrayIndexOutOfBoundsException)
+            Err(VmError::ArrayIndexOutOfBoundsException(index as i64, self.len()))
         } else {
             unsafe {
                 let ptr = self.ptr_to_array_element(index);
@@ -341,7 +434,7 @@ impl<'a> Array<'a> for AbstractObject<'a> {
 
     fn get_element(&self, index: usize) -> Result<Value<'a>, VmError> {
         if index >= self.len().into_usize_safe() {
-            Err(VmError::ArrayIndexOutOfBoundsException)
+            Err(VmError::ArrayIndexOutOfBoundsException(index as i64, self.len()))
         } else {
             unsafe {
                 let ptr = self.ptr_to_array_element(index);
@@ -374,6 +467,12 @@ impl<'a> Debug for AbstractObject<'a> {
 }
 
 pub fn string_from_char_array(array: AbstractObject) -> Result<String, VmError> {
+    let string_chars = utf16_from_char_array(array)?;
+    String::from_utf16(&string_chars).map_err(|_| VmError::ValidationException)
+}
+
+/// Returns the UTF-16 code units of a `char[]`, which need not be valid UTF-16
+pub fn utf16_from_char_array(array: AbstractObject) -> Result<Vec<u16>, VmError> {
     if array.kind() != ObjectKind::Array {
         return Err(VmError::ValidationException);
     }
@@ -392,7 +491,5 @@ pub fn string_from_char_array(array: AbstractObject) -> Result<String, VmError>
             string_chars.push(next_codepoint);
         }
     }
-
-    let string = String::from_utf16(&string_chars).expect("should have valid utf8 bytes");
-    Ok(string)
+    Ok(string_chars)
 }
@@ -7,10 +7,52 @@ use crate::{class::ClassId, class_resolver_by_id::ClassByIdResolver};
 pub enum ArrayEntryType {
     Base(BaseType),
     Object(ClassId),
-    Array,
+    /// The entries are arrays themselves, with the given number of dimensions and type of the
+    /// innermost entries. For example, the entries of an `int[][][]` are `Array(Int, 2)`.
+    /// We do not nest an `ArrayEntryType` because this is stored in the header of the array.
+    Array(ArrayLeafType, u8),
+}
+
+/// The type of the innermost entries of a multi-dimensional array
+#[derive(PartialEq, Clone, Debug)]
+#[repr(u8)]
+pub enum ArrayLeafType {
+    Base(BaseType),
+    Object(ClassId),
 }
 
 impl ArrayEntryType {
+    /// Returns the type of the entries of an array of arrays of `self`
+    pub fn array_of(self) -> ArrayEntryType {
+        match self {
+            ArrayEntryType::Base(base_type) => {
+                ArrayEntryType::Array(ArrayLeafType::Base(base_type), 1)
+            }
+            ArrayEntryType::Object(class_id) => {
+                ArrayEntryType::Array(ArrayLeafType::Object(class_id), 1)
+            }
+            ArrayEntryType::Array(leaf_type, dimensions) => {
+                ArrayEntryType::Array(leaf_type, dimensions + 1)
+            }
+        }
+    }
+
+    /// For entries that are arrays, returns the type of their entries
+    pub fn component_type(&self) -> Option<ArrayEntryType> {
+        match self {
+            ArrayEntryType::Array(ArrayLeafType::Base(base_type), 1) => {
+                Some(ArrayEntryType::Base(base_type.clone()))
+            }
+            ArrayEntryType::Array(ArrayLeafType::Object(class_id), 1) => {
+                Some(ArrayEntryType::Object(*class_id))
+            }
+            ArrayEntryType::Array(leaf_type, dimensions) => {
+                Some(ArrayEntryType::Array(leaf_type.clone(), dimensions - 1))
+            }
+            _ => None,
+        }
+    }
+
     pub fn into_field_type<'a>(
         self,
         class_resolver: &impl ClassByIdResolver<'a>,
@@ -20,9 +62,10 @@ impl ArrayEntryType {
             ArrayEntryType::Object(class_id) => class_resolver
                 .find_class_by_id(class_id)
                 .map(|class| FieldType::Object(class.name.clone())),
-            ArrayEntryType::Array => {
-                todo!("Arrays of arrays are not supported at the moment")
-            }
+            ArrayEntryType::Array(..) => self
+                .component_type()
+                .and_then(|component_type| component_type.into_field_type(class_resolver))
+                .map(|component_type| FieldType::Array(Box::new(component_type))),
         }
     }
 }
@@ -0,0 +1,35 @@
+use crate::{
+    exceptions::MethodCallFailed,
+    native_methods_reflection::{component_type_name, is_primitive_name},
+    vm::Vm,
+};
+
+/// The interfaces implemented by all arrays
+pub(crate) const ARRAY_INTERFACES: [&str; 2] = ["java/lang/Cloneable", "java/io/Serializable"];
+
+/// Returns whether a value of the `source` type can be assigned to a variable of the `target`
+/// type, given their internal names, with a widening reference conversion as in JLS 5.1.5
+pub(crate) fn is_assignable<'a>(
+    vm: &mut Vm<'a>,
+    target: &str,
+    source: &str,
+) -> Result<bool, MethodCallFailed<'a>> {
+    if target == source {
+        return Ok(true);
+    }
+    if is_primitive_name(target) || is_primitive_name(source) {
+        return Ok(false);
+    }
+    match (component_type_name(target), component_type_name(source)) {
+        (Some(target_component), Some(source_component)) => {
+            is_assignable(vm, &target_component, &source_component)
+        }
+        (None, Some(_)) => Ok(target == "java/lang/Object" || ARRAY_INTERFACES.contains(&target)),
+        (Some(_), None) => Ok(false),
+        (None, None) => {
+            let target_class = vm.get_or_resolve_class(target)?;
+            let source_class = vm.get_or_resolve_class(source)?;
+            Ok(source_class.is_subclass_of(target_class))
+        }
+    }
+}
@@ -1,13 +1,12 @@
-use log::{debug, warn};
+use log::debug;
 use rjvm_reader::{
     class_file_field::ClassFileField,
-    class_file_method::ClassFileMethod,
     constant_pool::ConstantPoolEntry,
     field_type::{
         BaseType,
         FieldType::{self, Base},
     },
-    instruction::{Instruction, NewArrayType},
+    instruction::{Instruction, NewArrayType, WideInstruction},
     line_number::LineNumber,
     program_counter::ProgramCounter,
     type_conversion::ToUsizeSafe,
@@ -17,19 +16,26 @@ use crate::{
     abstract_object::{AbstractObject, ObjectKind},
     array::Array,
     array_entry_type::ArrayEntryType,
-    call_frame::InstructionCompleted::{ContinueMethodExecution, ReturnFromMethod},
+    assignability::is_assignable,
+    call_frame::InstructionCompleted::{
+        ContinueMethodExecution, InvokeMethod, ReturnFromMethod, Yield,
+    },
     call_stack::CallStack,
-    class::Class,
+    class::{Class, ClassId, ClassRef},
     class_and_method::ClassAndMethod,
-    class_resolver_by_id::ClassByIdResolver,
+    decoded_code::DecodedCode,
     exceptions::{JavaException, MethodCallFailed},
-    java_objects_creation::{new_java_lang_class_object, new_java_lang_string_object},
+    invoke_dynamic::CallSite,
+    java_objects_creation::new_java_lang_string_object,
+    method_resolution,
     object::Object,
+    resolved_references::{InvokeKind, MethodDispatch, ResolvedField, ResolvedReference},
     stack_trace_element::StackTraceElement,
     value::Value::{self, Double, Float, Int, Long, Null},
     value_stack::ValueStack,
     vm::Vm,
     vm_error::VmError,
+    vtable,
 };
 
 pub type MethodCallResult<'a> = Result<Option<Value<'a>>, MethodCallFailed<'a>>;
@@ -39,6 +45,8 @@ struct MethodReference<'a> {
     class_name: &'a str,
     method_name: &'a str,
     type_descriptor: &'a str,
+    /// Whether the constant is an interface method reference
+    interface: bool,
 }
 
 #[derive(Debug)]
@@ -52,23 +60,28 @@ struct FieldReference<'a> {
 #[derive(Debug)]
 pub struct CallFrame<'a> {
     class_and_method: ClassAndMethod<'a>,
-    pc: ProgramCounter,
+    /// Index of the next instruction to execute
+    instruction_index: usize,
+    executed_instruction_index: usize,
     locals: Vec<Value<'a>>,
     stack: ValueStack<'a>,
-    code: &'a Vec<u8>,
-}
-
-#[derive(Clone, Copy)]
-enum InvokeKind {
-    Special,
-    Static,
-    Virtual,
-    Interface,
+    code: &'a DecodedCode<'a>,
+    /// The object whose monitor was entered when invoking a synchronized method
+    synchronized_on: Option<AbstractObject<'a>>,
 }
 
-enum InstructionCompleted<'a> {
+pub(crate) enum InstructionCompleted<'a> {
     ReturnFromMethod(Option<Value<'a>>),
     ContinueMethodExecution,
+    /// The method must be invoked, and its result given to `complete_invocation`
+    InvokeMethod(
+        ClassAndMethod<'a>,
+        Option<AbstractObject<'a>>,
+        Vec<Value<'a>>,
+    ),
+    /// The thread blocked or was preempted, and must give the way to the other ones before
+    /// continuing the execution of the method
+    Yield,
 }
 
 macro_rules! generate_pop {
@@ -115,10 +128,10 @@ macro_rules! generate_execute_math {
 }
 
 macro_rules! generate_execute_neg {
-    ($name: ident, $pop_fn: ident, $variant: ident) => {
+    ($name: ident, $pop_fn: ident, $variant: ident, $negate: expr) => {
         fn $name(&mut self) -> Result<(), MethodCallFailed<'a>> {
             let value = self.$pop_fn()?;
-            self.push($variant(-value))
+            self.push($variant($negate(value)))
         }
     };
 }
@@ -138,15 +151,18 @@ macro_rules! generate_execute_coerce {
 
 macro_rules! generate_compare {
     ($name: ident, $pop_fn:ident) => {
-        fn $name(&mut self, sign_for_greater: i32) -> Result<(), MethodCallFailed<'a>> {
+        /// Pushes 1, -1 or 0, or the given result when a value is NaN
+        fn $name(&mut self, result_for_nan: i32) -> Result<(), MethodCallFailed<'a>> {
             let val2 = self.$pop_fn()?;
             let val1 = self.$pop_fn()?;
             if val1 > val2 {
-                self.push(Int(sign_for_greater))
+                self.push(Int(1))
             } else if val1 < val2 {
-                self.push(Int(-sign_for_greater))
-            } else {
+                self.push(Int(-1))
+            } else if val1 == val2 {
                 self.push(Int(0))
+            } else {
+                self.push(Int(result_for_nan))
             }
         }
     };
@@ -186,8 +202,9 @@ macro_rules! generate_execute_store {
 macro_rules! generate_execute_array_load {
     ($name: ident, $($variant:pat),+) => {
         fn $name(&mut self) -> Result<(), MethodCallFailed<'a>> {
-            let index = self.pop_int()?.into_usize_safe();
+            let index = self.pop_int()?;
             let array = self.pop_array()?;
+            let index = Self::to_array_index(&array, index)?;
             let value = match array.elements_type() {
                 $($variant => {
                    array.get_element(index)
@@ -203,8 +220,9 @@ macro_rules! generate_execute_array_store {
     ($name: ident, $pop_fn: ident, $map_fn: ident, $($variant:pat),+) => {
         fn $name(&mut self) -> Result<(), MethodCallFailed<'a>> {
             let value = Self::$map_fn(self.$pop_fn()?);
-            let index = self.pop_int()?.into_usize_safe();
+            let index = self.pop_int()?;
             let array = self.pop_array()?;
+            let index = Self::to_array_index(&array, index)?;
             match array.elements_type() {
                 $($variant => {
                     array.set_element(index, value)?
@@ -218,7 +236,11 @@ macro_rules! generate_execute_array_store {
 }
 
 impl<'a> CallFrame<'a> {
-    pub fn new(class_and_method: ClassAndMethod<'a>, locals: Vec<Value<'a>>) -> Self {
+    pub fn new(
+        class_and_method: ClassAndMethod<'a>,
+        code: &'a DecodedCode<'a>,
+        locals: Vec<Value<'a>>,
+    ) -> Self {
         let max_stack_size = class_and_method
             .method
             .code
@@ -226,20 +248,30 @@ impl<'a> CallFrame<'a> {
             .expect("method is not native")
             .max_stack
             .into_usize_safe();
-        let code = &class_and_method
-            .method
-            .code
-            .as_ref()
-            .expect("method is not native")
-            .code;
 
-        CallFrame {
+        let frame = CallFrame {
             class_and_method,
-            pc: ProgramCounter(0),
+            instruction_index: 0,
+            executed_instruction_index: 0,
             locals,
             stack: ValueStack::with_max_size(max_stack_size),
             code,
-        }
+            synchronized_on: None,
+        };
+        frame.debug_start_execution();
+        frame
+    }
+
+    pub(crate) fn class_and_method(&self) -> ClassAndMethod<'a> {
+        self.class_and_method
+    }
+
+    pub(crate) fn synchronized_on(&self) -> Option<&AbstractObject<'a>> {
+        self.synchronized_on.as_ref()
+    }
+
+    pub(crate) fn set_synchronized_on(&mut self, monitor: AbstractObject<'a>) {
+        self.synchronized_on = Some(monitor);
     }
 
     pub fn to_stack_trace_element(&self) -> StackTraceElement<'a> {
@@ -254,55 +286,105 @@ impl<'a> CallFrame<'a> {
     fn get_line_number(&self) -> Option<LineNumber> {
         if let Some(code) = self.class_and_method.method.code.as_ref() {
             if let Some(line_number_table) = &code.line_number_table {
-                return Some(line_number_table.lookup_pc(self.pc));
+                return Some(line_number_table.lookup_pc(self.pc()));
             }
         }
         None
     }
 
-    pub fn execute(
+    /// Executes the method until it returns or invokes another method. Invocations are not
+    /// executed here, but by `Vm::invoke`, which pushes a new frame on the call stack, so
+    /// that Java calls do not consume the native stack. Uncaught exceptions are returned.
+    pub(crate) fn execute(
         &mut self,
         vm: &mut Vm<'a>,
         call_stack: &mut CallStack<'a>,
-    ) -> MethodCallResult<'a> {
-        self.debug_start_execution();
-
+    ) -> Result<InstructionCompleted<'a>, MethodCallFailed<'a>> {
+        let code = self.code;
         loop {
-            let executed_instruction_pc = self.pc;
-            let (instruction, new_address) =
-                Instruction::parse(self.code, executed_instruction_pc.0.into_usize_safe())
-                    .map_err(|_| MethodCallFailed::InternalError(VmError::ValidationException))?;
-            self.debug_print_status(&instruction);
+            if vm.should_preempt(call_stack.thread_id()) {
+                return Ok(Yield);
+            }
+            let executed_instruction_index = self.instruction_index;
+            let parsed_instruction;
+            let instruction = if vm.parse_every_step() {
+                let bytecode = &self
+                    .class_and_method
+                    .method
+                    .code
+                    .as_ref()
+                    .ok_or(VmError::ValidationException)?
+                    .code;
+                parsed_instruction =
+                    code.parse_instruction(bytecode, executed_instruction_index)?;
+                &parsed_instruction
+            } else {
+                code.instruction(executed_instruction_index).ok_or(
+                    MethodCallFailed::InternalError(VmError::ValidationException),
+                )?
+            };
+            self.debug_print_status(instruction);
 
-            self.pc = ProgramCounter(new_address as u16);
+            self.executed_instruction_index = executed_instruction_index;
+            self.instruction_index = executed_instruction_index + 1;
 
             let instruction_result = self.execute_instruction(vm, call_stack, instruction);
             match instruction_result {
-                Ok(ReturnFromMethod(return_value)) => return Ok(return_value),
-                Ok(ContinueMethodExecution) => { /* continue the loop */ }
+                Ok(ContinueMethodExecution) => continue,
+                Ok(completed) => return Ok(completed),
+                Err(failure) => self.handle_failure(vm, call_stack, failure)?,
+            }
+        }
+    }
 
-                Err(MethodCallFailed::InternalError(err)) => {
-                    return Err(MethodCallFailed::InternalError(err))
-                }
+    /// Completes the invocation requested by the last executed instruction, pushing the
+    /// returned value or handling the exception thrown by the invoked method
+    pub(crate) fn complete_invocation(
+        &mut self,
+        vm: &mut Vm<'a>,
+        call_stack: &mut CallStack<'a>,
+        result: MethodCallResult<'a>,
+    ) -> Result<(), MethodCallFailed<'a>> {
+        match result {
+            Ok(None) => Ok(()),
+            Ok(Some(value)) => self.push(value),
+            Err(failure) => self.handle_failure(vm, call_stack, failure),
+        }
+    }
 
-                Err(MethodCallFailed::ExceptionThrown(exception)) => {
-                    let exception_handler = self.find_exception_handler(
-                        vm,
-                        call_stack,
-                        executed_instruction_pc,
-                        &exception,
-                    );
-                    match exception_handler {
-                        Err(err) => return Err(err),
-                        Ok(None) => {
-                            return Err(MethodCallFailed::ExceptionThrown(exception));
-                        }
-                        Ok(Some(catch_handler_pc)) => {
-                            self.stack.push(Value::Object(exception.0))?;
-                            self.pc = catch_handler_pc
-                        }
-                    }
+    /// Jumps to the exception handler of the last executed instruction, if there is one.
+    /// Errors that are exceptions of the Java runtime get thrown as such.
+    fn handle_failure(
+        &mut self,
+        vm: &mut Vm<'a>,
+        call_stack: &mut CallStack<'a>,
+        failure: MethodCallFailed<'a>,
+    ) -> Result<(), MethodCallFailed<'a>> {
+        let exception = match failure {
+            MethodCallFailed::InternalError(VmError::StackOverflowError) => {
+                self.instruction_index = self.executed_instruction_index;
+                self.new_stack_overflow_error(vm, call_stack)?
+            }
+            MethodCallFailed::InternalError(err) => match err.to_java_exception() {
+                Some((exception_class_name, message)) => {
+                    // Point to the failed instruction in the exception's stack trace
+                    self.instruction_index = self.executed_instruction_index;
+                    self.new_java_exception(vm, call_stack, exception_class_name, message)?
                 }
+                None => return Err(MethodCallFailed::InternalError(err)),
+            },
+            MethodCallFailed::ExceptionThrown(exception) => exception,
+        };
+
+        let exception_handler =
+            self.find_exception_handler(vm, self.executed_instruction_pc(), &exception)?;
+        match exception_handler {
+            None => Err(MethodCallFailed::ExceptionThrown(exception)),
+            Some(catch_handler_pc) => {
+                self.stack.truncate(0)?;
+                self.stack.push(Value::Object(exception.0))?;
+                self.instruction_index = self.code.instruction_index(catch_handler_pc)?;
+                Ok(())
             }
         }
     }
@@ -311,9 +393,9 @@ impl<'a> CallFrame<'a> {
         &mut self,
         vm: &mut Vm<'a>,
         call_stack: &mut CallStack<'a>,
-        instruction: Instruction,
+        instruction: &Instruction,
     ) -> Result<InstructionCompleted<'a>, MethodCallFailed<'a>> {
-        match instruction {
+        match *instruction {
             Instruction::Aconst_null => self.push(Null)?,
             Instruction::Aload(index) => self.execute_aload(index.into_usize_safe())?,
             Instruction::Aload_0 => self.execute_aload(0)?,
@@ -418,7 +500,12 @@ impl<'a> CallFrame<'a> {
 
             Instruction::New(constant_index) => {
                 let new_object_class_name = self.get_constant_class_reference(constant_index)?;
-                let new_object = vm.new_object(call_stack, new_object_class_name)?;
+                let class = vm.get_or_resolve_class(new_object_class_name)?;
+                if !self.await_class_initialization(vm, call_stack, class) {
+                    return Ok(Yield);
+                }
+                vm.initialize_class(call_stack, class)?;
+                let new_object = vm.new_object_of_class(class);
                 self.push(Value::Object(new_object))?;
             }
 
@@ -432,20 +519,20 @@ impl<'a> CallFrame<'a> {
             Instruction::Pop2 => self.stack.pop2().map(|_| ())?,
             Instruction::Swap => self.stack.swap()?,
 
-            Instruction::Bipush(byte_value) => self.push(Int(byte_value as i32))?,
+            Instruction::Bipush(byte_value) => self.push(Int(byte_value as i8 as i32))?,
             Instruction::Sipush(short_value) => self.push(Int(short_value as i32))?,
 
             Instruction::Invokespecial(constant_index) => {
-                self.invoke_method(vm, call_stack, constant_index, InvokeKind::Special)?
+                return self.invoke_method(vm, call_stack, constant_index, InvokeKind::Special);
             }
             Instruction::Invokestatic(constant_index) => {
-                self.invoke_method(vm, call_stack, constant_index, InvokeKind::Static)?
+                return self.invoke_method(vm, call_stack, constant_index, InvokeKind::Static);
             }
             Instruction::Invokevirtual(constant_index) => {
-                self.invoke_method(vm, call_stack, constant_index, InvokeKind::Virtual)?
+                return self.invoke_method(vm, call_stack, constant_index, InvokeKind::Virtual);
             }
             Instruction::Invokeinterface(constant_index, _) => {
-                self.invoke_method(vm, call_stack, constant_index, InvokeKind::Interface)?
+                return self.invoke_method(vm, call_stack, constant_index, InvokeKind::Interface);
             }
             Instruction::Return => {
                 if !self.class_and_method.is_void() {
@@ -456,26 +543,24 @@ impl<'a> CallFrame<'a> {
                 self.debug_done_execution(None);
                 return Ok(ReturnFromMethod(None));
             }
-            Instruction::Areturn => return Ok(ReturnFromMethod(self.execute_areturn()?)),
+            Instruction::Areturn => return Ok(ReturnFromMethod(self.execute_areturn(vm)?)),
             Instruction::Ireturn => return Ok(ReturnFromMethod(self.execute_ireturn()?)),
             Instruction::Lreturn => return Ok(ReturnFromMethod(self.execute_lreturn()?)),
             Instruction::Freturn => return Ok(ReturnFromMethod(self.execute_freturn()?)),
             Instruction::Dreturn => return Ok(ReturnFromMethod(self.execute_dreturn()?)),
 
             Instruction::Instanceof(constant_index) => {
-                self.execute_instanceof(vm, call_stack, constant_index)?
-            }
-            Instruction::Checkcast(constant_index) => {
-                self.execute_checkcast(vm, call_stack, constant_index)?
+                self.execute_instanceof(vm, constant_index)?
             }
+            Instruction::Checkcast(constant_index) => self.execute_checkcast(vm, constant_index)?,
 
             Instruction::Putfield(field_index) => self.execute_putfield(vm, field_index)?,
             Instruction::Putstatic(field_index) => {
-                self.execute_putstatic(vm, call_stack, field_index)?
+                return self.execute_putstatic(vm, call_stack, field_index)
             }
             Instruction::Getfield(field_index) => self.execute_getfield(vm, field_index)?,
             Instruction::Getstatic(field_index) => {
-                self.execute_getstatic(vm, call_stack, field_index)?
+                return self.execute_getstatic(vm, call_stack, field_index)
             }
 
             Instruction::Iadd => self.execute_int_math(|a, b| Ok(a.wrapping_add(b)))?,
@@ -505,36 +590,28 @@ impl<'a> CallFrame<'a> {
             })?,
 
             Instruction::Iinc(index, constant) => {
-                let index = index.into_usize_safe();
-                let local = self.get_local_int_as_int(vm, index)?;
-                self.locals[index] = Int(local + constant as i32);
+                self.execute_iinc(vm, index.into_usize_safe(), constant as i32)?
             }
 
-            Instruction::Ladd => self.execute_long_math(|a, b| Ok(a + b))?,
-            Instruction::Lsub => self.execute_long_math(|a, b| Ok(a - b))?,
-            Instruction::Lmul => self.execute_long_math(|a, b| Ok(a * b))?,
+            Instruction::Ladd => self.execute_long_math(|a, b| Ok(a.wrapping_add(b)))?,
+            Instruction::Lsub => self.execute_long_math(|a, b| Ok(a.wrapping_sub(b)))?,
+            Instruction::Lmul => self.execute_long_math(|a, b| Ok(a.wrapping_mul(b)))?,
             Instruction::Ldiv => self.execute_long_math(|a, b| match b {
                 0 => Err(VmError::ArithmeticException),
-                _ => Ok(a / b),
+                _ => Ok(a.wrapping_div(b)),
             })?,
             Instruction::Lrem => self.execute_long_math(|a, b| match b {
                 0 => Err(VmError::ArithmeticException),
-                _ => Ok(a % b),
+                _ => Ok(a.wrapping_rem(b)),
             })?,
             Instruction::Land => self.execute_long_math(|a, b| Ok(a & b))?,
             Instruction::Lor => self.execute_long_math(|a, b| Ok(a | b))?,
             Instruction::Lxor => self.execute_long_math(|a, b| Ok(a ^ b))?,
-            Instruction::Lshr => self.execute_long_shift(|a, b| Ok(a >> (b & 0x1f)))?,
-            Instruction::Lshl => self.execute_long_shift(|a, b| Ok(a << (b & 0x1f)))?,
-            Instruction::Lushr => self.execute_long_shift(|a, b| {
-                Ok({
-                    if a > 0 {
-                        a >> (b & 0x1f)
-                    } else {
-                        ((a as u64) >> (b & 0x1f)) as i64
-                    }
-                })
-            })?,
+            Instruction::Lshr => self.execute_long_shift(|a, b| Ok(a >> (b & 0x3f)))?,
+            Instruction::Lshl => self.execute_long_shift(|a, b| Ok(a << (b & 0x3f)))?,
+            Instruction::Lushr => {
+                self.execute_long_shift(|a, b| Ok(((a as u64) >> (b & 0x3f)) as i64))?
+            }
 
             Instruction::Fadd => self.execute_float_math(|a, b| Ok(a + b))?,
             Instruction::Fsub => self.execute_float_math(|a, b| Ok(a - b))?,
@@ -582,6 +659,20 @@ impl<'a> CallFrame<'a> {
             Instruction::Dneg => self.execute_dneg()?,
 
             Instruction::Goto(jump_address) => self.goto(jump_address),
+            Instruction::Goto_w(jump_address) => self.goto(jump_address),
+
+            Instruction::Jsr(jump_address) => self.execute_jsr(jump_address)?,
+            Instruction::Jsr_w(jump_address) => self.execute_jsr(jump_address)?,
+            Instruction::Ret(index) => self.execute_ret(index.into_usize_safe())?,
+
+            Instruction::Tableswitch(ref table_switch) => {
+                let value = self.pop_int()?;
+                self.goto(table_switch.jump_address(value));
+            }
+            Instruction::Lookupswitch(ref lookup_switch) => {
+                let value = self.pop_int()?;
+                self.goto(lookup_switch.jump_address(value));
+            }
 
             Instruction::Ifeq(jump_address) => self.execute_if(jump_address, |v| v == 0)?,
             Instruction::Ifne(jump_address) => self.execute_if(jump_address, |v| v != 0)?,
@@ -613,17 +704,21 @@ impl<'a> CallFrame<'a> {
                 self.execute_if_icmp(jump_address, |a, b| a >= b)?
             }
 
-            Instruction::Lcmp => self.execute_long_compare(1)?,
-            Instruction::Fcmpg => self.execute_float_compare(-1)?,
-            Instruction::Fcmpl => self.execute_float_compare(1)?,
-            Instruction::Dcmpg => self.execute_double_compare(-1)?,
-            Instruction::Dcmpl => self.execute_double_compare(1)?,
+            // Longs are never unordered
+            Instruction::Lcmp => self.execute_long_compare(0)?,
+            Instruction::Fcmpg => self.execute_float_compare(1)?,
+            Instruction::Fcmpl => self.execute_float_compare(-1)?,
+            Instruction::Dcmpg => self.execute_double_compare(1)?,
+            Instruction::Dcmpl => self.execute_double_compare(-1)?,
 
             Instruction::Newarray(array_type) => {
                 self.execute_newarray(vm, array_type)?;
             }
             Instruction::Anewarray(constant_index) => {
-                self.execute_anewarray(vm, call_stack, constant_index)?;
+                self.execute_anewarray(vm, constant_index)?;
+            }
+            Instruction::Multianewarray(constant_index, dimensions) => {
+                self.execute_multianewarray(vm, constant_index, dimensions)?;
             }
 
             Instruction::Arraylength => self.execute_array_length()?,
@@ -646,32 +741,62 @@ impl<'a> CallFrame<'a> {
             Instruction::Dastore => self.execute_dastore()?,
             Instruction::Aastore => self.execute_aastore(vm)?,
 
-            Instruction::Monitorenter => self.execute_monitorenter()?,
-            Instruction::Monitorexit => self.execute_monitorexit()?,
+            Instruction::Monitorenter => return self.execute_monitorenter(vm, call_stack),
+            Instruction::Monitorexit => self.execute_monitorexit(call_stack)?,
 
             Instruction::Athrow => self.execute_athrow()?,
 
-            /* Unimplemented instructions:
-            Instruction::Goto_w => {}
-            Instruction::Invokedynamic(_) => {}
-            Instruction::Jsr(_) => {}
-            Instruction::Jsr_w => {}
-            Instruction::Lookupswitch => {}
-            Instruction::Multianewarray(_, _) => {}
-            Instruction::Ret(_) => {}
-            Instruction::Tableswitch => {}
-            Instruction::Wide => {}
-            */
-            Instruction::Nop => {}
-            _ => {
-                warn!("Unsupported instruction: {:?}", instruction);
-                return Err(MethodCallFailed::InternalError(VmError::NotImplemented));
+            Instruction::Invokedynamic(constant_index) => {
+                return self.execute_invokedynamic(
+                    vm,
+                    call_stack,
+                    self.executed_instruction_pc(),
+                    constant_index,
+                );
             }
+
+            Instruction::Wide(wide_instruction) => self.execute_wide(vm, wide_instruction)?,
+
+            Instruction::Nop => {}
         }
 
         Ok(ContinueMethodExecution)
     }
 
+    fn execute_wide(
+        &mut self,
+        vm: &Vm<'a>,
+        wide_instruction: WideInstruction,
+    ) -> Result<(), MethodCallFailed<'a>> {
+        match wide_instruction {
+            WideInstruction::Iload(index) => self.execute_iload(index.into_usize_safe()),
+            WideInstruction::Lload(index) => self.execute_lload(index.into_usize_safe()),
+            WideInstruction::Fload(index) => self.execute_fload(index.into_usize_safe()),
+            WideInstruction::Dload(index) => self.execute_dload(index.into_usize_safe()),
+            WideInstruction::Aload(index) => self.execute_aload(index.into_usize_safe()),
+            WideInstruction::Istore(index) => self.execute_istore(index.into_usize_safe()),
+            WideInstruction::Lstore(index) => self.execute_lstore(index.into_usize_safe()),
+            WideInstruction::Fstore(index) => self.execute_fstore(index.into_usize_safe()),
+            WideInstruction::Dstore(index) => self.execute_dstore(index.into_usize_safe()),
+            WideInstruction::Astore(index) => self.execute_astore(index.into_usize_safe()),
+            WideInstruction::Iinc(index, constant) => {
+                self.execute_iinc(vm, index.into_usize_safe(), constant as i32)
+            }
+            WideInstruction::Ret(index) => self.execute_ret(index.into_usize_safe()),
+        }
+    }
+
+    fn execute_iinc(
+        &mut self,
+        vm: &Vm<'a>,
+        index: usize,
+        constant: i32,
+    ) -> Result<(), MethodCallFailed<'a>> {
+        let local = self.get_local_int_as_int(vm, index)?;
+        self.locals[index] = Int(local.wrapping_add(constant));
+        Ok(())
+    }
+
     fn push(&mut self, value: Value<'a>) -> Result<(), MethodCallFailed<'a>> {
         self.stack.push(value).map_err(|err| err.into())
     }
@@ -747,40 +872,221 @@ impl<'a> CallFrame<'a> {
         call_stack: &mut CallStack<'a>,
         constant_index: u16,
         kind: InvokeKind,
-    ) -> Result<(), MethodCallFailed<'a>> {
-        let method_reference = self.get_constant_method_reference(constant_index)?;
-        if method_reference.class_name.starts_with('[') && method_reference.method_name == "clone" {
-            let array = self.pop()?;
-            let clone = vm.clone_array(array)?;
-            return self.push(clone);
+    ) -> Result<InstructionCompleted<'a>, MethodCallFailed<'a>> {
+        let (static_method_reference, dispatch) =
+            match self.resolve_method_reference(vm, constant_index, kind)? {
+                Some(resolved) => resolved,
+                None => {
+                    let array = self.pop()?;
+                    let clone = vm.clone_array(array)?;
+                    self.push(clone)?;
+                    return Ok(ContinueMethodExecution);
+                }
+            };
+        if let InvokeKind::Static = kind {
+            if !self.await_class_initialization(vm, call_stack, static_method_reference.class) {
+                return Ok(Yield);
+            }
+            vm.initialize_class(call_stack, static_method_reference.class)?;
         }
-
-        let static_method_reference =
-            self.get_method_to_invoke_statically(vm, call_stack, method_reference, kind)?;
         let (receiver, params, new_stack_len) =
             self.get_method_receiver_and_params(&static_method_reference)?;
-        let class_and_method = match kind {
-            InvokeKind::Virtual | InvokeKind::Interface => {
-                Self::resolve_virtual_method(vm, receiver.clone(), static_method_reference)?
+        let class_and_method = match (dispatch, &receiver) {
+            (MethodDispatch::Direct, _) => static_method_reference,
+            (_, Some(receiver)) if receiver.kind() == ObjectKind::Object => {
+                self.select_method(vm, receiver.class_id(), static_method_reference, dispatch)?
             }
+            // Arrays only have the methods of Object, and they override only clone
             _ => static_method_reference,
         };
+        if class_and_method.method.is_abstract() {
+            return Err(MethodCallFailed::InternalError(
+                VmError::AbstractMethodError(format!(
+                    "{}.{}{}",
+                    class_and_method.class.name.replace('/', "."),
+                    class_and_method.method.name,
+                    class_and_method.method.type_descriptor,
+                )),
+            ));
+        }
         self.stack.truncate(new_stack_len)?;
+        Ok(InvokeMethod(class_and_method, receiver, params))
+    }
+
+    fn execute_invokedynamic(
+        &mut self,
+        vm: &mut Vm<'a>,
+        call_stack: &mut CallStack<'a>,
+        pc: ProgramCounter,
+        constant_index: u16,
+    ) -> Result<InstructionCompleted<'a>, MethodCallFailed<'a>> {
+        let call_site =
+            vm.get_or_link_call_site(call_stack, self.class_and_method, pc, constant_index)?;
+        match call_site {
+            CallSite::Lambda(lambda_class) => {
+                // Allocate before popping the captured arguments, since the allocation can
+                // trigger a garbage collection and the arguments must still be gc roots
+                let lambda = vm.new_object_of_class(lambda_class);
+                let num_captured = lambda_class.fields.len();
+                let cur_stack_len = self.stack.len();
+                if cur_stack_len < num_captured {
+                    return Err(MethodCallFailed::InternalError(
+                        VmError::ValidationException,
+                    ));
+                }
+                let new_stack_len = cur_stack_len - num_captured;
+                for index in 0..num_captured {
+                    let captured = self.stack[new_stack_len + index].clone();
+                    lambda.set_field(lambda_class.first_field_index + index, captured);
+                }
+                self.stack.truncate(new_stack_len)?;
+                self.push(Value::Object(lambda))?;
+                Ok(ContinueMethodExecution)
+            }
+            CallSite::StaticMethod(class_and_method) => {
+                let (_, params, new_stack_len) =
+                    self.get_method_receiver_and_params(&class_and_method)?;
+                self.stack.truncate(new_stack_len)?;
+                Ok(InvokeMethod(class_and_method, None, params))
+            }
+        }
+    }
+
+    /// Resolves a method reference of the constant pool on its first execution by each kind
+    /// of invoke instruction, and returns the cached method afterwards. Returns `None` for the
+    /// `clone` method of arrays.
+    fn resolve_method_reference(
+        &self,
+        vm: &mut Vm<'a>,
+        constant_index: u16,
+        kind: InvokeKind,
+    ) -> Result<Option<(ClassAndMethod<'a>, MethodDispatch)>, MethodCallFailed<'a>> {
+        let resolved_references = &self.class_and_method.class.resolved_references;
+        match resolved_references.get(constant_index) {
+            Some(ResolvedReference::ArrayClone) => return Ok(None),
+            Some(ResolvedReference::Method(resolved_method)) => {
+                if let Some(resolved) = resolved_method.get(kind) {
+                    return Ok(Some(resolved));
+                }
+            }
+            Some(ResolvedReference::Field(_)) => {
+                return Err(MethodCallFailed::InternalError(
+                    VmError::ValidationException,
+                ))
+            }
+            None => {}
+        }
 
-        let method_return_type = class_and_method.return_type();
-        let result = vm.invoke(call_stack, class_and_method, receiver, params)?;
+        let method_reference = self.get_constant_method_reference(constant_index)?;
+        if method_reference.class_name.starts_with('[') && method_reference.method_name == "clone" {
+            resolved_references.set(constant_index, ResolvedReference::ArrayClone);
+            return Ok(None);
+        }
+        let resolved = self.resolve_method(vm, method_reference, kind)?;
+        resolved_references.set_method(constant_index, kind, resolved);
+        Ok(Some(resolved))
+    }
 
-        Self::validate_type_opt(vm, method_return_type, &result)?;
-        if let Some(value) = result {
-            self.push(value)?;
+    fn get_method_dispatch(
+        class_and_method: &ClassAndMethod<'a>,
+    ) -> Result<MethodDispatch, VmError> {
+        if !vtable::is_overridable(class_and_method.method) {
+            return Ok(MethodDispatch::Direct);
         }
-        Ok(())
+        let class = class_and_method.class;
+        let dispatch = if class.is_interface() {
+            Some(MethodDispatch::Interface(
+                class.id,
+                class_and_method.method_index,
+            ))
+        } else {
+            let method = class_and_method.method;
+            class
+                .vtable_slot(&method.name, &method.type_descriptor)
+                .map(MethodDispatch::Virtual)
+        };
+        dispatch.ok_or(VmError::ValidationException)
+    }
+
+    /// Selects the method to execute for the class of the receiver, using the inline cache
+    /// of the call site when the receiver has the same class as the previous one
+    fn select_method(
+        &self,
+        vm: &Vm<'a>,
+        receiver_class_id: ClassId,
+        class_and_method: ClassAndMethod<'a>,
+        dispatch: MethodDispatch,
+    ) -> Result<ClassAndMethod<'a>, VmError> {
+        let inline_cache = self
+            .code
+            .inline_cache(self.executed_instruction_index)
+            .ok_or(VmError::ValidationException)?;
+        if let Some(selected_method) = inline_cache.get(receiver_class_id) {
+            return Ok(selected_method);
+        }
+
+        let receiver_class = vm.get_class_by_id(receiver_class_id)?;
+        let selected_method = match dispatch {
+            MethodDispatch::Direct => Some(class_and_method),
+            MethodDispatch::Virtual(slot) => receiver_class.vtable_method(slot),
+            MethodDispatch::Interface(interface_id, method_index) => {
+                if !receiver_class.implements(interface_id) {
+                    return Err(VmError::IncompatibleClassChangeError(format!(
+                        "Class {} does not implement the requested interface {}",
+                        receiver_class.name.replace('/', "."),
+                        class_and_method.class.name.replace('/', "."),
+                    )));
+                }
+                receiver_class.itable_method(interface_id, method_index)
+            }
+        }
+        .ok_or_else(|| Self::conflicting_default_methods(&class_and_method))?;
+        debug!(
+            "resolved virtual method {}.{}:{} on object of class {}: using version of class {}",
+            class_and_method.class.name,
+            class_and_method.method.name,
+            class_and_method.method.type_descriptor,
+            receiver_class.name,
+            selected_method.class.name,
+        );
+        inline_cache.set(receiver_class_id, selected_method);
+        Ok(selected_method)
+    }
+
+    /// Resolves a field reference of the constant pool on its first execution, and returns
+    /// the cached field afterwards
+    fn resolve_field_reference(
+        &self,
+        vm: &mut Vm<'a>,
+        constant_index: u16,
+    ) -> Result<ResolvedField<'a>, MethodCallFailed<'a>> {
+        let resolved_references = &self.class_and_method.class.resolved_references;
+        match resolved_references.get(constant_index) {
+            Some(ResolvedReference::Field(resolved_field)) => return Ok(*resolved_field),
+            Some(_) => {
+                return Err(MethodCallFailed::InternalError(
+                    VmError::ValidationException,
+                ))
+            }
+            None => {}
+        }
+
+        let field_reference = self.get_constant_field_reference(constant_index)?;
+        let class = vm.get_or_resolve_class(field_reference.class_name)?;
+        let (class, index, field) = Self::get_field(class, field_reference)?;
+        let resolved_field = ResolvedField {
+            class,
+            index,
+            field,
+        };
+        resolved_references.set(constant_index, ResolvedReference::Field(resolved_field));
+        Ok(resolved_field)
     }
 
     fn get_field(
-        class: &'a Class,
+        class: ClassRef<'a>,
         field_reference: FieldReference,
-    ) -> Result<(usize, &'a ClassFileField), VmError> {
+    ) -> Result<(ClassRef<'a>, usize, &'a ClassFileField), VmError> {
         class
             .find_field(field_reference.field_name)
             .ok_or(VmError::FieldNotFoundException(
@@ -809,12 +1115,21 @@ impl<'a> CallFrame<'a> {
         let receiver = self.pop()?;
         match receiver {
             Value::Object(object) if object.kind() == ObjectKind::Array => Ok(object),
+            Null => Err(MethodCallFailed::InternalError(
+                VmError::NullPointerException,
+            )),
             _ => Err(MethodCallFailed::InternalError(
                 VmError::ValidationException,
             )),
         }
     }
 
+    /// Array indexes are ints on the stack, and negative ones are out of bounds too
+    fn to_array_index(array: &impl Array<'a>, index: i32) -> Result<usize, VmError> {
+        usize::try_from(index)
+            .map_err(|_| VmError::ArrayIndexOutOfBoundsException(index.into(), array.len()))
+    }
+
     fn get_constant(&self, constant_index: u16) -> Result<&ConstantPoolEntry, VmError> {
         self.class_and_method
             .class
@@ -835,12 +1150,12 @@ impl<'a> CallFrame<'a> {
     fn get_constant_method_reference(
         &self,
         constant_index: u16,
-    ) -> Result<MethodReference, VmError> {
+    ) -> Result<MethodReference<'_>, VmError> {
         let constant = self.get_constant(constant_index)?;
 
-        let (class_name_index, name_and_type_descriptor_index) = match *constant {
-            ConstantPoolEntry::MethodReference(c, n) => (c, n),
-            ConstantPoolEntry::InterfaceMethodReference(c, n) => (c, n),
+        let (class_name_index, name_and_type_descriptor_index, interface) = match *constant {
+            ConstantPoolEntry::MethodReference(c, n) => (c, n, false),
+            ConstantPoolEntry::InterfaceMethodReference(c, n) => (c, n, true),
             _ => return Err(VmError::ValidationException),
         };
 
@@ -855,13 +1170,17 @@ impl<'a> CallFrame<'a> {
                 class_name,
                 method_name,
                 type_descriptor,
+                interface,
             })
         } else {
             Err(VmError::ValidationException)
         }
     }
 
-    fn get_constant_field_reference(&self, constant_index: u16) -> Result<FieldReference, VmError> {
+    fn get_constant_field_reference(
+        &self,
+        constant_index: u16,
+    ) -> Result<FieldReference<'_>, VmError> {
         let constant = self.get_constant(constant_index)?;
         if let &ConstantPoolEntry::FieldReference(
             class_name_index,
@@ -894,105 +1213,88 @@ impl<'a> CallFrame<'a> {
         }
     }
 
-    fn get_method_to_invoke_statically(
+    /// Resolves a method reference as in JVMS 5.4.3.3 and 5.4.3.4, and decides how the
+    /// method to execute will be selected at each invocation
+    fn resolve_method(
         &self,
         vm: &mut Vm<'a>,
-        call_stack: &mut CallStack<'a>,
         method_reference: MethodReference,
         kind: InvokeKind,
-    ) -> Result<ClassAndMethod<'a>, MethodCallFailed<'a>> {
-        let class = vm.get_or_resolve_class(call_stack, method_reference.class_name)?;
-        match kind {
-            InvokeKind::Special | InvokeKind::Static => {
-                Self::get_method_of_class(class, method_reference)
-                    .map(|method| ClassAndMethod { class, method })
-            }
-            InvokeKind::Virtual | InvokeKind::Interface => {
-                Self::get_method_checking_superclasses(class, method_reference)
-            }
+    ) -> Result<(ClassAndMethod<'a>, MethodDispatch), MethodCallFailed<'a>> {
+        let class = vm.get_or_resolve_class(method_reference.class_name)?;
+        let resolve = if method_reference.interface {
+            method_resolution::resolve_interface_method
+        } else {
+            method_resolution::resolve_class_method
+        };
+        let class_and_method = resolve(
+            class,
+            method_reference.method_name,
+            method_reference.type_descriptor,
+        )?;
+
+        let expects_static = matches!(kind, InvokeKind::Static);
+        if class_and_method.is_static() != expects_static {
+            let expected_kind = if expects_static {
+                "static"
+            } else {
+                "non-static"
+            };
+            return Err(MethodCallFailed::InternalError(
+                VmError::IncompatibleClassChangeError(format!(
+                    "Expected {expected_kind} method {}.{}{}",
+                    class_and_method.class.name.replace('/', "."),
+                    class_and_method.method.name,
+                    class_and_method.method.type_descriptor,
+                )),
+            ));
         }
-    }
-
-    fn get_method_of_class<'b>(
-        class: &'b Class<'a>,
-        method_reference: MethodReference,
-    ) -> Result<&'b ClassFileMethod, MethodCallFailed<'a>> {
-        class
-            .find_method(
-                method_reference.method_name,
-                method_reference.type_descriptor,
-            )
-            .ok_or(MethodCallFailed::InternalError(
-                VmError::MethodNotFoundException(
-                    class.name.to_string(),
-                    method_reference.method_name.to_string(),
-                    method_reference.type_descriptor.to_string(),
-                ),
-            ))
-    }
 
-    fn get_method_checking_superclasses<'b>(
-        class: &'b Class<'a>,
-        method_reference: MethodReference,
-    ) -> Result<ClassAndMethod<'b>, MethodCallFailed<'a>> {
-        let mut curr_class = class;
-        loop {
-            if let Some(method) = curr_class.find_method(
-                method_reference.method_name,
-                method_reference.type_descriptor,
-            ) {
-                return Ok(ClassAndMethod {
-                    class: curr_class,
-                    method,
-                });
+        Ok(match kind {
+            InvokeKind::Static => (class_and_method, MethodDispatch::Direct),
+            InvokeKind::Special => (
+                self.select_special_method(class, class_and_method)?,
+                MethodDispatch::Direct,
+            ),
+            InvokeKind::Virtual | InvokeKind::Interface => {
+                let dispatch = Self::get_method_dispatch(&class_and_method)?;
+                (class_and_method, dispatch)
             }
+        })
+    }
 
-            if let Some(superclass) = curr_class.superclass {
-                curr_class = superclass;
-            } else {
-                return Err(MethodCallFailed::InternalError(
-                    VmError::MethodNotFoundException(
-                        class.name.to_string(),
-                        method_reference.method_name.to_string(),
-                        method_reference.type_descriptor.to_string(),
-                    ),
-                ));
+    /// Selects the method executed by `invokespecial`: calls of methods of a superclass,
+    /// like `super.method()`, use the version inherited by the direct superclass of the
+    /// current class rather than the resolved one
+    fn select_special_method(
+        &self,
+        referenced_class: ClassRef<'a>,
+        class_and_method: ClassAndMethod<'a>,
+    ) -> Result<ClassAndMethod<'a>, VmError> {
+        let current_class = self.class_and_method.class;
+        let superclass = match current_class.superclass {
+            Some(superclass)
+                if !referenced_class.is_interface()
+                    && !std::ptr::eq(referenced_class, current_class)
+                    && current_class.is_subclass_of(referenced_class)
+                    && vtable::is_overridable(class_and_method.method) =>
+            {
+                superclass
             }
-        }
+            _ => return Ok(class_and_method),
+        };
+        let method = class_and_method.method;
+        superclass
+            .vtable_slot(&method.name, &method.type_descriptor)
+            .and_then(|slot| superclass.vtable_method(slot))
+            .ok_or_else(|| Self::conflicting_default_methods(&class_and_method))
     }
 
-    fn resolve_virtual_method(
-        vm: &Vm<'a>,
-        receiver: Option<AbstractObject>,
-        class_and_method: ClassAndMethod,
-    ) -> Result<ClassAndMethod<'a>, MethodCallFailed<'a>> {
-        match receiver {
-            Some(receiver) if receiver.kind() == ObjectKind::Object => {
-                let receiver_class = vm.find_class_by_id(receiver.class_id()).ok_or(
-                    VmError::ClassNotFoundException(receiver.class_id().to_string()),
-                )?;
-                let resolved_method = Self::get_method_checking_superclasses(
-                    receiver_class,
-                    MethodReference {
-                        class_name: &class_and_method.class.name,
-                        method_name: &class_and_method.method.name,
-                        type_descriptor: &class_and_method.method.type_descriptor,
-                    },
-                )?;
-                debug!(
-                    "resolved virtual method {}.{}:{} on object of class {}: using version of class {}",
-                    class_and_method.class.name,
-                    class_and_method.method.name,
-                    class_and_method.method.type_descriptor,
-                    receiver_class.name,
-                    resolved_method.class.name,
-                );
-                Ok(resolved_method)
-            }
-            _ => Err(MethodCallFailed::InternalError(
-                VmError::ValidationException,
-            )),
-        }
+    fn conflicting_default_methods(class_and_method: &ClassAndMethod) -> VmError {
+        VmError::IncompatibleClassChangeError(format!(
+            "Conflicting default methods: {}{}",
+            class_and_method.method.name, class_and_method.method.type_descriptor
+        ))
     }
 
     fn get_method_receiver_and_params(
@@ -1052,11 +1354,12 @@ impl<'a> CallFrame<'a> {
             Value::Object(object) => {
                 Ok(object.clone())
             }
+            Null => Err(VmError::NullPointerException),
             _ => Err(VmError::ValidationException),
         }
     }
 
-    fn validate_type_opt(
+    pub(crate) fn validate_type_opt(
         vm: &Vm,
         expected_type: Option<FieldType>,
         value: &Option<Value<'a>>,
@@ -1083,10 +1386,11 @@ impl<'a> CallFrame<'a> {
         }
     }
 
-    fn execute_areturn(&mut self) -> MethodCallResult<'a> {
-        let result = self.pop()?;
-        self.debug_done_execution(Some(&result));
-        Ok(Some(result))
+    fn execute_areturn(&mut self, vm: &Vm<'a>) -> MethodCallResult<'a> {
+        let result = Some(self.pop()?);
+        Self::validate_type_opt(vm, self.class_and_method.return_type(), &result)?;
+        self.debug_done_execution(result.as_ref());
+        Ok(result)
     }
 
     generate_execute_return!(execute_ireturn, Int);
@@ -1130,18 +1434,49 @@ impl<'a> CallFrame<'a> {
             || ((a == 0f64 || a == -0f64) && (b == 0f64 || b == -0f64))
     }
 
-    generate_execute_neg!(execute_ineg, pop_int, Int);
-    generate_execute_neg!(execute_lneg, pop_long, Long);
-    generate_execute_neg!(execute_fneg, pop_float, Float);
-    generate_execute_neg!(execute_dneg, pop_double, Double);
+    // Negating the minimum value of an integral type overflows to itself
+    generate_execute_neg!(execute_ineg, pop_int, Int, i32::wrapping_neg);
+    generate_execute_neg!(execute_lneg, pop_long, Long, i64::wrapping_neg);
+    generate_execute_neg!(execute_fneg, pop_float, Float, |value: f32| -value);
+    generate_execute_neg!(execute_dneg, pop_double, Double, |value: f64| -value);
 
     generate_execute_coerce!(coerce_int, pop_int, i32);
     generate_execute_coerce!(coerce_long, pop_long, i64);
     generate_execute_coerce!(coerce_float, pop_float, f32);
     generate_execute_coerce!(coerce_double, pop_double, f64);
 
-    fn goto(&mut self, jump_address: u16) {
-        self.pc = ProgramCounter(jump_address);
+    /// Jumps to the given instruction. The decoded jump instructions contain the index of
+    /// their target instruction, rather than its address.
+    fn goto(&mut self, target_index: u16) {
+        self.instruction_index = target_index.into_usize_safe();
+    }
+
+    /// Returns the address of the next instruction to execute
+    fn pc(&self) -> ProgramCounter {
+        self.code.address(self.instruction_index)
+    }
+
+    fn executed_instruction_pc(&self) -> ProgramCounter {
+        self.code.address(self.executed_instruction_index)
+    }
+
+    fn execute_jsr(&mut self, jump_address: u16) -> Result<(), MethodCallFailed<'a>> {
+        // The pc has already been moved to the instruction following the jsr
+        self.push(Value::ReturnAddress(self.pc()))?;
+        self.goto(jump_address);
+        Ok(())
+    }
+
+    fn execute_ret(&mut self, index: usize) -> Result<(), MethodCallFailed<'a>> {
+        match self.locals.get(index) {
+            Some(&Value::ReturnAddress(return_address)) => {
+                self.instruction_index = self.code.instruction_index(return_address)?;
+                Ok(())
+            }
+            _ => Err(MethodCallFailed::InternalError(
+                VmError::ValidationException,
+            )),
+        }
     }
 
     fn execute_if<T>(
@@ -1261,7 +1596,8 @@ impl<'a> CallFrame<'a> {
     fn execute_astore(&mut self, index: usize) -> Result<(), MethodCallFailed<'a>> {
         let value = self.pop()?;
         match value {
-            Value::Object(..) => {
+            // astore is also used to store the return address pushed by jsr
+            Value::Object(..) | Value::ReturnAddress(_) => {
                 self.locals[index] = value;
                 Ok(())
             }
@@ -1294,7 +1630,7 @@ impl<'a> CallFrame<'a> {
                 let constant = self.get_constant(*string_index)?;
                 match constant {
                     ConstantPoolEntry::Utf8(string) => {
-                        let string_object = new_java_lang_string_object(vm, call_stack, string)?;
+                        let string_object = vm.intern_string(call_stack, string)?;
                         self.push(Value::Object(string_object))
                     }
                     _ => Err(MethodCallFailed::InternalError(
@@ -1306,7 +1642,7 @@ impl<'a> CallFrame<'a> {
                 let constant = self.get_constant(*class_index)?;
                 match constant {
                     ConstantPoolEntry::Utf8(class_name) => {
-                        let class_object = new_java_lang_class_object(vm, call_stack, class_name)?;
+                        let class_object = vm.get_class_mirror(call_stack, class_name)?;
                         self.push(Value::Object(class_object))
                     }
                     _ => Err(MethodCallFailed::InternalError(
@@ -1336,7 +1672,7 @@ impl<'a> CallFrame<'a> {
         vm: &mut Vm<'a>,
         array_type: NewArrayType,
     ) -> Result<(), MethodCallFailed<'a>> {
-        let length = self.pop_int()?.into_usize_safe();
+        let length = self.pop_array_length()?;
         let elements_type = match array_type {
             NewArrayType::Boolean => ArrayEntryType::Base(BaseType::Boolean),
             NewArrayType::Char => ArrayEntryType::Base(BaseType::Char),
@@ -1355,18 +1691,109 @@ impl<'a> CallFrame<'a> {
     fn execute_anewarray(
         &mut self,
         vm: &mut Vm<'a>,
-        call_stack: &mut CallStack<'a>,
         constant_index: u16,
     ) -> Result<(), MethodCallFailed<'a>> {
-        let length = self.pop_int()?.into_usize_safe();
+        let length = self.pop_array_length()?;
         let class_name = self.get_constant_class_reference(constant_index)?;
-        let class = vm.get_or_resolve_class(call_stack, class_name)?;
-        let elements_type = ArrayEntryType::Object(class.id);
+        let elements_type = if class_name.starts_with('[') {
+            let field_type =
+                FieldType::parse(class_name).map_err(|_| VmError::ValidationException)?;
+            Self::resolve_array_entry_type(vm, &field_type)?
+        } else {
+            let class = vm.get_or_resolve_class(class_name)?;
+            ArrayEntryType::Object(class.id)
+        };
 
         let array = vm.new_array(elements_type, length);
         self.push(Value::Object(array))
     }
 
+    fn execute_multianewarray(
+        &mut self,
+        vm: &mut Vm<'a>,
+        constant_index: u16,
+        dimensions: u8,
+    ) -> Result<(), MethodCallFailed<'a>> {
+        let mut lengths = Vec::with_capacity(dimensions.into());
+        for _ in 0..dimensions {
+            lengths.push(self.pop_array_length()?);
+        }
+        lengths.reverse();
+
+        let class_name = self.get_constant_class_reference(constant_index)?;
+        let array_type = match FieldType::parse(class_name) {
+            Ok(FieldType::Array(component_type)) => *component_type,
+            _ => return Err(MethodCallFailed::InternalError(VmError::ValidationException)),
+        };
+        let elements_type = Self::resolve_array_entry_type(vm, &array_type)?;
+        self.push_multi_dimensional_array(vm, elements_type, &lengths)
+    }
+
+    /// Allocates the array and, recursively, all its sub-arrays for the following dimensions.
+    /// Since allocating can trigger a garbage collection, the arrays being filled are kept
+    /// on the stack, so that they are gc roots and their address gets updated if they move.
+    fn push_multi_dimensional_array(
+        &mut self,
+        vm: &mut Vm<'a>,
+        elements_type: ArrayEntryType,
+        lengths: &[usize],
+    ) -> Result<(), MethodCallFailed<'a>> {
+        let (&length, sub_arrays_lengths) = lengths
+            .split_first()
+            .ok_or(VmError::ValidationException)?;
+        let array = vm.new_array(elements_type.clone(), length);
+        self.push(Value::Object(array))?;
+
+        if !sub_arrays_lengths.is_empty() {
+            let sub_arrays_elements_type = elements_type
+                .component_type()
+                .ok_or(VmError::ValidationException)?;
+            for index in 0..length {
+                self.push_multi_dimensional_array(
+                    vm,
+                    sub_arrays_elements_type.clone(),
+                    sub_arrays_lengths,
+                )?;
+                let sub_array = self.pop()?;
+                match &self.stack[self.stack.len() - 1] {
+                    Value::Object(array) => array.set_element(index, sub_array)?,
+                    _ => {
+                        return Err(MethodCallFailed::InternalError(
+                            VmError::ValidationException,
+                        ))
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_array_entry_type(
+        vm: &mut Vm<'a>,
+        field_type: &FieldType,
+    ) -> Result<ArrayEntryType, MethodCallFailed<'a>> {
+        Ok(match field_type {
+            Base(base_type) => ArrayEntryType::Base(base_type.clone()),
+            FieldType::Object(class_name) => {
+                ArrayEntryType::Object(vm.get_or_resolve_class(class_name)?.id)
+            }
+            FieldType::Array(component_type) => {
+                Self::resolve_array_entry_type(vm, component_type)?.array_of()
+            }
+        })
+    }
+
+    fn pop_array_length(&mut self) -> Result<usize, MethodCallFailed<'a>> {
+        let length = self.pop_int()?;
+        if length < 0 {
+            Err(MethodCallFailed::InternalError(
+                VmError::NegativeArraySizeException(length),
+            ))
+        } else {
+            Ok(length.into_usize_safe())
+        }
+    }
+
     fn execute_array_length(&mut self) -> Result<(), MethodCallFailed<'a>> {
         let array = self.pop_array()?;
         let len = array.len() as i32;
@@ -1385,7 +1812,11 @@ impl<'a> CallFrame<'a> {
     generate_execute_array_load!(execute_laload, ArrayEntryType::Base(BaseType::Long));
     generate_execute_array_load!(execute_faload, ArrayEntryType::Base(BaseType::Float));
     generate_execute_array_load!(execute_daload, ArrayEntryType::Base(BaseType::Double));
-    generate_execute_array_load!(execute_aaload, ArrayEntryType::Object(..));
+    generate_execute_array_load!(
+        execute_aaload,
+        ArrayEntryType::Object(..),
+        ArrayEntryType::Array(..)
+    );
 
     generate_execute_array_store!(
         execute_bastore,
@@ -1433,16 +1864,15 @@ impl<'a> CallFrame<'a> {
 
     fn execute_aastore(&mut self, vm: &Vm) -> Result<(), MethodCallFailed<'a>> {
         let value = self.pop_object_or_null()?;
-        let index = self.pop_int()?.into_usize_safe();
+        let index = self.pop_int()?;
         let array = self.pop_array()?;
+        let index = Self::to_array_index(&array, index)?;
         match array.elements_type() {
-            ArrayEntryType::Object(elements_class_id) => {
-                let elements_class_name = vm.get_class_by_id(elements_class_id)?;
-                Self::validate_type(
-                    vm,
-                    FieldType::Object(elements_class_name.name.clone()),
-                    &value,
-                )?;
+            elements_type @ (ArrayEntryType::Object(_) | ArrayEntryType::Array(..)) => {
+                let elements_field_type = elements_type
+                    .into_field_type(vm)
+                    .ok_or(VmError::ValidationException)?;
+                Self::validate_type(vm, elements_field_type, &value)?;
                 array.set_element(index, value)?
             }
             _ => {
@@ -1457,78 +1887,75 @@ impl<'a> CallFrame<'a> {
     fn execute_instanceof(
         &mut self,
         vm: &mut Vm<'a>,
-        call_stack: &mut CallStack<'a>,
         constant_index: u16,
     ) -> Result<(), MethodCallFailed<'a>> {
         let value = self.pop()?;
-        let is_instance_of = self.is_instanceof(vm, call_stack, constant_index, &value)?;
+        let is_instance_of = self.is_instanceof(vm, constant_index, &value)?;
         self.push(Int(is_instance_of as i32))
     }
 
     fn execute_checkcast(
         &mut self,
         vm: &mut Vm<'a>,
-        call_stack: &mut CallStack<'a>,
         constant_index: u16,
     ) -> Result<(), MethodCallFailed<'a>> {
         let value = self.pop()?;
-        let is_instance_of = self.is_instanceof(vm, call_stack, constant_index, &value)?;
-        if is_instance_of {
+        // Unlike instanceof, checkcast accepts null
+        if value == Null || self.is_instanceof(vm, constant_index, &value)? {
             self.push(value)
         } else {
-            Err(MethodCallFailed::InternalError(VmError::ClassCastException))
+            let class_name = match &value {
+                Value::Object(object) => Self::get_class_name_of(vm, object)?,
+                _ => {
+                    return Err(MethodCallFailed::InternalError(
+                        VmError::ValidationException,
+                    ))
+                }
+            };
+            let target_class_name = self.get_constant_class_reference(constant_index)?;
+            Err(MethodCallFailed::InternalError(
+                VmError::ClassCastException(class_name, target_class_name.to_string()),
+            ))
+        }
+    }
+
+    /// Returns the name of the class of the given object, using descriptors for arrays
+    /// like `Class.getName`
+    pub(crate) fn get_class_name_of(
+        vm: &Vm<'a>,
+        object: &AbstractObject<'a>,
+    ) -> Result<String, VmError> {
+        match object.kind() {
+            ObjectKind::Object => Ok(vm.get_class_by_id(object.class_id())?.name.clone()),
+            ObjectKind::Array => {
+                let elements_type = object
+                    .elements_type()
+                    .into_field_type(vm)
+                    .ok_or(VmError::ValidationException)?;
+                Ok(format!("[{}", elements_type.descriptor()))
+            }
         }
     }
 
+    /// Checks whether a value is an instance of the class or array type referenced by the
+    /// given constant, as `instanceof` and `checkcast` do
     fn is_instanceof(
         &mut self,
         vm: &mut Vm<'a>,
-        call_stack: &mut CallStack<'a>,
         constant_index: u16,
         value: &Value<'a>,
     ) -> Result<bool, MethodCallFailed<'a>> {
-        let class_name = self.get_constant_class_reference(constant_index)?;
-
-        let (is_array, expected_class) = {
-            if class_name.starts_with("[L") && class_name.ends_with(';') {
-                (
-                    true,
-                    vm.get_or_resolve_class(call_stack, &class_name[2..class_name.len() - 1])?,
-                )
-            } else {
-                (false, vm.get_or_resolve_class(call_stack, class_name)?)
-            }
-        };
-
-        let is_instance_of = match &value {
-            Null => false,
-
-            Value::Object(object) => match object.kind() {
-                ObjectKind::Object => {
-                    if is_array {
-                        false
-                    } else {
-                        let object_class = vm.get_class_by_id(object.class_id())?;
-                        object_class.is_subclass_of(expected_class)
-                    }
-                }
-                ObjectKind::Array => match object.elements_type() {
-                    ArrayEntryType::Base(_) => false,
-                    ArrayEntryType::Object(elements_class_id) => {
-                        let components_class = vm.get_class_by_id(elements_class_id)?;
-                        components_class.is_subclass_of(expected_class)
-                    }
-                    ArrayEntryType::Array => false,
-                },
-            },
-
-            _ => {
-                return Err(MethodCallFailed::InternalError(
-                    VmError::ValidationException,
-                ))
+        match value {
+            Null => Ok(false),
+            Value::Object(object) => {
+                let class_name = self.get_constant_class_reference(constant_index)?;
+                let object_class_name = Self::get_class_name_of(vm, object)?;
+                is_assignable(vm, class_name, &object_class_name)
             }
-        };
-        Ok(is_instance_of)
+            _ => Err(MethodCallFailed::InternalError(
+                VmError::ValidationException,
+            )),
+        }
     }
 
     fn execute_getfield(
@@ -1537,13 +1964,17 @@ impl<'a> CallFrame<'a> {
         field_index: u16,
     ) -> Result<(), MethodCallFailed<'a>> {
         let object = self.pop()?;
+        if object == Null {
+            return Err(MethodCallFailed::InternalError(
+                VmError::NullPointerException,
+            ));
+        }
         if let Value::Object(object_ref) = object {
             if object_ref.kind() == ObjectKind::Object {
-                let field_reference = self.get_constant_field_reference(field_index)?;
-                let object_class = vm.get_class_by_id(object_ref.class_id())?;
-                let (index, field) = Self::get_field(object_class, field_reference)?;
-                let field_value = object_ref.get_field(object_class, index);
-                Self::validate_type(vm, field.type_descriptor.clone(), &field_value)?;
+                let field = self.resolve_field_reference(vm, field_index)?;
+                let object_class = Self::get_class_with_field(vm, &object_ref, field)?;
+                let field_value = object_ref.get_field(object_class, field.index);
+                Self::validate_type(vm, field.field.type_descriptor.clone(), &field_value)?;
                 self.push(field_value)?;
                 return Ok(());
             }
@@ -1560,13 +1991,17 @@ impl<'a> CallFrame<'a> {
     ) -> Result<(), MethodCallFailed<'a>> {
         let value = self.pop()?;
         let object = self.pop()?;
+        if object == Null {
+            return Err(MethodCallFailed::InternalError(
+                VmError::NullPointerException,
+            ));
+        }
         if let Value::Object(object_ref) = object {
             if object_ref.kind() == ObjectKind::Object {
-                let field_reference = self.get_constant_field_reference(field_index)?;
-                let object_class = vm.get_class_by_id(object_ref.class_id())?;
-                let (index, field) = Self::get_field(object_class, field_reference)?;
-                Self::validate_type(vm, field.type_descriptor.clone(), &value)?;
-                object_ref.set_field(index, value);
+                let field = self.resolve_field_reference(vm, field_index)?;
+                Self::get_class_with_field(vm, &object_ref, field)?;
+                Self::validate_type(vm, field.field.type_descriptor.clone(), &value)?;
+                object_ref.set_field(field.index, value);
                 return Ok(());
             }
         }
@@ -1575,22 +2010,39 @@ impl<'a> CallFrame<'a> {
         ))
     }
 
+    /// Checks that no other thread is initializing a class used by the current instruction.
+    /// Otherwise the thread waits for it, and executes the instruction again when it resumes.
+    fn await_class_initialization(
+        &mut self,
+        vm: &mut Vm<'a>,
+        call_stack: &CallStack<'a>,
+        class: ClassRef<'a>,
+    ) -> bool {
+        if vm.await_class_initialization(call_stack, class) {
+            return true;
+        }
+        self.instruction_index = self.executed_instruction_index;
+        false
+    }
+
     fn execute_getstatic(
         &mut self,
         vm: &mut Vm<'a>,
         call_stack: &mut CallStack<'a>,
         field_index: u16,
-    ) -> Result<(), MethodCallFailed<'a>> {
-        let field_reference = self.get_constant_field_reference(field_index)?;
-        let object_class = vm.get_or_resolve_class(call_stack, field_reference.class_name)?;
-        let (index, field) = Self::get_field(object_class, field_reference)?;
-        let object = vm.get_static_instance(self.class_and_method.class.id);
+    ) -> Result<InstructionCompleted<'a>, MethodCallFailed<'a>> {
+        let field = self.resolve_field_reference(vm, field_index)?;
+        if !self.await_class_initialization(vm, call_stack, field.class) {
+            return Ok(Yield);
+        }
+        vm.initialize_class(call_stack, field.class)?;
+        let object = vm.get_static_instance(field.class.id);
         if let Some(object_ref) = object {
             if object_ref.kind() == ObjectKind::Object {
-                let field_value = object_ref.get_field(object_class, index);
-                Self::validate_type(vm, field.type_descriptor.clone(), &field_value)?;
+                let field_value = object_ref.get_field(field.class, field.index);
+                Self::validate_type(vm, field.field.type_descriptor.clone(), &field_value)?;
                 self.push(field_value)?;
-                return Ok(());
+                return Ok(ContinueMethodExecution);
             }
         }
         Err(MethodCallFailed::InternalError(
@@ -1598,22 +2050,38 @@ impl<'a> CallFrame<'a> {
         ))
     }
 
+    /// Returns the class of the object, checking that it has the resolved field
+    fn get_class_with_field(
+        vm: &Vm<'a>,
+        object: &AbstractObject<'a>,
+        field: ResolvedField<'a>,
+    ) -> Result<ClassRef<'a>, VmError> {
+        let object_class = vm.get_class_by_id(object.class_id())?;
+        if field.index < object_class.num_total_fields {
+            Ok(object_class)
+        } else {
+            Err(VmError::ValidationException)
+        }
+    }
+
     fn execute_putstatic(
         &mut self,
         vm: &mut Vm<'a>,
         call_stack: &mut CallStack<'a>,
         field_index: u16,
-    ) -> Result<(), MethodCallFailed<'a>> {
-        let field_reference = self.get_constant_field_reference(field_index)?;
-        let object_class = vm.get_or_resolve_class(call_stack, field_reference.class_name)?;
-        let (index, field) = Self::get_field(object_class, field_reference)?;
+    ) -> Result<InstructionCompleted<'a>, MethodCallFailed<'a>> {
+        let field = self.resolve_field_reference(vm, field_index)?;
+        if !self.await_class_initialization(vm, call_stack, field.class) {
+            return Ok(Yield);
+        }
+        vm.initialize_class(call_stack, field.class)?;
         let value = self.pop()?;
-        Self::validate_type(vm, field.type_descriptor.clone(), &value)?;
-        let object = vm.get_static_instance(self.class_and_method.class.id);
+        Self::validate_type(vm, field.field.type_descriptor.clone(), &value)?;
+        let object = vm.get_static_instance(field.class.id);
         if let Some(object_ref) = object {
             if object_ref.kind() == ObjectKind::Object {
-                object_ref.set_field(index, value);
-                return Ok(());
+                object_ref.set_field(field.index, value);
+                return Ok(ContinueMethodExecution);
             }
         }
         Err(MethodCallFailed::InternalError(
@@ -1621,24 +2089,37 @@ impl<'a> CallFrame<'a> {
         ))
     }
 
-    fn execute_monitorenter(&mut self) -> Result<(), MethodCallFailed<'a>> {
+    fn execute_monitorenter(
+        &mut self,
+        vm: &mut Vm<'a>,
+        call_stack: &CallStack<'a>,
+    ) -> Result<InstructionCompleted<'a>, MethodCallFailed<'a>> {
         let obj = self.pop()?;
         match obj {
-            Value::Object(_) => {
-                Ok(())
+            // If the thread blocks, it will own the monitor when it runs again
+            Value::Object(object) if vm.enter_monitor(call_stack, &object) => {
+                Ok(ContinueMethodExecution)
             }
+            Value::Object(_) => Ok(Yield),
+            Null => Err(MethodCallFailed::InternalError(
+                VmError::NullPointerException,
+            )),
             _ => Err(MethodCallFailed::InternalError(
                 VmError::ValidationException,
             )),
         }
     }
 
-    fn execute_monitorexit(&mut self) -> Result<(), MethodCallFailed<'a>> {
+    fn execute_monitorexit(
+        &mut self,
+        call_stack: &CallStack<'a>,
+    ) -> Result<(), MethodCallFailed<'a>> {
         let obj = self.pop()?;
         match obj {
-            Value::Object(_) => {
-                Ok(())
-            }
+            Value::Object(object) => Ok(object.exit_monitor(call_stack.thread_id())?),
+            Null => Err(MethodCallFailed::InternalError(
+                VmError::NullPointerException,
+            )),
             _ => Err(MethodCallFailed::InternalError(
                 VmError::ValidationException,
             )),
@@ -1651,16 +2132,77 @@ impl<'a> CallFrame<'a> {
             Value::Object(exception) => {
                 Err(MethodCallFailed::ExceptionThrown(JavaException(exception)))
             },
+            Null => Err(MethodCallFailed::InternalError(
+                VmError::NullPointerException,
+            )),
             _ => Err(MethodCallFailed::InternalError(
                 VmError::ValidationException,
             )),
         }
     }
 
+    /// Creates an exception for an error of the Java runtime raised while executing an
+    /// instruction. The operand stack gets discarded when throwing anyway, so we use it to
+    /// keep the exception reachable, since creating the message and invoking the
+    /// constructor could trigger a garbage collection and move it.
+    fn new_java_exception(
+        &mut self,
+        vm: &mut Vm<'a>,
+        call_stack: &mut CallStack<'a>,
+        exception_class_name: &str,
+        message: Option<String>,
+    ) -> Result<JavaException<'a>, MethodCallFailed<'a>> {
+        self.stack.truncate(0)?;
+        let exception = vm.new_object(call_stack, exception_class_name)?;
+        self.push(Value::Object(exception))?;
+
+        let constructor_descriptor = match message {
+            Some(_) => "(Ljava/lang/String;)V",
+            None => "()V",
+        };
+        let constructor = vm.resolve_class_method(
+            call_stack,
+            exception_class_name,
+            "<init>",
+            constructor_descriptor,
+        )?;
+        let args = match message {
+            Some(message) => vec![Value::Object(new_java_lang_string_object(
+                vm, call_stack, &message,
+            )?)],
+            None => Vec::new(),
+        };
+        let exception = self.get_object_from_stack(0, constructor.class)?;
+        vm.invoke(call_stack, constructor, Some(exception), args)?;
+
+        match self.pop()? {
+            Value::Object(exception) => Ok(JavaException(exception)),
+            _ => Err(MethodCallFailed::InternalError(
+                VmError::ValidationException,
+            )),
+        }
+    }
+
+    /// Creating the error needs some frames, so it can use the ones reserved on the call stack
+    fn new_stack_overflow_error(
+        &mut self,
+        vm: &mut Vm<'a>,
+        call_stack: &mut CallStack<'a>,
+    ) -> Result<JavaException<'a>, MethodCallFailed<'a>> {
+        if !call_stack.use_reserved_frames() {
+            return Err(MethodCallFailed::InternalError(
+                VmError::StackOverflowError,
+            ));
+        }
+        let result =
+            self.new_java_exception(vm, call_stack, "java/lang/StackOverflowError", None);
+        call_stack.release_reserved_frames();
+        result
+    }
+
     fn find_exception_handler(
         &self,
         vm: &mut Vm<'a>,
-        call_stack: &mut CallStack<'a>,
         executed_instruction_pc: ProgramCounter,
         exception: &JavaException<'a>,
     ) -> Result<Option<ProgramCounter>, MethodCallFailed<'a>> {
@@ -1678,7 +2220,7 @@ impl<'a> CallFrame<'a> {
             match &catch_handler.catch_class {
                 None => return Ok(Some(catch_handler.handler_pc)),
                 Some(class_name) => {
-                    let catch_class = vm.get_or_resolve_class(call_stack, class_name)?;
+                    let catch_class = vm.get_or_resolve_class(class_name)?;
                     let exception_class = vm.get_class_by_id(exception.0.class_id())?;
                     if exception_class.is_subclass_of(catch_class) {
                         return Ok(Some(catch_handler.handler_pc));
@@ -1701,7 +2243,7 @@ impl<'a> CallFrame<'a> {
             "FRAME STATUS: executing {} signature {} pc: {}",
             self.to_stack_trace_element(),
             self.class_and_method.method.type_descriptor,
-            self.pc
+            self.pc()
         );
         debug!("  stack:");
         for stack_entry in self.stack.iter() {
@@ -1731,6 +2273,11 @@ impl<'a> CallFrame<'a> {
             Value::Object(o) => Some(o as *mut AbstractObject),
             _ => None,
         }));
+        roots.extend(
+            self.synchronized_on
+                .iter_mut()
+                .map(|o| o as *mut AbstractObject),
+        );
         roots.into_iter()
     }
 }
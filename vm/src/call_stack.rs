@@ -1,21 +1,39 @@
 use rjvm_reader::{
     class_file_method::ClassFileMethodCode, method_flags::MethodFlags, type_conversion::ToUsizeSafe,
 };
-use typed_arena::Arena;
 
 use crate::{
-    abstract_object::AbstractObject,
-    call_frame::CallFrame,
-    class_and_method::ClassAndMethod,
-    stack_trace_element::StackTraceElement,
-    value::Value,
-    vm_error::VmError,
+    abstract_object::AbstractObject, call_frame::CallFrame, class_and_method::ClassAndMethod,
+    stack_trace_element::StackTraceElement, value::Value, vm_error::VmError,
 };
 
-#[derive(Default)]
+/// Number of frames that can be pushed beyond the maximum depth while the
+/// `StackOverflowError` is created, so that its constructor can run
+const STACK_OVERFLOW_RESERVED_FRAMES: usize = 32;
+
+/// Identifies the thread executing a call stack, for example as owner of a monitor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ThreadId(u32);
+
+impl ThreadId {
+    /// Ids start from one, since zero marks monitors without owner
+    pub fn new(id: u32) -> Self {
+        assert_ne!(0, id, "thread ids must not be zero");
+        Self(id)
+    }
+
+    pub fn as_u32(&self) -> u32 {
+        self.0
+    }
+}
+
 pub struct CallStack<'a> {
-    frames: Vec<CallFrameReference<'a>>,
-    allocator: Arena<CallFrame<'a>>,
+    // Frames are boxed since they are referenced by pointer while the stack grows
+    #[allow(clippy::vec_box)]
+    frames: Vec<Box<CallFrame<'a>>>,
+    maximum_depth: usize,
+    using_reserved_frames: bool,
+    thread_id: ThreadId,
 }
 
 #[derive(Debug, Clone)]
@@ -34,8 +52,17 @@ impl<'a> AsMut<CallFrame<'a>> for CallFrameReference<'a> {
 }
 
 impl<'a> CallStack<'a> {
-    pub fn new() -> Self {
-        Default::default()
+    pub fn new(maximum_depth: usize, thread_id: ThreadId) -> Self {
+        Self {
+            frames: Vec::new(),
+            maximum_depth,
+            using_reserved_frames: false,
+            thread_id,
+        }
+    }
+
+    pub fn thread_id(&self) -> ThreadId {
+        self.thread_id
     }
 
     pub fn add_frame(
@@ -45,15 +72,62 @@ impl<'a> CallStack<'a> {
         args: Vec<Value<'a>>,
     ) -> Result<CallFrameReference<'a>, VmError> {
         Self::check_receiver(&class_and_method, receiver.clone())?;
+        self.check_depth()?;
         let code = Self::get_code(&class_and_method)?;
+        let decoded_code = class_and_method
+            .class
+            .decoded_code(class_and_method.method_index)?;
         let locals = Self::prepare_locals(code, receiver, args);
-        let new_frame = self
-            .allocator
-            .alloc(CallFrame::new(class_and_method, locals));
+        self.frames.push(Box::new(CallFrame::new(
+            class_and_method,
+            decoded_code,
+            locals,
+        )));
+        self.top_frame()
+    }
+
+    fn check_depth(&self) -> Result<(), VmError> {
+        let maximum_depth = if self.using_reserved_frames {
+            self.maximum_depth + STACK_OVERFLOW_RESERVED_FRAMES
+        } else {
+            self.maximum_depth
+        };
+        if self.frames.len() >= maximum_depth {
+            Err(VmError::StackOverflowError)
+        } else {
+            Ok(())
+        }
+    }
 
-        let reference = CallFrameReference(new_frame);
-        self.frames.push(reference.clone());
-        Ok(reference)
+    /// Allows pushing some frames beyond the maximum depth, to create the
+    /// `StackOverflowError`. Returns false if the reserved frames are already in use,
+    /// i.e. if we overflowed again while creating the error.
+    pub(crate) fn use_reserved_frames(&mut self) -> bool {
+        !std::mem::replace(&mut self.using_reserved_frames, true)
+    }
+
+    pub(crate) fn release_reserved_frames(&mut self) {
+        self.using_reserved_frames = false;
+    }
+
+    pub fn depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns the method executed by the frame at the given depth, counting from the top
+    pub(crate) fn method_at_depth(&self, depth: usize) -> Option<ClassAndMethod<'a>> {
+        self.frames
+            .iter()
+            .rev()
+            .nth(depth)
+            .map(|frame| frame.class_and_method())
+    }
+
+    pub fn top_frame(&mut self) -> Result<CallFrameReference<'a>, VmError> {
+        self.frames
+            .last_mut()
+            .map(|frame| CallFrameReference(frame.as_mut()))
+            .ok_or(VmError::ValidationException)
     }
 
     fn check_receiver(
@@ -89,7 +163,7 @@ impl<'a> CallStack<'a> {
         let mut locals: Vec<Value<'a>> = receiver
             .map(Value::Object)
             .into_iter()
-            .chain(args.into_iter())
+            .chain(args)
             .collect();
         while locals.len() < code.max_locals.into_usize_safe() {
             locals.push(Value::Uninitialized);
@@ -97,28 +171,26 @@ impl<'a> CallStack<'a> {
         locals
     }
 
+    /// Pops the top frame, exiting the monitor entered by its method if it is synchronized
     pub fn pop_frame(&mut self) -> Result<(), VmError> {
-        self.frames
-            .pop()
-            .map(|_| ())
-            .ok_or(VmError::ValidationException)
+        let frame = self.frames.pop().ok_or(VmError::ValidationException)?;
+        match frame.synchronized_on() {
+            Some(monitor) => monitor.exit_monitor(self.thread_id),
+            None => Ok(()),
+        }
     }
 
     pub fn get_stack_trace_elements(&self) -> Vec<StackTraceElement<'a>> {
         self.frames
             .iter()
             .rev()
-            .map(|frame| frame.as_ref().to_stack_trace_element())
+            .map(|frame| frame.to_stack_trace_element())
             .collect()
     }
 
     pub fn gc_roots(&mut self) -> impl Iterator<Item = *mut AbstractObject<'a>> {
         let mut roots = vec![];
-        roots.extend(
-            self.frames
-                .iter_mut()
-                .flat_map(|frame| frame.as_mut().gc_roots()),
-        );
+        roots.extend(self.frames.iter_mut().flat_map(|frame| frame.gc_roots()));
         roots.into_iter()
     }
 }
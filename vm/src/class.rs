@@ -1,10 +1,23 @@
-use std::{fmt, fmt::Formatter};
+use std::{
+    cell::{Cell, OnceCell},
+    fmt,
+    fmt::Formatter,
+};
 
 use rjvm_reader::{
-    class_access_flags::ClassAccessFlags, class_file_field::ClassFileField,
+    bootstrap_method::BootstrapMethod, class_access_flags::ClassAccessFlags, class_file_field::ClassFileField,
     class_file_method::ClassFileMethod, constant_pool::ConstantPool,
 };
 
+use crate::{
+    call_stack::ThreadId,
+    class_and_method::ClassAndMethod,
+    decoded_code::DecodedCode,
+    resolved_references::ResolvedReferences,
+    vm_error::VmError,
+    vtable::{Itables, VtableEntry},
+};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(transparent)]
 pub struct ClassId(u32);
@@ -39,6 +52,26 @@ pub struct Class<'a> {
     pub methods: Vec<ClassFileMethod>,
     pub first_field_index: usize,
     pub num_total_fields: usize,
+    pub bootstrap_methods: Vec<BootstrapMethod>,
+    /// The decoded code of each method, filled on its first invocation
+    pub(crate) decoded_code: Vec<OnceCell<DecodedCode<'a>>>,
+    pub(crate) resolved_references: ResolvedReferences<'a>,
+    /// The method selected by an instance of this class for each virtual method slot
+    pub(crate) vtable: Vec<VtableEntry<'a>>,
+    pub(crate) itables: Itables,
+    pub(crate) initialization_state: Cell<InitializationState>,
+}
+
+/// The progress of the initialization of a class, i.e. of the execution of its static
+/// initializer, as in JVMS 5.5. Classes are initialized on their first active use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitializationState {
+    NotInitialized,
+    /// The given thread is executing the static initializer
+    BeingInitialized(ThreadId),
+    Initialized,
+    /// The static initializer failed, so the class cannot be used
+    Erroneous,
 }
 
 pub type ClassRef<'a> = &'a Class<'a>;
@@ -48,7 +81,7 @@ impl<'a> Class<'a> {
         self.name == base.name
             || self
                 .superclass
-                .map_or(false, |superclass| superclass.is_subclass_of(base))
+                .is_some_and(|superclass| superclass.is_subclass_of(base))
             || self.interfaces.iter().any(|intf| intf.is_subclass_of(base))
     }
 
@@ -62,18 +95,96 @@ impl<'a> Class<'a> {
             .find(|method| method.name == method_name && method.type_descriptor == type_descriptor)
     }
 
-    pub fn find_field(&self, field_name: &str) -> Option<(usize, &ClassFileField)> {
+    /// Like `find_method`, but returns the method together with its class and index
+    pub fn find_class_and_method(
+        &'a self,
+        method_name: &str,
+        type_descriptor: &str,
+    ) -> Option<ClassAndMethod<'a>> {
+        self.methods
+            .iter()
+            .position(|method| {
+                method.name == method_name && method.type_descriptor == type_descriptor
+            })
+            .map(|method_index| ClassAndMethod::new(self, method_index))
+    }
+
+    /// Returns the decoded code of the method with the given index, decoding it on its
+    /// first use
+    pub fn decoded_code(&self, method_index: usize) -> Result<&DecodedCode<'a>, VmError> {
+        let decoded_code = self
+            .decoded_code
+            .get(method_index)
+            .ok_or(VmError::ValidationException)?;
+        if let Some(decoded_code) = decoded_code.get() {
+            return Ok(decoded_code);
+        }
+
+        let code = self.methods[method_index]
+            .code
+            .as_ref()
+            .ok_or(VmError::ValidationException)?;
+        let decoded = DecodedCode::decode(&code.code)?;
+        Ok(decoded_code.get_or_init(|| decoded))
+    }
+
+    pub fn is_interface(&self) -> bool {
+        self.flags.contains(ClassAccessFlags::INTERFACE)
+    }
+
+    /// Returns the method selected by an instance of this class for a virtual method slot,
+    /// or `None` if the class inherits conflicting default methods for it
+    pub(crate) fn vtable_method(&'a self, slot: usize) -> Option<ClassAndMethod<'a>> {
+        let entry = self.vtable.get(slot).filter(|entry| !entry.conflicting)?;
+        let class = entry.class.unwrap_or(self);
+        Some(ClassAndMethod::new(class, entry.method_index))
+    }
+
+    /// Returns the slot of the virtual method table used for the methods with the given name
+    /// and descriptor
+    pub(crate) fn vtable_slot(&'a self, method_name: &str, type_descriptor: &str) -> Option<usize> {
+        self.vtable.iter().position(|entry| {
+            let method = &entry.class.unwrap_or(self).methods[entry.method_index];
+            method.name == method_name && method.type_descriptor == type_descriptor
+        })
+    }
+
+    /// Returns the method selected by an instance of this class for the method with the
+    /// given index of one of its interfaces, or `None` if the class does not implement
+    /// the interface or inherits conflicting default methods
+    pub(crate) fn itable_method(
+        &'a self,
+        interface_id: ClassId,
+        method_index: usize,
+    ) -> Option<ClassAndMethod<'a>> {
+        let slot = self.itables.get(&interface_id)?.get(method_index)?;
+        slot.and_then(|slot| self.vtable_method(slot))
+    }
+
+    pub(crate) fn implements(&self, interface_id: ClassId) -> bool {
+        self.itables.contains_key(&interface_id)
+    }
+
+    /// Looks up a field as in JVMS 5.4.3.2: in this class, then in its superinterfaces, and
+    /// then in its superclass. Returns the class declaring the field, which for static
+    /// fields is the one holding the value, and the index of the field.
+    pub fn find_field(
+        &'a self,
+        field_name: &str,
+    ) -> Option<(ClassRef<'a>, usize, &'a ClassFileField)> {
         self.fields
             .iter()
             .enumerate()
             .find(|entry| entry.1.name == field_name)
-            .map(|(index, field)| (index + self.first_field_index, field))
+            .map(|(index, field)| (self, index + self.first_field_index, field))
+            .or_else(|| {
+                self.interfaces
+                    .iter()
+                    .find_map(|interface| interface.find_field(field_name))
+            })
             .or_else(|| {
-                if let Some(superclass) = &self.superclass {
-                    superclass.find_field(field_name)
-                } else {
-                    None
-                }
+                self.superclass
+                    .and_then(|superclass| superclass.find_field(field_name))
             })
     }
 
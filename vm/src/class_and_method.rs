@@ -2,13 +2,22 @@ use rjvm_reader::{class_file_method::ClassFileMethod, field_type::FieldType};
 
 use crate::class::ClassRef;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 pub struct ClassAndMethod<'a> {
     pub class: ClassRef<'a>,
     pub method: &'a ClassFileMethod,
+    /// Position of the method among the methods of its class
+    pub method_index: usize,
 }
 
 impl<'a> ClassAndMethod<'a> {
+    pub fn new(class: ClassRef<'a>, method_index: usize) -> Self {
+        Self {
+            class,
+            method: &class.methods[method_index],
+            method_index,
+        }
+    }
 
     pub fn num_arguments(&self) -> usize {
         self.method.parsed_type_descriptor.num_arguments()
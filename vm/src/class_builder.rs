@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use rjvm_reader::{
+    constant_pool::{ConstantPool, ConstantPoolEntry},
+    field_type::{BaseType, FieldType},
+};
+
+// Helpers to generate classes at runtime, used to link invokedynamic call sites
+
+pub(crate) const LDC_W: u8 = 0x13;
+pub(crate) const LDC2_W: u8 = 0x14;
+pub(crate) const ALOAD_0: u8 = 0x2a;
+pub(crate) const POP: u8 = 0x57;
+pub(crate) const POP2: u8 = 0x58;
+pub(crate) const DUP: u8 = 0x59;
+pub(crate) const ARETURN: u8 = 0xb0;
+pub(crate) const RETURN: u8 = 0xb1;
+pub(crate) const GETFIELD: u8 = 0xb4;
+pub(crate) const INVOKEVIRTUAL: u8 = 0xb6;
+pub(crate) const INVOKESPECIAL: u8 = 0xb7;
+pub(crate) const INVOKESTATIC: u8 = 0xb8;
+pub(crate) const INVOKEINTERFACE: u8 = 0xb9;
+pub(crate) const NEW: u8 = 0xbb;
+pub(crate) const CHECKCAST: u8 = 0xc0;
+
+/// Builds a constant pool, reusing the existing entries when possible
+pub(crate) struct ConstantPoolBuilder {
+    constants: ConstantPool,
+    indexes: HashMap<String, u16>,
+    next_index: u16,
+}
+
+impl Default for ConstantPoolBuilder {
+    fn default() -> Self {
+        Self {
+            constants: Default::default(),
+            indexes: Default::default(),
+            next_index: 1,
+        }
+    }
+}
+
+impl ConstantPoolBuilder {
+    pub fn build(self) -> ConstantPool {
+        self.constants
+    }
+
+    pub fn add(&mut self, entry: ConstantPoolEntry) -> u16 {
+        let key = format!("{entry:?}");
+        if let Some(index) = self.indexes.get(&key) {
+            return *index;
+        }
+        let index = self.next_index;
+        // Long and double constants take up two slots in the pool
+        self.next_index += match entry {
+            ConstantPoolEntry::Long(_) | ConstantPoolEntry::Double(_) => 2,
+            _ => 1,
+        };
+        self.constants.add(entry);
+        self.indexes.insert(key, index);
+        index
+    }
+
+    pub fn utf8(&mut self, string: &str) -> u16 {
+        self.add(ConstantPoolEntry::Utf8(string.to_string()))
+    }
+
+    pub fn string(&mut self, string: &str) -> u16 {
+        let string_index = self.utf8(string);
+        self.add(ConstantPoolEntry::StringReference(string_index))
+    }
+
+    pub fn class(&mut self, class_name: &str) -> u16 {
+        let name_index = self.utf8(class_name);
+        self.add(ConstantPoolEntry::ClassReference(name_index))
+    }
+
+    pub fn name_and_type(&mut self, name: &str, type_descriptor: &str) -> u16 {
+        let name_index = self.utf8(name);
+        let type_descriptor_index = self.utf8(type_descriptor);
+        self.add(ConstantPoolEntry::NameAndTypeDescriptor(
+            name_index,
+            type_descriptor_index,
+        ))
+    }
+
+    pub fn field(&mut self, class_name: &str, field_name: &str, type_descriptor: &str) -> u16 {
+        let class_index = self.class(class_name);
+        let name_and_type_index = self.name_and_type(field_name, type_descriptor);
+        self.add(ConstantPoolEntry::FieldReference(
+            class_index,
+            name_and_type_index,
+        ))
+    }
+
+    pub fn method(
+        &mut self,
+        class_name: &str,
+        method_name: &str,
+        type_descriptor: &str,
+        is_interface: bool,
+    ) -> u16 {
+        let class_index = self.class(class_name);
+        let name_and_type_index = self.name_and_type(method_name, type_descriptor);
+        if is_interface {
+            self.add(ConstantPoolEntry::InterfaceMethodReference(
+                class_index,
+                name_and_type_index,
+            ))
+        } else {
+            self.add(ConstantPoolEntry::MethodReference(
+                class_index,
+                name_and_type_index,
+            ))
+        }
+    }
+}
+
+pub(crate) fn slots(field_type: &FieldType) -> usize {
+    match field_type {
+        FieldType::Base(BaseType::Long) | FieldType::Base(BaseType::Double) => 2,
+        _ => 1,
+    }
+}
+
+pub(crate) fn load_opcode(field_type: &FieldType) -> u8 {
+    match field_type {
+        FieldType::Base(BaseType::Long) => 0x16,   // lload
+        FieldType::Base(BaseType::Float) => 0x17,  // fload
+        FieldType::Base(BaseType::Double) => 0x18, // dload
+        FieldType::Base(_) => 0x15,                // iload
+        _ => 0x19,                                 // aload
+    }
+}
+
+pub(crate) fn return_opcode(field_type: &FieldType) -> u8 {
+    match field_type {
+        FieldType::Base(BaseType::Long) => 0xad,   // lreturn
+        FieldType::Base(BaseType::Float) => 0xae,  // freturn
+        FieldType::Base(BaseType::Double) => 0xaf, // dreturn
+        FieldType::Base(_) => 0xac,                // ireturn
+        _ => 0xb0,                                 // areturn
+    }
+}
@@ -1,17 +1,24 @@
 use core::fmt;
-use std::{collections::HashMap, fmt::Formatter};
+use std::{
+    cell::{Cell, OnceCell},
+    collections::HashMap,
+    fmt::Formatter,
+};
 
 use indexmap::IndexMap;
 use log::debug;
-use rjvm_reader::{class_file::ClassFile, class_reader};
+use rjvm_reader::{class_access_flags::ClassAccessFlags, class_file::ClassFile, class_reader};
 use typed_arena::Arena;
 
 use crate::{
-    class::{Class, ClassId, ClassRef},
+    class::{Class, ClassId, ClassRef, InitializationState},
     class_loader::ClassLoader,
     class_path::{ClassPath, ClassPathParseError},
     class_resolver_by_id::ClassByIdResolver,
+    resolved_references::ResolvedReferences,
     vm_error::VmError,
+    vtable,
+    vtable::Itables,
 };
 
 pub(crate) struct ClassManager<'a> {
@@ -45,27 +52,6 @@ impl<'a> fmt::Debug for ClassManager<'a> {
     }
 }
 
-#[derive(Debug, Clone)]
-pub(crate) enum ResolvedClass<'a> {
-    AlreadyLoaded(ClassRef<'a>),
-    NewClass(ClassesToInitialize<'a>),
-}
-
-impl<'a> ResolvedClass<'a> {
-    pub fn get_class(&self) -> ClassRef<'a> {
-        match self {
-            ResolvedClass::AlreadyLoaded(class) => class,
-            ResolvedClass::NewClass(class_to_initialize) => class_to_initialize.resolved_class,
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub(crate) struct ClassesToInitialize<'a> {
-    resolved_class: ClassRef<'a>,
-    pub(crate) to_initialize: Vec<ClassRef<'a>>,
-}
-
 impl<'a> ClassByIdResolver<'a> for ClassManager<'a> {
     fn find_class_by_id(&self, id: ClassId) -> Option<ClassRef<'a>> {
         self.classes_by_id.get(&id).cloned()
@@ -81,19 +67,16 @@ impl<'a> ClassManager<'a> {
         self.classes_by_name.get(class_name).cloned()
     }
 
-    pub fn get_or_resolve_class(&mut self, class_name: &str) -> Result<ResolvedClass<'a>, VmError> {
+    /// Loads a class, if it was not loaded yet. Loading does not initialize the class.
+    pub fn get_or_resolve_class(&mut self, class_name: &str) -> Result<ClassRef<'a>, VmError> {
         if let Some(already_loaded_class) = self.find_class_by_name(class_name) {
-            Ok(ResolvedClass::AlreadyLoaded(already_loaded_class))
+            Ok(already_loaded_class)
         } else {
             self.resolve_and_load_class(class_name)
-                .map(ResolvedClass::NewClass)
         }
     }
 
-    fn resolve_and_load_class(
-        &mut self,
-        class_name: &str,
-    ) -> Result<ClassesToInitialize<'a>, VmError> {
+    fn resolve_and_load_class(&mut self, class_name: &str) -> Result<ClassRef<'a>, VmError> {
         let class_file_bytes = self
             .class_path
             .resolve(class_name)
@@ -104,18 +87,29 @@ impl<'a> ClassManager<'a> {
         self.load_class(class_file)
     }
 
-    fn load_class(&mut self, class_file: ClassFile) -> Result<ClassesToInitialize<'a>, VmError> {
+    /// Loads a class that was generated at runtime rather than read from the class path
+    pub fn define_class(&mut self, class_file: ClassFile) -> Result<ClassRef<'a>, VmError> {
+        if self.find_class_by_name(&class_file.name).is_some() {
+            return Err(VmError::ClassLoadingError(format!(
+                "class {} is already defined",
+                class_file.name
+            )));
+        }
+        self.load_class(class_file)
+    }
+
+    fn load_class(&mut self, class_file: ClassFile) -> Result<ClassRef<'a>, VmError> {
         let referenced_classes = self.resolve_super_and_interfaces(&class_file)?;
         let loaded_class = self.allocate(class_file, referenced_classes)?;
-        self.register_loaded_class(loaded_class.resolved_class);
+        self.register_loaded_class(loaded_class);
         Ok(loaded_class)
     }
 
     fn resolve_super_and_interfaces(
         &mut self,
         class_file: &ClassFile,
-    ) -> Result<IndexMap<String, ResolvedClass<'a>>, VmError> {
-        let mut resolved_classes: IndexMap<String, ResolvedClass<'a>> = Default::default();
+    ) -> Result<IndexMap<String, ClassRef<'a>>, VmError> {
+        let mut resolved_classes: IndexMap<String, ClassRef<'a>> = Default::default();
         if let Some(superclass_name) = &class_file.superclass {
             self.resolve_and_collect_class(superclass_name, &mut resolved_classes)?;
         }
@@ -128,7 +122,7 @@ impl<'a> ClassManager<'a> {
     fn resolve_and_collect_class(
         &mut self,
         class_name: &str,
-        resolved_classes: &mut IndexMap<String, ResolvedClass<'a>>,
+        resolved_classes: &mut IndexMap<String, ClassRef<'a>>,
     ) -> Result<(), VmError> {
         let class = self.get_or_resolve_class(class_name)?;
         resolved_classes.insert(class_name.to_string(), class);
@@ -138,8 +132,8 @@ impl<'a> ClassManager<'a> {
     fn allocate(
         &mut self,
         class_file: ClassFile,
-        referenced_classes: IndexMap<String, ResolvedClass<'a>>,
-    ) -> Result<ClassesToInitialize<'a>, VmError> {
+        referenced_classes: IndexMap<String, ClassRef<'a>>,
+    ) -> Result<ClassRef<'a>, VmError> {
         let next_id = self.next_id;
         self.next_id += 1;
 
@@ -153,44 +147,22 @@ impl<'a> ClassManager<'a> {
             &*class_ptr
         };
 
-        let mut classes_to_init: Vec<ClassRef<'a>> = Vec::new();
-        for resolved_class in referenced_classes.values() {
-            if let ResolvedClass::NewClass(new_class) = resolved_class {
-                for to_initialize in new_class.to_initialize.iter() {
-                    classes_to_init.push(to_initialize)
-                }
-            }
-        }
-        classes_to_init.push(class_ref);
-
-        debug!(
-            "initializing class {}, classes to init {:?}",
-            class_ref.name,
-            classes_to_init
-                .iter()
-                .map(|c| &c.name)
-                .collect::<Vec<&String>>()
-        );
-
-        Ok(ClassesToInitialize {
-            resolved_class: class_ref,
-            to_initialize: classes_to_init,
-        })
+        Ok(class_ref)
     }
 
     fn new_class(
         class_file: ClassFile,
         id: ClassId,
-        resolved_classes: &IndexMap<String, ResolvedClass<'a>>,
+        resolved_classes: &IndexMap<String, ClassRef<'a>>,
     ) -> Result<Class<'a>, VmError> {
         let superclass = class_file
             .superclass
             .as_ref()
-            .map(|superclass_name| resolved_classes.get(superclass_name).unwrap().get_class());
+            .map(|superclass_name| *resolved_classes.get(superclass_name).unwrap());
         let interfaces: Vec<ClassRef<'a>> = class_file
             .interfaces
             .iter()
-            .map(|interface_name| resolved_classes.get(interface_name).unwrap().get_class())
+            .map(|interface_name| *resolved_classes.get(interface_name).unwrap())
             .collect();
 
         let num_superclass_fields = match superclass {
@@ -198,6 +170,13 @@ impl<'a> ClassManager<'a> {
             None => 0,
         };
         let num_this_class_fields = class_file.fields.len();
+        let decoded_code = class_file.methods.iter().map(|_| OnceCell::new()).collect();
+        let resolved_references = ResolvedReferences::new(class_file.constants.len());
+        let (vtable, itables) = if class_file.flags.contains(ClassAccessFlags::INTERFACE) {
+            (Vec::new(), Itables::new())
+        } else {
+            vtable::build_tables(superclass, &interfaces, &class_file.methods)
+        };
 
         Ok(Class {
             id,
@@ -211,6 +190,12 @@ impl<'a> ClassManager<'a> {
             methods: class_file.methods,
             num_total_fields: num_superclass_fields + num_this_class_fields,
             first_field_index: num_superclass_fields,
+            bootstrap_methods: class_file.bootstrap_methods,
+            decoded_code,
+            resolved_references,
+            vtable,
+            itables,
+            initialization_state: Cell::new(InitializationState::NotInitialized),
         })
     }
 
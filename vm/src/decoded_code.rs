@@ -0,0 +1,218 @@
+use std::{cell::Cell, fmt, fmt::Formatter};
+
+use rjvm_reader::{instruction::Instruction, program_counter::ProgramCounter};
+
+use crate::{class::ClassId, class_and_method::ClassAndMethod, vm_error::VmError};
+
+/// The code of a method, decoded once into its instructions so that the interpreter does not
+/// need to parse the bytecode at every step. Instructions are identified by their index,
+/// and the jump targets of the decoded instructions are indexes rather than addresses.
+#[derive(Debug)]
+pub struct DecodedCode<'a> {
+    instructions: Vec<Instruction>,
+    /// The address of each instruction, followed by the length of the code
+    addresses: Vec<ProgramCounter>,
+    /// The inline cache of each instruction, used by the virtual and interface invocations
+    inline_caches: Vec<InlineCache<'a>>,
+}
+
+/// Remembers the method selected by the last receiver of a call site. Most call sites only
+/// ever see one class, so they can skip the virtual method table lookup.
+#[derive(Default)]
+pub(crate) struct InlineCache<'a> {
+    entry: Cell<Option<(ClassId, ClassAndMethod<'a>)>>,
+}
+
+impl<'a> InlineCache<'a> {
+    pub fn get(&self, receiver_class_id: ClassId) -> Option<ClassAndMethod<'a>> {
+        self.entry
+            .get()
+            .filter(|(class_id, _)| *class_id == receiver_class_id)
+            .map(|(_, class_and_method)| class_and_method)
+    }
+
+    pub fn set(&self, receiver_class_id: ClassId, class_and_method: ClassAndMethod<'a>) {
+        self.entry.set(Some((receiver_class_id, class_and_method)));
+    }
+}
+
+// The cached method can belong to the class owning the code, so we do not print it
+impl<'a> fmt::Debug for InlineCache<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.entry.get() {
+            Some((class_id, _)) => write!(f, "cached for class {class_id}"),
+            None => write!(f, "empty"),
+        }
+    }
+}
+
+impl<'a> DecodedCode<'a> {
+    pub fn decode(code: &[u8]) -> Result<Self, VmError> {
+        let mut instructions = Vec::new();
+        let mut addresses = Vec::new();
+        let mut address = 0;
+        while address < code.len() {
+            let (instruction, next_address) =
+                Instruction::parse(code, address).map_err(|_| VmError::ValidationException)?;
+            instructions.push(instruction);
+            addresses.push(Self::to_program_counter(address)?);
+            address = next_address;
+        }
+        addresses.push(Self::to_program_counter(code.len())?);
+
+        for instruction in instructions.iter_mut() {
+            Self::resolve_jump_targets(&addresses, instruction)?;
+        }
+        let inline_caches = instructions
+            .iter()
+            .map(|_| InlineCache::default())
+            .collect();
+        Ok(Self {
+            instructions,
+            addresses,
+            inline_caches,
+        })
+    }
+
+    fn to_program_counter(address: usize) -> Result<ProgramCounter, VmError> {
+        u16::try_from(address)
+            .map(ProgramCounter)
+            .map_err(|_| VmError::ValidationException)
+    }
+
+    fn resolve_jump_targets(
+        addresses: &[ProgramCounter],
+        instruction: &mut Instruction,
+    ) -> Result<(), VmError> {
+        let resolve = |target: &mut u16| -> Result<(), VmError> {
+            let index = Self::index_of(addresses, ProgramCounter(*target))?;
+            *target = u16::try_from(index).map_err(|_| VmError::ValidationException)?;
+            Ok(())
+        };
+        match instruction {
+            Instruction::Goto(target)
+            | Instruction::Goto_w(target)
+            | Instruction::Jsr(target)
+            | Instruction::Jsr_w(target)
+            | Instruction::Ifeq(target)
+            | Instruction::Ifne(target)
+            | Instruction::Iflt(target)
+            | Instruction::Ifle(target)
+            | Instruction::Ifgt(target)
+            | Instruction::Ifge(target)
+            | Instruction::Ifnull(target)
+            | Instruction::Ifnonnull(target)
+            | Instruction::If_acmpeq(target)
+            | Instruction::If_acmpne(target)
+            | Instruction::If_icmpeq(target)
+            | Instruction::If_icmpne(target)
+            | Instruction::If_icmplt(target)
+            | Instruction::If_icmple(target)
+            | Instruction::If_icmpgt(target)
+            | Instruction::If_icmpge(target) => resolve(target),
+            Instruction::Tableswitch(table_switch) => {
+                resolve(&mut table_switch.default)?;
+                table_switch.jumps.iter_mut().try_for_each(resolve)
+            }
+            Instruction::Lookupswitch(lookup_switch) => {
+                resolve(&mut lookup_switch.default)?;
+                lookup_switch
+                    .pairs
+                    .iter_mut()
+                    .try_for_each(|(_, target)| resolve(target))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn index_of(addresses: &[ProgramCounter], address: ProgramCounter) -> Result<usize, VmError> {
+        // The last entry is the end of the code, which is not an instruction
+        addresses[..addresses.len() - 1]
+            .binary_search(&address)
+            .map_err(|_| VmError::ValidationException)
+    }
+
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    pub fn instruction(&self, index: usize) -> Option<&Instruction> {
+        self.instructions.get(index)
+    }
+
+    /// Parses the instruction at the given index from the bytecode again, resolving its jump
+    /// targets like `decode` does
+    pub fn parse_instruction(&self, code: &[u8], index: usize) -> Result<Instruction, VmError> {
+        let address = self
+            .addresses
+            .get(index)
+            .ok_or(VmError::ValidationException)?;
+        let (mut instruction, _) = Instruction::parse(code, usize::from(address.0))
+            .map_err(|_| VmError::ValidationException)?;
+        Self::resolve_jump_targets(&self.addresses, &mut instruction)?;
+        Ok(instruction)
+    }
+
+    pub(crate) fn inline_cache(&self, index: usize) -> Option<&InlineCache<'a>> {
+        self.inline_caches.get(index)
+    }
+
+    /// Returns the address of the instruction at the given index, or the length of the code
+    /// for the index following the last instruction
+    pub fn address(&self, index: usize) -> ProgramCounter {
+        self.addresses[index.min(self.addresses.len() - 1)]
+    }
+
+    /// Returns the index of the instruction starting at the given address
+    pub fn instruction_index(&self, address: ProgramCounter) -> Result<usize, VmError> {
+        Self::index_of(&self.addresses, address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rjvm_reader::{
+        instruction::{Instruction, TableSwitch},
+        program_counter::ProgramCounter,
+    };
+
+    use crate::decoded_code::DecodedCode;
+
+    #[test]
+    fn jump_targets_are_instruction_indexes() {
+        let code = [
+            0x03, // 0: iconst_0
+            0x3c, // 1: istore_1
+            0x1b, // 2: iload_1
+            0xaa, // 3: tableswitch
+            0x00, 0x00, 0x00, 0x17, // default: 26
+            0x00, 0x00, 0x00, 0x00, // low: 0
+            0x00, 0x00, 0x00, 0x00, // high: 0
+            0x00, 0x00, 0x00, 0x11, // 0: 20
+            0x84, 0x01, 0x01, // 20: iinc 1, 1
+            0xa7, 0xff, 0xeb, // 23: goto 2
+            0xb1, // 26: return
+        ];
+        let decoded = DecodedCode::decode(&code).expect("should decode the code");
+
+        assert_eq!(7, decoded.len());
+        assert_eq!(
+            Some(&Instruction::Tableswitch(TableSwitch {
+                default: 6,
+                low: 0,
+                high: 0,
+                jumps: vec![4],
+            })),
+            decoded.instruction(3)
+        );
+        assert_eq!(Some(&Instruction::Goto(2)), decoded.instruction(5));
+        assert_eq!(ProgramCounter(23), decoded.address(5));
+        assert_eq!(ProgramCounter(27), decoded.address(7));
+        assert_eq!(Ok(4), decoded.instruction_index(ProgramCounter(20)));
+        assert!(decoded.instruction_index(ProgramCounter(21)).is_err());
+    }
+}
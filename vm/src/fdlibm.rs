@@ -0,0 +1,1038 @@
+//! Ports of the functions of fdlibm 5.3 whose results the `libm` crate does not reproduce
+//! exactly. `StrictMath` must return the same bits as fdlibm, and the `libm` crate is a
+//! port of musl, which has revised some of the algorithms since.
+//!
+//! The code follows the C sources closely, including their manipulation of the high and
+//! low 32 bits of doubles, so that it can be checked against them.
+
+// The constants are written as in fdlibm, and `x - x` is how it produces NaN
+#![allow(clippy::excessive_precision, clippy::approx_constant, clippy::eq_op)]
+
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+const TWO24: f64 = 1.67772160000000000000e+07; // 0x41700000, 0x00000000
+const TWON24: f64 = 5.96046447753906250000e-08; // 0x3E700000, 0x00000000
+const TWO54: f64 = 1.80143985094819840000e+16; // 0x43500000, 0x00000000
+const LN2_HI: f64 = 6.93147180369123816490e-01; // 0x3fe62e42, 0xfee00000
+const LN2_LO: f64 = 1.90821492927058770002e-10; // 0x3dea39ef, 0x35793c76
+
+fn high_word(x: f64) -> i32 {
+    (x.to_bits() >> 32) as i32
+}
+
+fn low_word(x: f64) -> u32 {
+    x.to_bits() as u32
+}
+
+fn from_words(high: i32, low: u32) -> f64 {
+    f64::from_bits(((high as u32 as u64) << 32) | low as u64)
+}
+
+fn with_high_word(x: f64, high: i32) -> f64 {
+    from_words(high, low_word(x))
+}
+
+fn with_low_word(x: f64, low: u32) -> f64 {
+    from_words(high_word(x), low)
+}
+
+pub(crate) fn sin(x: f64) -> f64 {
+    let ix = high_word(x) & 0x7fffffff;
+    if ix <= 0x3fe921fb {
+        return kernel_sin(x, 0.0, false);
+    }
+    if ix >= 0x7ff00000 {
+        return x - x;
+    }
+    let (n, y0, y1) = rem_pio2(x);
+    match n & 3 {
+        0 => kernel_sin(y0, y1, true),
+        1 => kernel_cos(y0, y1),
+        2 => -kernel_sin(y0, y1, true),
+        _ => -kernel_cos(y0, y1),
+    }
+}
+
+pub(crate) fn cos(x: f64) -> f64 {
+    let ix = high_word(x) & 0x7fffffff;
+    if ix <= 0x3fe921fb {
+        return kernel_cos(x, 0.0);
+    }
+    if ix >= 0x7ff00000 {
+        return x - x;
+    }
+    let (n, y0, y1) = rem_pio2(x);
+    match n & 3 {
+        0 => kernel_cos(y0, y1),
+        1 => -kernel_sin(y0, y1, true),
+        2 => -kernel_cos(y0, y1),
+        _ => kernel_sin(y0, y1, true),
+    }
+}
+
+pub(crate) fn tan(x: f64) -> f64 {
+    let ix = high_word(x) & 0x7fffffff;
+    if ix <= 0x3fe921fb {
+        return kernel_tan(x, 0.0, 1);
+    }
+    if ix >= 0x7ff00000 {
+        return x - x;
+    }
+    let (n, y0, y1) = rem_pio2(x);
+    // 1 when n is even, -1 when it is odd
+    kernel_tan(y0, y1, 1 - ((n & 1) << 1))
+}
+
+/// Sine on [-pi/4, pi/4], where `y` is the tail of `x`, which is used when `has_tail`
+fn kernel_sin(x: f64, y: f64, has_tail: bool) -> f64 {
+    const S1: f64 = -1.66666666666666324348e-01; // 0xBFC55555, 0x55555549
+    const S2: f64 = 8.33333333332248946124e-03; // 0x3F811111, 0x1110F8A6
+    const S3: f64 = -1.98412698298579493134e-04; // 0xBF2A01A0, 0x19C161D5
+    const S4: f64 = 2.75573137070700676789e-06; // 0x3EC71DE3, 0x57B1FE7D
+    const S5: f64 = -2.50507602534068634195e-08; // 0xBE5AE5E6, 0x8A2B9CEB
+    const S6: f64 = 1.58969099521155010221e-10; // 0x3DE5D93A, 0x5ACFD57C
+
+    let ix = high_word(x) & 0x7fffffff;
+    if ix < 0x3e400000 && x as i32 == 0 {
+        return x;
+    }
+    let z = x * x;
+    let v = z * x;
+    let r = S2 + z * (S3 + z * (S4 + z * (S5 + z * S6)));
+    if has_tail {
+        x - ((z * (0.5 * y - v * r) - y) - v * S1)
+    } else {
+        x + v * (S1 + z * r)
+    }
+}
+
+/// Cosine on [-pi/4, pi/4], where `y` is the tail of `x`
+fn kernel_cos(x: f64, y: f64) -> f64 {
+    const C1: f64 = 4.16666666666666019037e-02; // 0x3FA55555, 0x5555554C
+    const C2: f64 = -1.38888888888741095749e-03; // 0xBF56C16C, 0x16C15177
+    const C3: f64 = 2.48015872894767294178e-05; // 0x3EFA01A0, 0x19CB1590
+    const C4: f64 = -2.75573143513906633035e-07; // 0xBE927E4F, 0x809C52AD
+    const C5: f64 = 2.08757232129817482790e-09; // 0x3E21EE9E, 0xBDB4B1C4
+    const C6: f64 = -1.13596475577881948265e-11; // 0xBDA8FAE9, 0xBE8838D4
+
+    let ix = high_word(x) & 0x7fffffff;
+    if ix < 0x3e400000 && x as i32 == 0 {
+        return 1.0;
+    }
+    let z = x * x;
+    let r = z * (C1 + z * (C2 + z * (C3 + z * (C4 + z * (C5 + z * C6)))));
+    if ix < 0x3fd33333 {
+        return 1.0 - (0.5 * z - (z * r - x * y));
+    }
+    let qx = if ix > 0x3fe90000 {
+        0.28125
+    } else {
+        from_words(ix - 0x00200000, 0)
+    };
+    let hz = 0.5 * z - qx;
+    let a = 1.0 - qx;
+    a - (hz - (z * r - x * y))
+}
+
+/// Tangent on [-pi/4, pi/4], where `y` is the tail of `x`. Returns `tan(x + y)` when
+/// `iy` is 1, and `-1 / tan(x + y)` when it is -1.
+fn kernel_tan(mut x: f64, mut y: f64, iy: i32) -> f64 {
+    const T: [f64; 13] = [
+        3.33333333333334091986e-01,  // 3FD55555, 55555563
+        1.33333333333201242699e-01,  // 3FC11111, 1110FE7A
+        5.39682539762260521377e-02,  // 3FABA1BA, 1BB341FE
+        2.18694882948595424599e-02,  // 3F9664F4, 8406D637
+        8.86323982359930005737e-03,  // 3F8226E3, E96E8493
+        3.59207910759131235356e-03,  // 3F6D6D22, C9560328
+        1.45620945432529025516e-03,  // 3F57DBC8, FEE08315
+        5.88041240820264096874e-04,  // 3F4344D8, F2F26501
+        2.46463134818469906812e-04,  // 3F3026F7, 1A8D1068
+        7.81794442939557092300e-05,  // 3F147E88, A03792A6
+        7.14072491382608190305e-05,  // 3F12B80F, 32F0A7E9
+        -1.85586374855275456654e-05, // BEF375CB, DB605373
+        2.59073051863633712884e-05,  // 3EFB2A70, 74BF7AD4
+    ];
+    const PIO4_LO: f64 = 3.06161699786838301793e-17; // 3C81A626, 33145C07
+
+    let hx = high_word(x);
+    let ix = hx & 0x7fffffff;
+    if ix < 0x3e300000 && x as i32 == 0 {
+        if ((ix as u32 | low_word(x)) | (iy + 1) as u32) == 0 {
+            return 1.0 / x.abs();
+        }
+        if iy == 1 {
+            return x;
+        }
+        return negated_inverse(x, y, x + y);
+    }
+    if ix >= 0x3FE59428 {
+        if hx < 0 {
+            x = -x;
+            y = -y;
+        }
+        let z = FRAC_PI_4 - x;
+        let w = PIO4_LO - y;
+        x = z + w;
+        y = 0.0;
+    }
+    let z = x * x;
+    let w = z * z;
+    let r = T[1] + w * (T[3] + w * (T[5] + w * (T[7] + w * (T[9] + w * T[11]))));
+    let v = z * (T[2] + w * (T[4] + w * (T[6] + w * (T[8] + w * (T[10] + w * T[12])))));
+    let s = z * x;
+    let mut r = y + z * (s * (r + v) + y);
+    r += T[0] * s;
+    let w = x + r;
+    if ix >= 0x3FE59428 {
+        let v = iy as f64;
+        return (1 - ((hx >> 30) & 2)) as f64 * (v - 2.0 * (x - (w * w / (w + v) - r)));
+    }
+    if iy == 1 {
+        w
+    } else {
+        negated_inverse(x, r, w)
+    }
+}
+
+/// Computes `-1 / w` accurately, where `w = x + r`
+fn negated_inverse(x: f64, r: f64, w: f64) -> f64 {
+    let z = with_low_word(w, 0);
+    let v = r - (z - x);
+    let a = -1.0 / w;
+    let t = with_low_word(a, 0);
+    let s = 1.0 + t * z;
+    t + a * (s + t * v)
+}
+
+/// Returns `n` and the head and tail of `x - n * pi/2`, which is in [-pi/4, pi/4]
+fn rem_pio2(x: f64) -> (i32, f64, f64) {
+    const NPIO2_HW: [i32; 32] = [
+        0x3FF921FB, 0x400921FB, 0x4012D97C, 0x401921FB, 0x401F6A7A, 0x4022D97C, 0x4025FDBB,
+        0x402921FB, 0x402C463A, 0x402F6A7A, 0x4031475C, 0x4032D97C, 0x40346B9C, 0x4035FDBB,
+        0x40378FDB, 0x403921FB, 0x403AB41B, 0x403C463A, 0x403DD85A, 0x403F6A7A, 0x40407E4C,
+        0x4041475C, 0x4042106C, 0x4042D97C, 0x4043A28C, 0x40446B9C, 0x404534AC, 0x4045FDBB,
+        0x4046C6CB, 0x40478FDB, 0x404858EB, 0x404921FB,
+    ];
+    const INVPIO2: f64 = 6.36619772367581382433e-01; // 0x3FE45F30, 0x6DC9C883
+    const PIO2_1: f64 = 1.57079632673412561417e+00; // 0x3FF921FB, 0x54400000
+    const PIO2_1T: f64 = 6.07710050650619224932e-11; // 0x3DD0B461, 0x1A626331
+    const PIO2_2: f64 = 6.07710050630396597660e-11; // 0x3DD0B461, 0x1A600000
+    const PIO2_2T: f64 = 2.02226624879595063154e-21; // 0x3BA3198A, 0x2E037073
+    const PIO2_3: f64 = 2.02226624871116645580e-21; // 0x3BA3198A, 0x2E000000
+    const PIO2_3T: f64 = 8.47842766036889956997e-32; // 0x397B839A, 0x252049C1
+
+    let hx = high_word(x);
+    let ix = hx & 0x7fffffff;
+    if ix <= 0x3fe921fb {
+        return (0, x, 0.0);
+    }
+    if ix < 0x4002d97c {
+        // |x| < 3pi/4, so n is 1 or -1
+        return if hx > 0 {
+            let mut z = x - PIO2_1;
+            if ix != 0x3ff921fb {
+                let y0 = z - PIO2_1T;
+                (1, y0, (z - y0) - PIO2_1T)
+            } else {
+                z -= PIO2_2;
+                let y0 = z - PIO2_2T;
+                (1, y0, (z - y0) - PIO2_2T)
+            }
+        } else {
+            let mut z = x + PIO2_1;
+            if ix != 0x3ff921fb {
+                let y0 = z + PIO2_1T;
+                (-1, y0, (z - y0) + PIO2_1T)
+            } else {
+                z += PIO2_2;
+                let y0 = z + PIO2_2T;
+                (-1, y0, (z - y0) + PIO2_2T)
+            }
+        };
+    }
+    if ix <= 0x413921fb {
+        // |x| <= 2^19 * pi/2
+        let t = x.abs();
+        let n = (t * INVPIO2 + 0.5) as i32;
+        let f_n = n as f64;
+        let mut r = t - f_n * PIO2_1;
+        let mut w = f_n * PIO2_1T;
+        let mut y0 = r - w;
+        if n >= 32 || ix == NPIO2_HW[(n - 1) as usize] {
+            // There might have been a cancellation
+            let j = ix >> 20;
+            let i = j - ((high_word(y0) >> 20) & 0x7ff);
+            if i > 16 {
+                let t = r;
+                w = f_n * PIO2_2;
+                r = t - w;
+                w = f_n * PIO2_2T - ((t - r) - w);
+                y0 = r - w;
+                let i = j - ((high_word(y0) >> 20) & 0x7ff);
+                if i > 49 {
+                    let t = r;
+                    w = f_n * PIO2_3;
+                    r = t - w;
+                    w = f_n * PIO2_3T - ((t - r) - w);
+                    y0 = r - w;
+                }
+            }
+        }
+        let y1 = (r - y0) - w;
+        return if hx < 0 { (-n, -y0, -y1) } else { (n, y0, y1) };
+    }
+    if ix >= 0x7ff00000 {
+        return (0, x - x, x - x);
+    }
+    // Split |x| / 2^(ilogb(x) - 23) into three 24 bits chunks
+    let e0 = (ix >> 20) - 1046;
+    let mut z = from_words(ix - (e0 << 20), low_word(x));
+    let mut tx = [0.0; 3];
+    for chunk in tx.iter_mut().take(2) {
+        *chunk = z as i32 as f64;
+        z = (z - *chunk) * TWO24;
+    }
+    tx[2] = z;
+    let mut nx = 3;
+    while tx[nx - 1] == 0.0 {
+        nx -= 1;
+    }
+    let (n, y0, y1) = kernel_rem_pio2(&tx[..nx], e0);
+    if hx < 0 {
+        (-n, -y0, -y1)
+    } else {
+        (n, y0, y1)
+    }
+}
+
+/// The reduction of large arguments of `rem_pio2`, with the precision of doubles, i.e.
+/// `__kernel_rem_pio2` with `prec` 2. `x` are the 24 bits chunks of the argument divided
+/// by 2^e0.
+fn kernel_rem_pio2(x: &[f64], e0: i32) -> (i32, f64, f64) {
+    const TWO_OVER_PI: [i32; 66] = [
+        0xA2F983, 0x6E4E44, 0x1529FC, 0x2757D1, 0xF534DD, 0xC0DB62, 0x95993C, 0x439041, 0xFE5163,
+        0xABDEBB, 0xC561B7, 0x246E3A, 0x424DD2, 0xE00649, 0x2EEA09, 0xD1921C, 0xFE1DEB, 0x1CB129,
+        0xA73EE8, 0x8235F5, 0x2EBB44, 0x84E99C, 0x7026B4, 0x5F7E41, 0x3991D6, 0x398353, 0x39F49C,
+        0x845F8B, 0xBDF928, 0x3B1FF8, 0x97FFDE, 0x05980F, 0xEF2F11, 0x8B5A0A, 0x6D1F6D, 0x367ECF,
+        0x27CB09, 0xB74F46, 0x3F669E, 0x5FEA2D, 0x7527BA, 0xC7EBE5, 0xF17B3D, 0x0739F7, 0x8A5292,
+        0xEA6BFB, 0x5FB11F, 0x8D5D08, 0x560330, 0x46FC7B, 0x6BABF0, 0xCFBC20, 0x9AF436, 0x1DA9E3,
+        0x91615E, 0xE61B08, 0x659985, 0x5F14A0, 0x68408D, 0xFFD880, 0x4D7327, 0x310606, 0x1556CA,
+        0x73A8C9, 0x60E27B, 0xC08C6B,
+    ];
+    const PIO2: [f64; 8] = [
+        1.57079625129699707031e+00, // 0x3FF921FB, 0x40000000
+        7.54978941586159635335e-08, // 0x3E74442D, 0x00000000
+        5.39030252995776476554e-15, // 0x3CF84698, 0x80000000
+        3.28200341580791294123e-22, // 0x3B78CC51, 0x60000000
+        1.27065575308067607349e-29, // 0x39F01B83, 0x80000000
+        1.22933308981111328932e-36, // 0x387A2520, 0x40000000
+        2.73370053816464559624e-44, // 0x36E38222, 0x80000000
+        2.16741683877804819444e-51, // 0x3569F31D, 0x00000000
+    ];
+    // The number of terms of the initial computation, for the precision of doubles
+    const JK: usize = 4;
+    const JP: usize = JK;
+
+    let mut iq = [0i32; 20];
+    let mut f = [0.0; 20];
+    let mut fq = [0.0; 20];
+    let mut q = [0.0; 20];
+
+    let jx = x.len() - 1;
+    let jv = ((e0 - 3) / 24).max(0) as usize;
+    let mut q0 = e0 - 24 * (jv as i32 + 1);
+
+    // f[0..=jx + JK] = TWO_OVER_PI[jv - jx..=jv + JK]
+    for (i, value) in f.iter_mut().enumerate().take(jx + JK + 1) {
+        let j = jv as isize - jx as isize + i as isize;
+        *value = if j < 0 {
+            0.0
+        } else {
+            TWO_OVER_PI[j as usize] as f64
+        };
+    }
+    for i in 0..=JK {
+        q[i] = (0..=jx)
+            .map(|j| x[j] * f[jx + i - j])
+            .fold(0.0, |sum, term| sum + term);
+    }
+
+    let mut jz = JK;
+    let (n, ih, z) = loop {
+        // Distill q into iq, in reverse order
+        let mut z = q[jz];
+        for (i, j) in (1..=jz).rev().enumerate() {
+            let fw = (TWON24 * z) as i32 as f64;
+            iq[i] = (z - TWO24 * fw) as i32;
+            z = q[j - 1] + fw;
+        }
+
+        z = scalbn(z, q0);
+        z -= 8.0 * (z * 0.125).floor();
+        let mut n = z as i32;
+        z -= n as f64;
+        let mut ih = 0;
+        if q0 > 0 {
+            let i = iq[jz - 1] >> (24 - q0);
+            n += i;
+            iq[jz - 1] -= i << (24 - q0);
+            ih = iq[jz - 1] >> (23 - q0);
+        } else if q0 == 0 {
+            ih = iq[jz - 1] >> 23;
+        } else if z >= 0.5 {
+            ih = 2;
+        }
+
+        if ih > 0 {
+            // q > 0.5
+            n += 1;
+            let mut carry = false;
+            for value in iq.iter_mut().take(jz) {
+                let j = *value;
+                if !carry {
+                    if j != 0 {
+                        carry = true;
+                        *value = 0x1000000 - j;
+                    }
+                } else {
+                    *value = 0xffffff - j;
+                }
+            }
+            match q0 {
+                1 => iq[jz - 1] &= 0x7fffff,
+                2 => iq[jz - 1] &= 0x3fffff,
+                _ => {}
+            }
+            if ih == 2 {
+                z = 1.0 - z;
+                if carry {
+                    z -= scalbn(1.0, q0);
+                }
+            }
+        }
+
+        if z == 0.0 && iq[JK..jz].iter().all(|&value| value == 0) {
+            // Recompute with more terms
+            let mut k = 1;
+            while iq[JK - k] == 0 {
+                k += 1;
+            }
+            for i in jz + 1..=jz + k {
+                f[jx + i] = TWO_OVER_PI[jv + i] as f64;
+                q[i] = (0..=jx)
+                    .map(|j| x[j] * f[jx + i - j])
+                    .fold(0.0, |sum, term| sum + term);
+            }
+            jz += k;
+            continue;
+        }
+        break (n, ih, z);
+    };
+
+    // Chop off the zero terms
+    if z == 0.0 {
+        jz -= 1;
+        q0 -= 24;
+        while iq[jz] == 0 {
+            jz -= 1;
+            q0 -= 24;
+        }
+    } else {
+        let z = scalbn(z, -q0);
+        if z >= TWO24 {
+            let fw = (TWON24 * z) as i32 as f64;
+            iq[jz] = (z - TWO24 * fw) as i32;
+            jz += 1;
+            q0 += 24;
+            iq[jz] = fw as i32;
+        } else {
+            iq[jz] = z as i32;
+        }
+    }
+
+    let mut fw = scalbn(1.0, q0);
+    for i in (0..=jz).rev() {
+        q[i] = fw * iq[i] as f64;
+        fw *= TWON24;
+    }
+
+    for i in (0..=jz).rev() {
+        let mut fw = 0.0;
+        let mut k = 0;
+        while k <= JP && k <= jz - i {
+            fw += PIO2[k] * q[i + k];
+            k += 1;
+        }
+        fq[jz - i] = fw;
+    }
+
+    let mut fw = 0.0;
+    for value in fq[..=jz].iter().rev() {
+        fw += value;
+    }
+    let y0 = if ih == 0 { fw } else { -fw };
+    fw = fq[0] - fw;
+    for value in &fq[1..=jz] {
+        fw += value;
+    }
+    let y1 = if ih == 0 { fw } else { -fw };
+    (n & 7, y0, y1)
+}
+
+/// `x * 2^n`, computed exactly like fdlibm does
+fn scalbn(x: f64, n: i32) -> f64 {
+    const TWOM54: f64 = 5.55111512312578270212e-17; // 0x3C900000, 0x00000000
+    const HUGE: f64 = 1.0e+300;
+    const TINY: f64 = 1.0e-300;
+
+    let mut x = x;
+    let hx = high_word(x);
+    let mut k = (hx & 0x7ff00000) >> 20;
+    if k == 0 {
+        if (low_word(x) | (hx & 0x7fffffff) as u32) == 0 {
+            return x;
+        }
+        x *= TWO54;
+        k = ((high_word(x) & 0x7ff00000) >> 20) - 54;
+        if n < -50000 {
+            return TINY * x;
+        }
+    }
+    if k == 0x7ff {
+        return x + x;
+    }
+    let k = k + n;
+    if k > 0x7fe {
+        return HUGE * HUGE.copysign(x);
+    }
+    if k > 0 {
+        return with_high_word(x, (high_word(x) & !0x7ff00000) | (k << 20));
+    }
+    if k <= -54 {
+        return if n > 50000 {
+            HUGE * HUGE.copysign(x)
+        } else {
+            TINY * TINY.copysign(x)
+        };
+    }
+    let k = k + 54;
+    with_high_word(x, (high_word(x) & !0x7ff00000) | (k << 20)) * TWOM54
+}
+
+pub(crate) fn log(x: f64) -> f64 {
+    const LG1: f64 = 6.666666666666735130e-01; // 3FE55555 55555593
+    const LG2: f64 = 3.999999999940941908e-01; // 3FD99999 9997FA04
+    const LG3: f64 = 2.857142874366239149e-01; // 3FD24924 94229359
+    const LG4: f64 = 2.222219843214978396e-01; // 3FCC71C5 1D8E78AF
+    const LG5: f64 = 1.818357216161805012e-01; // 3FC74664 96CB03DE
+    const LG6: f64 = 1.531383769920937332e-01; // 3FC39A09 D078C69F
+    const LG7: f64 = 1.479819860511658591e-01; // 3FC2F112 DF3E5244
+
+    let mut x = x;
+    let mut hx = high_word(x);
+    let lx = low_word(x);
+    let mut k = 0;
+    if hx < 0x00100000 {
+        if ((hx & 0x7fffffff) as u32 | lx) == 0 {
+            return f64::NEG_INFINITY;
+        }
+        if hx < 0 {
+            return f64::NAN;
+        }
+        // Subnormal number: scale it up
+        k -= 54;
+        x *= TWO54;
+        hx = high_word(x);
+    }
+    if hx >= 0x7ff00000 {
+        return x + x;
+    }
+    k += (hx >> 20) - 1023;
+    hx &= 0x000fffff;
+    let i = (hx + 0x95f64) & 0x100000;
+    // Normalize x or x/2
+    x = with_high_word(x, hx | (i ^ 0x3ff00000));
+    k += i >> 20;
+    let f = x - 1.0;
+    let dk = k as f64;
+    if (0x000fffff & (2 + hx)) < 3 {
+        // |f| < 2^-20
+        if f == 0.0 {
+            return if k == 0 {
+                0.0
+            } else {
+                dk * LN2_HI + dk * LN2_LO
+            };
+        }
+        let r = f * f * (0.5 - 0.33333333333333333 * f);
+        return if k == 0 {
+            f - r
+        } else {
+            dk * LN2_HI - ((r - dk * LN2_LO) - f)
+        };
+    }
+    let s = f / (2.0 + f);
+    let z = s * s;
+    let mut i = hx - 0x6147a;
+    let w = z * z;
+    let j = 0x6b851 - hx;
+    let t1 = w * (LG2 + w * (LG4 + w * LG6));
+    let t2 = z * (LG1 + w * (LG3 + w * (LG5 + w * LG7)));
+    i |= j;
+    let r = t2 + t1;
+    if i > 0 {
+        let hfsq = 0.5 * f * f;
+        if k == 0 {
+            f - (hfsq - s * (hfsq + r))
+        } else {
+            dk * LN2_HI - ((hfsq - (s * (hfsq + r) + dk * LN2_LO)) - f)
+        }
+    } else if k == 0 {
+        f - s * (f - r)
+    } else {
+        dk * LN2_HI - ((s * (f - r) - dk * LN2_LO) - f)
+    }
+}
+
+pub(crate) fn log10(x: f64) -> f64 {
+    const IVLN10: f64 = 4.34294481903251816668e-01; // 0x3FDBCB7B, 0x1526E50E
+    const LOG10_2HI: f64 = 3.01029995663611771306e-01; // 0x3FD34413, 0x509F6000
+    const LOG10_2LO: f64 = 3.69423907715893078616e-13; // 0x3D59FEF3, 0x11F12B36
+
+    let mut x = x;
+    let mut hx = high_word(x);
+    let lx = low_word(x);
+    let mut k = 0;
+    if hx < 0x00100000 {
+        if ((hx & 0x7fffffff) as u32 | lx) == 0 {
+            return f64::NEG_INFINITY;
+        }
+        if hx < 0 {
+            return f64::NAN;
+        }
+        k -= 54;
+        x *= TWO54;
+        hx = high_word(x);
+    }
+    if hx >= 0x7ff00000 {
+        return x + x;
+    }
+    k += (hx >> 20) - 1023;
+    let i = ((k as u32 & 0x80000000) >> 31) as i32;
+    hx = (hx & 0x000fffff) | ((0x3ff - i) << 20);
+    let y = (k + i) as f64;
+    x = with_high_word(x, hx);
+    let z = y * LOG10_2LO + IVLN10 * log(x);
+    z + y * LOG10_2HI
+}
+
+pub(crate) fn log1p(x: f64) -> f64 {
+    const LP1: f64 = 6.666666666666735130e-01; // 3FE55555 55555593
+    const LP2: f64 = 3.999999999940941908e-01; // 3FD99999 9997FA04
+    const LP3: f64 = 2.857142874366239149e-01; // 3FD24924 94229359
+    const LP4: f64 = 2.222219843214978396e-01; // 3FCC71C5 1D8E78AF
+    const LP5: f64 = 1.818357216161805012e-01; // 3FC74664 96CB03DE
+    const LP6: f64 = 1.531383769920937332e-01; // 3FC39A09 D078C69F
+    const LP7: f64 = 1.479819860511658591e-01; // 3FC2F112 DF3E5244
+
+    let hx = high_word(x);
+    let ax = hx & 0x7fffffff;
+
+    let mut k = 1;
+    let mut f = 0.0;
+    let mut hu = 0;
+    let mut c = 0.0;
+    if hx < 0x3FDA827A {
+        // x < 0.41422
+        if ax >= 0x3ff00000 {
+            // x <= -1.0
+            return if x == -1.0 {
+                f64::NEG_INFINITY
+            } else {
+                f64::NAN
+            };
+        }
+        if ax < 0x3e200000 {
+            // |x| < 2^-29
+            return if ax < 0x3c900000 { x } else { x - x * x * 0.5 };
+        }
+        if hx > 0 || hx <= 0xbfd2bec3_u32 as i32 {
+            // -0.2929 < x < 0.41422
+            k = 0;
+            f = x;
+            hu = 1;
+        }
+    }
+    if hx >= 0x7ff00000 {
+        return x + x;
+    }
+    if k != 0 {
+        let mut u;
+        if hx < 0x43400000 {
+            u = 1.0 + x;
+            hu = high_word(u);
+            k = (hu >> 20) - 1023;
+            // Correction term
+            c = if k > 0 { 1.0 - (u - x) } else { x - (u - 1.0) };
+            c /= u;
+        } else {
+            u = x;
+            hu = high_word(u);
+            k = (hu >> 20) - 1023;
+            c = 0.0;
+        }
+        hu &= 0x000fffff;
+        if hu < 0x6a09e {
+            // Normalize u
+            u = with_high_word(u, hu | 0x3ff00000);
+        } else {
+            // Normalize u/2
+            k += 1;
+            u = with_high_word(u, hu | 0x3fe00000);
+            hu = (0x00100000 - hu) >> 2;
+        }
+        f = u - 1.0;
+    }
+    let hfsq = 0.5 * f * f;
+    let dk = k as f64;
+    if hu == 0 {
+        // |f| < 2^-20
+        if f == 0.0 {
+            return if k == 0 {
+                0.0
+            } else {
+                c += dk * LN2_LO;
+                dk * LN2_HI + c
+            };
+        }
+        let r = hfsq * (1.0 - 0.66666666666666666 * f);
+        return if k == 0 {
+            f - r
+        } else {
+            dk * LN2_HI - ((r - (dk * LN2_LO + c)) - f)
+        };
+    }
+    let s = f / (2.0 + f);
+    let z = s * s;
+    let r = z * (LP1 + z * (LP2 + z * (LP3 + z * (LP4 + z * (LP5 + z * (LP6 + z * LP7))))));
+    if k == 0 {
+        f - (hfsq - s * (hfsq + r))
+    } else {
+        dk * LN2_HI - ((hfsq - (s * (hfsq + r) + (dk * LN2_LO + c))) - f)
+    }
+}
+
+pub(crate) fn cbrt(x: f64) -> f64 {
+    const B1: u32 = 715094163; // B1 = (682-0.03306235651)*2**20
+    const B2: u32 = 696219795; // B2 = (664-0.03306235651)*2**20
+    const C: f64 = 5.42857142857142815906e-01; // 19/35 = 0x3FE15F15, 0xF15F15F1
+    const D: f64 = -7.05306122448979611050e-01; // -864/1225 = 0xBFE691DE, 0x2532C834
+    const E: f64 = 1.41428571428571436819e+00; // 99/70 = 0x3FF6A0EA, 0x0EA0EA0F
+    const F: f64 = 1.60714285714285720630e+00; // 45/28 = 0x3FF9B6DB, 0x6DB6DB6E
+    const G: f64 = 3.57142857142857150787e-01; // 5/14 = 0x3FD6DB6D, 0xB6DB6DB7
+
+    let hx = high_word(x);
+    let sign = hx as u32 & 0x80000000;
+    let hx = (hx as u32 ^ sign) as i32;
+    if hx >= 0x7ff00000 {
+        return x + x;
+    }
+    if (hx as u32 | low_word(x)) == 0 {
+        return x;
+    }
+    let x = with_high_word(x, hx);
+
+    // Rough cbrt to 5 bits
+    let mut t = if hx < 0x00100000 {
+        let t = from_words(0x43500000, 0) * x;
+        with_high_word(t, (high_word(t) as u32 / 3 + B2) as i32)
+    } else {
+        from_words((hx as u32 / 3 + B1) as i32, 0)
+    };
+
+    // New cbrt to 23 bits
+    let r = t * t / x;
+    let s = C + r * t;
+    t *= G + F / (s + E + D / s);
+
+    // Chopped to 20 bits and made larger than cbrt(x)
+    t = from_words(high_word(t) + 1, 0);
+
+    // One step of Newton iteration to 53 bits, with an error less than 0.667 ulps
+    let s = t * t;
+    let r = x / s;
+    let w = t + t;
+    let r = (r - t) / (w + r);
+    t += t * r;
+
+    with_high_word(t, (high_word(t) as u32 | sign) as i32)
+}
+
+pub(crate) fn atan2(y: f64, x: f64) -> f64 {
+    const TINY: f64 = 1.0e-300;
+    const PI_LO: f64 = 1.2246467991473531772E-16; // 0x3CA1A626, 0x33145C07
+
+    let hx = high_word(x);
+    let ix = hx & 0x7fffffff;
+    let lx = low_word(x);
+    let hy = high_word(y);
+    let iy = hy & 0x7fffffff;
+    let ly = low_word(y);
+    if x.is_nan() || y.is_nan() {
+        return x + y;
+    }
+    if ((hx.wrapping_sub(0x3ff00000) as u32) | lx) == 0 {
+        return libm::atan(y);
+    }
+    // 2 * sign(x) + sign(y)
+    let m = ((hy >> 31) & 1) | ((hx >> 30) & 2);
+
+    if (iy as u32 | ly) == 0 {
+        return match m {
+            0 | 1 => y,
+            2 => PI + TINY,
+            _ => -PI - TINY,
+        };
+    }
+    if (ix as u32 | lx) == 0 {
+        return if hy < 0 {
+            -FRAC_PI_2 - TINY
+        } else {
+            FRAC_PI_2 + TINY
+        };
+    }
+    if ix == 0x7ff00000 {
+        return if iy == 0x7ff00000 {
+            match m {
+                0 => FRAC_PI_4 + TINY,
+                1 => -FRAC_PI_4 - TINY,
+                2 => 3.0 * FRAC_PI_4 + TINY,
+                _ => -3.0 * FRAC_PI_4 - TINY,
+            }
+        } else {
+            match m {
+                0 => 0.0,
+                1 => -0.0,
+                2 => PI + TINY,
+                _ => -PI - TINY,
+            }
+        };
+    }
+    if iy == 0x7ff00000 {
+        return if hy < 0 {
+            -FRAC_PI_2 - TINY
+        } else {
+            FRAC_PI_2 + TINY
+        };
+    }
+
+    let k = (iy - ix) >> 20;
+    let z = if k > 60 {
+        // |y/x| > 2^60
+        FRAC_PI_2 + 0.5 * PI_LO
+    } else if hx < 0 && k < -60 {
+        // |y|/x < -2^60
+        0.0
+    } else {
+        libm::atan((y / x).abs())
+    };
+    match m {
+        0 => z,
+        1 => -z,
+        2 => PI - (z - PI_LO),
+        _ => (z - PI_LO) - PI,
+    }
+}
+
+pub(crate) fn sinh(x: f64) -> f64 {
+    const SHUGE: f64 = 1.0e307;
+
+    let jx = high_word(x);
+    let ix = jx & 0x7fffffff;
+    if ix >= 0x7ff00000 {
+        return x + x;
+    }
+    let h = if jx < 0 { -0.5 } else { 0.5 };
+    if ix < 0x40360000 {
+        // |x| < 22
+        if ix < 0x3e300000 {
+            // |x| < 2^-28
+            return x;
+        }
+        let t = libm::expm1(x.abs());
+        if ix < 0x3ff00000 {
+            return h * (2.0 * t - t * t / (t + 1.0));
+        }
+        return h * (t + t / (t + 1.0));
+    }
+    if ix < 0x40862E42 {
+        // |x| in [22, log(maxdouble)]
+        return h * libm::exp(x.abs());
+    }
+    if ix < 0x408633CE || (ix == 0x408633ce && low_word(x) <= 0x8fb9f87d) {
+        // |x| in [log(maxdouble), overflow threshold]
+        let w = libm::exp(0.5 * x.abs());
+        let t = h * w;
+        return t * w;
+    }
+    x * SHUGE
+}
+
+pub(crate) fn cosh(x: f64) -> f64 {
+    const HUGE: f64 = 1.0e300;
+
+    let ix = high_word(x) & 0x7fffffff;
+    if ix >= 0x7ff00000 {
+        return x * x;
+    }
+    if ix < 0x3fd62e43 {
+        // |x| in [0, 0.5 * ln2]
+        let t = libm::expm1(x.abs());
+        let w = 1.0 + t;
+        if ix < 0x3c800000 {
+            return w;
+        }
+        return 1.0 + (t * t) / (w + w);
+    }
+    if ix < 0x40360000 {
+        // |x| in [0.5 * ln2, 22]
+        let t = libm::exp(x.abs());
+        return 0.5 * t + 0.5 / t;
+    }
+    if ix < 0x40862E42 {
+        // |x| in [22, log(maxdouble)]
+        return 0.5 * libm::exp(x.abs());
+    }
+    if ix < 0x408633CE || (ix == 0x408633ce && low_word(x) <= 0x8fb9f87d) {
+        // |x| in [log(maxdouble), overflow threshold]
+        let w = libm::exp(0.5 * x.abs());
+        let t = 0.5 * w;
+        return t * w;
+    }
+    HUGE * HUGE
+}
+
+pub(crate) fn tanh(x: f64) -> f64 {
+    const TINY: f64 = 1.0e-300;
+
+    let jx = high_word(x);
+    let ix = jx & 0x7fffffff;
+    if ix >= 0x7ff00000 {
+        // tanh(+-inf) = +-1, tanh(NaN) = NaN
+        return if jx >= 0 {
+            1.0 / x + 1.0
+        } else {
+            1.0 / x - 1.0
+        };
+    }
+    let z = if ix < 0x40360000 {
+        // |x| < 22
+        if ix < 0x3c800000 {
+            // |x| < 2^-55
+            return x * (1.0 + x);
+        }
+        if ix >= 0x3ff00000 {
+            let t = libm::expm1(2.0 * x.abs());
+            1.0 - 2.0 / (t + 2.0)
+        } else {
+            let t = libm::expm1(-2.0 * x.abs());
+            -t / (t + 2.0)
+        }
+    } else {
+        1.0 - TINY
+    };
+    if jx >= 0 {
+        z
+    } else {
+        -z
+    }
+}
+
+pub(crate) fn hypot(x: f64, y: f64) -> f64 {
+    let mut ha = high_word(x) & 0x7fffffff;
+    let mut hb = high_word(y) & 0x7fffffff;
+    let (mut a, mut b) = if hb > ha {
+        std::mem::swap(&mut ha, &mut hb);
+        (y, x)
+    } else {
+        (x, y)
+    };
+    a = with_high_word(a, ha);
+    b = with_high_word(b, hb);
+    if ha - hb > 0x3c00000 {
+        // a/b > 2^60
+        return a + b;
+    }
+    let mut k = 0;
+    if ha > 0x5f300000 {
+        // a > 2^500
+        if ha >= 0x7ff00000 {
+            // Infinity or NaN: infinity wins over NaN
+            let mut w = a + b;
+            if ((ha & 0xfffff) as u32 | low_word(a)) == 0 {
+                w = a;
+            }
+            if ((hb ^ 0x7ff00000) as u32 | low_word(b)) == 0 {
+                w = b;
+            }
+            return w;
+        }
+        // Scale a and b by 2^-600
+        ha -= 0x25800000;
+        hb -= 0x25800000;
+        k += 600;
+        a = with_high_word(a, ha);
+        b = with_high_word(b, hb);
+    }
+    if hb < 0x20b00000 {
+        // b < 2^-500
+        if hb <= 0x000fffff {
+            // Subnormal b or 0
+            if (hb as u32 | low_word(b)) == 0 {
+                return a;
+            }
+            // 2^1022
+            let t1 = from_words(0x7fd00000, 0);
+            b *= t1;
+            a *= t1;
+            k -= 1022;
+        } else {
+            // Scale a and b by 2^600
+            ha += 0x25800000;
+            hb += 0x25800000;
+            k -= 600;
+            a = with_high_word(a, ha);
+            b = with_high_word(b, hb);
+        }
+    }
+    let mut w = a - b;
+    if w > b {
+        let t1 = from_words(ha, 0);
+        let t2 = a - t1;
+        w = (t1 * t1 - (b * (-b) - t2 * (a + t1))).sqrt();
+    } else {
+        a += a;
+        let y1 = from_words(hb, 0);
+        let y2 = b - y1;
+        let t1 = from_words(ha + 0x00100000, 0);
+        let t2 = a - t1;
+        w = (t1 * y1 - (w * (-w) - (t1 * y2 + t2 * b))).sqrt();
+    }
+    if k != 0 {
+        from_words(0x3ff00000 + (k << 20), 0) * w
+    } else {
+        w
+    }
+}
+
+/// fdlibm follows the C99 `pow` except on the special cases where the exponent is NaN or
+/// infinite and the base is 1 or -1, whose result is NaN
+pub(crate) fn pow(x: f64, y: f64) -> f64 {
+    if y == 0.0 {
+        1.0
+    } else if x.is_nan() || y.is_nan() || (y.is_infinite() && x.abs() == 1.0) {
+        f64::NAN
+    } else {
+        libm::pow(x, y)
+    }
+}
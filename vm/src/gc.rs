@@ -226,7 +226,7 @@ impl<'a> ObjectAllocator<'a> {
             ArrayEntryType::Base(_) => {
                 Ok(())
             }
-            ArrayEntryType::Object(_) => {
+            ArrayEntryType::Object(_) | ArrayEntryType::Array(..) => {
                 for i in 0..array.len().into_usize_safe() {
                     let value = array.get_element(i);
                     match value {
@@ -240,9 +240,6 @@ impl<'a> ObjectAllocator<'a> {
                 }
                 Ok(())
             }
-            ArrayEntryType::Array => {
-                todo!("arrays of arrays are not supported yet")
-            }
         }
     }
 
@@ -306,8 +303,8 @@ impl<'a> ObjectAllocator<'a> {
                 // No objects are kept alive by this GC-reachable array!
                 Ok(())
             }
-            ArrayEntryType::Object(class_id) => {
-                debug!("fixing entries of array {array:?} of type {class_id}");
+            ArrayEntryType::Object(_) | ArrayEntryType::Array(..) => {
+                debug!("fixing entries of array {array:?}");
                 for i in 0..array.len().into_usize_safe() {
                     let element_ptr = array.ptr_to_array_element(i);
                     debug!(
@@ -323,9 +320,6 @@ impl<'a> ObjectAllocator<'a> {
                 }
                 Ok(())
             }
-            ArrayEntryType::Array => {
-                todo!("arrays of arrays are not supported yet")
-            }
         }
     }
 
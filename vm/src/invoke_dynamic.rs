@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+
+use log::{debug, warn};
+use rjvm_reader::{
+    constant_pool::{ConstantPool, ConstantPoolEntry, MethodHandleKind},
+    program_counter::ProgramCounter,
+    type_conversion::ToUsizeSafe,
+};
+
+use crate::{
+    call_stack::CallStack,
+    class::{ClassId, ClassRef},
+    class_and_method::ClassAndMethod,
+    exceptions::MethodCallFailed,
+    lambda_metafactory::spin_lambda_class,
+    string_concat_factory::{spin_string_concat_class, CONCAT_METHOD_NAME},
+    vm::Vm,
+    vm_error::VmError,
+};
+
+/// A linked `invokedynamic` call site.
+///
+/// We do not have an implementation of `java.lang.invoke`, so rather than executing the
+/// bootstrap methods we recognize the ones emitted by javac and link them directly in the VM.
+#[derive(Debug, Clone)]
+pub(crate) enum CallSite<'a> {
+    /// Every invocation creates a new instance of the given class, spun for a
+    /// `LambdaMetafactory` call site, whose fields are the captured arguments
+    Lambda(ClassRef<'a>),
+    /// Every invocation calls the given static method, passing the call site arguments
+    StaticMethod(ClassAndMethod<'a>),
+}
+
+/// Linked call sites, one per `invokedynamic` instruction, identified by its class, the
+/// index of its method, and its address
+#[derive(Debug, Default)]
+pub(crate) struct CallSites<'a> {
+    linked: HashMap<(ClassId, usize, ProgramCounter), CallSite<'a>>,
+    next_synthetic_class_id: u32,
+}
+
+impl<'a> CallSites<'a> {
+    pub fn get(
+        &self,
+        class_id: ClassId,
+        method_index: usize,
+        pc: ProgramCounter,
+    ) -> Option<CallSite<'a>> {
+        self.linked.get(&(class_id, method_index, pc)).cloned()
+    }
+
+    pub fn insert(
+        &mut self,
+        class_id: ClassId,
+        method_index: usize,
+        pc: ProgramCounter,
+        call_site: CallSite<'a>,
+    ) {
+        self.linked.insert((class_id, method_index, pc), call_site);
+    }
+
+    pub fn next_synthetic_class_id(&mut self) -> u32 {
+        self.next_synthetic_class_id += 1;
+        self.next_synthetic_class_id
+    }
+}
+
+/// The target of a `CONSTANT_MethodHandle` pointing to a method
+#[derive(Debug)]
+pub(crate) struct MethodHandleTarget<'b> {
+    pub kind: MethodHandleKind,
+    pub class_name: &'b str,
+    pub method_name: &'b str,
+    pub type_descriptor: &'b str,
+    pub is_interface: bool,
+}
+
+pub(crate) fn link_call_site<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    class: ClassRef<'a>,
+    constant_index: u16,
+) -> Result<CallSite<'a>, MethodCallFailed<'a>> {
+    let constants = &class.constants;
+    let (bootstrap_method_index, name_and_type_index) = constants
+        .get_invoke_dynamic(constant_index)
+        .map_err(|_| VmError::ValidationException)?;
+    let (name, descriptor) = get_name_and_type(constants, name_and_type_index)?;
+    let bootstrap_method = class
+        .bootstrap_methods
+        .get(bootstrap_method_index.into_usize_safe())
+        .ok_or(VmError::ValidationException)?;
+    let bootstrap_method_handle = get_method_handle_target(constants, bootstrap_method.method_ref)?;
+    debug!(
+        "linking call site {}:{} of class {} with bootstrap method {:?}",
+        name, descriptor, class.name, bootstrap_method_handle
+    );
+
+    match (
+        bootstrap_method_handle.class_name,
+        bootstrap_method_handle.method_name,
+    ) {
+        ("java/lang/invoke/LambdaMetafactory", "metafactory" | "altMetafactory") => {
+            let lambda_class_name =
+                format!("{}$$Lambda${}", class.name, vm.next_synthetic_class_id());
+            let lambda_class_file = spin_lambda_class(
+                class,
+                lambda_class_name,
+                name,
+                descriptor,
+                &bootstrap_method.arguments,
+            )?;
+            let lambda_class = vm.define_class(call_stack, lambda_class_file)?;
+            Ok(CallSite::Lambda(lambda_class))
+        }
+        (
+            "java/lang/invoke/StringConcatFactory",
+            method_name @ ("makeConcat" | "makeConcatWithConstants"),
+        ) => {
+            // makeConcatWithConstants receives the recipe followed by the constants
+            let (recipe, constant_arguments) = if method_name == "makeConcatWithConstants" {
+                let (recipe_index, constant_arguments) = bootstrap_method
+                    .arguments
+                    .split_first()
+                    .ok_or(VmError::ValidationException)?;
+                let recipe = match constants.get(*recipe_index) {
+                    Ok(&ConstantPoolEntry::StringReference(string_index)) => {
+                        get_utf8(constants, string_index)?
+                    }
+                    _ => {
+                        return Err(MethodCallFailed::InternalError(
+                            VmError::ValidationException,
+                        ))
+                    }
+                };
+                (Some(recipe), constant_arguments)
+            } else {
+                (None, &[][..])
+            };
+            let concat_class_name = format!(
+                "{}$$StringConcat${}",
+                class.name,
+                vm.next_synthetic_class_id()
+            );
+            let concat_class_file = spin_string_concat_class(
+                class,
+                concat_class_name,
+                descriptor,
+                recipe,
+                constant_arguments,
+            )?;
+            let concat_class = vm.define_class(call_stack, concat_class_file)?;
+            let concat_method = concat_class
+                .find_class_and_method(CONCAT_METHOD_NAME, descriptor)
+                .ok_or(VmError::ValidationException)?;
+            Ok(CallSite::StaticMethod(concat_method))
+        }
+        _ => {
+            warn!(
+                "unsupported bootstrap method {}::{} {}",
+                bootstrap_method_handle.class_name,
+                bootstrap_method_handle.method_name,
+                bootstrap_method_handle.type_descriptor
+            );
+            Err(MethodCallFailed::InternalError(VmError::NotImplemented))
+        }
+    }
+}
+
+pub(crate) fn get_method_handle_target(
+    constants: &ConstantPool,
+    constant_index: u16,
+) -> Result<MethodHandleTarget<'_>, VmError> {
+    let (kind, reference_index) = constants
+        .get_method_handle(constant_index)
+        .map_err(|_| VmError::ValidationException)?;
+    let (class_index, name_and_type_index, is_interface) = match constants.get(reference_index) {
+        Ok(&ConstantPoolEntry::MethodReference(c, n)) => (c, n, false),
+        Ok(&ConstantPoolEntry::InterfaceMethodReference(c, n)) => (c, n, true),
+        _ => return Err(VmError::ValidationException),
+    };
+    let class_name = match constants.get(class_index) {
+        Ok(&ConstantPoolEntry::ClassReference(name_index)) => get_utf8(constants, name_index)?,
+        _ => return Err(VmError::ValidationException),
+    };
+    let (method_name, type_descriptor) = get_name_and_type(constants, name_and_type_index)?;
+    Ok(MethodHandleTarget {
+        kind,
+        class_name,
+        method_name,
+        type_descriptor,
+        is_interface,
+    })
+}
+
+fn get_name_and_type(
+    constants: &ConstantPool,
+    constant_index: u16,
+) -> Result<(&str, &str), VmError> {
+    match constants.get(constant_index) {
+        Ok(&ConstantPoolEntry::NameAndTypeDescriptor(name_index, type_descriptor_index)) => Ok((
+            get_utf8(constants, name_index)?,
+            get_utf8(constants, type_descriptor_index)?,
+        )),
+        _ => Err(VmError::ValidationException),
+    }
+}
+
+pub(crate) fn get_utf8(constants: &ConstantPool, constant_index: u16) -> Result<&str, VmError> {
+    match constants.get(constant_index) {
+        Ok(ConstantPoolEntry::Utf8(string)) => Ok(string),
+        _ => Err(VmError::ValidationException),
+    }
+}
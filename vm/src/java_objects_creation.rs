@@ -1,7 +1,7 @@
 use rjvm_reader::{field_type::BaseType, line_number::LineNumber};
 
 use crate::{
-    abstract_object::{string_from_char_array, AbstractObject},
+    abstract_object::{string_from_char_array, utf16_from_char_array, AbstractObject},
     array::Array,
     array_entry_type::ArrayEntryType,
     call_stack::CallStack,
@@ -29,7 +29,12 @@ pub fn new_java_lang_string_object<'a>(
         .enumerate()
         .for_each(|(index, value)| java_array.set_element(index, value).unwrap());
 
-    let string_object = vm.new_object(call_stack, "java/lang/String")?;
+    // Allocating the string can trigger a garbage collection, which would move the array
+    let base = vm.push_temporary_root(java_array);
+    let string_object = vm.new_object(call_stack, "java/lang/String");
+    let java_array = vm.temporary_root(base);
+    vm.truncate_temporary_roots(base);
+    let string_object = string_object?;
     string_object.set_field(0, Value::Object(java_array));
     string_object.set_field(1, Value::Int(0));
     string_object.set_field(6, Value::Int(0));
@@ -50,18 +55,53 @@ pub fn extract_str_from_java_lang_string<'a>(
     Err(VmError::ValidationException)
 }
 
-pub fn new_java_lang_class_object<'a>(
+/// Like `extract_str_from_java_lang_string`, but returns the UTF-16 code units of the
+/// string, since Java strings can contain unpaired surrogates
+pub(crate) fn extract_utf16_from_java_lang_string<'a>(
+    vm: &Vm<'a>,
+    object: &impl Object<'a>,
+) -> Result<Vec<u16>, VmError> {
+    let class = vm.get_class_by_id(object.class_id())?;
+    if class.name == "java/lang/String" {
+        if let Value::Object(array) = object.get_field(class, 0) {
+            return utf16_from_char_array(array);
+        }
+    }
+    Err(VmError::ValidationException)
+}
+
+/// Creates an instance of `java.lang.Class` given the internal name of a class, such as
+/// `java/lang/String`, of an array class, such as `[I`, or of a primitive type, such as `int`.
+/// Each type must have a single instance, which `Vm::get_class_mirror` returns.
+pub(crate) fn new_java_lang_class_object<'a>(
     vm: &mut Vm<'a>,
     call_stack: &mut CallStack<'a>,
     class_name: &str,
 ) -> Result<AbstractObject<'a>, MethodCallFailed<'a>> {
     let class_object = vm.new_object(call_stack, "java/lang/Class")?;
-    // TODO: build a proper instance of Class object
-    let string_object = new_java_lang_string_object(vm, call_stack, class_name)?;
-    class_object.set_field(5, Value::Object(string_object));
+    let base = vm.push_temporary_root(class_object);
+    let string_object = new_java_lang_string_object(vm, call_stack, &class_name.replace('/', "."));
+    let class_object = vm.temporary_root(base);
+    vm.truncate_temporary_roots(base);
+    class_object.set_field(5, Value::Object(string_object?));
     Ok(class_object)
 }
 
+/// Given an instance of `java.lang.Class`, returns the internal name of the class it
+/// represents
+pub fn extract_class_name_from_java_lang_class<'a>(
+    vm: &Vm<'a>,
+    object: &impl Object<'a>,
+) -> Result<String, VmError> {
+    let class = vm.get_class_by_id(object.class_id())?;
+    if class.name == "java/lang/Class" {
+        if let Value::Object(name) = object.get_field(class, 5) {
+            return Ok(extract_str_from_java_lang_string(vm, &name)?.replace('.', "/"));
+        }
+    }
+    Err(VmError::ValidationException)
+}
+
 pub fn new_java_lang_stack_trace_element_object<'a>(
     vm: &mut Vm<'a>,
     call_stack: &mut CallStack<'a>,
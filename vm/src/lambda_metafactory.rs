@@ -0,0 +1,400 @@
+use rjvm_reader::{
+    class_access_flags::ClassAccessFlags,
+    class_file::ClassFile,
+    class_file_field::ClassFileField,
+    class_file_method::{ClassFileMethod, ClassFileMethodCode},
+    constant_pool::{ConstantPool, ConstantPoolEntry, MethodHandleKind},
+    field_flags::FieldFlags,
+    field_type::{BaseType, FieldType},
+    method_descriptor::MethodDescriptor,
+    method_flags::MethodFlags,
+};
+
+use crate::{
+    class::Class,
+    class_builder::{
+        load_opcode, return_opcode, slots, ConstantPoolBuilder, ALOAD_0, CHECKCAST, DUP, GETFIELD,
+        INVOKEINTERFACE, INVOKESPECIAL, INVOKESTATIC, INVOKEVIRTUAL, NEW, POP, POP2, RETURN,
+    },
+    invoke_dynamic::{get_method_handle_target, get_utf8, MethodHandleTarget},
+    vm_error::VmError,
+};
+
+// Flags of LambdaMetafactory.altMetafactory
+const FLAG_SERIALIZABLE: i32 = 1;
+const FLAG_MARKERS: i32 = 2;
+const FLAG_BRIDGES: i32 = 4;
+
+/// Generates the class that implements the functional interface of a call site bootstrapped
+/// by `LambdaMetafactory.metafactory` or `altMetafactory`, much like the JDK's
+/// `InnerClassLambdaMetafactory` does. The captured arguments are stored in fields, and the
+/// interface method forwards them, followed by its own arguments, to the implementation
+/// method, boxing, unboxing and casting values where the erased types require it.
+pub(crate) fn spin_lambda_class(
+    caller: &Class,
+    lambda_class_name: String,
+    interface_method_name: &str,
+    call_site_descriptor: &str,
+    bootstrap_arguments: &[u16],
+) -> Result<ClassFile, VmError> {
+    let constants = &caller.constants;
+    let call_site_type = parse_method_descriptor(call_site_descriptor)?;
+    let interface_name = match call_site_type.return_type {
+        Some(FieldType::Object(interface_name)) => interface_name,
+        _ => return Err(VmError::ValidationException),
+    };
+    let [sam_method_type, implementation, _instantiated_method_type, alt_arguments @ ..] =
+        bootstrap_arguments
+    else {
+        return Err(VmError::ValidationException);
+    };
+    let sam_method_type = get_method_type(constants, *sam_method_type)?;
+    let implementation = get_method_handle_target(constants, *implementation)?;
+    let (marker_interfaces, bridges) = parse_alt_metafactory_arguments(constants, alt_arguments)?;
+
+    let mut builder = LambdaClassBuilder::new(lambda_class_name, call_site_type.parameters);
+    builder.add_forwarding_method(
+        interface_method_name,
+        sam_method_type,
+        &implementation,
+        MethodFlags::PUBLIC,
+    )?;
+    for bridge in bridges {
+        builder.add_forwarding_method(
+            interface_method_name,
+            bridge,
+            &implementation,
+            MethodFlags::PUBLIC | MethodFlags::BRIDGE | MethodFlags::SYNTHETIC,
+        )?;
+    }
+
+    let mut interfaces = vec![interface_name];
+    for marker_interface in marker_interfaces {
+        if !interfaces.contains(&marker_interface) {
+            interfaces.push(marker_interface);
+        }
+    }
+    Ok(builder.build(interfaces))
+}
+
+/// Returns the additional marker interfaces and the descriptors of the bridge methods
+fn parse_alt_metafactory_arguments<'c>(
+    constants: &'c ConstantPool,
+    arguments: &[u16],
+) -> Result<(Vec<String>, Vec<&'c str>), VmError> {
+    let mut marker_interfaces = Vec::new();
+    let mut bridges = Vec::new();
+
+    let mut arguments = arguments.iter();
+    let mut next_argument = || arguments.next().ok_or(VmError::ValidationException);
+    let flags = match next_argument() {
+        Ok(index) => get_int(constants, *index)?,
+        Err(_) => return Ok((marker_interfaces, bridges)),
+    };
+    if flags & FLAG_SERIALIZABLE != 0 {
+        marker_interfaces.push("java/io/Serializable".to_string());
+    }
+    if flags & FLAG_MARKERS != 0 {
+        let count = get_int(constants, *next_argument()?)?;
+        for _ in 0..count {
+            let marker_interface = constants
+                .text_of(*next_argument()?)
+                .map_err(|_| VmError::ValidationException)?;
+            marker_interfaces.push(marker_interface);
+        }
+    }
+    if flags & FLAG_BRIDGES != 0 {
+        let count = get_int(constants, *next_argument()?)?;
+        for _ in 0..count {
+            bridges.push(get_method_type(constants, *next_argument()?)?);
+        }
+    }
+    Ok((marker_interfaces, bridges))
+}
+
+fn get_int(constants: &ConstantPool, constant_index: u16) -> Result<i32, VmError> {
+    match constants.get(constant_index) {
+        Ok(&ConstantPoolEntry::Integer(value)) => Ok(value),
+        _ => Err(VmError::ValidationException),
+    }
+}
+
+fn get_method_type(constants: &ConstantPool, constant_index: u16) -> Result<&str, VmError> {
+    let descriptor_index = constants
+        .get_method_type(constant_index)
+        .map_err(|_| VmError::ValidationException)?;
+    get_utf8(constants, descriptor_index)
+}
+
+fn parse_method_descriptor(descriptor: &str) -> Result<MethodDescriptor, VmError> {
+    MethodDescriptor::parse(descriptor).map_err(|_| VmError::ValidationException)
+}
+
+struct LambdaClassBuilder {
+    class_name: String,
+    captured: Vec<FieldType>,
+    constants: ConstantPoolBuilder,
+    methods: Vec<ClassFileMethod>,
+}
+
+impl LambdaClassBuilder {
+    fn new(class_name: String, captured: Vec<FieldType>) -> Self {
+        Self {
+            class_name,
+            captured,
+            constants: Default::default(),
+            methods: Vec::new(),
+        }
+    }
+
+    fn captured_field_name(index: usize) -> String {
+        format!("arg${}", index + 1)
+    }
+
+    fn build(mut self, interfaces: Vec<String>) -> ClassFile {
+        self.constants.class(&self.class_name);
+        let fields = self
+            .captured
+            .into_iter()
+            .enumerate()
+            .map(|(index, field_type)| ClassFileField {
+                flags: FieldFlags::PRIVATE | FieldFlags::FINAL,
+                name: Self::captured_field_name(index),
+                type_descriptor: field_type,
+                constant_value: None,
+                deprecated: false,
+            })
+            .collect();
+        ClassFile {
+            constants: self.constants.build(),
+            flags: ClassAccessFlags::FINAL | ClassAccessFlags::SUPER | ClassAccessFlags::SYNTHETIC,
+            name: self.class_name,
+            superclass: Some("java/lang/Object".to_string()),
+            interfaces,
+            fields,
+            methods: self.methods,
+            ..Default::default()
+        }
+    }
+
+    fn add_forwarding_method(
+        &mut self,
+        method_name: &str,
+        type_descriptor: &str,
+        implementation: &MethodHandleTarget,
+        flags: MethodFlags,
+    ) -> Result<(), VmError> {
+        let method_type = parse_method_descriptor(type_descriptor)?;
+        let implementation_type = parse_method_descriptor(implementation.type_descriptor)?;
+        let implementation_class = FieldType::Object(implementation.class_name.to_string());
+        let mut code: Vec<u8> = Vec::new();
+
+        // The receiver, if any, is the first of the captured or received arguments
+        let mut target_parameters = Vec::new();
+        let (invoke_opcode, target_return_type) = match implementation.kind {
+            MethodHandleKind::InvokeStatic => (INVOKESTATIC, implementation_type.return_type),
+            MethodHandleKind::InvokeVirtual => {
+                target_parameters.push(implementation_class);
+                (INVOKEVIRTUAL, implementation_type.return_type)
+            }
+            MethodHandleKind::InvokeInterface => {
+                target_parameters.push(implementation_class);
+                (INVOKEINTERFACE, implementation_type.return_type)
+            }
+            MethodHandleKind::InvokeSpecial => {
+                target_parameters.push(implementation_class);
+                (INVOKESPECIAL, implementation_type.return_type)
+            }
+            MethodHandleKind::NewInvokeSpecial => {
+                let class_index = self.constants.class(implementation.class_name);
+                code.push(NEW);
+                code.extend_from_slice(&class_index.to_be_bytes());
+                code.push(DUP);
+                (INVOKESPECIAL, Some(implementation_class))
+            }
+            _ => return Err(VmError::ValidationException),
+        };
+        target_parameters.extend(implementation_type.parameters);
+        if target_parameters.len() != self.captured.len() + method_type.parameters.len() {
+            return Err(VmError::ValidationException);
+        }
+
+        for index in 0..self.captured.len() {
+            let field_index = self.constants.field(
+                &self.class_name,
+                &Self::captured_field_name(index),
+                &self.captured[index].descriptor(),
+            );
+            code.push(ALOAD_0);
+            code.push(GETFIELD);
+            code.extend_from_slice(&field_index.to_be_bytes());
+        }
+
+        let mut local_index = 1;
+        for (parameter, target_parameter) in method_type
+            .parameters
+            .iter()
+            .zip(target_parameters.iter().skip(self.captured.len()))
+        {
+            code.push(load_opcode(parameter));
+            code.push(u8::try_from(local_index).map_err(|_| VmError::ValidationException)?);
+            local_index += slots(parameter);
+            self.emit_conversion(&mut code, parameter, target_parameter)?;
+        }
+
+        let method_index = self.constants.method(
+            implementation.class_name,
+            implementation.method_name,
+            implementation.type_descriptor,
+            implementation.is_interface,
+        );
+        code.push(invoke_opcode);
+        code.extend_from_slice(&method_index.to_be_bytes());
+        let target_parameters_slots: usize = target_parameters.iter().map(slots).sum();
+        if invoke_opcode == INVOKEINTERFACE {
+            code.push(
+                u8::try_from(target_parameters_slots).map_err(|_| VmError::ValidationException)?,
+            );
+            code.push(0);
+        }
+
+        match (&target_return_type, &method_type.return_type) {
+            (None, None) => code.push(RETURN),
+            (Some(returned), None) => {
+                code.push(if slots(returned) == 2 { POP2 } else { POP });
+                code.push(RETURN);
+            }
+            (Some(returned), Some(expected)) => {
+                self.emit_conversion(&mut code, returned, expected)?;
+                code.push(return_opcode(expected));
+            }
+            (None, Some(_)) => return Err(VmError::ValidationException),
+        }
+
+        self.methods.push(ClassFileMethod {
+            flags,
+            name: method_name.to_string(),
+            type_descriptor: type_descriptor.to_string(),
+            parsed_type_descriptor: method_type,
+            attributes: Vec::new(),
+            code: Some(ClassFileMethodCode {
+                // The two extra slots are for new/dup and for the return value
+                max_stack: u16::try_from(target_parameters_slots + 2)
+                    .map_err(|_| VmError::ValidationException)?,
+                max_locals: u16::try_from(local_index).map_err(|_| VmError::ValidationException)?,
+                code,
+                ..Default::default()
+            }),
+            deprecated: false,
+            thrown_exceptions: Vec::new(),
+        });
+        Ok(())
+    }
+
+    fn emit_conversion(
+        &mut self,
+        code: &mut Vec<u8>,
+        from: &FieldType,
+        to: &FieldType,
+    ) -> Result<(), VmError> {
+        match (from, to) {
+            _ if from == to => Ok(()),
+            (FieldType::Base(from), FieldType::Base(to)) => emit_widening(code, from, to),
+            (FieldType::Base(from), _) => {
+                let (wrapper_class, _) = wrapper_of(from);
+                let descriptor = format!("({})L{};", from.descriptor(), wrapper_class);
+                let method_index =
+                    self.constants
+                        .method(wrapper_class, "valueOf", &descriptor, false);
+                code.push(INVOKESTATIC);
+                code.extend_from_slice(&method_index.to_be_bytes());
+                Ok(())
+            }
+            (_, FieldType::Base(to)) => {
+                // Unbox to the primitive type of the wrapper, if we know it, and then widen
+                let unboxed_type = match from {
+                    FieldType::Object(class_name) => {
+                        base_type_of_wrapper(class_name).unwrap_or(to.clone())
+                    }
+                    _ => to.clone(),
+                };
+                let (wrapper_class, unbox_method) = wrapper_of(&unboxed_type);
+                let class_index = self.constants.class(wrapper_class);
+                code.push(CHECKCAST);
+                code.extend_from_slice(&class_index.to_be_bytes());
+                let descriptor = format!("(){}", unboxed_type.descriptor());
+                let method_index =
+                    self.constants
+                        .method(wrapper_class, unbox_method, &descriptor, false);
+                code.push(INVOKEVIRTUAL);
+                code.extend_from_slice(&method_index.to_be_bytes());
+                emit_widening(code, &unboxed_type, to)
+            }
+            (_, FieldType::Object(class_name)) if class_name == "java/lang/Object" => Ok(()),
+            (_, FieldType::Object(class_name)) => {
+                let class_index = self.constants.class(class_name);
+                code.push(CHECKCAST);
+                code.extend_from_slice(&class_index.to_be_bytes());
+                Ok(())
+            }
+            (_, FieldType::Array(_)) => {
+                let class_index = self.constants.class(&to.descriptor());
+                code.push(CHECKCAST);
+                code.extend_from_slice(&class_index.to_be_bytes());
+                Ok(())
+            }
+        }
+    }
+}
+
+fn is_int_like(base_type: &BaseType) -> bool {
+    matches!(
+        base_type,
+        BaseType::Byte | BaseType::Char | BaseType::Short | BaseType::Int | BaseType::Boolean
+    )
+}
+
+fn emit_widening(code: &mut Vec<u8>, from: &BaseType, to: &BaseType) -> Result<(), VmError> {
+    let opcode = match (from, to) {
+        _ if from == to => return Ok(()),
+        (from, to) if is_int_like(from) && is_int_like(to) => return Ok(()),
+        (from, BaseType::Long) if is_int_like(from) => 0x85, // i2l
+        (from, BaseType::Float) if is_int_like(from) => 0x86, // i2f
+        (from, BaseType::Double) if is_int_like(from) => 0x87, // i2d
+        (BaseType::Long, BaseType::Float) => 0x89,           // l2f
+        (BaseType::Long, BaseType::Double) => 0x8a,          // l2d
+        (BaseType::Float, BaseType::Double) => 0x8d,         // f2d
+        _ => return Err(VmError::ValidationException),
+    };
+    code.push(opcode);
+    Ok(())
+}
+
+/// Returns the wrapper class of a primitive type and the name of its unboxing method
+fn wrapper_of(base_type: &BaseType) -> (&'static str, &'static str) {
+    match base_type {
+        BaseType::Byte => ("java/lang/Byte", "byteValue"),
+        BaseType::Char => ("java/lang/Character", "charValue"),
+        BaseType::Double => ("java/lang/Double", "doubleValue"),
+        BaseType::Float => ("java/lang/Float", "floatValue"),
+        BaseType::Int => ("java/lang/Integer", "intValue"),
+        BaseType::Long => ("java/lang/Long", "longValue"),
+        BaseType::Short => ("java/lang/Short", "shortValue"),
+        BaseType::Boolean => ("java/lang/Boolean", "booleanValue"),
+    }
+}
+
+fn base_type_of_wrapper(class_name: &str) -> Option<BaseType> {
+    [
+        BaseType::Byte,
+        BaseType::Char,
+        BaseType::Double,
+        BaseType::Float,
+        BaseType::Int,
+        BaseType::Long,
+        BaseType::Short,
+        BaseType::Boolean,
+    ]
+    .into_iter()
+    .find(|base_type| wrapper_of(base_type).0 == class_name)
+}
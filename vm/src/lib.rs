@@ -2,26 +2,41 @@ pub mod abstract_object;
 pub mod alloc_entry;
 pub mod array;
 pub mod array_entry_type;
+mod assignability;
 mod call_frame;
 pub mod call_stack;
 pub mod class;
 pub mod class_and_method;
+mod class_builder;
 mod class_loader;
 mod class_manager;
 mod class_path;
 mod class_path_entry;
 mod class_resolver_by_id;
+pub mod decoded_code;
 pub mod exceptions;
+mod fdlibm;
 mod file_system_class_path_entry;
 mod gc;
+mod invoke_dynamic;
 mod jar_file_class_path_entry;
 pub mod java_objects_creation;
+mod lambda_metafactory;
+mod method_resolution;
 mod native_methods_impl;
+mod native_methods_io;
+mod native_methods_reflection;
 pub mod native_methods_registry;
 pub mod object;
+mod open_files;
+mod resolved_references;
+mod scheduler;
 pub mod stack_trace_element;
+pub mod standard_streams;
+mod string_concat_factory;
 mod time;
 pub mod value;
 mod value_stack;
 pub mod vm;
 pub mod vm_error;
+mod vtable;
@@ -0,0 +1,166 @@
+use rjvm_reader::method_flags::MethodFlags;
+
+use crate::{class::ClassRef, class_and_method::ClassAndMethod, vm_error::VmError};
+
+/// Resolves a method reference whose class is not an interface, as in JVMS 5.4.3.3: the
+/// method is looked up in the class and its superclasses, and then in its superinterfaces
+pub(crate) fn resolve_class_method<'a>(
+    class: ClassRef<'a>,
+    method_name: &str,
+    type_descriptor: &str,
+) -> Result<ClassAndMethod<'a>, VmError> {
+    if class.is_interface() {
+        return Err(VmError::IncompatibleClassChangeError(format!(
+            "Found interface {}, but class was expected",
+            class.name.replace('/', ".")
+        )));
+    }
+
+    let mut curr_class = Some(class);
+    while let Some(class) = curr_class {
+        if let Some(method) = class.find_class_and_method(method_name, type_descriptor) {
+            return Ok(method);
+        }
+        curr_class = class.superclass;
+    }
+
+    let superinterfaces = all_superinterfaces(class.superclass, &class.interfaces);
+    resolve_in_superinterfaces(&superinterfaces, method_name, type_descriptor)
+        .ok_or_else(|| method_not_found(class, method_name, type_descriptor))
+}
+
+/// Resolves a method reference whose class is an interface, as in JVMS 5.4.3.4: the method
+/// is looked up in the interface, then in the public methods of `Object`, and then in its
+/// superinterfaces
+pub(crate) fn resolve_interface_method<'a>(
+    interface: ClassRef<'a>,
+    method_name: &str,
+    type_descriptor: &str,
+) -> Result<ClassAndMethod<'a>, VmError> {
+    if !interface.is_interface() {
+        return Err(VmError::IncompatibleClassChangeError(format!(
+            "Found class {}, but interface was expected",
+            interface.name.replace('/', ".")
+        )));
+    }
+
+    if let Some(method) = interface.find_class_and_method(method_name, type_descriptor) {
+        return Ok(method);
+    }
+
+    // The superclass of an interface is always java.lang.Object
+    let object_method = interface.superclass.and_then(|object| {
+        object
+            .find_class_and_method(method_name, type_descriptor)
+            .filter(|method| {
+                method.method.flags.contains(MethodFlags::PUBLIC) && !method.is_static()
+            })
+    });
+    if let Some(object_method) = object_method {
+        return Ok(object_method);
+    }
+
+    let superinterfaces = all_superinterfaces(None, &interface.interfaces);
+    resolve_in_superinterfaces(&superinterfaces, method_name, type_descriptor)
+        .ok_or_else(|| method_not_found(interface, method_name, type_descriptor))
+}
+
+/// Uses the only non-abstract maximally-specific superinterface method if there is one,
+/// or otherwise any of the superinterface methods
+fn resolve_in_superinterfaces<'a>(
+    superinterfaces: &[ClassRef<'a>],
+    method_name: &str,
+    type_descriptor: &str,
+) -> Option<ClassAndMethod<'a>> {
+    let candidates = superinterface_methods(superinterfaces, method_name, type_descriptor);
+    let maximally_specific = maximally_specific_methods(&candidates);
+    match non_abstract_methods(&maximally_specific).as_slice() {
+        [method] => Some(*method),
+        _ => maximally_specific.first().cloned(),
+    }
+}
+
+fn method_not_found(class: ClassRef, method_name: &str, type_descriptor: &str) -> VmError {
+    VmError::MethodNotFoundException(
+        class.name.to_string(),
+        method_name.to_string(),
+        type_descriptor.to_string(),
+    )
+}
+
+/// Returns the instance methods with the given name and descriptor declared by the given
+/// interfaces, excluding the private ones
+fn superinterface_methods<'a>(
+    superinterfaces: &[ClassRef<'a>],
+    method_name: &str,
+    type_descriptor: &str,
+) -> Vec<ClassAndMethod<'a>> {
+    superinterfaces
+        .iter()
+        .filter_map(|&interface| {
+            interface
+                .find_class_and_method(method_name, type_descriptor)
+                .filter(|method| {
+                    !method
+                        .method
+                        .flags
+                        .intersects(MethodFlags::STATIC | MethodFlags::PRIVATE)
+                })
+        })
+        .collect()
+}
+
+/// Filters the methods declared by interfaces that are not overridden by a method of one
+/// of their subinterfaces, i.e. the maximally-specific methods of JVMS 5.4.3.3
+pub(crate) fn maximally_specific_methods<'a>(
+    candidates: &[ClassAndMethod<'a>],
+) -> Vec<ClassAndMethod<'a>> {
+    candidates
+        .iter()
+        .filter(|candidate| {
+            !candidates.iter().any(|other| {
+                !std::ptr::eq(other.class, candidate.class)
+                    && other.class.is_subclass_of(candidate.class)
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+pub(crate) fn non_abstract_methods<'a>(methods: &[ClassAndMethod<'a>]) -> Vec<ClassAndMethod<'a>> {
+    methods
+        .iter()
+        .filter(|method| !method.method.is_abstract())
+        .cloned()
+        .collect()
+}
+
+/// Returns all the interfaces implemented by a class, directly or through its superclasses
+/// and superinterfaces, without duplicates
+pub(crate) fn all_superinterfaces<'a>(
+    superclass: Option<ClassRef<'a>>,
+    interfaces: &[ClassRef<'a>],
+) -> Vec<ClassRef<'a>> {
+    let mut all_interfaces = Vec::new();
+    for interface in interfaces {
+        collect_interface(interface, &mut all_interfaces);
+    }
+    let mut class = superclass;
+    while let Some(curr_class) = class {
+        for interface in curr_class.interfaces.iter() {
+            collect_interface(interface, &mut all_interfaces);
+        }
+        class = curr_class.superclass;
+    }
+    all_interfaces
+}
+
+fn collect_interface<'a>(interface: ClassRef<'a>, all_interfaces: &mut Vec<ClassRef<'a>>) {
+    if all_interfaces.iter().any(|other| other.id == interface.id) {
+        return;
+    }
+    all_interfaces.push(interface);
+    for superinterface in interface.interfaces.iter() {
+        collect_interface(superinterface, all_interfaces);
+    }
+}
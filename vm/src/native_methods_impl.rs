@@ -1,18 +1,32 @@
-use log::{debug, info};
+use std::{
+    env::consts::{ARCH, DLL_PREFIX, DLL_SUFFIX, OS},
+    io::Write,
+    path::MAIN_SEPARATOR_STR,
+};
+
+use log::info;
 use rjvm_reader::type_conversion::ToUsizeSafe;
 
 use crate::{
-    abstract_object::{AbstractObject, ObjectKind}, array::Array, call_frame::MethodCallResult, call_stack::CallStack, exceptions::MethodCallFailed, java_objects_creation::{extract_str_from_java_lang_string, new_java_lang_class_object, new_java_lang_stack_trace_element_object}, native_methods_registry::NativeMethodsRegistry, object::Object, time::{get_current_time_millis, get_nano_time}, value::{expect_abstract_object_at, expect_array_at, expect_concrete_object_at, expect_double_at, expect_float_at, expect_int_at, Value}, vm::Vm, vm_error::VmError
+    abstract_object::{AbstractObject, ObjectKind}, array::Array, call_frame::{CallFrame, MethodCallResult}, call_stack::CallStack, exceptions::MethodCallFailed, fdlibm, java_objects_creation::{extract_str_from_java_lang_string, new_java_lang_stack_trace_element_object, new_java_lang_string_object}, native_methods_io::register_io_methods, native_methods_reflection::{register_reflection_methods, register_unsafe_methods}, native_methods_registry::NativeMethodsRegistry, object::Object, time::{get_current_time_millis, get_nano_time}, value::{expect_abstract_object_at, expect_array_at, expect_concrete_object_at, expect_double_at, expect_float_at, expect_int_at, expect_long_at, Value}, vm::Vm, vm_error::VmError
 };
 
 pub(crate) fn register_natives(registry: &mut NativeMethodsRegistry) {
     registry.register_temp_print(|vm, _, _, args| temp_print(vm, args));
     register_noops(registry);
+    register_object_methods(registry);
     register_time_methods(registry);
     register_gc_methods(registry);
     register_native_repr_methods(registry);
+    register_math_methods(registry);
     register_reflection_methods(registry);
     register_throwable_methods(registry);
+    register_string_methods(registry);
+    register_thread_methods(registry);
+    register_system_methods(registry);
+    register_io_methods(registry);
+    register_security_methods(registry);
+    register_unsafe_methods(registry);
 }
 
 fn register_noops(registry: &mut NativeMethodsRegistry) {
@@ -37,6 +51,44 @@ fn register_noops(registry: &mut NativeMethodsRegistry) {
         "()V",
         |_, _, _, _| Ok(None),
     );
+    registry.register(
+        "java/lang/Thread",
+        "registerNatives",
+        "()V",
+        |_, _, _, _| Ok(None),
+    );
+    registry.register("sun/misc/VM", "initialize", "()V", |_, _, _, _| Ok(None));
+    registry.register("jdk/internal/misc/VM", "initialize", "()V", |_, _, _, _| {
+        Ok(None)
+    });
+    // The scheduler ignores priorities
+    registry.register("java/lang/Thread", "setPriority0", "(I)V", |_, _, _, _| {
+        Ok(None)
+    });
+}
+
+fn register_object_methods(registry: &mut NativeMethodsRegistry) {
+    registry.register(
+        "java/lang/Object",
+        "getClass",
+        "()Ljava/lang/Class;",
+        |vm, call_stack, receiver, _| get_class(vm, call_stack, receiver),
+    );
+    registry.register(
+        "java/lang/Object",
+        "hashCode",
+        "()I",
+        |_, _, receiver, _| {
+            let receiver = expect_some_receiver(receiver)?;
+            Ok(Some(Value::Int(receiver.identity_hash_code())))
+        },
+    );
+    registry.register(
+        "java/lang/Object",
+        "clone",
+        "()Ljava/lang/Object;",
+        |vm, _, receiver, _| clone(vm, receiver),
+    );
 }
 
 fn register_time_methods(registry: &mut NativeMethodsRegistry) {
@@ -83,27 +135,86 @@ fn register_native_repr_methods(registry: &mut NativeMethodsRegistry) {
         "(D)J",
         |_, _, _, args| double_to_raw_long_bits(&args),
     );
-}
-
-fn register_reflection_methods(registry: &mut NativeMethodsRegistry) {
     registry.register(
-        "java/lang/Class",
-        "getClassLoader0",
-        "()Ljava/lang/ClassLoader;",
-        |_, _, receiver, _| get_class_loader(receiver),
+        "java/lang/Float",
+        "intBitsToFloat",
+        "(I)F",
+        |_, _, _, args| int_bits_to_float(&args),
     );
     registry.register(
-        "java/lang/Class",
-        "desiredAssertionStatus0",
-        "(Ljava/lang/Class;)Z",
-        |_, _, _, _| Ok(Some(Value::Int(1))),
+        "java/lang/Double",
+        "longBitsToDouble",
+        "(J)D",
+        |_, _, _, args| long_bits_to_double(&args),
     );
+}
+
+/// The natives of `StrictMath` must return exactly the results of fdlibm. The `libm`
+/// crate does for some functions, and we have ported the others. `Math` delegates to them.
+fn register_math_methods(registry: &mut NativeMethodsRegistry) {
+    registry.register("java/lang/StrictMath", "sin", "(D)D", |_, _, _, args| {
+        unary_math_function(&args, fdlibm::sin)
+    });
+    registry.register("java/lang/StrictMath", "cos", "(D)D", |_, _, _, args| {
+        unary_math_function(&args, fdlibm::cos)
+    });
+    registry.register("java/lang/StrictMath", "tan", "(D)D", |_, _, _, args| {
+        unary_math_function(&args, fdlibm::tan)
+    });
+    registry.register("java/lang/StrictMath", "asin", "(D)D", |_, _, _, args| {
+        unary_math_function(&args, libm::asin)
+    });
+    registry.register("java/lang/StrictMath", "acos", "(D)D", |_, _, _, args| {
+        unary_math_function(&args, libm::acos)
+    });
+    registry.register("java/lang/StrictMath", "atan", "(D)D", |_, _, _, args| {
+        unary_math_function(&args, libm::atan)
+    });
+    registry.register("java/lang/StrictMath", "exp", "(D)D", |_, _, _, args| {
+        unary_math_function(&args, libm::exp)
+    });
+    registry.register("java/lang/StrictMath", "log", "(D)D", |_, _, _, args| {
+        unary_math_function(&args, fdlibm::log)
+    });
+    registry.register("java/lang/StrictMath", "log10", "(D)D", |_, _, _, args| {
+        unary_math_function(&args, fdlibm::log10)
+    });
+    registry.register("java/lang/StrictMath", "sqrt", "(D)D", |_, _, _, args| {
+        unary_math_function(&args, libm::sqrt)
+    });
+    registry.register("java/lang/StrictMath", "cbrt", "(D)D", |_, _, _, args| {
+        unary_math_function(&args, fdlibm::cbrt)
+    });
+    registry.register("java/lang/StrictMath", "sinh", "(D)D", |_, _, _, args| {
+        unary_math_function(&args, fdlibm::sinh)
+    });
+    registry.register("java/lang/StrictMath", "cosh", "(D)D", |_, _, _, args| {
+        unary_math_function(&args, fdlibm::cosh)
+    });
+    registry.register("java/lang/StrictMath", "tanh", "(D)D", |_, _, _, args| {
+        unary_math_function(&args, fdlibm::tanh)
+    });
+    registry.register("java/lang/StrictMath", "expm1", "(D)D", |_, _, _, args| {
+        unary_math_function(&args, libm::expm1)
+    });
+    registry.register("java/lang/StrictMath", "log1p", "(D)D", |_, _, _, args| {
+        unary_math_function(&args, fdlibm::log1p)
+    });
     registry.register(
-        "java/lang/Class",
-        "getPrimitiveClass",
-        "(Ljava/lang/String;)Ljava/lang/Class;",
-        |vm, stack, _, args| get_primitive_class(vm, stack, &args),
+        "java/lang/StrictMath",
+        "IEEEremainder",
+        "(DD)D",
+        |_, _, _, args| binary_math_function(&args, libm::remainder),
     );
+    registry.register("java/lang/StrictMath", "atan2", "(DD)D", |_, _, _, args| {
+        binary_math_function(&args, fdlibm::atan2)
+    });
+    registry.register("java/lang/StrictMath", "pow", "(DD)D", |_, _, _, args| {
+        binary_math_function(&args, fdlibm::pow)
+    });
+    registry.register("java/lang/StrictMath", "hypot", "(DD)D", |_, _, _, args| {
+        binary_math_function(&args, fdlibm::hypot)
+    });
 }
 
 fn register_throwable_methods(registry: &mut NativeMethodsRegistry) {
@@ -127,8 +238,203 @@ fn register_throwable_methods(registry: &mut NativeMethodsRegistry) {
     );
 }
 
+fn register_string_methods(registry: &mut NativeMethodsRegistry) {
+    registry.register(
+        "java/lang/String",
+        "intern",
+        "()Ljava/lang/String;",
+        |vm, _, receiver, _| intern(vm, receiver),
+    );
+}
+
+fn register_thread_methods(registry: &mut NativeMethodsRegistry) {
+    registry.register(
+        "java/lang/Thread",
+        "currentThread",
+        "()Ljava/lang/Thread;",
+        |vm, call_stack, _, _| {
+            let java_thread = vm.current_java_thread(call_stack)?;
+            Ok(Some(Value::Object(java_thread)))
+        },
+    );
+    registry.register("java/lang/Thread", "yield", "()V", |vm, _, _, _| {
+        vm.yield_thread();
+        Ok(None)
+    });
+    registry.register(
+        "java/lang/Thread",
+        "sleep",
+        "(J)V",
+        |vm, call_stack, _, args| sleep(vm, call_stack, args),
+    );
+    registry.register("java/lang/Thread", "start0", "()V", |vm, _, receiver, _| {
+        vm.start_thread(expect_some_receiver(receiver)?)?;
+        Ok(None)
+    });
+    registry.register(
+        "java/lang/Thread",
+        "isAlive",
+        "()Z",
+        |vm, _, receiver, _| {
+            let receiver = expect_some_receiver(receiver)?;
+            let alive = vm
+                .find_thread(&receiver)
+                .is_some_and(|thread_id| vm.is_thread_alive(thread_id));
+            Ok(Some(Value::Int(alive.into())))
+        },
+    );
+    registry.register(
+        "java/lang/Thread",
+        "interrupt0",
+        "()V",
+        |vm, _, receiver, _| {
+            vm.interrupt(&expect_some_receiver(receiver)?);
+            Ok(None)
+        },
+    );
+    registry.register(
+        "java/lang/Thread",
+        "isInterrupted",
+        "(Z)Z",
+        |vm, _, receiver, args| {
+            let receiver = expect_some_receiver(receiver)?;
+            let interrupted = vm.is_interrupted(&receiver, expect_int_at(&args, 0)? != 0);
+            Ok(Some(Value::Int(interrupted.into())))
+        },
+    );
+    registry.register(
+        "java/lang/Object",
+        "notify",
+        "()V",
+        |vm, call_stack, receiver, _| {
+            vm.notify(call_stack, &expect_some_receiver(receiver)?, false)?;
+            Ok(None)
+        },
+    );
+    registry.register(
+        "java/lang/Object",
+        "notifyAll",
+        "()V",
+        |vm, call_stack, receiver, _| {
+            vm.notify(call_stack, &expect_some_receiver(receiver)?, true)?;
+            Ok(None)
+        },
+    );
+    registry.register(
+        "java/lang/Object",
+        "wait",
+        "(J)V",
+        |vm, call_stack, receiver, args| wait(vm, call_stack, receiver, args),
+    );
+}
+
+fn register_system_methods(registry: &mut NativeMethodsRegistry) {
+    registry.register(
+        "java/lang/System",
+        "initProperties",
+        "(Ljava/util/Properties;)Ljava/util/Properties;",
+        |vm, call_stack, _, args| {
+            let properties = expect_abstract_object_at(&args, 0)?;
+            let properties = vm.set_properties(call_stack, properties, &system_properties())?;
+            Ok(Some(Value::Object(properties)))
+        },
+    );
+    registry.register(
+        "java/lang/System",
+        "setIn0",
+        "(Ljava/io/InputStream;)V",
+        |vm, call_stack, _, args| set_standard_stream(vm, call_stack, "in", args),
+    );
+    registry.register(
+        "java/lang/System",
+        "setOut0",
+        "(Ljava/io/PrintStream;)V",
+        |vm, call_stack, _, args| set_standard_stream(vm, call_stack, "out", args),
+    );
+    registry.register(
+        "java/lang/System",
+        "setErr0",
+        "(Ljava/io/PrintStream;)V",
+        |vm, call_stack, _, args| set_standard_stream(vm, call_stack, "err", args),
+    );
+    registry.register(
+        "java/lang/System",
+        "mapLibraryName",
+        "(Ljava/lang/String;)Ljava/lang/String;",
+        |vm, call_stack, _, args| {
+            let name =
+                extract_str_from_java_lang_string(vm, &expect_concrete_object_at(&args, 0)?)?;
+            let file_name = format!("{DLL_PREFIX}{name}{DLL_SUFFIX}");
+            let file_name = new_java_lang_string_object(vm, call_stack, &file_name)?;
+            Ok(Some(Value::Object(file_name)))
+        },
+    );
+    registry.register(
+        "java/lang/ClassLoader$NativeLibrary",
+        "findBuiltinLib",
+        "(Ljava/lang/String;)Ljava/lang/String;",
+        |vm, call_stack, _, args| find_builtin_lib(vm, call_stack, args),
+    );
+    registry.register(
+        "java/lang/ClassLoader$NativeLibrary",
+        "load",
+        "(Ljava/lang/String;Z)V",
+        |vm, _, receiver, _| {
+            // The natives of all the libraries are built into the vm
+            let receiver = expect_some_receiver(receiver)?;
+            set_field_by_name(vm, &receiver, "loaded", Value::Int(1))?;
+            Ok(None)
+        },
+    );
+}
+
+fn register_security_methods(registry: &mut NativeMethodsRegistry) {
+    for class_name in ["sun/reflect/Reflection", "jdk/internal/reflect/Reflection"] {
+        registry.register(
+            class_name,
+            "getCallerClass",
+            "()Ljava/lang/Class;",
+            |vm, call_stack, _, _| get_caller_class(vm, call_stack),
+        );
+    }
+    // There is no security manager, so privileged actions are simply executed
+    registry.register(
+        "java/security/AccessController",
+        "doPrivileged",
+        "(Ljava/security/PrivilegedAction;)Ljava/lang/Object;",
+        |vm, call_stack, _, args| do_privileged(vm, call_stack, args),
+    );
+    registry.register(
+        "java/security/AccessController",
+        "doPrivileged",
+        "(Ljava/security/PrivilegedAction;Ljava/security/AccessControlContext;)Ljava/lang/Object;",
+        |vm, call_stack, _, args| do_privileged(vm, call_stack, args),
+    );
+    registry.register(
+        "java/security/AccessController",
+        "doPrivileged",
+        "(Ljava/security/PrivilegedExceptionAction;)Ljava/lang/Object;",
+        |vm, call_stack, _, args| do_privileged_with_exception(vm, call_stack, args),
+    );
+    registry.register(
+        "java/security/AccessController",
+        "doPrivileged",
+        "(Ljava/security/PrivilegedExceptionAction;Ljava/security/AccessControlContext;)Ljava/lang/Object;",
+        |vm, call_stack, _, args| do_privileged_with_exception(vm, call_stack, args),
+    );
+    registry.register(
+        "java/security/AccessController",
+        "getStackAccessControlContext",
+        "()Ljava/security/AccessControlContext;",
+        |_, _, _, _| Ok(Some(Value::Null)),
+    );
+}
+
+/// Writes a line with the argument to the standard output. Values are formatted like Java
+/// literals, so that the tests can tell their types apart: longs have an `L` suffix, floats
+/// an `f` suffix, and strings are quoted and escaped.
 fn temp_print<'a>(vm: &mut Vm<'a>, args: Vec<Value<'a>>) -> MethodCallResult<'a> {
-    let arg = args.get(0).ok_or(VmError::ValidationException)?;
+    let arg = args.first().ok_or(VmError::ValidationException)?;
 
     let formatted = match arg {
         Value::Object(object) if object.kind() == ObjectKind::Object => {
@@ -136,36 +442,94 @@ fn temp_print<'a>(vm: &mut Vm<'a>, args: Vec<Value<'a>>) -> MethodCallResult<'a>
                 .get_class_by_id(object.class_id())
                 .expect("cannot get an object without a valid class id");
             if class.name == "java/lang/String" {
-                extract_str_from_java_lang_string(vm, object)
-                    .expect("should be able to get a string's content")
+                let string = extract_str_from_java_lang_string(vm, object)
+                    .expect("should be able to get a string's content");
+                format!("{:?}", string)
             } else {
                 format!("{:?}", object)
             }
         }
+        Value::Int(int) => int.to_string(),
+        Value::Long(long) => format!("{long}L"),
+        // Debug keeps the decimal point of integral values, like Java does
+        Value::Float(float) => format!("{:?}f", float),
+        Value::Double(double) => format!("{:?}", double),
+        Value::Null => "null".to_string(),
         _ => format!("{:?}", arg),
     };
     info!("TEMP implementation of native method: printing value {formatted}",);
-    vm.printed.push(arg.clone());
+    let stdout = &mut vm.standard_streams().stdout;
+    writeln!(stdout, "{formatted}")
+        .and_then(|_| stdout.flush())
+        .map_err(|err| VmError::IOException(err.to_string()))?;
     Ok(None)
 }
 
+fn get_class<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    receiver: Option<AbstractObject<'a>>,
+) -> MethodCallResult<'a> {
+    let receiver = expect_some_receiver(receiver)?;
+    let class_name = CallFrame::get_class_name_of(vm, &receiver)?;
+    let mirror = vm.get_class_mirror(call_stack, &class_name)?;
+    Ok(Some(Value::Object(mirror)))
+}
+
+/// Implements `Object.clone`, which copies the fields of objects whose class implements
+/// `Cloneable`. Arrays are always cloneable.
+fn clone<'a>(vm: &mut Vm<'a>, receiver: Option<AbstractObject<'a>>) -> MethodCallResult<'a> {
+    let receiver = expect_some_receiver(receiver)?;
+    if receiver.kind() == ObjectKind::Array {
+        return Ok(Some(vm.clone_array(Value::Object(receiver))?));
+    }
+    let class = vm.get_class_by_id(receiver.class_id())?;
+    let cloneable = vm.get_or_resolve_class("java/lang/Cloneable")?;
+    if !class.is_subclass_of(cloneable) {
+        return Err(VmError::CloneNotSupportedException(class.name.replace('/', ".")).into());
+    }
+    Ok(Some(Value::Object(vm.clone_object(&receiver)?)))
+}
+
 fn identity_hash_code(args: Vec<Value<'_>>) -> MethodCallResult<'_> {
     let object = expect_abstract_object_at(&args, 0)?;
     Ok(Some(Value::Int(object.identity_hash_code())))
 }
 
 fn native_array_copy(args: Vec<Value>) -> MethodCallResult {
-    // TODO: handle NullPointerException with the correct error
+    if args.first() == Some(&Value::Null) || args.get(2) == Some(&Value::Null) {
+        return Err(MethodCallFailed::InternalError(
+            VmError::NullPointerException,
+        ));
+    }
 
     let src = expect_array_at(&args, 0)?;
     let src_pos = expect_int_at(&args, 1)?;
     let dest = expect_array_at(&args, 2)?;
     let dest_pos = expect_int_at(&args, 3)?;
     let length = expect_int_at(&args, 4)?;
+    check_array_copy_bounds(&src, src_pos, length)?;
+    check_array_copy_bounds(&dest, dest_pos, length)?;
     array_copy(&src, src_pos, &dest, dest_pos, length.into_usize_safe())?;
     Ok(None)
 }
 
+/// Validates the range before copying anything, like the real `System.arraycopy`
+pub(crate) fn check_array_copy_bounds<'a>(
+    array: &impl Array<'a>,
+    position: i32,
+    length: i32,
+) -> Result<(), VmError> {
+    let end = i64::from(position) + i64::from(length);
+    if position < 0 {
+        Err(VmError::ArrayIndexOutOfBoundsException(position.into(), array.len()))
+    } else if length < 0 || end > i64::from(array.len()) {
+        Err(VmError::ArrayIndexOutOfBoundsException(end, array.len()))
+    } else {
+        Ok(())
+    }
+}
+
 pub fn array_copy<'a>(
     src: &impl Array<'a>,
     src_pos: i32,
@@ -200,22 +564,29 @@ fn double_to_raw_long_bits<'a>(args: &[Value<'a>]) -> MethodCallResult<'a> {
     Ok(Some(Value::Long(long_bits)))
 }
 
-fn get_class_loader(receiver: Option<AbstractObject>) -> MethodCallResult {
-    debug!("invoked get class loader for object {:?}", receiver);
+fn int_bits_to_float<'a>(args: &[Value<'a>]) -> MethodCallResult<'a> {
+    let arg = expect_int_at(args, 0)?;
+    Ok(Some(Value::Float(f32::from_bits(arg as u32))))
+}
 
-    // It seems ok to return just null for the moment
-    Ok(Some(Value::Null))
+fn long_bits_to_double<'a>(args: &[Value<'a>]) -> MethodCallResult<'a> {
+    let arg = expect_long_at(args, 0)?;
+    Ok(Some(Value::Double(f64::from_bits(arg as u64))))
 }
 
-fn get_primitive_class<'a>(
-    vm: &mut Vm<'a>,
-    stack: &mut CallStack<'a>,
+fn unary_math_function<'a>(args: &[Value<'a>], function: fn(f64) -> f64) -> MethodCallResult<'a> {
+    let arg = expect_double_at(args, 0)?;
+    Ok(Some(Value::Double(function(arg))))
+}
+
+fn binary_math_function<'a>(
     args: &[Value<'a>],
+    function: fn(f64, f64) -> f64,
 ) -> MethodCallResult<'a> {
-    let arg = expect_concrete_object_at(args, 0)?;
-    let class_name = extract_str_from_java_lang_string(vm, &arg)?;
-    let java_lang_class_instance = new_java_lang_class_object(vm, stack, &class_name)?;
-    Ok(Some(Value::Object(java_lang_class_instance)))
+    // Like in the local variables, a double takes two slots
+    let first = expect_double_at(args, 0)?;
+    let second = expect_double_at(args, 2)?;
+    Ok(Some(Value::Double(function(first, second))))
 }
 
 fn fill_in_stack_trace<'a>(
@@ -263,7 +634,223 @@ fn get_stack_trace_element<'a>(
     }
 }
 
-fn expect_some_receiver(receiver: Option<AbstractObject>) -> Result<AbstractObject, VmError> {
+fn intern<'a>(vm: &mut Vm<'a>, receiver: Option<AbstractObject<'a>>) -> MethodCallResult<'a> {
+    let receiver = expect_some_receiver(receiver)?;
+    let interned = vm.intern_string_object(receiver)?;
+    Ok(Some(Value::Object(interned)))
+}
+
+fn sleep<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    args: Vec<Value<'a>>,
+) -> MethodCallResult<'a> {
+    let millis = expect_long_at(&args, 0)?;
+    match millis {
+        ..0 => Err(negative_timeout().into()),
+        0 => {
+            vm.yield_thread();
+            Ok(None)
+        }
+        _ => {
+            vm.sleep(call_stack, wake_up_time(millis))?;
+            Ok(None)
+        }
+    }
+}
+
+fn wait<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    receiver: Option<AbstractObject<'a>>,
+    args: Vec<Value<'a>>,
+) -> MethodCallResult<'a> {
+    let receiver = expect_some_receiver(receiver)?;
+    let millis = expect_long_at(&args, 0)?;
+    if millis < 0 {
+        return Err(negative_timeout().into());
+    }
+    let wake_up_at = (millis > 0).then(|| wake_up_time(millis));
+    vm.wait(call_stack, receiver, wake_up_at)?;
+    Ok(None)
+}
+
+fn negative_timeout() -> VmError {
+    VmError::IllegalArgumentException("timeout value is negative".to_string())
+}
+
+/// Returns the value of `get_nano_time` after the given number of milliseconds
+fn wake_up_time(millis: i64) -> i64 {
+    get_nano_time().saturating_add(millis.saturating_mul(1_000_000))
+}
+
+/// The properties that `System.initProperties` sets, using the same names and formats as
+/// HotSpot for the operating system and architecture
+fn system_properties() -> Vec<(String, String)> {
+    let os_name = match OS {
+        "linux" => "Linux",
+        "macos" => "Mac OS X",
+        "windows" => "Windows",
+        other => other,
+    };
+    let os_arch = match ARCH {
+        "x86_64" => "amd64",
+        "x86" => "i386",
+        other => other,
+    };
+    let (path_separator, line_separator) = if cfg!(windows) {
+        (";", "\r\n")
+    } else {
+        (":", "\n")
+    };
+    let env_var = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| std::env::var(name).ok())
+            .unwrap_or_else(|| "?".to_string())
+    };
+    let user_dir = std::env::current_dir()
+        .map(|dir| dir.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let tmp_dir = std::env::temp_dir().to_string_lossy().into_owned();
+
+    [
+        ("java.version", "1.8.0"),
+        ("java.vendor", "rjvm"),
+        ("java.specification.version", "1.8"),
+        ("java.class.version", "52.0"),
+        ("java.vm.name", "rjvm"),
+        ("java.vm.vendor", "rjvm"),
+        ("java.vm.version", env!("CARGO_PKG_VERSION")),
+        ("java.library.path", ""),
+        ("sun.boot.library.path", ""),
+        ("java.io.tmpdir", &tmp_dir),
+        ("os.name", os_name),
+        ("os.arch", os_arch),
+        ("file.separator", MAIN_SEPARATOR_STR),
+        ("path.separator", path_separator),
+        ("line.separator", line_separator),
+        ("file.encoding", "UTF-8"),
+        ("sun.jnu.encoding", "UTF-8"),
+        ("user.dir", &user_dir),
+        ("user.home", &env_var(&["HOME", "USERPROFILE"])),
+        ("user.name", &env_var(&["USER", "USERNAME"])),
+        // Keeps reflective calls on the natives, rather than on generated accessor classes
+        ("sun.reflect.inflationThreshold", "2147483647"),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value.to_string()))
+    .collect()
+}
+
+/// Implements `System.setIn0`, `setOut0` and `setErr0`, which change the `final` fields
+fn set_standard_stream<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    field_name: &str,
+    args: Vec<Value<'a>>,
+) -> MethodCallResult<'a> {
+    let stream = args.first().ok_or(VmError::ValidationException)?;
+    vm.set_static_field(call_stack, "java/lang/System", field_name, stream.clone())?;
+    Ok(None)
+}
+
+/// All the natives are built into the vm, so any library is found, under the name
+/// without the platform's prefix and suffix
+fn find_builtin_lib<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    args: Vec<Value<'a>>,
+) -> MethodCallResult<'a> {
+    let file_name = extract_str_from_java_lang_string(vm, &expect_concrete_object_at(&args, 0)?)?;
+    match file_name
+        .strip_prefix(DLL_PREFIX)
+        .and_then(|name| name.strip_suffix(DLL_SUFFIX))
+    {
+        Some(name) => {
+            let name = new_java_lang_string_object(vm, call_stack, name)?;
+            Ok(Some(Value::Object(name)))
+        }
+        None => Ok(Some(Value::Null)),
+    }
+}
+
+pub(crate) fn get_field_by_name<'a>(
+    vm: &Vm<'a>,
+    object: &AbstractObject<'a>,
+    field_name: &str,
+) -> Result<Value<'a>, VmError> {
+    let class = vm.get_class_by_id(object.class_id())?;
+    let (_, index, _) = class.find_field(field_name).ok_or_else(|| {
+        VmError::FieldNotFoundException(class.name.clone(), field_name.to_string())
+    })?;
+    Ok(object.get_field(class, index))
+}
+
+pub(crate) fn set_field_by_name<'a>(
+    vm: &Vm<'a>,
+    object: &AbstractObject<'a>,
+    field_name: &str,
+    value: Value<'a>,
+) -> Result<(), VmError> {
+    let class = vm.get_class_by_id(object.class_id())?;
+    let (_, index, _) = class.find_field(field_name).ok_or_else(|| {
+        VmError::FieldNotFoundException(class.name.clone(), field_name.to_string())
+    })?;
+    object.set_field(index, value);
+    Ok(())
+}
+
+/// Returns the class of the method that invoked the caller of `getCallerClass`. Natives
+/// have no frame, so the top one is the caller's.
+fn get_caller_class<'a>(vm: &mut Vm<'a>, call_stack: &mut CallStack<'a>) -> MethodCallResult<'a> {
+    let caller = call_stack
+        .method_at_depth(1)
+        .ok_or(VmError::ValidationException)?;
+    let class = vm.get_class_mirror(call_stack, &caller.class.name)?;
+    Ok(Some(Value::Object(class)))
+}
+
+fn do_privileged<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    args: Vec<Value<'a>>,
+) -> MethodCallResult<'a> {
+    match args.first() {
+        Some(Value::Object(action)) => {
+            vm.invoke_virtual(call_stack, action.clone(), "run", "()Ljava/lang/Object;")
+        }
+        Some(Value::Null) => Err(VmError::NullPointerException.into()),
+        _ => Err(VmError::ValidationException.into()),
+    }
+}
+
+/// Executes a `PrivilegedExceptionAction`, wrapping the checked exceptions that it throws
+/// in a `PrivilegedActionException`
+fn do_privileged_with_exception<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    args: Vec<Value<'a>>,
+) -> MethodCallResult<'a> {
+    match do_privileged(vm, call_stack, args) {
+        Err(MethodCallFailed::ExceptionThrown(exception))
+            if vm.is_checked_exception(&exception)? =>
+        {
+            let wrapper = vm.new_wrapping_exception(
+                call_stack,
+                "java/security/PrivilegedActionException",
+                "(Ljava/lang/Exception;)V",
+                exception,
+            )?;
+            Err(MethodCallFailed::ExceptionThrown(wrapper))
+        }
+        result => result,
+    }
+}
+
+pub(crate) fn expect_some_receiver(
+    receiver: Option<AbstractObject>,
+) -> Result<AbstractObject, VmError> {
     match receiver {
         Some(v) => Ok(v),
         None => Err(VmError::ValidationException),
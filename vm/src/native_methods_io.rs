@@ -0,0 +1,644 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Component, Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use rjvm_reader::type_conversion::ToUsizeSafe;
+
+use crate::{
+    abstract_object::AbstractObject,
+    array::Array,
+    call_frame::MethodCallResult,
+    call_stack::CallStack,
+    java_objects_creation::{extract_str_from_java_lang_string, new_java_lang_string_object},
+    native_methods_impl::{
+        check_array_copy_bounds, expect_some_receiver, get_field_by_name, set_field_by_name,
+    },
+    native_methods_registry::NativeMethodsRegistry,
+    open_files::bad_file_descriptor,
+    value::{expect_abstract_object_at, expect_array_at, expect_int_at, expect_long_at, Value},
+    vm::Vm,
+    vm_error::VmError,
+};
+
+// Values of the file attributes returned by `FileSystem.getBooleanAttributes`
+const BA_EXISTS: i32 = 0x01;
+const BA_REGULAR: i32 = 0x02;
+const BA_DIRECTORY: i32 = 0x04;
+
+// Values of the mode of `RandomAccessFile.open0`
+const O_RDWR: i32 = 2;
+
+/// The value of the field `fd` of a closed `FileDescriptor`
+const CLOSED_FILE_DESCRIPTOR: i32 = -1;
+
+pub(crate) fn register_io_methods(registry: &mut NativeMethodsRegistry) {
+    register_file_descriptor_methods(registry);
+    register_file_input_stream_methods(registry);
+    register_file_output_stream_methods(registry);
+    register_random_access_file_methods(registry);
+    register_file_system_methods(registry);
+}
+
+fn register_file_descriptor_methods(registry: &mut NativeMethodsRegistry) {
+    registry.register("java/io/FileDescriptor", "initIDs", "()V", |_, _, _, _| {
+        Ok(None)
+    });
+    registry.register(
+        "java/io/FileDescriptor",
+        "sync",
+        "()V",
+        |vm, _, receiver, _| {
+            let receiver = expect_some_receiver(receiver)?;
+            let result = match expect_int_field(vm, &receiver, "fd")? {
+                1 => vm.standard_streams().stdout.flush(),
+                2 => vm.standard_streams().stderr.flush(),
+                fd => match vm.open_files().get(fd) {
+                    Ok(file) => file.sync_all(),
+                    Err(_) => Err(io::ErrorKind::InvalidInput.into()),
+                },
+            };
+            result.map_err(|_| VmError::SyncFailedException("sync failed".to_string()))?;
+            Ok(None)
+        },
+    );
+}
+
+fn register_file_input_stream_methods(registry: &mut NativeMethodsRegistry) {
+    registry.register("java/io/FileInputStream", "initIDs", "()V", |_, _, _, _| {
+        Ok(None)
+    });
+    registry.register(
+        "java/io/FileInputStream",
+        "open0",
+        "(Ljava/lang/String;)V",
+        |vm, _, receiver, args| open_file(vm, receiver, &args, OpenOptions::new().read(true)),
+    );
+    registry.register(
+        "java/io/FileInputStream",
+        "read0",
+        "()I",
+        |vm, _, receiver, _| read_byte(vm, receiver),
+    );
+    registry.register(
+        "java/io/FileInputStream",
+        "readBytes",
+        "([BII)I",
+        |vm, _, receiver, args| read_bytes(vm, receiver, args),
+    );
+    registry.register(
+        "java/io/FileInputStream",
+        "skip",
+        "(J)J",
+        |vm, _, receiver, args| {
+            let receiver = expect_some_receiver(receiver)?;
+            let count = expect_long_at(&args, 0)?;
+            let skipped = match file_descriptor(vm, &receiver)? {
+                0 => io::copy(
+                    &mut vm
+                        .standard_streams()
+                        .stdin
+                        .by_ref()
+                        .take(count.max(0) as u64),
+                    &mut io::sink(),
+                )
+                .map(|skipped| skipped as i64),
+                fd => {
+                    let file = vm.open_files().get(fd)?;
+                    file.stream_position().and_then(|start| {
+                        let end = file.seek(SeekFrom::Current(count))?;
+                        Ok(end as i64 - start as i64)
+                    })
+                }
+            }
+            .map_err(io_exception)?;
+            Ok(Some(Value::Long(skipped)))
+        },
+    );
+    // We cannot know how much the standard input could provide without blocking
+    registry.register(
+        "java/io/FileInputStream",
+        "available",
+        "()I",
+        |vm, _, receiver, _| {
+            let receiver = expect_some_receiver(receiver)?;
+            let available = match file_descriptor(vm, &receiver)? {
+                0 => 0,
+                fd => {
+                    let file = vm.open_files().get(fd)?;
+                    let length = file.metadata().map_err(io_exception)?.len();
+                    let position = file.stream_position().map_err(io_exception)?;
+                    length.saturating_sub(position).min(i32::MAX as u64) as i32
+                }
+            };
+            Ok(Some(Value::Int(available)))
+        },
+    );
+    registry.register(
+        "java/io/FileInputStream",
+        "close0",
+        "()V",
+        |vm, _, receiver, _| close_file(vm, receiver),
+    );
+}
+
+fn register_file_output_stream_methods(registry: &mut NativeMethodsRegistry) {
+    registry.register(
+        "java/io/FileOutputStream",
+        "initIDs",
+        "()V",
+        |_, _, _, _| Ok(None),
+    );
+    registry.register(
+        "java/io/FileOutputStream",
+        "open0",
+        "(Ljava/lang/String;Z)V",
+        |vm, _, receiver, args| {
+            let append = expect_int_at(&args, 1)? != 0;
+            let mut options = OpenOptions::new();
+            options
+                .write(true)
+                .create(true)
+                .append(append)
+                .truncate(!append);
+            open_file(vm, receiver, &args, &options)
+        },
+    );
+    registry.register(
+        "java/io/FileOutputStream",
+        "write",
+        "(IZ)V",
+        |vm, _, receiver, args| write_byte(vm, receiver, args),
+    );
+    registry.register(
+        "java/io/FileOutputStream",
+        "writeBytes",
+        "([BIIZ)V",
+        |vm, _, receiver, args| write_bytes(vm, receiver, args),
+    );
+    registry.register(
+        "java/io/FileOutputStream",
+        "close0",
+        "()V",
+        |vm, _, receiver, _| close_file(vm, receiver),
+    );
+}
+
+fn register_random_access_file_methods(registry: &mut NativeMethodsRegistry) {
+    registry.register(
+        "java/io/RandomAccessFile",
+        "initIDs",
+        "()V",
+        |_, _, _, _| Ok(None),
+    );
+    registry.register(
+        "java/io/RandomAccessFile",
+        "open0",
+        "(Ljava/lang/String;I)V",
+        |vm, _, receiver, args| {
+            let writable = expect_int_at(&args, 1)? & O_RDWR != 0;
+            let mut options = OpenOptions::new();
+            options.read(true).write(writable).create(writable);
+            open_file(vm, receiver, &args, &options)
+        },
+    );
+    registry.register(
+        "java/io/RandomAccessFile",
+        "read0",
+        "()I",
+        |vm, _, receiver, _| read_byte(vm, receiver),
+    );
+    registry.register(
+        "java/io/RandomAccessFile",
+        "readBytes",
+        "([BII)I",
+        |vm, _, receiver, args| read_bytes(vm, receiver, args),
+    );
+    registry.register(
+        "java/io/RandomAccessFile",
+        "write0",
+        "(I)V",
+        |vm, _, receiver, args| write_byte(vm, receiver, args),
+    );
+    registry.register(
+        "java/io/RandomAccessFile",
+        "writeBytes",
+        "([BII)V",
+        |vm, _, receiver, args| write_bytes(vm, receiver, args),
+    );
+    registry.register(
+        "java/io/RandomAccessFile",
+        "getFilePointer",
+        "()J",
+        |vm, _, receiver, _| {
+            let file = random_access_file(vm, receiver)?;
+            let position = file.stream_position().map_err(io_exception)?;
+            Ok(Some(Value::Long(position as i64)))
+        },
+    );
+    registry.register(
+        "java/io/RandomAccessFile",
+        "seek0",
+        "(J)V",
+        |vm, _, receiver, args| {
+            let position = expect_long_at(&args, 0)?;
+            let file = random_access_file(vm, receiver)?;
+            file.seek(SeekFrom::Start(position as u64))
+                .map_err(io_exception)?;
+            Ok(None)
+        },
+    );
+    registry.register(
+        "java/io/RandomAccessFile",
+        "length",
+        "()J",
+        |vm, _, receiver, _| {
+            let file = random_access_file(vm, receiver)?;
+            let length = file.metadata().map_err(io_exception)?.len();
+            Ok(Some(Value::Long(length as i64)))
+        },
+    );
+    registry.register(
+        "java/io/RandomAccessFile",
+        "setLength",
+        "(J)V",
+        |vm, _, receiver, args| {
+            let length = expect_long_at(&args, 0)?;
+            let file = random_access_file(vm, receiver)?;
+            set_file_length(file, length).map_err(io_exception)?;
+            Ok(None)
+        },
+    );
+    registry.register(
+        "java/io/RandomAccessFile",
+        "close0",
+        "()V",
+        |vm, _, receiver, _| close_file(vm, receiver),
+    );
+}
+
+fn register_file_system_methods(registry: &mut NativeMethodsRegistry) {
+    registry.register("java/io/UnixFileSystem", "initIDs", "()V", |_, _, _, _| {
+        Ok(None)
+    });
+    registry.register(
+        "java/io/UnixFileSystem",
+        "canonicalize0",
+        "(Ljava/lang/String;)Ljava/lang/String;",
+        |vm, call_stack, _, args| {
+            let path = expect_string_at(vm, &args, 0)?;
+            let canonical_path = canonicalize(Path::new(&path)).map_err(io_exception)?;
+            let canonical_path =
+                new_java_lang_string_object(vm, call_stack, &canonical_path.to_string_lossy())?;
+            Ok(Some(Value::Object(canonical_path)))
+        },
+    );
+    registry.register(
+        "java/io/UnixFileSystem",
+        "getBooleanAttributes0",
+        "(Ljava/io/File;)I",
+        |vm, _, _, args| {
+            let attributes = match fs::metadata(file_path_at(vm, &args, 0)?) {
+                Ok(metadata) if metadata.is_dir() => BA_EXISTS | BA_DIRECTORY,
+                Ok(metadata) if metadata.is_file() => BA_EXISTS | BA_REGULAR,
+                Ok(_) => BA_EXISTS,
+                Err(_) => 0,
+            };
+            Ok(Some(Value::Int(attributes)))
+        },
+    );
+    registry.register(
+        "java/io/UnixFileSystem",
+        "getLastModifiedTime",
+        "(Ljava/io/File;)J",
+        |vm, _, _, args| {
+            let last_modified_time = fs::metadata(file_path_at(vm, &args, 0)?)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |duration| duration.as_millis() as i64);
+            Ok(Some(Value::Long(last_modified_time)))
+        },
+    );
+    registry.register(
+        "java/io/UnixFileSystem",
+        "getLength",
+        "(Ljava/io/File;)J",
+        |vm, _, _, args| {
+            let length = fs::metadata(file_path_at(vm, &args, 0)?)
+                .map_or(0, |metadata| metadata.len() as i64);
+            Ok(Some(Value::Long(length)))
+        },
+    );
+    registry.register(
+        "java/io/UnixFileSystem",
+        "delete0",
+        "(Ljava/io/File;)Z",
+        |vm, _, _, args| {
+            let path = file_path_at(vm, &args, 0)?;
+            let deleted = if Path::new(&path).is_dir() {
+                fs::remove_dir(path).is_ok()
+            } else {
+                fs::remove_file(path).is_ok()
+            };
+            Ok(Some(Value::Int(deleted.into())))
+        },
+    );
+    registry.register(
+        "java/io/UnixFileSystem",
+        "createDirectory",
+        "(Ljava/io/File;)Z",
+        |vm, _, _, args| {
+            let created = fs::create_dir(file_path_at(vm, &args, 0)?).is_ok();
+            Ok(Some(Value::Int(created.into())))
+        },
+    );
+    registry.register(
+        "java/io/UnixFileSystem",
+        "list",
+        "(Ljava/io/File;)[Ljava/lang/String;",
+        |vm, call_stack, _, args| list_directory(vm, call_stack, &args),
+    );
+}
+
+/// Opens the file whose name is the first argument, and stores its descriptor in the
+/// `FileDescriptor` of the given stream
+fn open_file<'a>(
+    vm: &mut Vm<'a>,
+    receiver: Option<AbstractObject<'a>>,
+    args: &[Value<'a>],
+    options: &OpenOptions,
+) -> MethodCallResult<'a> {
+    let receiver = expect_some_receiver(receiver)?;
+    let path = expect_string_at(vm, args, 0)?;
+    let file = options
+        .open(&path)
+        .map_err(|err| VmError::FileNotFoundException(format!("{path} ({})", describe(&err))))?;
+    // Like the JDK, we refuse to open directories, even just for reading
+    if file.metadata().is_ok_and(|metadata| metadata.is_dir()) {
+        return Err(VmError::FileNotFoundException(format!("{path} (Is a directory)")).into());
+    }
+    let fd = vm.open_files().add(file);
+    set_file_descriptor(vm, &receiver, fd)?;
+    Ok(None)
+}
+
+fn close_file<'a>(vm: &mut Vm<'a>, receiver: Option<AbstractObject<'a>>) -> MethodCallResult<'a> {
+    let receiver = expect_some_receiver(receiver)?;
+    let file_descriptor_object = match get_field_by_name(vm, &receiver, "fd")? {
+        Value::Object(file_descriptor_object) => file_descriptor_object,
+        _ => return Ok(None),
+    };
+    match expect_int_field(vm, &file_descriptor_object, "fd")? {
+        // The standard streams belong to the host, which will close them
+        CLOSED_FILE_DESCRIPTOR | 0..=2 => {}
+        fd => vm.open_files().remove(fd)?,
+    }
+    set_field_by_name(
+        vm,
+        &file_descriptor_object,
+        "fd",
+        Value::Int(CLOSED_FILE_DESCRIPTOR),
+    )?;
+    Ok(None)
+}
+
+fn read_byte<'a>(vm: &mut Vm<'a>, receiver: Option<AbstractObject<'a>>) -> MethodCallResult<'a> {
+    let receiver = expect_some_receiver(receiver)?;
+    let mut buffer = [0u8];
+    let read = read_from_file_descriptor(vm, &receiver, &mut buffer)?;
+    let byte = if read == 0 { -1 } else { buffer[0].into() };
+    Ok(Some(Value::Int(byte)))
+}
+
+fn read_bytes<'a>(
+    vm: &mut Vm<'a>,
+    receiver: Option<AbstractObject<'a>>,
+    args: Vec<Value<'a>>,
+) -> MethodCallResult<'a> {
+    let receiver = expect_some_receiver(receiver)?;
+    if args.first() == Some(&Value::Null) {
+        return Err(VmError::NullPointerException.into());
+    }
+    let array = expect_array_at(&args, 0)?;
+    let offset = expect_int_at(&args, 1)?;
+    let length = expect_int_at(&args, 2)?;
+    check_array_copy_bounds(&array, offset, length)?;
+    if length == 0 {
+        return Ok(Some(Value::Int(0)));
+    }
+    let mut buffer = vec![0u8; length.into_usize_safe()];
+    let read = read_from_file_descriptor(vm, &receiver, &mut buffer)?;
+    if read == 0 {
+        return Ok(Some(Value::Int(-1)));
+    }
+    for (index, byte) in buffer[..read].iter().enumerate() {
+        array.set_element(
+            offset.into_usize_safe() + index,
+            Value::Int(*byte as i8 as i32),
+        )?;
+    }
+    Ok(Some(Value::Int(read as i32)))
+}
+
+fn write_byte<'a>(
+    vm: &mut Vm<'a>,
+    receiver: Option<AbstractObject<'a>>,
+    args: Vec<Value<'a>>,
+) -> MethodCallResult<'a> {
+    let receiver = expect_some_receiver(receiver)?;
+    let byte = expect_int_at(&args, 0)? as u8;
+    write_to_file_descriptor(vm, &receiver, &[byte])?;
+    Ok(None)
+}
+
+fn write_bytes<'a>(
+    vm: &mut Vm<'a>,
+    receiver: Option<AbstractObject<'a>>,
+    args: Vec<Value<'a>>,
+) -> MethodCallResult<'a> {
+    let receiver = expect_some_receiver(receiver)?;
+    if args.first() == Some(&Value::Null) {
+        return Err(VmError::NullPointerException.into());
+    }
+    let array = expect_array_at(&args, 0)?;
+    let offset = expect_int_at(&args, 1)?;
+    let length = expect_int_at(&args, 2)?;
+    check_array_copy_bounds(&array, offset, length)?;
+    let bytes = (offset..offset + length)
+        .map(|index| match array.get_element(index.into_usize_safe())? {
+            Value::Int(byte) => Ok(byte as u8),
+            _ => Err(VmError::ValidationException),
+        })
+        .collect::<Result<Vec<u8>, VmError>>()?;
+    write_to_file_descriptor(vm, &receiver, &bytes)?;
+    Ok(None)
+}
+
+/// Writes to the file descriptor of a stream: the standard output and error are the ones
+/// provided by the host, the others are the files opened by the Java code
+fn write_to_file_descriptor<'a>(
+    vm: &mut Vm<'a>,
+    stream: &AbstractObject<'a>,
+    bytes: &[u8],
+) -> Result<(), VmError> {
+    let output: &mut dyn Write = match file_descriptor(vm, stream)? {
+        0 => return Err(bad_file_descriptor()),
+        1 => &mut vm.standard_streams().stdout,
+        2 => &mut vm.standard_streams().stderr,
+        fd => vm.open_files().get(fd)?,
+    };
+    output
+        .write_all(bytes)
+        .and_then(|_| output.flush())
+        .map_err(io_exception)
+}
+
+/// Reads from the file descriptor of a stream: the standard input is the one provided
+/// by the host, the others are the files opened by the Java code. Returns zero at the
+/// end of the stream.
+fn read_from_file_descriptor<'a>(
+    vm: &mut Vm<'a>,
+    stream: &AbstractObject<'a>,
+    buffer: &mut [u8],
+) -> Result<usize, VmError> {
+    let input: &mut dyn Read = match file_descriptor(vm, stream)? {
+        0 => &mut vm.standard_streams().stdin,
+        1 | 2 => return Err(bad_file_descriptor()),
+        fd => vm.open_files().get(fd)?,
+    };
+    input.read(buffer).map_err(io_exception)
+}
+
+/// Returns the number in the `FileDescriptor` of a stream, which must not be closed
+fn file_descriptor<'a>(vm: &Vm<'a>, stream: &AbstractObject<'a>) -> Result<i32, VmError> {
+    match get_field_by_name(vm, stream, "fd")? {
+        Value::Object(file_descriptor) => match expect_int_field(vm, &file_descriptor, "fd")? {
+            CLOSED_FILE_DESCRIPTOR => Err(stream_closed()),
+            fd => Ok(fd),
+        },
+        _ => Err(stream_closed()),
+    }
+}
+
+fn set_file_descriptor<'a>(
+    vm: &Vm<'a>,
+    stream: &AbstractObject<'a>,
+    fd: i32,
+) -> Result<(), VmError> {
+    match get_field_by_name(vm, stream, "fd")? {
+        Value::Object(file_descriptor) => {
+            set_field_by_name(vm, &file_descriptor, "fd", Value::Int(fd))
+        }
+        _ => Err(VmError::ValidationException),
+    }
+}
+
+fn random_access_file<'a, 'b>(
+    vm: &'b mut Vm<'a>,
+    receiver: Option<AbstractObject<'a>>,
+) -> Result<&'b mut File, VmError> {
+    let receiver = expect_some_receiver(receiver)?;
+    let fd = file_descriptor(vm, &receiver)?;
+    vm.open_files().get(fd)
+}
+
+/// Truncates or extends a file, moving its position back if it was past the new end
+fn set_file_length(file: &mut File, length: i64) -> io::Result<()> {
+    let length = u64::try_from(length).map_err(|_| io::ErrorKind::InvalidInput)?;
+    file.set_len(length)?;
+    if file.stream_position()? > length {
+        file.seek(SeekFrom::Start(length))?;
+    }
+    Ok(())
+}
+
+fn list_directory<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    args: &[Value<'a>],
+) -> MethodCallResult<'a> {
+    let names = fs::read_dir(file_path_at(vm, args, 0)?).and_then(|entries| {
+        entries
+            .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned()))
+            .collect::<io::Result<Vec<String>>>()
+    });
+    match names {
+        Ok(names) => Ok(Some(Value::Object(
+            vm.new_string_array(call_stack, &names)?,
+        ))),
+        Err(_) => Ok(Some(Value::Null)),
+    }
+}
+
+/// Resolves the symbolic links, `.` and `..` of an absolute path, like `realpath` does,
+/// except that the file does not need to exist: only its longest existing prefix is
+/// resolved, and the remaining components are appended to it.
+fn canonicalize(path: &Path) -> io::Result<PathBuf> {
+    for existing in path.ancestors() {
+        if let Ok(mut result) = existing.canonicalize() {
+            let remaining = path.strip_prefix(existing).unwrap_or(Path::new(""));
+            for component in remaining.components() {
+                match component {
+                    Component::ParentDir => {
+                        result.pop();
+                    }
+                    Component::Normal(name) => result.push(name),
+                    _ => {}
+                }
+            }
+            return Ok(result);
+        }
+    }
+    Err(io::ErrorKind::NotFound.into())
+}
+
+/// Returns the field `path` of the `java.io.File` at the given index
+fn file_path_at<'a>(vm: &Vm<'a>, args: &[Value<'a>], index: usize) -> Result<String, VmError> {
+    if args.get(index) == Some(&Value::Null) {
+        return Err(VmError::NullPointerException);
+    }
+    let file = expect_abstract_object_at(args, index)?;
+    match get_field_by_name(vm, &file, "path")? {
+        Value::Object(path) => extract_str_from_java_lang_string(vm, &path),
+        _ => Err(VmError::NullPointerException),
+    }
+}
+
+fn expect_string_at<'a>(vm: &Vm<'a>, args: &[Value<'a>], index: usize) -> Result<String, VmError> {
+    match args.get(index) {
+        Some(Value::Object(string)) => extract_str_from_java_lang_string(vm, string),
+        Some(Value::Null) => Err(VmError::NullPointerException),
+        _ => Err(VmError::ValidationException),
+    }
+}
+
+fn expect_int_field<'a>(
+    vm: &Vm<'a>,
+    object: &AbstractObject<'a>,
+    field_name: &str,
+) -> Result<i32, VmError> {
+    match get_field_by_name(vm, object, field_name)? {
+        Value::Int(value) => Ok(value),
+        _ => Err(VmError::ValidationException),
+    }
+}
+
+fn stream_closed() -> VmError {
+    VmError::IOException("Stream Closed".to_string())
+}
+
+fn io_exception(err: io::Error) -> VmError {
+    VmError::IOException(describe(&err))
+}
+
+/// Describes an error like `strerror` does, i.e. without the error code appended by Rust
+fn describe(err: &io::Error) -> String {
+    let description = err.to_string();
+    match description.find(" (os error ") {
+        Some(index) => description[..index].to_string(),
+        None => description,
+    }
+}
@@ -0,0 +1,1137 @@
+use log::debug;
+use rjvm_reader::{
+    class_access_flags::ClassAccessFlags,
+    class_file_method::ClassFileMethod,
+    field_flags::FieldFlags,
+    field_type::{BaseType, FieldType},
+    method_flags::MethodFlags,
+    type_conversion::ToUsizeSafe,
+};
+
+use crate::{
+    abstract_object::{AbstractObject, ObjectKind},
+    array::Array,
+    array_entry_type::ArrayEntryType,
+    assignability::{is_assignable, ARRAY_INTERFACES},
+    call_frame::{CallFrame, MethodCallResult},
+    call_stack::CallStack,
+    class::ClassRef,
+    class_and_method::ClassAndMethod,
+    exceptions::MethodCallFailed,
+    java_objects_creation::{
+        extract_class_name_from_java_lang_class, extract_str_from_java_lang_string,
+        new_java_lang_string_object,
+    },
+    native_methods_impl::{expect_some_receiver, get_field_by_name, set_field_by_name},
+    native_methods_registry::NativeMethodsRegistry,
+    object::Object,
+    value::{
+        expect_abstract_object_at, expect_concrete_object_at, expect_int_at, expect_long_at, Value,
+    },
+    vm::Vm,
+    vm_error::VmError,
+};
+
+/// The primitive types, with the name of their `java.lang.Class` and of their box class
+const PRIMITIVE_TYPES: [(BaseType, &str, &str); 8] = [
+    (BaseType::Boolean, "boolean", "java/lang/Boolean"),
+    (BaseType::Byte, "byte", "java/lang/Byte"),
+    (BaseType::Char, "char", "java/lang/Character"),
+    (BaseType::Short, "short", "java/lang/Short"),
+    (BaseType::Int, "int", "java/lang/Integer"),
+    (BaseType::Long, "long", "java/lang/Long"),
+    (BaseType::Float, "float", "java/lang/Float"),
+    (BaseType::Double, "double", "java/lang/Double"),
+];
+
+/// The `public`, `private` and `protected` modifiers, as in `java.lang.reflect.Modifier`
+const ACCESS_MODIFIERS: i32 = 0x0007;
+
+pub(crate) fn register_reflection_methods(registry: &mut NativeMethodsRegistry) {
+    registry.register(
+        "java/lang/Class",
+        "getClassLoader0",
+        "()Ljava/lang/ClassLoader;",
+        |_, _, receiver, _| get_class_loader(receiver),
+    );
+    registry.register(
+        "java/lang/Class",
+        "desiredAssertionStatus0",
+        "(Ljava/lang/Class;)Z",
+        |_, _, _, _| Ok(Some(Value::Int(1))),
+    );
+    registry.register(
+        "java/lang/Class",
+        "getPrimitiveClass",
+        "(Ljava/lang/String;)Ljava/lang/Class;",
+        |vm, stack, _, args| get_primitive_class(vm, stack, &args),
+    );
+    registry.register(
+        "java/lang/Class",
+        "getName0",
+        "()Ljava/lang/String;",
+        |vm, stack, receiver, _| get_name(vm, stack, receiver),
+    );
+    registry.register("java/lang/Class", "isArray", "()Z", |vm, _, receiver, _| {
+        let class_name = mirror_name(vm, receiver)?;
+        Ok(Some(Value::Int(class_name.starts_with('[').into())))
+    });
+    registry.register(
+        "java/lang/Class",
+        "isPrimitive",
+        "()Z",
+        |vm, _, receiver, _| {
+            let class_name = mirror_name(vm, receiver)?;
+            Ok(Some(Value::Int(is_primitive_name(&class_name).into())))
+        },
+    );
+    registry.register(
+        "java/lang/Class",
+        "isInterface",
+        "()Z",
+        |vm, _, receiver, _| is_interface(vm, receiver),
+    );
+    registry.register(
+        "java/lang/Class",
+        "getComponentType",
+        "()Ljava/lang/Class;",
+        |vm, stack, receiver, _| get_component_type(vm, stack, receiver),
+    );
+    registry.register(
+        "java/lang/Class",
+        "getSuperclass",
+        "()Ljava/lang/Class;",
+        |vm, stack, receiver, _| get_superclass(vm, stack, receiver),
+    );
+    // Older versions of the JDK declare getInterfaces itself as native
+    for method_name in ["getInterfaces", "getInterfaces0"] {
+        registry.register(
+            "java/lang/Class",
+            method_name,
+            "()[Ljava/lang/Class;",
+            |vm, stack, receiver, _| get_interfaces(vm, stack, receiver),
+        );
+    }
+    registry.register(
+        "java/lang/Class",
+        "isInstance",
+        "(Ljava/lang/Object;)Z",
+        |vm, _, receiver, args| is_instance(vm, receiver, &args),
+    );
+    registry.register(
+        "java/lang/Class",
+        "isAssignableFrom",
+        "(Ljava/lang/Class;)Z",
+        |vm, _, receiver, args| is_assignable_from(vm, receiver, &args),
+    );
+    registry.register(
+        "java/lang/Class",
+        "getModifiers",
+        "()I",
+        |vm, _, receiver, _| {
+            let class_name = mirror_name(vm, receiver)?;
+            Ok(Some(Value::Int(class_modifiers(vm, &class_name)?)))
+        },
+    );
+    // Java 7 does not pass the caller
+    registry.register(
+        "java/lang/Class",
+        "forName0",
+        "(Ljava/lang/String;ZLjava/lang/ClassLoader;)Ljava/lang/Class;",
+        |vm, stack, _, args| for_name(vm, stack, &args),
+    );
+    registry.register(
+        "java/lang/Class",
+        "forName0",
+        "(Ljava/lang/String;ZLjava/lang/ClassLoader;Ljava/lang/Class;)Ljava/lang/Class;",
+        |vm, stack, _, args| for_name(vm, stack, &args),
+    );
+    registry.register(
+        "java/lang/Class",
+        "getDeclaredFields0",
+        "(Z)[Ljava/lang/reflect/Field;",
+        |vm, stack, receiver, args| get_declared_fields(vm, stack, receiver, &args),
+    );
+    registry.register(
+        "java/lang/Class",
+        "getDeclaredMethods0",
+        "(Z)[Ljava/lang/reflect/Method;",
+        |vm, stack, receiver, args| {
+            get_declared_methods(vm, stack, receiver, &args, "java/lang/reflect/Method")
+        },
+    );
+    registry.register(
+        "java/lang/Class",
+        "getDeclaredConstructors0",
+        "(Z)[Ljava/lang/reflect/Constructor;",
+        |vm, stack, receiver, args| {
+            get_declared_methods(vm, stack, receiver, &args, "java/lang/reflect/Constructor")
+        },
+    );
+    for class_name in [
+        "sun/reflect/NativeMethodAccessorImpl",
+        "jdk/internal/reflect/NativeMethodAccessorImpl",
+    ] {
+        registry.register(
+            class_name,
+            "invoke0",
+            "(Ljava/lang/reflect/Method;Ljava/lang/Object;[Ljava/lang/Object;)Ljava/lang/Object;",
+            |vm, stack, _, args| invoke_method(vm, stack, args),
+        );
+    }
+    for class_name in [
+        "sun/reflect/NativeConstructorAccessorImpl",
+        "jdk/internal/reflect/NativeConstructorAccessorImpl",
+    ] {
+        registry.register(
+            class_name,
+            "newInstance0",
+            "(Ljava/lang/reflect/Constructor;[Ljava/lang/Object;)Ljava/lang/Object;",
+            |vm, stack, _, args| new_instance(vm, stack, args),
+        );
+    }
+}
+
+/// Registers the natives of `sun.misc.Unsafe` that the field accessors of `java.lang.reflect`
+/// use. The offset of a field is its index, which for static fields refers to the object
+/// holding the static fields of the class, and the offset of an array element is its index.
+pub(crate) fn register_unsafe_methods(registry: &mut NativeMethodsRegistry) {
+    registry.register("sun/misc/Unsafe", "registerNatives", "()V", |_, _, _, _| {
+        Ok(None)
+    });
+    registry.register(
+        "sun/misc/Unsafe",
+        "arrayBaseOffset",
+        "(Ljava/lang/Class;)I",
+        |_, _, _, _| Ok(Some(Value::Int(0))),
+    );
+    registry.register(
+        "sun/misc/Unsafe",
+        "arrayIndexScale",
+        "(Ljava/lang/Class;)I",
+        |_, _, _, _| Ok(Some(Value::Int(1))),
+    );
+    registry.register("sun/misc/Unsafe", "addressSize", "()I", |_, _, _, _| {
+        Ok(Some(Value::Int(std::mem::size_of::<usize>() as i32)))
+    });
+    registry.register(
+        "sun/misc/Unsafe",
+        "objectFieldOffset",
+        "(Ljava/lang/reflect/Field;)J",
+        |vm, _, _, args| field_offset(vm, &args),
+    );
+    registry.register(
+        "sun/misc/Unsafe",
+        "staticFieldOffset",
+        "(Ljava/lang/reflect/Field;)J",
+        |vm, _, _, args| field_offset(vm, &args),
+    );
+    registry.register(
+        "sun/misc/Unsafe",
+        "staticFieldBase",
+        "(Ljava/lang/reflect/Field;)Ljava/lang/Object;",
+        |vm, stack, _, args| static_field_base(vm, stack, &args),
+    );
+    registry.register(
+        "sun/misc/Unsafe",
+        "ensureClassInitialized",
+        "(Ljava/lang/Class;)V",
+        |vm, stack, _, args| ensure_class_initialized(vm, stack, &args),
+    );
+
+    for (type_name, descriptor) in [
+        ("Boolean", "Z"),
+        ("Byte", "B"),
+        ("Char", "C"),
+        ("Short", "S"),
+        ("Int", "I"),
+        ("Long", "J"),
+        ("Float", "F"),
+        ("Double", "D"),
+        ("Object", "Ljava/lang/Object;"),
+    ] {
+        for suffix in ["", "Volatile"] {
+            registry.register(
+                "sun/misc/Unsafe",
+                &format!("get{type_name}{suffix}"),
+                &format!("(Ljava/lang/Object;J){descriptor}"),
+                |vm, _, _, args| get_at_offset(vm, &args),
+            );
+            registry.register(
+                "sun/misc/Unsafe",
+                &format!("put{type_name}{suffix}"),
+                &format!("(Ljava/lang/Object;J{descriptor})V"),
+                |vm, _, _, args| put_at_offset(vm, args),
+            );
+        }
+    }
+}
+
+fn get_class_loader(receiver: Option<AbstractObject>) -> MethodCallResult {
+    debug!("invoked get class loader for object {:?}", receiver);
+
+    // It seems ok to return just null for the moment
+    Ok(Some(Value::Null))
+}
+
+fn get_primitive_class<'a>(
+    vm: &mut Vm<'a>,
+    stack: &mut CallStack<'a>,
+    args: &[Value<'a>],
+) -> MethodCallResult<'a> {
+    let arg = expect_concrete_object_at(args, 0)?;
+    let class_name = extract_str_from_java_lang_string(vm, &arg)?;
+    let java_lang_class_instance = vm.get_class_mirror(stack, &class_name)?;
+    Ok(Some(Value::Object(java_lang_class_instance)))
+}
+
+fn get_name<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    receiver: Option<AbstractObject<'a>>,
+) -> MethodCallResult<'a> {
+    let class_name = mirror_name(vm, receiver)?;
+    let name = new_java_lang_string_object(vm, call_stack, &class_name.replace('/', "."))?;
+    Ok(Some(Value::Object(name)))
+}
+
+fn is_interface<'a>(vm: &mut Vm<'a>, receiver: Option<AbstractObject<'a>>) -> MethodCallResult<'a> {
+    let receiver = expect_some_receiver(receiver)?;
+    let is_interface = class_of_mirror(vm, &receiver)?.is_some_and(|class| class.is_interface());
+    Ok(Some(Value::Int(is_interface.into())))
+}
+
+fn get_component_type<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    receiver: Option<AbstractObject<'a>>,
+) -> MethodCallResult<'a> {
+    let class_name = mirror_name(vm, receiver)?;
+    match component_type_name(&class_name) {
+        Some(component_type_name) => {
+            let component_type = vm.get_class_mirror(call_stack, &component_type_name)?;
+            Ok(Some(Value::Object(component_type)))
+        }
+        None => Ok(Some(Value::Null)),
+    }
+}
+
+/// Implements `Class.getSuperclass`, which returns `null` for `Object`, interfaces and
+/// primitive types, and `Object` for arrays
+fn get_superclass<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    receiver: Option<AbstractObject<'a>>,
+) -> MethodCallResult<'a> {
+    let receiver = expect_some_receiver(receiver)?;
+    let class_name = extract_class_name_from_java_lang_class(vm, &receiver)?;
+    let superclass_name = if class_name.starts_with('[') {
+        Some("java/lang/Object".to_string())
+    } else {
+        match class_of_mirror(vm, &receiver)? {
+            Some(class) if !class.is_interface() => {
+                class.superclass.map(|superclass| superclass.name.clone())
+            }
+            _ => None,
+        }
+    };
+    match superclass_name {
+        Some(superclass_name) => {
+            let superclass = vm.get_class_mirror(call_stack, &superclass_name)?;
+            Ok(Some(Value::Object(superclass)))
+        }
+        None => Ok(Some(Value::Null)),
+    }
+}
+
+/// Implements `Class.getInterfaces`, which returns the interfaces directly implemented by a
+/// class in declaration order
+fn get_interfaces<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    receiver: Option<AbstractObject<'a>>,
+) -> MethodCallResult<'a> {
+    let receiver = expect_some_receiver(receiver)?;
+    let class_name = extract_class_name_from_java_lang_class(vm, &receiver)?;
+    let interface_names: Vec<String> = if class_name.starts_with('[') {
+        ARRAY_INTERFACES
+            .iter()
+            .map(|name| name.to_string())
+            .collect()
+    } else {
+        match class_of_mirror(vm, &receiver)? {
+            Some(class) => class
+                .interfaces
+                .iter()
+                .map(|interface| interface.name.clone())
+                .collect(),
+            None => Vec::new(),
+        }
+    };
+
+    let array = new_object_array(
+        vm,
+        call_stack,
+        "java/lang/Class",
+        interface_names.len(),
+        |vm, call_stack, index| {
+            let interface = vm.get_class_mirror(call_stack, &interface_names[index])?;
+            Ok(Value::Object(interface))
+        },
+    )?;
+    Ok(Some(Value::Object(array)))
+}
+
+fn is_instance<'a>(
+    vm: &mut Vm<'a>,
+    receiver: Option<AbstractObject<'a>>,
+    args: &[Value<'a>],
+) -> MethodCallResult<'a> {
+    let class_name = mirror_name(vm, receiver)?;
+    let is_instance = match args.first() {
+        Some(Value::Object(object)) => {
+            let object_class_name = CallFrame::get_class_name_of(vm, object)?;
+            is_assignable(vm, &class_name, &object_class_name)?
+        }
+        _ => false,
+    };
+    Ok(Some(Value::Int(is_instance.into())))
+}
+
+fn is_assignable_from<'a>(
+    vm: &mut Vm<'a>,
+    receiver: Option<AbstractObject<'a>>,
+    args: &[Value<'a>],
+) -> MethodCallResult<'a> {
+    let class_name = mirror_name(vm, receiver)?;
+    let other_class_name = match args.first() {
+        Some(Value::Null) => return Err(VmError::NullPointerException.into()),
+        _ => extract_class_name_from_java_lang_class(vm, &expect_abstract_object_at(args, 0)?)?,
+    };
+    let is_assignable = is_assignable(vm, &class_name, &other_class_name)?;
+    Ok(Some(Value::Int(is_assignable.into())))
+}
+
+/// Returns the modifiers of a type, as `Class.getModifiers` does. We do not read the
+/// `InnerClasses` attribute, so nested classes lack the `private`, `protected` and `static`
+/// modifiers that their declaration can have.
+fn class_modifiers<'a>(vm: &mut Vm<'a>, class_name: &str) -> Result<i32, MethodCallFailed<'a>> {
+    let abstract_and_final =
+        i32::from((ClassAccessFlags::ABSTRACT | ClassAccessFlags::FINAL).bits());
+    if is_primitive_name(class_name) {
+        return Ok(i32::from(ClassAccessFlags::PUBLIC.bits()) | abstract_and_final);
+    }
+    if let Some(component_type_name) = component_type_name(class_name) {
+        // Arrays are as accessible as their components
+        let component_modifiers = class_modifiers(vm, &component_type_name)?;
+        return Ok(component_modifiers & ACCESS_MODIFIERS | abstract_and_final);
+    }
+    let class = vm.get_or_resolve_class(class_name)?;
+    // ACC_SUPER is not a modifier, and has the same value as `Modifier.SYNCHRONIZED`
+    Ok(i32::from((class.flags - ClassAccessFlags::SUPER).bits()))
+}
+
+/// Implements `Class.forName0`, which takes a binary name such as `java.lang.String` or
+/// `[Ljava.lang.String;` and loads the class, initializing it if requested
+fn for_name<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    args: &[Value<'a>],
+) -> MethodCallResult<'a> {
+    let name = match args.first() {
+        Some(Value::Null) => return Err(VmError::NullPointerException.into()),
+        _ => expect_concrete_object_at(args, 0)?,
+    };
+    let name = extract_str_from_java_lang_string(vm, &name)?;
+    let initialize = expect_int_at(args, 1)? != 0;
+
+    if name.contains('/') {
+        return Err(VmError::ClassNotFoundByName(name).into());
+    }
+    let class_name = name.replace('.', "/");
+    let is_array = class_name.starts_with('[');
+    let leaf_class_name = if is_array {
+        match FieldType::parse(&class_name) {
+            Ok(FieldType::Array(component_type)) => leaf_class_name(&component_type),
+            _ => return Err(VmError::ClassNotFoundByName(name).into()),
+        }
+    } else {
+        Some(class_name.clone())
+    };
+    if let Some(leaf_class_name) = leaf_class_name {
+        let class = match vm.get_or_resolve_class(&leaf_class_name) {
+            Err(MethodCallFailed::InternalError(VmError::ClassNotFoundException(_))) => {
+                return Err(VmError::ClassNotFoundByName(name).into())
+            }
+            result => result?,
+        };
+        // Loading an array class does not initialize the class of its elements
+        if initialize && !is_array {
+            vm.initialize_class(call_stack, class)?;
+        }
+    }
+
+    let class_object = vm.get_class_mirror(call_stack, &class_name)?;
+    Ok(Some(Value::Object(class_object)))
+}
+
+/// Returns the class of the elements of an array type, if they are objects
+fn leaf_class_name(field_type: &FieldType) -> Option<String> {
+    match field_type {
+        FieldType::Base(_) => None,
+        FieldType::Object(class_name) => Some(class_name.clone()),
+        FieldType::Array(component_type) => leaf_class_name(component_type),
+    }
+}
+
+/// Implements `Class.getDeclaredFields0`, which returns the fields in declaration order. The
+/// slot of each `Field` is the index of the field in the objects of the class or, for static
+/// fields, in the object holding the static fields.
+fn get_declared_fields<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    receiver: Option<AbstractObject<'a>>,
+    args: &[Value<'a>],
+) -> MethodCallResult<'a> {
+    let receiver = expect_some_receiver(receiver)?;
+    let public_only = expect_int_at(args, 0)? != 0;
+    let fields: Vec<(ClassRef<'a>, usize)> = match class_of_mirror(vm, &receiver)? {
+        Some(class) => (0..class.fields.len())
+            .filter(|index| !public_only || class.fields[*index].flags.contains(FieldFlags::PUBLIC))
+            .map(|index| (class, index))
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let array = new_object_array(
+        vm,
+        call_stack,
+        "java/lang/reflect/Field",
+        fields.len(),
+        |vm, call_stack, index| {
+            let (class, field_index) = fields[index];
+            new_field_object(vm, call_stack, class, field_index)
+        },
+    )?;
+    Ok(Some(Value::Object(array)))
+}
+
+/// Implements `Class.getDeclaredMethods0` and `getDeclaredConstructors0`, depending on the
+/// class of the objects to return. The slot of each of them is the index of the method.
+fn get_declared_methods<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    receiver: Option<AbstractObject<'a>>,
+    args: &[Value<'a>],
+    member_class_name: &str,
+) -> MethodCallResult<'a> {
+    let receiver = expect_some_receiver(receiver)?;
+    let public_only = expect_int_at(args, 0)? != 0;
+    let constructors = member_class_name == "java/lang/reflect/Constructor";
+    let methods: Vec<(ClassRef<'a>, usize)> = match class_of_mirror(vm, &receiver)? {
+        Some(class) => (0..class.methods.len())
+            .filter(|index| {
+                let method = &class.methods[*index];
+                (method.name == "<init>") == constructors
+                    && method.name != "<clinit>"
+                    && (!public_only || method.flags.contains(MethodFlags::PUBLIC))
+            })
+            .map(|index| (class, index))
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let array = new_object_array(
+        vm,
+        call_stack,
+        member_class_name,
+        methods.len(),
+        |vm, call_stack, index| {
+            let (class, method_index) = methods[index];
+            new_method_object(vm, call_stack, member_class_name, class, method_index)
+        },
+    )?;
+    Ok(Some(Value::Object(array)))
+}
+
+fn new_field_object<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    class: ClassRef<'a>,
+    field_index: usize,
+) -> Result<Value<'a>, MethodCallFailed<'a>> {
+    let field_object = vm.new_object(call_stack, "java/lang/reflect/Field")?;
+    let root = vm.push_temporary_root(field_object);
+    let result = fill_rooted_field_object(vm, call_stack, root, class, field_index);
+    vm.truncate_temporary_roots(root);
+    result
+}
+
+fn fill_rooted_field_object<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    root: usize,
+    class: ClassRef<'a>,
+    field_index: usize,
+) -> Result<Value<'a>, MethodCallFailed<'a>> {
+    let field = &class.fields[field_index];
+    let declaring_class = vm.get_class_mirror(call_stack, &class.name)?;
+    set_member_field(vm, root, "clazz", Value::Object(declaring_class))?;
+    let name = vm.intern_string(call_stack, &field.name)?;
+    set_member_field(vm, root, "name", Value::Object(name))?;
+    let field_type = new_type_object(vm, call_stack, Some(&field.type_descriptor))?;
+    set_member_field(vm, root, "type", field_type)?;
+    set_member_field(vm, root, "modifiers", Value::Int(field.flags.bits().into()))?;
+    let slot = class.first_field_index + field_index;
+    set_member_field(vm, root, "slot", Value::Int(slot as i32))?;
+    Ok(Value::Object(vm.temporary_root(root)))
+}
+
+/// Creates an instance of `java.lang.reflect.Method` or `java.lang.reflect.Constructor`
+fn new_method_object<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    member_class_name: &str,
+    class: ClassRef<'a>,
+    method_index: usize,
+) -> Result<Value<'a>, MethodCallFailed<'a>> {
+    let method_object = vm.new_object(call_stack, member_class_name)?;
+    let root = vm.push_temporary_root(method_object);
+    let result = fill_rooted_method_object(vm, call_stack, root, class, method_index);
+    vm.truncate_temporary_roots(root);
+    result
+}
+
+fn fill_rooted_method_object<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    root: usize,
+    class: ClassRef<'a>,
+    method_index: usize,
+) -> Result<Value<'a>, MethodCallFailed<'a>> {
+    let method = &class.methods[method_index];
+    let declaring_class = vm.get_class_mirror(call_stack, &class.name)?;
+    set_member_field(vm, root, "clazz", Value::Object(declaring_class))?;
+    if method.name != "<init>" {
+        let name = vm.intern_string(call_stack, &method.name)?;
+        set_member_field(vm, root, "name", Value::Object(name))?;
+        let return_type = method.parsed_type_descriptor.return_type.as_ref();
+        let return_type = new_type_object(vm, call_stack, return_type)?;
+        set_member_field(vm, root, "returnType", return_type)?;
+    }
+
+    let parameters = &method.parsed_type_descriptor.parameters;
+    let parameter_types = new_object_array(
+        vm,
+        call_stack,
+        "java/lang/Class",
+        parameters.len(),
+        |vm, call_stack, index| new_type_object(vm, call_stack, Some(&parameters[index])),
+    )?;
+    set_member_field(vm, root, "parameterTypes", Value::Object(parameter_types))?;
+    let exceptions = &method.thrown_exceptions;
+    let exception_types = new_object_array(
+        vm,
+        call_stack,
+        "java/lang/Class",
+        exceptions.len(),
+        |vm, call_stack, index| {
+            let exception_type = vm.get_class_mirror(call_stack, &exceptions[index])?;
+            Ok(Value::Object(exception_type))
+        },
+    )?;
+    set_member_field(vm, root, "exceptionTypes", Value::Object(exception_types))?;
+
+    set_member_field(
+        vm,
+        root,
+        "modifiers",
+        Value::Int(method.flags.bits().into()),
+    )?;
+    set_member_field(vm, root, "slot", Value::Int(method_index as i32))?;
+    Ok(Value::Object(vm.temporary_root(root)))
+}
+
+fn set_member_field<'a>(
+    vm: &Vm<'a>,
+    root: usize,
+    field_name: &str,
+    value: Value<'a>,
+) -> Result<(), VmError> {
+    set_field_by_name(vm, &vm.temporary_root(root), field_name, value)
+}
+
+/// Returns the `java.lang.Class` of a type, where `None` stands for `void`
+fn new_type_object<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    field_type: Option<&FieldType>,
+) -> Result<Value<'a>, MethodCallFailed<'a>> {
+    let class_name = type_name(field_type);
+    Ok(Value::Object(vm.get_class_mirror(call_stack, &class_name)?))
+}
+
+/// Allocates an array of objects of the given class, creating each element with the given
+/// function. The array is kept in the temporary gc roots while the elements are created.
+fn new_object_array<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    elements_class_name: &str,
+    length: usize,
+    mut new_element: impl FnMut(
+        &mut Vm<'a>,
+        &mut CallStack<'a>,
+        usize,
+    ) -> Result<Value<'a>, MethodCallFailed<'a>>,
+) -> Result<AbstractObject<'a>, MethodCallFailed<'a>> {
+    let elements_class = vm.get_or_resolve_class(elements_class_name)?;
+    let array = vm.new_array(ArrayEntryType::Object(elements_class.id), length);
+    let root = vm.push_temporary_root(array);
+    let result = (0..length).try_for_each(|index| {
+        let element = new_element(vm, call_stack, index)?;
+        vm.temporary_root(root).set_element(index, element)?;
+        Ok(())
+    });
+    let array = vm.temporary_root(root);
+    vm.truncate_temporary_roots(root);
+    result.map(|_| array)
+}
+
+/// Implements `NativeMethodAccessorImpl.invoke0`: unboxes the arguments, selects the method
+/// to invoke like `invokevirtual` does for instance methods, and boxes the result. Exceptions
+/// thrown by the method are wrapped in an `InvocationTargetException`.
+fn invoke_method<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    args: Vec<Value<'a>>,
+) -> MethodCallResult<'a> {
+    let method_object = expect_abstract_object_at(&args, 0)?;
+    let reflected = reflected_method(vm, &method_object)?;
+    let ClassAndMethod { class, method, .. } = reflected;
+
+    let (receiver, args) = if method.is_static() {
+        // The static initializer can trigger a garbage collection
+        let ((), args) = with_rooted_values(vm, args, |vm| vm.initialize_class(call_stack, class))?;
+        (None, args)
+    } else {
+        match args.get(1) {
+            Some(Value::Object(receiver)) => {
+                let receiver_class = vm.get_class_by_id(receiver.class_id())?;
+                let is_instance = match receiver.kind() {
+                    ObjectKind::Object => receiver_class.is_subclass_of(class),
+                    ObjectKind::Array => class.name == "java/lang/Object",
+                };
+                if !is_instance {
+                    return Err(VmError::IllegalArgumentException(
+                        "object is not an instance of declaring class".to_string(),
+                    )
+                    .into());
+                }
+                (Some(receiver.clone()), args)
+            }
+            _ => return Err(VmError::NullPointerException.into()),
+        }
+    };
+
+    let arguments = unbox_arguments(vm, method, &args[2])?;
+    let class_and_method = match &receiver {
+        Some(receiver) if receiver.kind() == ObjectKind::Object => {
+            select_method(vm.get_class_by_id(receiver.class_id())?, reflected)?
+        }
+        _ => reflected,
+    };
+    let result = vm.invoke(call_stack, class_and_method, receiver, arguments);
+    let result = wrap_thrown_exception(vm, call_stack, result)?;
+    box_result(vm, call_stack, method, result)
+}
+
+/// Implements `NativeConstructorAccessorImpl.newInstance0`, which allocates an object and
+/// invokes the constructor on it
+fn new_instance<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    args: Vec<Value<'a>>,
+) -> MethodCallResult<'a> {
+    let constructor_object = expect_abstract_object_at(&args, 0)?;
+    let constructor = reflected_method(vm, &constructor_object)?;
+    let class = constructor.class;
+    if class.flags.contains(ClassAccessFlags::ABSTRACT) {
+        return Err(VmError::InstantiationException(class.name.replace('/', ".")).into());
+    }
+
+    let (object, args) = with_rooted_values(vm, args, |vm| {
+        vm.initialize_class(call_stack, class)?;
+        Ok(vm.new_object_of_class(class))
+    })?;
+    let arguments = unbox_arguments(vm, constructor.method, &args[1])?;
+    let root = vm.push_temporary_root(object.clone());
+    let result = vm.invoke(call_stack, constructor, Some(object), arguments);
+    let object = vm.temporary_root(root);
+    vm.truncate_temporary_roots(root);
+    wrap_thrown_exception(vm, call_stack, result)?;
+    Ok(Some(Value::Object(object)))
+}
+
+/// Returns the method described by an instance of `java.lang.reflect.Method`
+/// or `java.lang.reflect.Constructor`
+fn reflected_method<'a>(
+    vm: &mut Vm<'a>,
+    member: &AbstractObject<'a>,
+) -> Result<ClassAndMethod<'a>, MethodCallFailed<'a>> {
+    let class = declaring_class(vm, member)?;
+    let slot = member_slot(vm, member)?;
+    if slot >= class.methods.len() {
+        return Err(VmError::ValidationException.into());
+    }
+    Ok(ClassAndMethod::new(class, slot))
+}
+
+fn declaring_class<'a>(
+    vm: &mut Vm<'a>,
+    member: &AbstractObject<'a>,
+) -> Result<ClassRef<'a>, MethodCallFailed<'a>> {
+    match get_field_by_name(vm, member, "clazz")? {
+        Value::Object(mirror) => {
+            class_of_mirror(vm, &mirror)?.ok_or(VmError::ValidationException.into())
+        }
+        _ => Err(VmError::ValidationException.into()),
+    }
+}
+
+fn member_slot<'a>(vm: &Vm<'a>, member: &AbstractObject<'a>) -> Result<usize, VmError> {
+    match get_field_by_name(vm, member, "slot")? {
+        Value::Int(slot) if slot >= 0 => Ok(slot.into_usize_safe()),
+        _ => Err(VmError::ValidationException),
+    }
+}
+
+/// Returns the internal name of the class represented by the receiver of a native of
+/// `java.lang.Class`
+fn mirror_name<'a>(vm: &Vm<'a>, receiver: Option<AbstractObject<'a>>) -> Result<String, VmError> {
+    extract_class_name_from_java_lang_class(vm, &expect_some_receiver(receiver)?)
+}
+
+/// Returns the name of the `java.lang.Class` of a type, where `None` stands for `void`
+fn type_name(field_type: Option<&FieldType>) -> String {
+    match field_type {
+        None => "void".to_string(),
+        Some(FieldType::Base(base_type)) => primitive_type(base_type).1.to_string(),
+        Some(FieldType::Object(class_name)) => class_name.clone(),
+        Some(array_type) => array_type.descriptor(),
+    }
+}
+
+/// Returns the name of the `java.lang.Class` of the components of an array class
+pub(crate) fn component_type_name(class_name: &str) -> Option<String> {
+    match FieldType::parse(class_name) {
+        Ok(FieldType::Array(component_type)) => Some(type_name(Some(&component_type))),
+        _ => None,
+    }
+}
+
+pub(crate) fn is_primitive_name(class_name: &str) -> bool {
+    class_name == "void"
+        || PRIMITIVE_TYPES
+            .iter()
+            .any(|(_, primitive_name, _)| class_name == *primitive_name)
+}
+
+/// Returns the class represented by an instance of `java.lang.Class`, or `None` for
+/// primitive types and arrays, which have no fields or methods of their own
+fn class_of_mirror<'a>(
+    vm: &mut Vm<'a>,
+    mirror: &AbstractObject<'a>,
+) -> Result<Option<ClassRef<'a>>, MethodCallFailed<'a>> {
+    let class_name = extract_class_name_from_java_lang_class(vm, mirror)?;
+    if is_primitive_name(&class_name) || class_name.starts_with('[') {
+        return Ok(None);
+    }
+    Ok(Some(vm.get_or_resolve_class(&class_name)?))
+}
+
+/// Runs an action that can trigger a garbage collection, such as a class initialization or an
+/// allocation, keeping the objects among some values in the temporary gc roots. Returns the
+/// result of the action and the values, updated in case the collector moved the objects.
+fn with_rooted_values<'a, T>(
+    vm: &mut Vm<'a>,
+    values: Vec<Value<'a>>,
+    action: impl FnOnce(&mut Vm<'a>) -> Result<T, MethodCallFailed<'a>>,
+) -> Result<(T, Vec<Value<'a>>), MethodCallFailed<'a>> {
+    let roots: Vec<Option<usize>> = values
+        .iter()
+        .map(|value| match value {
+            Value::Object(object) => Some(vm.push_temporary_root(object.clone())),
+            _ => None,
+        })
+        .collect();
+    let result = action(vm);
+    let values = values
+        .into_iter()
+        .zip(roots.iter())
+        .map(|(value, root)| match root {
+            Some(root) => Value::Object(vm.temporary_root(*root)),
+            None => value,
+        })
+        .collect();
+    if let Some(first_root) = roots.iter().flatten().next() {
+        vm.truncate_temporary_roots(*first_root);
+    }
+    result.map(|result| (result, values))
+}
+
+/// Selects the implementation of an instance method for the class of the receiver, as
+/// `invokevirtual` does. Private methods and constructors are invoked directly.
+fn select_method<'a>(
+    receiver_class: ClassRef<'a>,
+    reflected: ClassAndMethod<'a>,
+) -> Result<ClassAndMethod<'a>, VmError> {
+    let method = reflected.method;
+    let selected = if method.flags.contains(MethodFlags::PRIVATE) || method.name == "<init>" {
+        None
+    } else {
+        receiver_class
+            .vtable_slot(&method.name, &method.type_descriptor)
+            .and_then(|slot| receiver_class.vtable_method(slot))
+    };
+    let selected = selected.unwrap_or(reflected);
+    if selected.method.is_abstract() {
+        return Err(VmError::AbstractMethodError(format!(
+            "{}.{}{}",
+            selected.class.name.replace('/', "."),
+            selected.method.name,
+            selected.method.type_descriptor,
+        )));
+    }
+    Ok(selected)
+}
+
+/// Converts the `Object[]` given to `Method.invoke` or `Constructor.newInstance` to the
+/// arguments of the method, unboxing and widening the primitive ones
+fn unbox_arguments<'a>(
+    vm: &Vm<'a>,
+    method: &ClassFileMethod,
+    arguments: &Value<'a>,
+) -> Result<Vec<Value<'a>>, VmError> {
+    let arguments = match arguments {
+        Value::Null => Vec::new(),
+        Value::Object(array) if array.kind() == ObjectKind::Array => (0..array.len())
+            .map(|index| array.get_element(index.into_usize_safe()))
+            .collect::<Result<Vec<_>, _>>()?,
+        _ => return Err(VmError::ValidationException),
+    };
+    let parameters = &method.parsed_type_descriptor.parameters;
+    if arguments.len() != parameters.len() {
+        return Err(VmError::IllegalArgumentException(
+            "wrong number of arguments".to_string(),
+        ));
+    }
+
+    let mut result = Vec::with_capacity(arguments.len());
+    for (argument, parameter_type) in arguments.into_iter().zip(parameters) {
+        let argument = unbox_argument(vm, argument, parameter_type)?;
+        // Longs and doubles take two slots, like in the operand stack
+        let takes_two_slots = matches!(argument, Value::Long(_) | Value::Double(_));
+        result.push(argument);
+        if takes_two_slots {
+            result.push(Value::Uninitialized);
+        }
+    }
+    Ok(result)
+}
+
+fn unbox_argument<'a>(
+    vm: &Vm<'a>,
+    argument: Value<'a>,
+    parameter_type: &FieldType,
+) -> Result<Value<'a>, VmError> {
+    match parameter_type {
+        FieldType::Base(target_type) => {
+            let Value::Object(boxed) = &argument else {
+                return Err(argument_type_mismatch());
+            };
+            let box_class = vm.get_class_by_id(boxed.class_id())?;
+            let (source_type, _, _) = PRIMITIVE_TYPES
+                .iter()
+                .find(|(_, _, box_class_name)| box_class.name == *box_class_name)
+                .ok_or_else(argument_type_mismatch)?;
+            let value = get_field_by_name(vm, boxed, "value")?;
+            widen(value, source_type, target_type).ok_or_else(argument_type_mismatch)
+        }
+        // Arrays are instances of Object too
+        FieldType::Object(class_name) if class_name == "java/lang/Object" => Ok(argument),
+        _ => {
+            let matches = argument.matches_type(parameter_type.clone(), vm, |class_name| {
+                vm.find_class_by_name(class_name)
+            });
+            if matches {
+                Ok(argument)
+            } else {
+                Err(argument_type_mismatch())
+            }
+        }
+    }
+}
+
+/// Applies a widening primitive conversion, as in JLS 5.1.2. Returns `None` if the source
+/// type cannot be widened to the target one.
+fn widen<'a>(
+    value: Value<'a>,
+    source_type: &BaseType,
+    target_type: &BaseType,
+) -> Option<Value<'a>> {
+    use BaseType::*;
+
+    if source_type == target_type {
+        return Some(value);
+    }
+    match (source_type, target_type, value) {
+        (Byte, Short | Int, value) | (Short | Char, Int, value) => Some(value),
+        (Byte | Short | Char | Int, Long, Value::Int(value)) => Some(Value::Long(value.into())),
+        (Byte | Short | Char | Int, Float, Value::Int(value)) => Some(Value::Float(value as f32)),
+        (Byte | Short | Char | Int, Double, Value::Int(value)) => Some(Value::Double(value.into())),
+        (Long, Float, Value::Long(value)) => Some(Value::Float(value as f32)),
+        (Long, Double, Value::Long(value)) => Some(Value::Double(value as f64)),
+        (Float, Double, Value::Float(value)) => Some(Value::Double(value.into())),
+        _ => None,
+    }
+}
+
+fn argument_type_mismatch() -> VmError {
+    VmError::IllegalArgumentException("argument type mismatch".to_string())
+}
+
+/// Wraps an exception thrown by a method invoked through reflection in an
+/// `InvocationTargetException`
+fn wrap_thrown_exception<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    result: MethodCallResult<'a>,
+) -> MethodCallResult<'a> {
+    match result {
+        Err(MethodCallFailed::ExceptionThrown(exception)) => {
+            let wrapper = vm.new_wrapping_exception(
+                call_stack,
+                "java/lang/reflect/InvocationTargetException",
+                "(Ljava/lang/Throwable;)V",
+                exception,
+            )?;
+            Err(MethodCallFailed::ExceptionThrown(wrapper))
+        }
+        result => result,
+    }
+}
+
+/// Boxes the value returned by a method invoked through reflection. Methods returning `void`
+/// return `null`.
+fn box_result<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    method: &ClassFileMethod,
+    result: Option<Value<'a>>,
+) -> MethodCallResult<'a> {
+    match (&method.parsed_type_descriptor.return_type, result) {
+        (None, _) => Ok(Some(Value::Null)),
+        (Some(FieldType::Base(base_type)), Some(value)) => {
+            let (_, _, box_class_name) = primitive_type(base_type);
+            let boxed = vm.new_object(call_stack, box_class_name)?;
+            set_field_by_name(vm, &boxed, "value", value)?;
+            Ok(Some(Value::Object(boxed)))
+        }
+        (Some(_), Some(value)) => Ok(Some(value)),
+        (Some(_), None) => Err(VmError::ValidationException.into()),
+    }
+}
+
+fn primitive_type(base_type: &BaseType) -> &'static (BaseType, &'static str, &'static str) {
+    PRIMITIVE_TYPES
+        .iter()
+        .find(|(primitive_type, _, _)| primitive_type == base_type)
+        .expect("all primitive types are listed")
+}
+
+fn field_offset<'a>(vm: &Vm<'a>, args: &[Value<'a>]) -> MethodCallResult<'a> {
+    let field = expect_abstract_object_at(args, 0)?;
+    let slot = member_slot(vm, &field)?;
+    Ok(Some(Value::Long(slot as i64)))
+}
+
+/// Returns the object holding the static fields of the class declaring a field
+fn static_field_base<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    args: &[Value<'a>],
+) -> MethodCallResult<'a> {
+    let field = expect_abstract_object_at(args, 0)?;
+    let class = declaring_class(vm, &field)?;
+    vm.initialize_class(call_stack, class)?;
+    let statics = vm
+        .get_static_instance(class.id)
+        .ok_or(VmError::ValidationException)?;
+    Ok(Some(Value::Object(statics)))
+}
+
+fn ensure_class_initialized<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    args: &[Value<'a>],
+) -> MethodCallResult<'a> {
+    let mirror = match args.first() {
+        Some(Value::Null) => return Err(VmError::NullPointerException.into()),
+        _ => expect_abstract_object_at(args, 0)?,
+    };
+    if let Some(class) = class_of_mirror(vm, &mirror)? {
+        vm.initialize_class(call_stack, class)?;
+    }
+    Ok(None)
+}
+
+/// Implements the `get` natives of `Unsafe`, which take an object and an offset
+fn get_at_offset<'a>(vm: &Vm<'a>, args: &[Value<'a>]) -> MethodCallResult<'a> {
+    let (object, offset) = object_and_offset(args)?;
+    let value = match object.kind() {
+        ObjectKind::Array => object.get_element(offset)?,
+        ObjectKind::Object => {
+            let class = vm.get_class_by_id(object.class_id())?;
+            class
+                .field_at_index(offset)
+                .ok_or(VmError::ValidationException)?;
+            object.get_field(class, offset)
+        }
+    };
+    Ok(Some(value))
+}
+
+/// Implements the `put` natives of `Unsafe`, which take an object, an offset and a value
+fn put_at_offset<'a>(vm: &Vm<'a>, args: Vec<Value<'a>>) -> MethodCallResult<'a> {
+    let (object, offset) = object_and_offset(&args)?;
+    let value = args.get(3).cloned().ok_or(VmError::ValidationException)?;
+    let holds_references = match object.kind() {
+        ObjectKind::Array => !matches!(object.elements_type(), ArrayEntryType::Base(_)),
+        ObjectKind::Object => {
+            let class = vm.get_class_by_id(object.class_id())?;
+            let field = class
+                .field_at_index(offset)
+                .ok_or(VmError::ValidationException)?;
+            !matches!(field.type_descriptor, FieldType::Base(_))
+        }
+    };
+    // The garbage collector would follow a primitive stored in place of a reference
+    if holds_references != matches!(value, Value::Object(_) | Value::Null) {
+        return Err(VmError::ValidationException.into());
+    }
+    match object.kind() {
+        ObjectKind::Array => object.set_element(offset, value)?,
+        ObjectKind::Object => object.set_field(offset, value),
+    }
+    Ok(None)
+}
+
+fn object_and_offset<'a>(args: &[Value<'a>]) -> Result<(AbstractObject<'a>, usize), VmError> {
+    let object = match args.first() {
+        Some(Value::Null) => return Err(VmError::NullPointerException),
+        _ => expect_abstract_object_at(args, 0)?,
+    };
+    let offset =
+        usize::try_from(expect_long_at(args, 1)?).map_err(|_| VmError::ValidationException)?;
+    Ok((object, offset))
+}
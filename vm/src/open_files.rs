@@ -0,0 +1,50 @@
+use std::{collections::HashMap, fs::File};
+
+use crate::vm_error::VmError;
+
+/// The first descriptor of the opened files: the previous ones are the standard streams
+const FIRST_FILE_DESCRIPTOR: i32 = 3;
+
+/// The files opened by the Java code, identified by the number stored in their
+/// `java.io.FileDescriptor`
+pub(crate) struct OpenFiles {
+    files: HashMap<i32, File>,
+    next_file_descriptor: i32,
+}
+
+impl Default for OpenFiles {
+    fn default() -> Self {
+        Self {
+            files: HashMap::new(),
+            next_file_descriptor: FIRST_FILE_DESCRIPTOR,
+        }
+    }
+}
+
+impl OpenFiles {
+    /// Registers a file, returning its descriptor
+    pub fn add(&mut self, file: File) -> i32 {
+        let file_descriptor = self.next_file_descriptor;
+        self.next_file_descriptor += 1;
+        self.files.insert(file_descriptor, file);
+        file_descriptor
+    }
+
+    pub fn get(&mut self, file_descriptor: i32) -> Result<&mut File, VmError> {
+        self.files
+            .get_mut(&file_descriptor)
+            .ok_or_else(bad_file_descriptor)
+    }
+
+    /// Closes a file by dropping it
+    pub fn remove(&mut self, file_descriptor: i32) -> Result<(), VmError> {
+        self.files
+            .remove(&file_descriptor)
+            .map(|_| ())
+            .ok_or_else(bad_file_descriptor)
+    }
+}
+
+pub(crate) fn bad_file_descriptor() -> VmError {
+    VmError::IOException("Bad file descriptor".to_string())
+}
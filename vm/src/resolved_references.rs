@@ -0,0 +1,136 @@
+use std::{cell::OnceCell, fmt, fmt::Formatter};
+
+use rjvm_reader::class_file_field::ClassFileField;
+
+use crate::{
+    class::{ClassId, ClassRef},
+    class_and_method::ClassAndMethod,
+};
+
+/// Resolution cache of the field and method references of the constant pool of a class.
+/// Each slot is resolved by name on the first execution of an instruction using it, and
+/// later executions use the cached class, field index or method directly.
+pub(crate) struct ResolvedReferences<'a> {
+    slots: Vec<OnceCell<ResolvedReference<'a>>>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum ResolvedReference<'a> {
+    Field(ResolvedField<'a>),
+    Method(ResolvedMethod<'a>),
+    /// The `clone` method of arrays, which does not exist in any class file
+    ArrayClone,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ResolvedField<'a> {
+    pub class: ClassRef<'a>,
+    pub index: usize,
+    pub field: &'a ClassFileField,
+}
+
+/// The instructions that invoke a method reference
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum InvokeKind {
+    Special,
+    Static,
+    Virtual,
+    Interface,
+}
+
+/// A method reference, resolved separately for each kind of invoke instruction that uses
+/// it, since they can select different methods: `invokespecial` executes the superclass
+/// version of an overridden method, while `invokevirtual` selects it by the receiver
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ResolvedMethod<'a> {
+    by_kind: [OnceCell<(ClassAndMethod<'a>, MethodDispatch)>; 4],
+}
+
+impl<'a> ResolvedMethod<'a> {
+    pub fn get(&self, kind: InvokeKind) -> Option<(ClassAndMethod<'a>, MethodDispatch)> {
+        self.by_kind[kind as usize].get().cloned()
+    }
+
+    fn set(&self, kind: InvokeKind, resolved: (ClassAndMethod<'a>, MethodDispatch)) {
+        let _ = self.by_kind[kind as usize].set(resolved);
+    }
+}
+
+/// How the method to execute is selected from a resolved method
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum MethodDispatch {
+    /// The resolved method is executed, as for static, private and super methods
+    Direct,
+    /// The receiver's class selects the method in the given slot of its virtual method table
+    Virtual(usize),
+    /// The receiver's class selects the implementation of the method with the given index
+    /// of an interface
+    Interface(ClassId, usize),
+}
+
+impl<'a> ResolvedReferences<'a> {
+    pub fn new(num_constants: usize) -> Self {
+        Self {
+            // Constant pool indexes start from 1
+            slots: (0..=num_constants).map(|_| OnceCell::new()).collect(),
+        }
+    }
+
+    pub fn get(&self, constant_index: u16) -> Option<&ResolvedReference<'a>> {
+        self.slots
+            .get(usize::from(constant_index))
+            .and_then(OnceCell::get)
+    }
+
+    pub fn set(&self, constant_index: u16, resolved_reference: ResolvedReference<'a>) {
+        if let Some(slot) = self.slots.get(usize::from(constant_index)) {
+            let _ = slot.set(resolved_reference);
+        }
+    }
+
+    pub fn set_method(
+        &self,
+        constant_index: u16,
+        kind: InvokeKind,
+        resolved: (ClassAndMethod<'a>, MethodDispatch),
+    ) {
+        let slot = self
+            .slots
+            .get(usize::from(constant_index))
+            .map(|slot| slot.get_or_init(|| ResolvedReference::Method(Default::default())));
+        if let Some(ResolvedReference::Method(resolved_method)) = slot {
+            resolved_method.set(kind, resolved);
+        }
+    }
+}
+
+// The resolved references can point to the class itself, so we do not print them
+impl<'a> fmt::Debug for ResolvedReferences<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let num_resolved = self
+            .slots
+            .iter()
+            .filter(|slot| slot.get().is_some())
+            .count();
+        write!(f, "{num_resolved} resolved references")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::resolved_references::{ResolvedReference, ResolvedReferences};
+
+    #[test]
+    fn slots_are_resolved_once() {
+        let resolved_references = ResolvedReferences::new(3);
+        assert!(resolved_references.get(3).is_none());
+
+        resolved_references.set(3, ResolvedReference::ArrayClone);
+        assert!(matches!(
+            resolved_references.get(3),
+            Some(ResolvedReference::ArrayClone)
+        ));
+        assert!(resolved_references.get(2).is_none());
+        assert!(resolved_references.get(4).is_none());
+    }
+}
@@ -0,0 +1,322 @@
+use log::debug;
+
+use crate::{
+    abstract_object::AbstractObject,
+    call_stack::{CallStack, ThreadId},
+    class::ClassId,
+    vm_error::VmError,
+};
+
+/// Number of bytecode instructions that a thread executes before the scheduler switches
+/// to another one
+pub const DEFAULT_PREEMPTION_INTERVAL: usize = 1000;
+
+/// What a green thread is doing
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ThreadState<'a> {
+    Runnable,
+    /// Waiting to enter the monitor of an object, which it will then own as if it had
+    /// entered it `entry_count` times
+    Blocked {
+        monitor: AbstractObject<'a>,
+        entry_count: u32,
+    },
+    /// Inside `Thread.sleep`, until `get_nano_time` reaches `wake_up_at`
+    Sleeping {
+        wake_up_at: i64,
+    },
+    /// Inside `Object.wait`, having released the monitor of the object, until it is
+    /// notified or the timeout expires. It will then enter the monitor again.
+    Waiting {
+        monitor: AbstractObject<'a>,
+        entry_count: u32,
+        wake_up_at: Option<i64>,
+    },
+    /// Waiting for another thread to complete the initialization of a class
+    WaitingForInitialization {
+        class_id: ClassId,
+    },
+    Terminated,
+}
+
+pub(crate) struct GreenThread<'a> {
+    pub call_stack: *mut CallStack<'a>,
+    /// The `java.lang.Thread` instance of the thread, created lazily for the threads not
+    /// started from Java code
+    pub java_thread: Option<AbstractObject<'a>>,
+    pub state: ThreadState<'a>,
+    /// Number of executions of the thread's frames on the native stack. A thread with
+    /// nested executions cannot be preempted, and a thread with any cannot be resumed.
+    pub native_depth: usize,
+    /// The interrupt status of the thread
+    pub interrupted: bool,
+    /// Thrown by the thread when it resumes, if it was interrupted while sleeping or waiting
+    pub pending_exception: Option<VmError>,
+}
+
+/// Schedules green threads, each with its own call stack, in a deterministic round-robin
+/// order. Threads are switched when they block or every `preemption_interval` bytecode
+/// instructions.
+pub(crate) struct Scheduler<'a> {
+    threads: Vec<GreenThread<'a>>,
+    preemption_interval: usize,
+    instructions_until_preemption: usize,
+    last_resumed: ThreadId,
+    /// The threads inside `Object.wait`, in the order in which they started waiting
+    wait_set: Vec<ThreadId>,
+}
+
+impl<'a> Default for Scheduler<'a> {
+    fn default() -> Self {
+        Self {
+            threads: Vec::new(),
+            preemption_interval: DEFAULT_PREEMPTION_INTERVAL,
+            instructions_until_preemption: DEFAULT_PREEMPTION_INTERVAL,
+            last_resumed: ThreadId::new(1),
+            wait_set: Vec::new(),
+        }
+    }
+}
+
+impl<'a> Scheduler<'a> {
+    pub fn set_preemption_interval(&mut self, preemption_interval: usize) {
+        self.preemption_interval = preemption_interval;
+        self.instructions_until_preemption = preemption_interval;
+    }
+
+    pub fn next_thread_id(&self) -> ThreadId {
+        ThreadId::new(self.threads.len() as u32 + 1)
+    }
+
+    pub fn add_thread(&mut self, call_stack: *mut CallStack<'a>) {
+        self.threads.push(GreenThread {
+            call_stack,
+            java_thread: None,
+            state: ThreadState::Runnable,
+            native_depth: 0,
+            interrupted: false,
+            pending_exception: None,
+        });
+    }
+
+    pub fn thread(&self, thread_id: ThreadId) -> &GreenThread<'a> {
+        &self.threads[thread_id.as_u32() as usize - 1]
+    }
+
+    pub fn thread_mut(&mut self, thread_id: ThreadId) -> &mut GreenThread<'a> {
+        &mut self.threads[thread_id.as_u32() as usize - 1]
+    }
+
+    pub fn find_by_java_thread(&self, java_thread: &AbstractObject<'a>) -> Option<ThreadId> {
+        self.threads
+            .iter()
+            .position(|thread| {
+                thread
+                    .java_thread
+                    .as_ref()
+                    .is_some_and(|object| object.is_same_as(java_thread))
+            })
+            .map(|index| ThreadId::new(index as u32 + 1))
+    }
+
+    pub fn is_runnable(&self, thread_id: ThreadId) -> bool {
+        self.thread(thread_id).state == ThreadState::Runnable
+    }
+
+    pub fn set_state(&mut self, thread_id: ThreadId, state: ThreadState<'a>) {
+        debug!("thread {:?} is now {:?}", thread_id, state);
+        self.wait_set.retain(|&waiting| waiting != thread_id);
+        if matches!(state, ThreadState::Waiting { .. }) {
+            self.wait_set.push(thread_id);
+        }
+        self.thread_mut(thread_id).state = state;
+    }
+
+    /// Counts an executed instruction, returning true if the thread executing it should
+    /// give the way to the other ones. Nested executions, for example of a static
+    /// initializer invoked by the vm, are never preempted.
+    pub fn should_preempt(&mut self, thread_id: ThreadId) -> bool {
+        if self.instructions_until_preemption > 0 {
+            self.instructions_until_preemption -= 1;
+            return false;
+        }
+        if self.thread(thread_id).native_depth != 1 {
+            return false;
+        }
+        self.instructions_until_preemption = self.preemption_interval;
+        self.threads.len() > 1
+    }
+
+    /// Makes the current thread give the way to the others before its next instruction
+    pub fn yield_now(&mut self) {
+        self.instructions_until_preemption = 0;
+    }
+
+    /// Makes a thread runnable if it can proceed, i.e. if the time it was waiting for has
+    /// passed, or if it can enter the monitor it was blocked on
+    pub fn try_resume(&mut self, thread_id: ThreadId, now: i64) -> bool {
+        match self.thread(thread_id).state {
+            ThreadState::Sleeping { wake_up_at } if wake_up_at <= now => {
+                self.set_state(thread_id, ThreadState::Runnable);
+            }
+            ThreadState::Waiting {
+                wake_up_at: Some(wake_up_at),
+                ..
+            } if wake_up_at <= now => self.stop_waiting(thread_id),
+            _ => {}
+        }
+        let thread = self.thread_mut(thread_id);
+        if let ThreadState::Blocked {
+            monitor,
+            entry_count,
+        } = &thread.state
+        {
+            if monitor.try_reenter_monitor(thread_id, *entry_count) {
+                thread.state = ThreadState::Runnable;
+            }
+        }
+        thread.state == ThreadState::Runnable
+    }
+
+    /// Returns the next thread, in round-robin order after the last one resumed, that is not
+    /// executing and can proceed
+    pub fn next_thread_to_resume(&mut self, now: i64) -> Option<ThreadId> {
+        let count = self.threads.len() as u32;
+        let last = self.last_resumed.as_u32();
+        let thread_id = (1..=count)
+            .map(|offset| ThreadId::new((last - 1 + offset) % count + 1))
+            .find(|&thread_id| {
+                self.thread(thread_id).native_depth == 0 && self.try_resume(thread_id, now)
+            })?;
+        self.last_resumed = thread_id;
+        self.instructions_until_preemption = self.preemption_interval;
+        Some(thread_id)
+    }
+
+    /// Returns the earliest time at which a sleeping or waiting thread that can be resumed,
+    /// or the given one, will wake up
+    pub fn earliest_wake_up(&self, current: ThreadId) -> Option<i64> {
+        self.thread_ids()
+            .map(|thread_id| (thread_id, self.thread(thread_id)))
+            .filter(|(thread_id, thread)| thread.native_depth == 0 || *thread_id == current)
+            .filter_map(|(_, thread)| match thread.state {
+                ThreadState::Sleeping { wake_up_at } => Some(wake_up_at),
+                ThreadState::Waiting { wake_up_at, .. } => wake_up_at,
+                _ => None,
+            })
+            .min()
+    }
+
+    pub fn thread_count(&self) -> usize {
+        self.threads.len()
+    }
+
+    pub fn thread_ids(&self) -> impl Iterator<Item = ThreadId> {
+        (1..=self.threads.len() as u32).map(ThreadId::new)
+    }
+
+    /// Wakes up the thread that has been waiting the longest on the monitor of an object
+    pub fn notify(&mut self, object: &AbstractObject<'a>) {
+        if let Some(&thread_id) = self.waiting_on(object).first() {
+            self.stop_waiting(thread_id);
+        }
+    }
+
+    /// Wakes up all the threads waiting on the monitor of an object
+    pub fn notify_all(&mut self, object: &AbstractObject<'a>) {
+        for thread_id in self.waiting_on(object) {
+            self.stop_waiting(thread_id);
+        }
+    }
+
+    fn waiting_on(&self, object: &AbstractObject<'a>) -> Vec<ThreadId> {
+        self.wait_set
+            .iter()
+            .copied()
+            .filter(|&thread_id| {
+                matches!(&self.thread(thread_id).state,
+                    ThreadState::Waiting { monitor, .. } if monitor.is_same_as(object))
+            })
+            .collect()
+    }
+
+    /// Wakes up the threads waiting for the initialization of a class, which has either
+    /// completed or failed
+    pub fn class_initialized(&mut self, class_id: ClassId) {
+        let waiting = ThreadState::WaitingForInitialization { class_id };
+        for thread_id in self.thread_ids().collect::<Vec<_>>() {
+            if self.thread(thread_id).state == waiting {
+                self.set_state(thread_id, ThreadState::Runnable);
+            }
+        }
+    }
+
+    /// Removes a thread from the wait set, so that it can enter the monitor again
+    fn stop_waiting(&mut self, thread_id: ThreadId) {
+        if let ThreadState::Waiting {
+            monitor,
+            entry_count,
+            ..
+        } = &self.thread(thread_id).state
+        {
+            let state = ThreadState::Blocked {
+                monitor: monitor.clone(),
+                entry_count: *entry_count,
+            };
+            self.set_state(thread_id, state);
+        }
+    }
+
+    /// Interrupts a thread. A sleeping or waiting thread wakes up and will throw an
+    /// `InterruptedException` when it resumes; any other one has its interrupt status set.
+    pub fn interrupt(&mut self, thread_id: ThreadId) {
+        match self.thread(thread_id).state {
+            ThreadState::Sleeping { .. } => {
+                self.set_state(thread_id, ThreadState::Runnable);
+                self.thread_mut(thread_id).pending_exception = Some(VmError::InterruptedException(
+                    Some("sleep interrupted".to_string()),
+                ));
+            }
+            ThreadState::Waiting { .. } => {
+                self.stop_waiting(thread_id);
+                self.thread_mut(thread_id).pending_exception =
+                    Some(VmError::InterruptedException(None));
+            }
+            ThreadState::Terminated => {}
+            _ => self.thread_mut(thread_id).interrupted = true,
+        }
+    }
+
+    /// Returns the interrupt status of a thread, clearing it if requested
+    pub fn is_interrupted(&mut self, thread_id: ThreadId, clear: bool) -> bool {
+        let thread = self.thread_mut(thread_id);
+        let interrupted = thread.interrupted;
+        if clear {
+            thread.interrupted = false;
+        }
+        interrupted
+    }
+
+    pub fn take_pending_exception(&mut self, thread_id: ThreadId) -> Option<VmError> {
+        self.thread_mut(thread_id).pending_exception.take()
+    }
+
+    pub fn gc_roots(&mut self) -> impl Iterator<Item = *mut AbstractObject<'a>> {
+        let mut roots = vec![];
+        for thread in self.threads.iter_mut() {
+            roots.extend(
+                thread
+                    .java_thread
+                    .iter_mut()
+                    .map(|object| object as *mut AbstractObject<'a>),
+            );
+            match &mut thread.state {
+                ThreadState::Blocked { monitor, .. } | ThreadState::Waiting { monitor, .. } => {
+                    roots.push(monitor as *mut AbstractObject<'a>)
+                }
+                _ => {}
+            }
+        }
+        roots.into_iter()
+    }
+}
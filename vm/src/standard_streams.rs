@@ -0,0 +1,30 @@
+use std::io::{Read, Write};
+
+/// The standard input, output and error of the Java program, i.e. the file descriptors
+/// 0, 1 and 2. They are provided by the host, which can capture or redirect them when
+/// embedding the vm; by default they are the ones of the process.
+pub struct StandardStreams {
+    pub stdin: Box<dyn Read>,
+    pub stdout: Box<dyn Write>,
+    pub stderr: Box<dyn Write>,
+}
+
+impl Default for StandardStreams {
+    fn default() -> Self {
+        Self {
+            stdin: Box::new(std::io::stdin()),
+            stdout: Box::new(std::io::stdout()),
+            stderr: Box::new(std::io::stderr()),
+        }
+    }
+}
+
+impl StandardStreams {
+    pub fn new(stdin: Box<dyn Read>, stdout: Box<dyn Write>, stderr: Box<dyn Write>) -> Self {
+        Self {
+            stdin,
+            stdout,
+            stderr,
+        }
+    }
+}
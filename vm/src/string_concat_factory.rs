@@ -0,0 +1,314 @@
+use rjvm_reader::{
+    class_access_flags::ClassAccessFlags,
+    class_file::ClassFile,
+    class_file_method::{ClassFileMethod, ClassFileMethodCode},
+    constant_pool::{ConstantPool, ConstantPoolEntry},
+    field_type::{BaseType, FieldType},
+    method_descriptor::MethodDescriptor,
+    method_flags::MethodFlags,
+};
+
+use crate::{
+    class::Class,
+    class_builder::{
+        load_opcode, slots, ConstantPoolBuilder, ARETURN, DUP, INVOKESPECIAL, INVOKEVIRTUAL,
+        LDC2_W, LDC_W, NEW,
+    },
+    vm_error::VmError,
+};
+
+pub(crate) const CONCAT_METHOD_NAME: &str = "concat";
+
+// Tags of the recipes of StringConcatFactory.makeConcatWithConstants
+const TAG_ARGUMENT: char = '\u{1}';
+const TAG_CONSTANT: char = '\u{2}';
+
+const STRING_BUILDER: &str = "java/lang/StringBuilder";
+
+/// Generates a class with a single static method, named `concat` and with the same descriptor
+/// as the call site, that concatenates its arguments with a `StringBuilder`, just like javac
+/// did before Java 9. The arguments and constants are appended following the recipe of
+/// `StringConcatFactory.makeConcatWithConstants`, or all the arguments in order for
+/// `StringConcatFactory.makeConcat` (i.e. when there is no recipe).
+pub(crate) fn spin_string_concat_class(
+    caller: &Class,
+    class_name: String,
+    call_site_descriptor: &str,
+    recipe: Option<&str>,
+    constant_arguments: &[u16],
+) -> Result<ClassFile, VmError> {
+    let call_site_type =
+        MethodDescriptor::parse(call_site_descriptor).map_err(|_| VmError::ValidationException)?;
+    if call_site_type.return_type != Some(FieldType::Object("java/lang/String".to_string())) {
+        return Err(VmError::ValidationException);
+    }
+
+    let mut constants = ConstantPoolBuilder::default();
+    constants.class(&class_name);
+    let mut code: Vec<u8> = Vec::new();
+
+    let string_builder_index = constants.class(STRING_BUILDER);
+    let constructor_index = constants.method(STRING_BUILDER, "<init>", "()V", false);
+    code.push(NEW);
+    code.extend_from_slice(&string_builder_index.to_be_bytes());
+    code.push(DUP);
+    code.push(INVOKESPECIAL);
+    code.extend_from_slice(&constructor_index.to_be_bytes());
+
+    let local_indexes: Vec<usize> = call_site_type
+        .parameters
+        .iter()
+        .scan(0, |next_local_index, parameter| {
+            let local_index = *next_local_index;
+            *next_local_index += slots(parameter);
+            Some(local_index)
+        })
+        .collect();
+    let append_argument = |constants: &mut ConstantPoolBuilder,
+                           code: &mut Vec<u8>,
+                           argument_index: usize|
+     -> Result<(), VmError> {
+        let parameter = call_site_type
+            .parameters
+            .get(argument_index)
+            .ok_or(VmError::ValidationException)?;
+        code.push(load_opcode(parameter));
+        code.push(
+            u8::try_from(local_indexes[argument_index])
+                .map_err(|_| VmError::ValidationException)?,
+        );
+        append(constants, code, parameter);
+        Ok(())
+    };
+
+    let elements = match recipe {
+        None => (0..call_site_type.parameters.len())
+            .map(RecipeElement::Argument)
+            .collect(),
+        Some(recipe) => parse_recipe(recipe, call_site_type.parameters.len(), constant_arguments)?,
+    };
+    for element in elements {
+        match element {
+            RecipeElement::Literal(literal) => {
+                let string_index = constants.string(&literal);
+                code.push(LDC_W);
+                code.extend_from_slice(&string_index.to_be_bytes());
+                append(&mut constants, &mut code, &string_type());
+            }
+            RecipeElement::Argument(argument_index) => {
+                append_argument(&mut constants, &mut code, argument_index)?
+            }
+            RecipeElement::Constant(constant_index) => {
+                append_constant(&mut constants, &mut code, &caller.constants, constant_index)?
+            }
+        }
+    }
+
+    let to_string_index =
+        constants.method(STRING_BUILDER, "toString", "()Ljava/lang/String;", false);
+    code.push(INVOKEVIRTUAL);
+    code.extend_from_slice(&to_string_index.to_be_bytes());
+    code.push(ARETURN);
+
+    let max_locals: usize = call_site_type.parameters.iter().map(slots).sum();
+    let concat_method = ClassFileMethod {
+        flags: MethodFlags::PUBLIC | MethodFlags::STATIC,
+        name: CONCAT_METHOD_NAME.to_string(),
+        type_descriptor: call_site_descriptor.to_string(),
+        parsed_type_descriptor: call_site_type,
+        attributes: Vec::new(),
+        code: Some(ClassFileMethodCode {
+            // The builder, its copy and a long or double argument
+            max_stack: 4,
+            max_locals: u16::try_from(max_locals).map_err(|_| VmError::ValidationException)?,
+            code,
+            ..Default::default()
+        }),
+        deprecated: false,
+        thrown_exceptions: Vec::new(),
+    };
+
+    Ok(ClassFile {
+        constants: constants.build(),
+        flags: ClassAccessFlags::FINAL | ClassAccessFlags::SUPER | ClassAccessFlags::SYNTHETIC,
+        name: class_name,
+        superclass: Some("java/lang/Object".to_string()),
+        methods: vec![concat_method],
+        ..Default::default()
+    })
+}
+
+/// A piece of the concatenated string
+#[derive(Debug, PartialEq)]
+enum RecipeElement {
+    Literal(String),
+    /// Index of an argument of the call site
+    Argument(usize),
+    /// Index of a constant in the caller's constant pool
+    Constant(u16),
+}
+
+/// Splits a recipe into its literals, arguments and constants. Every argument of the call site
+/// and every constant must be used exactly once, otherwise the recipe is malformed.
+fn parse_recipe(
+    recipe: &str,
+    num_arguments: usize,
+    constant_arguments: &[u16],
+) -> Result<Vec<RecipeElement>, VmError> {
+    let mut elements = Vec::new();
+    let mut next_argument = 0;
+    let mut constant_arguments = constant_arguments.iter();
+    let mut literal = String::new();
+    for c in recipe.chars() {
+        if c != TAG_ARGUMENT && c != TAG_CONSTANT {
+            literal.push(c);
+            continue;
+        }
+        if !literal.is_empty() {
+            elements.push(RecipeElement::Literal(std::mem::take(&mut literal)));
+        }
+        if c == TAG_CONSTANT {
+            let constant_index = constant_arguments
+                .next()
+                .ok_or(VmError::ValidationException)?;
+            elements.push(RecipeElement::Constant(*constant_index));
+        } else {
+            if next_argument == num_arguments {
+                return Err(VmError::ValidationException);
+            }
+            elements.push(RecipeElement::Argument(next_argument));
+            next_argument += 1;
+        }
+    }
+    if !literal.is_empty() {
+        elements.push(RecipeElement::Literal(literal));
+    }
+
+    if next_argument != num_arguments || constant_arguments.next().is_some() {
+        return Err(VmError::ValidationException);
+    }
+    Ok(elements)
+}
+
+fn string_type() -> FieldType {
+    FieldType::Object("java/lang/String".to_string())
+}
+
+/// Pushes a constant of the caller's constant pool and appends it
+fn append_constant(
+    constants: &mut ConstantPoolBuilder,
+    code: &mut Vec<u8>,
+    caller_constants: &ConstantPool,
+    constant_index: u16,
+) -> Result<(), VmError> {
+    let entry = caller_constants
+        .get(constant_index)
+        .map_err(|_| VmError::ValidationException)?;
+    let (entry, opcode, field_type) = match entry {
+        ConstantPoolEntry::StringReference(string_index) => {
+            let string = caller_constants
+                .text_of(*string_index)
+                .map_err(|_| VmError::ValidationException)?;
+            let string_index = constants.utf8(&string);
+            (
+                ConstantPoolEntry::StringReference(string_index),
+                LDC_W,
+                string_type(),
+            )
+        }
+        ConstantPoolEntry::Integer(value) => (
+            ConstantPoolEntry::Integer(*value),
+            LDC_W,
+            FieldType::Base(BaseType::Int),
+        ),
+        ConstantPoolEntry::Float(value) => (
+            ConstantPoolEntry::Float(*value),
+            LDC_W,
+            FieldType::Base(BaseType::Float),
+        ),
+        ConstantPoolEntry::Long(value) => (
+            ConstantPoolEntry::Long(*value),
+            LDC2_W,
+            FieldType::Base(BaseType::Long),
+        ),
+        ConstantPoolEntry::Double(value) => (
+            ConstantPoolEntry::Double(*value),
+            LDC2_W,
+            FieldType::Base(BaseType::Double),
+        ),
+        _ => return Err(VmError::NotImplemented),
+    };
+    let index = constants.add(entry);
+    code.push(opcode);
+    code.extend_from_slice(&index.to_be_bytes());
+    append(constants, code, &field_type);
+    Ok(())
+}
+
+/// Invokes the `StringBuilder.append` overload for the given type
+fn append(constants: &mut ConstantPoolBuilder, code: &mut Vec<u8>, field_type: &FieldType) {
+    let parameter = match field_type {
+        FieldType::Base(BaseType::Byte | BaseType::Short | BaseType::Int) => "I",
+        FieldType::Base(BaseType::Char) => "C",
+        FieldType::Base(BaseType::Boolean) => "Z",
+        FieldType::Base(BaseType::Long) => "J",
+        FieldType::Base(BaseType::Float) => "F",
+        FieldType::Base(BaseType::Double) => "D",
+        FieldType::Object(class_name) if class_name == "java/lang/String" => "Ljava/lang/String;",
+        _ => "Ljava/lang/Object;",
+    };
+    let method_index = constants.method(
+        STRING_BUILDER,
+        "append",
+        &format!("({parameter})Ljava/lang/StringBuilder;"),
+        false,
+    );
+    code.push(INVOKEVIRTUAL);
+    code.extend_from_slice(&method_index.to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        string_concat_factory::{parse_recipe, RecipeElement},
+        vm_error::VmError,
+    };
+
+    #[test]
+    fn recipes_are_split_into_literals_arguments_and_constants() {
+        assert_eq!(
+            Ok(vec![
+                RecipeElement::Literal("x=".to_string()),
+                RecipeElement::Argument(0),
+                RecipeElement::Constant(7),
+                RecipeElement::Argument(1),
+                RecipeElement::Literal("!".to_string()),
+            ]),
+            parse_recipe("x=\u{1}\u{2}\u{1}!", 2, &[7])
+        );
+    }
+
+    #[test]
+    fn malformed_recipes_are_rejected() {
+        // More arguments than the call site has
+        assert_eq!(
+            Err(VmError::ValidationException),
+            parse_recipe("\u{1}\u{1}", 1, &[])
+        );
+        // Fewer arguments than the call site has
+        assert_eq!(
+            Err(VmError::ValidationException),
+            parse_recipe("\u{1}", 2, &[])
+        );
+        // More constants than the bootstrap method has
+        assert_eq!(
+            Err(VmError::ValidationException),
+            parse_recipe("\u{2}\u{2}", 0, &[3])
+        );
+        // Constants that the recipe does not use
+        assert_eq!(
+            Err(VmError::ValidationException),
+            parse_recipe("\u{1}", 1, &[3])
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use rjvm_reader::field_type::{BaseType, FieldType};
+use rjvm_reader::{
+    field_type::{BaseType, FieldType},
+    program_counter::ProgramCounter,
+};
 
 use crate::{
     abstract_object::{AbstractObject, ObjectKind},
@@ -19,6 +22,8 @@ pub enum Value<'a> {
     Double(f64),
     Object(AbstractObject<'a>),
     Null,
+    /// Pushed by `jsr` and consumed by `ret`
+    ReturnAddress(ProgramCounter),
 }
 
 impl<'a> Value<'a> {
@@ -32,7 +37,7 @@ impl<'a> Value<'a> {
         ResByName: FnOnce(&str) -> Option<ClassRef<'b>>,
     {
         match self {
-            Value::Uninitialized => false,
+            Value::Uninitialized | Value::ReturnAddress(_) => false,
             Value::Int(_) => match expected_type {
                 FieldType::Base(base_type) => matches!(
                     base_type,
@@ -78,7 +83,7 @@ impl<'a> Value<'a> {
                                 class_resolver_by_id.find_class_by_id(object.class_id());
                             if let Some(object_class) = value_class {
                                 let expected_class = class_resolver_by_name(&expected_class_name);
-                                expected_class.map_or(false, |expected_class| {
+                                expected_class.is_some_and(|expected_class| {
                                     object_class.is_subclass_of(expected_class)
                                 })
                             } else {
@@ -141,6 +146,15 @@ pub fn expect_int_at(vec: &[Value], index: usize) -> Result<i32, VmError> {
     }
 }
 
+pub fn expect_long_at(vec: &[Value], index: usize) -> Result<i64, VmError> {
+    let value = vec.get(index);
+    if let Some(Value::Long(long)) = value {
+        Ok(*long)
+    } else {
+        Err(VmError::ValidationException)
+    }
+}
+
 pub fn expect_float_at(vec: &[Value], index: usize) -> Result<f32, VmError> {
     let value = vec.get(index);
     if let Some(Value::Float(float)) = value {
@@ -68,11 +68,11 @@ impl<'a> ValueStack<'a> {
         self.stack.get(index)
     }
 
-    pub fn iter(&self) -> Iter<Value<'a>> {
+    pub fn iter(&self) -> Iter<'_, Value<'a>> {
         self.stack.iter()
     }
 
-    pub fn iter_mut(&mut self) -> IterMut<Value<'a>> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, Value<'a>> {
         self.stack.iter_mut()
     }
 
@@ -93,45 +93,46 @@ impl<'a> ValueStack<'a> {
 
     pub fn dup_x2(&mut self) -> Result<(), ValueStackError> {
         let value1 = self.pop()?;
-        let value2 = self.pop()?;
-        let value3 = self.pop()?;
+        let values2 = self.pop_two_words()?;
         self.push(value1.clone())?;
-        self.push(value3)?;
-        self.push(value2)?;
+        self.push_all(values2)?;
         self.push(value1)
     }
 
     pub fn dup2(&mut self) -> Result<(), ValueStackError> {
-        let value1 = self.pop()?;
-        let value2 = self.pop()?;
-        self.push(value2.clone())?;
-        self.push(value1.clone())?;
-        self.push(value2)?;
-        self.push(value1)
+        let values1 = self.pop_two_words()?;
+        self.push_all(values1.clone())?;
+        self.push_all(values1)
     }
 
     pub fn dup2_x1(&mut self) -> Result<(), ValueStackError> {
-        let value1 = self.pop()?;
+        let values1 = self.pop_two_words()?;
         let value2 = self.pop()?;
-        let value3 = self.pop()?;
-        self.push(value2.clone())?;
-        self.push(value1.clone())?;
-        self.push(value3)?;
+        self.push_all(values1.clone())?;
         self.push(value2)?;
-        self.push(value1)
+        self.push_all(values1)
     }
 
     pub fn dup2_x2(&mut self) -> Result<(), ValueStackError> {
-        let value1 = self.pop()?;
-        let value2 = self.pop()?;
-        let value3 = self.pop()?;
-        let value4 = self.pop()?;
-        self.push(value2.clone())?;
-        self.push(value1.clone())?;
-        self.push(value4)?;
-        self.push(value3)?;
-        self.push(value2)?;
-        self.push(value1)
+        let values1 = self.pop_two_words()?;
+        let values2 = self.pop_two_words()?;
+        self.push_all(values1.clone())?;
+        self.push_all(values2)?;
+        self.push_all(values1)
+    }
+
+    /// Longs and doubles take a single entry of this stack, but two words of the JVM
+    /// operand stack: pops either one of them or two other values, in stack order
+    fn pop_two_words(&mut self) -> Result<Vec<Value<'a>>, ValueStackError> {
+        let value = self.pop()?;
+        match value {
+            Value::Long(_) | Value::Double(_) => Ok(vec![value]),
+            _ => Ok(vec![self.pop()?, value]),
+        }
+    }
+
+    fn push_all(&mut self, values: Vec<Value<'a>>) -> Result<(), ValueStackError> {
+        values.into_iter().try_for_each(|value| self.push(value))
     }
 
     pub fn swap(&mut self) -> Result<(), ValueStackError> {
@@ -288,4 +289,54 @@ mod tests {
         assert_eq!(Ok(Value::Int(1)), stack.pop());
         assert_eq!(Ok(Value::Int(2)), stack.pop());
     }
+
+    #[test]
+    fn can_invoke_dup2_with_a_long() {
+        let mut stack = ValueStack::with_max_size(3);
+        stack.push(Value::Int(2)).expect("should be able to push");
+        stack.push(Value::Long(1)).expect("should be able to push");
+        stack.dup2().expect("should be able to dup2");
+        assert_eq!(3, stack.len());
+        assert_eq!(Ok(Value::Long(1)), stack.pop());
+        assert_eq!(Ok(Value::Long(1)), stack.pop());
+        assert_eq!(Ok(Value::Int(2)), stack.pop());
+    }
+
+    #[test]
+    fn can_invoke_dup2_x1_with_a_double() {
+        let mut stack = ValueStack::with_max_size(3);
+        stack.push(Value::Int(2)).expect("should be able to push");
+        stack
+            .push(Value::Double(1.0))
+            .expect("should be able to push");
+        stack.dup2_x1().expect("should be able to dup2_x1");
+        assert_eq!(3, stack.len());
+        assert_eq!(Ok(Value::Double(1.0)), stack.pop());
+        assert_eq!(Ok(Value::Int(2)), stack.pop());
+        assert_eq!(Ok(Value::Double(1.0)), stack.pop());
+    }
+
+    #[test]
+    fn can_invoke_dup2_x2_with_longs() {
+        let mut stack = ValueStack::with_max_size(3);
+        stack.push(Value::Long(2)).expect("should be able to push");
+        stack.push(Value::Long(1)).expect("should be able to push");
+        stack.dup2_x2().expect("should be able to dup2_x2");
+        assert_eq!(3, stack.len());
+        assert_eq!(Ok(Value::Long(1)), stack.pop());
+        assert_eq!(Ok(Value::Long(2)), stack.pop());
+        assert_eq!(Ok(Value::Long(1)), stack.pop());
+    }
+
+    #[test]
+    fn can_invoke_dup_x2_with_a_long() {
+        let mut stack = ValueStack::with_max_size(3);
+        stack.push(Value::Long(2)).expect("should be able to push");
+        stack.push(Value::Int(1)).expect("should be able to push");
+        stack.dup_x2().expect("should be able to dup_x2");
+        assert_eq!(3, stack.len());
+        assert_eq!(Ok(Value::Int(1)), stack.pop());
+        assert_eq!(Ok(Value::Long(2)), stack.pop());
+        assert_eq!(Ok(Value::Int(1)), stack.pop());
+    }
 }
@@ -1,25 +1,38 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
-use log::{debug, error, info};
-use rjvm_reader::type_conversion::ToUsizeSafe;
+use log::{debug, error, info, warn};
+use rjvm_reader::{
+    class_file::ClassFile, class_file_field::FieldConstantValue, field_flags::FieldFlags,
+    method_flags::MethodFlags, program_counter::ProgramCounter, type_conversion::ToUsizeSafe,
+};
 use typed_arena::Arena;
 
 use crate::{
     abstract_object::{AbstractObject, ObjectKind},
     array::Array,
     array_entry_type::ArrayEntryType,
-    call_frame::MethodCallResult,
-    call_stack::CallStack,
-    class::{ClassId, ClassRef},
+    call_frame::{CallFrame, InstructionCompleted, MethodCallResult},
+    call_stack::{CallStack, ThreadId},
+    class::{ClassId, ClassRef, InitializationState},
     class_and_method::ClassAndMethod,
-    class_manager::{ClassManager, ResolvedClass},
+    class_manager::ClassManager,
     class_path::ClassPathParseError,
     class_resolver_by_id::ClassByIdResolver,
-    exceptions::MethodCallFailed,
+    exceptions::{JavaException, MethodCallFailed},
     gc::ObjectAllocator,
+    invoke_dynamic::{link_call_site, CallSite, CallSites},
+    java_objects_creation::{
+        extract_str_from_java_lang_string, extract_utf16_from_java_lang_string,
+        new_java_lang_class_object, new_java_lang_string_object,
+    },
     native_methods_impl::array_copy,
-    native_methods_registry::NativeMethodsRegistry,
+    native_methods_registry::{NativeCallback, NativeMethodsRegistry},
+    object::Object,
+    open_files::OpenFiles,
+    scheduler::{Scheduler, ThreadState},
     stack_trace_element::StackTraceElement,
+    standard_streams::StandardStreams,
+    time::get_nano_time,
     value::Value,
     vm_error::VmError,
 };
@@ -33,17 +46,55 @@ pub struct Vm<'a> {
 
     statics: HashMap<ClassId, AbstractObject<'a>>,
 
+    /// Objects used by the vm while it executes some Java code, which must survive a
+    /// garbage collection
+    temporary_roots: Vec<AbstractObject<'a>>,
+
+    /// Keyed by UTF-16 code units, since Java strings need not be valid UTF-16
+    interned_strings: HashMap<Vec<u16>, AbstractObject<'a>>,
+
+    /// The unique instance of `java.lang.Class` of each class, array class and primitive
+    /// type, by internal name
+    class_mirrors: HashMap<String, AbstractObject<'a>>,
+
     pub native_methods_registry: NativeMethodsRegistry<'a>,
 
     throwable_call_stacks: HashMap<i32, Vec<StackTraceElement<'a>>>,
 
-    pub printed: Vec<Value<'a>>,
+    call_sites: CallSites<'a>,
+
+    scheduler: Scheduler<'a>,
+
+    standard_streams: StandardStreams,
+
+    open_files: OpenFiles,
+
+    maximum_stack_depth: usize,
+
+    /// Whether the interpreter parses each instruction from the bytecode again before
+    /// executing it, rather than using the decoded code
+    parse_every_step: bool,
 }
 
+/// How the execution of the frames of a thread ended
+enum FramesExecution<'a> {
+    Returned(Option<Value<'a>>),
+    /// The thread blocked or was preempted, and its frames will be resumed later
+    Suspended,
+}
+
+// Values of the field `threadStatus` of `java.lang.Thread`, as defined by JVMTI
+const THREAD_STATUS_RUNNABLE: i32 = 0x0005;
+const THREAD_STATUS_TERMINATED: i32 = 0x0002;
+const THREAD_NORM_PRIORITY: i32 = 5;
+
 pub const ONE_MEGABYTE: usize = 1024 * 1024;
 const DEFAULT_MAX_MB_OF_MEMORY: usize = 100;
 pub const DEFAULT_MAX_MEMORY: usize = 100 * ONE_MEGABYTE;
 pub const DEFAULT_MAX_MEMORY_MB_STR: &str = const_format::formatcp!("{}", DEFAULT_MAX_MB_OF_MEMORY);
+pub const DEFAULT_MAX_STACK_DEPTH: usize = 10000;
+pub const DEFAULT_MAX_STACK_DEPTH_STR: &str =
+    const_format::formatcp!("{}", DEFAULT_MAX_STACK_DEPTH);
 
 impl<'a> ClassByIdResolver<'a> for Vm<'a> {
     fn find_class_by_id(&self, class_id: ClassId) -> Option<ClassRef<'a>> {
@@ -53,20 +104,53 @@ impl<'a> ClassByIdResolver<'a> for Vm<'a> {
 
 impl<'a> Vm<'a> {
     pub fn new(max_memory: usize) -> Self {
-        info!("Creating new VM with maximum memory {}", max_memory);
+        Self::with_maximum_stack_depth(max_memory, DEFAULT_MAX_STACK_DEPTH)
+    }
+
+    pub fn with_maximum_stack_depth(max_memory: usize, maximum_stack_depth: usize) -> Self {
+        Self::with_standard_streams(max_memory, maximum_stack_depth, Default::default())
+    }
+
+    /// Creates a vm whose Java code reads and writes the given streams, rather than the
+    /// ones of the process, as its standard input, output and error
+    pub fn with_standard_streams(
+        max_memory: usize,
+        maximum_stack_depth: usize,
+        standard_streams: StandardStreams,
+    ) -> Self {
+        info!(
+            "Creating new VM with maximum memory {} and maximum stack depth {}",
+            max_memory, maximum_stack_depth
+        );
         let mut result = Self {
             class_manager: Default::default(),
             object_allocator: ObjectAllocator::with_maximum_memory(max_memory),
             call_stacks: Arena::new(),
             statics: Default::default(),
+            temporary_roots: Vec::new(),
+            interned_strings: Default::default(),
+            class_mirrors: Default::default(),
             native_methods_registry: Default::default(),
             throwable_call_stacks: Default::default(),
-            printed: Vec::new(),
+            call_sites: Default::default(),
+            scheduler: Default::default(),
+            standard_streams,
+            open_files: Default::default(),
+            maximum_stack_depth,
+            parse_every_step: false,
         };
         crate::native_methods_impl::register_natives(&mut result.native_methods_registry);
         result
     }
 
+    pub(crate) fn standard_streams(&mut self) -> &mut StandardStreams {
+        &mut self.standard_streams
+    }
+
+    pub(crate) fn open_files(&mut self) -> &mut OpenFiles {
+        &mut self.open_files
+    }
+
     pub(crate) fn get_static_instance(&self, class_id: ClassId) -> Option<AbstractObject<'a>> {
         self.statics.get(&class_id).cloned()
     }
@@ -75,18 +159,47 @@ impl<'a> Vm<'a> {
         self.class_manager.append_class_path(class_path)
     }
 
+    /// Loads a class, without initializing it
     pub fn get_or_resolve_class(
         &mut self,
-        stack: &mut CallStack<'a>,
         class_name: &str,
     ) -> Result<ClassRef<'a>, MethodCallFailed<'a>> {
-        let class = self.class_manager.get_or_resolve_class(class_name)?;
-        if let ResolvedClass::NewClass(classes_to_init) = &class {
-            for class_to_init in classes_to_init.to_initialize.iter() {
-                self.init_class(stack, class_to_init)?;
-            }
+        Ok(self.class_manager.get_or_resolve_class(class_name)?)
+    }
+
+    /// Loads and initializes a class generated at runtime
+    pub(crate) fn define_class(
+        &mut self,
+        stack: &mut CallStack<'a>,
+        class_file: ClassFile,
+    ) -> Result<ClassRef<'a>, MethodCallFailed<'a>> {
+        let class = self.class_manager.define_class(class_file)?;
+        self.initialize_class(stack, class)?;
+        Ok(class)
+    }
+
+    /// Returns the call site of the `invokedynamic` instruction at the given address of a
+    /// method, linking it on its first execution
+    pub(crate) fn get_or_link_call_site(
+        &mut self,
+        stack: &mut CallStack<'a>,
+        class_and_method: ClassAndMethod<'a>,
+        pc: ProgramCounter,
+        constant_index: u16,
+    ) -> Result<CallSite<'a>, MethodCallFailed<'a>> {
+        let class = class_and_method.class;
+        let method_index = class_and_method.method_index;
+        if let Some(call_site) = self.call_sites.get(class.id, method_index, pc) {
+            return Ok(call_site);
         }
-        Ok(class.get_class())
+        let call_site = link_call_site(self, stack, class, constant_index)?;
+        self.call_sites
+            .insert(class.id, method_index, pc, call_site.clone());
+        Ok(call_site)
+    }
+
+    pub(crate) fn next_synthetic_class_id(&mut self) -> u32 {
+        self.call_sites.next_synthetic_class_id()
     }
 
     pub fn invoke(
@@ -95,16 +208,688 @@ impl<'a> Vm<'a> {
         class_and_method: ClassAndMethod<'a>,
         object: Option<AbstractObject<'a>>,
         args: Vec<Value<'a>>,
+    ) -> MethodCallResult<'a> {
+        let thread_id = call_stack.thread_id();
+        self.scheduler.thread_mut(thread_id).native_depth += 1;
+        let result = self.invoke_nested(call_stack, class_and_method, object, args);
+        self.scheduler.thread_mut(thread_id).native_depth -= 1;
+        result
+    }
+
+    fn invoke_nested(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+        class_and_method: ClassAndMethod<'a>,
+        object: Option<AbstractObject<'a>>,
+        args: Vec<Value<'a>>,
     ) -> MethodCallResult<'a> {
         if class_and_method.method.is_native() {
-            return self.invoke_native(call_stack, class_and_method, object, args);
+            let result = self.invoke_native(call_stack, class_and_method, object, args);
+            // Natives such as Thread.sleep block the thread rather than waiting themselves
+            let thread_id = call_stack.thread_id();
+            self.wait_until_runnable(thread_id)?;
+            if let Some(err) = self.scheduler.take_pending_exception(thread_id) {
+                return Err(err.into());
+            }
+            return result;
+        }
+
+        let base_depth = call_stack.depth();
+        self.push_frame(call_stack, class_and_method, object, args)?;
+        match self.execute_frames(call_stack, base_depth, false)? {
+            FramesExecution::Returned(return_value) => Ok(return_value),
+            FramesExecution::Suspended => Err(VmError::ValidationException.into()),
+        }
+    }
+
+    /// Pushes a frame for a method, entering the monitor of the object on which it is
+    /// synchronized, which will be exited when the frame is popped. If the monitor is
+    /// owned by another thread, the frame will start executing once it is released.
+    fn push_frame(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+        class_and_method: ClassAndMethod<'a>,
+        receiver: Option<AbstractObject<'a>>,
+        args: Vec<Value<'a>>,
+    ) -> Result<(), MethodCallFailed<'a>> {
+        let monitor = self.synchronized_on(class_and_method, &receiver)?;
+        let mut frame = call_stack.add_frame(class_and_method, receiver, args)?;
+        if let Some(monitor) = monitor {
+            self.enter_monitor(call_stack, &monitor);
+            frame.as_mut().set_synchronized_on(monitor);
+        }
+        Ok(())
+    }
+
+    /// Returns the object whose monitor must be held while executing a method, if it is
+    /// synchronized. Static methods are synchronized on the `java.lang.Class` of their class,
+    /// which is created when the class is initialized.
+    fn synchronized_on(
+        &self,
+        class_and_method: ClassAndMethod<'a>,
+        receiver: &Option<AbstractObject<'a>>,
+    ) -> Result<Option<AbstractObject<'a>>, VmError> {
+        if !class_and_method
+            .method
+            .flags
+            .contains(MethodFlags::SYNCHRONIZED)
+        {
+            return Ok(None);
+        }
+        match receiver {
+            Some(receiver) => Ok(Some(receiver.clone())),
+            None => self
+                .class_mirrors
+                .get(&class_and_method.class.name)
+                .cloned()
+                .map(Some)
+                .ok_or(VmError::ValidationException),
+        }
+    }
+
+    /// Enters the monitor of an object on behalf of the thread executing the given stack.
+    /// If another thread owns the monitor, blocks the thread and returns false: the thread
+    /// will own the monitor when it runs again.
+    pub(crate) fn enter_monitor(
+        &mut self,
+        call_stack: &CallStack<'a>,
+        object: &AbstractObject<'a>,
+    ) -> bool {
+        let thread_id = call_stack.thread_id();
+        if object.try_enter_monitor(thread_id) {
+            return true;
+        }
+        self.scheduler.set_state(
+            thread_id,
+            ThreadState::Blocked {
+                monitor: object.clone(),
+                entry_count: 1,
+            },
+        );
+        false
+    }
+
+    /// Executes the frames above `base_depth` until the bottom one of them completes.
+    /// The methods invoked by the frames are pushed on the call stack and executed by this
+    /// loop rather than recursively, so deep Java recursion is limited by the maximum depth
+    /// of the call stack and does not overflow the native stack.
+    /// When the thread blocks or is preempted, the execution is suspended if allowed, so
+    /// that the scheduler can resume it later; otherwise, the other threads run meanwhile.
+    fn execute_frames(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+        base_depth: usize,
+        suspendable: bool,
+    ) -> Result<FramesExecution<'a>, MethodCallFailed<'a>> {
+        let thread_id = call_stack.thread_id();
+        loop {
+            if !self.scheduler.is_runnable(thread_id) {
+                if suspendable {
+                    return Ok(FramesExecution::Suspended);
+                }
+                self.wait_until_runnable(thread_id)?;
+            }
+
+            let mut frame = call_stack.top_frame()?;
+            let execution = match self.scheduler.take_pending_exception(thread_id) {
+                // The native method that blocked the thread, such as Thread.sleep, has
+                // already completed: its invocation throws the exception instead
+                Some(err) => frame
+                    .as_mut()
+                    .complete_invocation(self, call_stack, Err(err.into()))
+                    .map(|_| InstructionCompleted::ContinueMethodExecution),
+                None => frame.as_mut().execute(self, call_stack),
+            };
+            let mut result = match execution {
+                Ok(InstructionCompleted::InvokeMethod(class_and_method, receiver, args)) => {
+                    let invocation_result = if class_and_method.is_native() {
+                        self.invoke_native(call_stack, class_and_method, receiver, args)
+                    } else {
+                        match self.push_frame(call_stack, class_and_method, receiver, args) {
+                            Ok(()) => continue,
+                            Err(err) => Err(err),
+                        }
+                    };
+                    match frame
+                        .as_mut()
+                        .complete_invocation(self, call_stack, invocation_result)
+                    {
+                        Ok(()) => continue,
+                        Err(failure) => Err(failure),
+                    }
+                }
+                Ok(InstructionCompleted::ReturnFromMethod(return_value)) => Ok(return_value),
+                Ok(InstructionCompleted::ContinueMethodExecution) => continue,
+                Ok(InstructionCompleted::Yield) => {
+                    // A thread that blocked is handled at the start of the loop
+                    if self.scheduler.is_runnable(thread_id) {
+                        if suspendable {
+                            return Ok(FramesExecution::Suspended);
+                        }
+                        self.run_other_threads_once()?;
+                    }
+                    continue;
+                }
+                Err(failure) => Err(failure),
+            };
+
+            // The top frame has completed: give its result to the callers, unwinding the
+            // ones that do not catch the exception it threw
+            loop {
+                match call_stack.pop_frame() {
+                    Ok(()) => {}
+                    // The method exited the monitor of its synchronized method too many times
+                    Err(VmError::IllegalMonitorStateException) => {
+                        result = Err(VmError::IllegalMonitorStateException.into())
+                    }
+                    Err(err) => return Err(err.into()),
+                }
+                if call_stack.depth() == base_depth {
+                    return result.map(FramesExecution::Returned);
+                }
+                let mut caller = call_stack.top_frame()?;
+                match caller
+                    .as_mut()
+                    .complete_invocation(self, call_stack, result)
+                {
+                    Ok(()) => break,
+                    Err(failure) => result = Err(failure),
+                }
+            }
+        }
+    }
+
+    pub(crate) fn should_preempt(&mut self, thread_id: ThreadId) -> bool {
+        self.scheduler.should_preempt(thread_id)
+    }
+
+    /// Sets how many bytecode instructions a thread executes before the scheduler switches
+    /// to another one
+    pub fn set_preemption_interval(&mut self, preemption_interval: usize) {
+        self.scheduler.set_preemption_interval(preemption_interval);
+    }
+
+    /// Makes the interpreter parse each instruction from the bytecode before executing it, as
+    /// it did before methods were decoded once. This is only useful to measure the speedup
+    /// given by the decoded code.
+    pub fn set_parse_every_step(&mut self, parse_every_step: bool) {
+        self.parse_every_step = parse_every_step;
+    }
+
+    pub(crate) fn parse_every_step(&self) -> bool {
+        self.parse_every_step
+    }
+
+    /// Executes a thread until it blocks, is preempted, or terminates
+    fn run_thread_slice(&mut self, thread_id: ThreadId) -> Result<(), VmError> {
+        debug!("resuming thread {:?}", thread_id);
+        // The scheduler does not resume threads whose execution is on the native stack, so
+        // nobody else is using this call stack
+        let call_stack = unsafe { &mut *self.scheduler.thread(thread_id).call_stack };
+        self.scheduler.thread_mut(thread_id).native_depth += 1;
+        let result = self.execute_frames(call_stack, 0, true);
+        self.scheduler.thread_mut(thread_id).native_depth -= 1;
+        match result {
+            Ok(FramesExecution::Suspended) => Ok(()),
+            Ok(FramesExecution::Returned(_)) => self.terminate_thread(call_stack, None),
+            Err(MethodCallFailed::ExceptionThrown(exception)) => {
+                self.terminate_thread(call_stack, Some(exception))
+            }
+            Err(MethodCallFailed::InternalError(err)) => {
+                error!("thread {:?} failed: {}", thread_id, err);
+                self.terminate_thread(call_stack, None)
+            }
+        }
+    }
+
+    /// Gives a slice of execution to each of the other threads that can proceed
+    fn run_other_threads_once(&mut self) -> Result<(), VmError> {
+        let now = get_nano_time();
+        for _ in 1..self.scheduler.thread_count() {
+            match self.scheduler.next_thread_to_resume(now) {
+                Some(thread_id) => self.run_thread_slice(thread_id)?,
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs the other threads until the given one, whose execution cannot be suspended,
+    /// can proceed
+    fn wait_until_runnable(&mut self, thread_id: ThreadId) -> Result<(), VmError> {
+        self.run_other_threads_until(thread_id, |vm, thread_id, now| {
+            vm.scheduler.try_resume(thread_id, now)
+        })
+    }
+
+    /// Runs the threads started by the program until all the non-daemon ones have
+    /// terminated, like the JVM does when the main method returns
+    pub fn wait_for_non_daemon_threads(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+    ) -> Result<(), VmError> {
+        let thread_id = call_stack.thread_id();
+        self.scheduler.thread_mut(thread_id).native_depth += 1;
+        let result = self.run_other_threads_until(thread_id, |vm, thread_id, _| {
+            !vm.scheduler.thread_ids().any(|other| {
+                other != thread_id
+                    && vm.scheduler.thread(other).state != ThreadState::Terminated
+                    && !vm.is_daemon(other)
+            })
+        });
+        self.scheduler.thread_mut(thread_id).native_depth -= 1;
+        result
+    }
+
+    fn run_other_threads_until(
+        &mut self,
+        thread_id: ThreadId,
+        condition: fn(&mut Self, ThreadId, i64) -> bool,
+    ) -> Result<(), VmError> {
+        loop {
+            let now = get_nano_time();
+            if condition(self, thread_id, now) {
+                return Ok(());
+            }
+            match self.scheduler.next_thread_to_resume(now) {
+                Some(other) => self.run_thread_slice(other)?,
+                None => match self.scheduler.earliest_wake_up(thread_id) {
+                    Some(wake_up_at) => {
+                        std::thread::sleep(Duration::from_nanos((wake_up_at - now).max(0) as u64))
+                    }
+                    None => return Err(self.deadlock()),
+                },
+            }
+        }
+    }
+
+    /// Describes the state of the threads that cannot proceed, i.e. all the alive ones
+    fn deadlock(&self) -> VmError {
+        let threads = self
+            .scheduler
+            .thread_ids()
+            .filter(|&thread_id| self.is_thread_alive(thread_id))
+            .map(|thread_id| {
+                let thread = self.thread_description(thread_id);
+                match &self.scheduler.thread(thread_id).state {
+                    ThreadState::Blocked { monitor, .. } => format!(
+                        "{thread} is blocked on {}",
+                        self.monitor_description(monitor)
+                    ),
+                    ThreadState::Waiting { monitor, .. } => format!(
+                        "{thread} is waiting on {}",
+                        self.monitor_description(monitor)
+                    ),
+                    ThreadState::WaitingForInitialization { class_id } => format!(
+                        "{thread} is waiting for the initialization of {}",
+                        self.get_class_by_id(*class_id)
+                            .map(|class| class.name.clone())
+                            .unwrap_or_default()
+                    ),
+                    // Threads that must resume a nested execution on the native stack
+                    // before this one can proceed
+                    _ => format!("{thread} cannot be resumed"),
+                }
+            })
+            .collect();
+        VmError::Deadlock(threads)
+    }
+
+    fn thread_description(&self, thread_id: ThreadId) -> String {
+        let name = self
+            .scheduler
+            .thread(thread_id)
+            .java_thread
+            .as_ref()
+            .and_then(|java_thread| self.get_thread_field(java_thread, "name"))
+            .and_then(|name| match name {
+                Value::Object(name) => extract_str_from_java_lang_string(self, &name).ok(),
+                _ => None,
+            });
+        match name {
+            Some(name) => format!("thread {} \"{}\"", thread_id.as_u32(), name),
+            None => format!("thread {}", thread_id.as_u32()),
+        }
+    }
+
+    fn monitor_description(&self, monitor: &AbstractObject<'a>) -> String {
+        let class_name = match monitor.kind() {
+            ObjectKind::Array => "an array".to_string(),
+            ObjectKind::Object => self
+                .get_class_by_id(monitor.class_id())
+                .map(|class| class.name.clone())
+                .unwrap_or_default(),
+        };
+        match monitor.monitor_owner() {
+            Some(owner) => format!("{class_name} owned by {}", self.thread_description(owner)),
+            None => class_name,
+        }
+    }
+
+    fn is_daemon(&self, thread_id: ThreadId) -> bool {
+        self.scheduler
+            .thread(thread_id)
+            .java_thread
+            .as_ref()
+            .and_then(|java_thread| self.get_thread_field(java_thread, "daemon"))
+            .is_some_and(|daemon| daemon == Value::Int(1))
+    }
+
+    /// Starts executing the `run` method of a `java.lang.Thread` in a new green thread
+    pub(crate) fn start_thread(
+        &mut self,
+        java_thread: AbstractObject<'a>,
+    ) -> Result<(), MethodCallFailed<'a>> {
+        let class = self.get_class_by_id(java_thread.class_id())?;
+        let run_method = class
+            .vtable_slot("run", "()V")
+            .and_then(|slot| class.vtable_method(slot))
+            .ok_or(VmError::ValidationException)?;
+        let call_stack = self.allocate_call_stack();
+        debug!("starting thread {:?}", call_stack.thread_id());
+        self.set_thread_field(&java_thread, "threadStatus", THREAD_STATUS_RUNNABLE)?;
+        self.scheduler
+            .thread_mut(call_stack.thread_id())
+            .java_thread = Some(java_thread.clone());
+        self.push_frame(call_stack, run_method, Some(java_thread), Vec::new())
+    }
+
+    /// Like HotSpot, dispatches the uncaught exception of a terminated thread and invokes
+    /// `Thread.exit`, and then wakes up the threads joining it
+    fn terminate_thread(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+        uncaught_exception: Option<JavaException<'a>>,
+    ) -> Result<(), VmError> {
+        let thread_id = call_stack.thread_id();
+        debug!("thread {:?} terminated", thread_id);
+        if let Some(exception) = uncaught_exception {
+            self.invoke_thread_method(
+                call_stack,
+                "dispatchUncaughtException",
+                "(Ljava/lang/Throwable;)V",
+                vec![Value::Object(exception.0)],
+            );
+        }
+        self.invoke_thread_method(call_stack, "exit", "()V", Vec::new());
+
+        self.scheduler.set_state(thread_id, ThreadState::Terminated);
+        if let Some(java_thread) = self.scheduler.thread(thread_id).java_thread.clone() {
+            self.set_thread_field(&java_thread, "threadStatus", THREAD_STATUS_TERMINATED)?;
+            self.scheduler.notify_all(&java_thread);
+        }
+        Ok(())
+    }
+
+    /// Invokes a private method of `java.lang.Thread` on a terminating thread. Failures
+    /// are only logged, since the thread is terminating anyway.
+    fn invoke_thread_method(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+        method_name: &str,
+        type_descriptor: &str,
+        args: Vec<Value<'a>>,
+    ) {
+        let result = self
+            .resolve_class_method(call_stack, "java/lang/Thread", method_name, type_descriptor)
+            .and_then(|method| {
+                let java_thread = self
+                    .scheduler
+                    .thread(call_stack.thread_id())
+                    .java_thread
+                    .clone();
+                self.invoke(call_stack, method, java_thread, args)
+            });
+        if let Err(err) = result {
+            warn!(
+                "invocation of Thread.{} on thread {:?} failed: {:?}",
+                method_name,
+                call_stack.thread_id(),
+                err
+            );
+        }
+    }
+
+    /// Returns the `java.lang.Thread` instance of the thread executing the given call stack
+    pub(crate) fn current_java_thread(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+    ) -> Result<AbstractObject<'a>, MethodCallFailed<'a>> {
+        match &self.scheduler.thread(call_stack.thread_id()).java_thread {
+            Some(java_thread) => Ok(java_thread.clone()),
+            None => self.new_initial_thread(call_stack),
+        }
+    }
+
+    /// Creates the `java.lang.Thread` of a thread not started from Java code, such as the
+    /// one executing `main`
+    fn new_initial_thread(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+    ) -> Result<AbstractObject<'a>, MethodCallFailed<'a>> {
+        let base = self.temporary_roots.len();
+        let result = self.create_initial_thread(call_stack, base);
+        self.temporary_roots.truncate(base);
+        result
+    }
+
+    // Like HotSpot, we create the system and main thread groups, and run the constructor
+    // of the thread after registering it as the current one, since the constructor uses it
+    fn create_initial_thread(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+        base: usize,
+    ) -> Result<AbstractObject<'a>, MethodCallFailed<'a>> {
+        let thread_group_constructor = self.resolve_class_method(
+            call_stack,
+            "java/lang/ThreadGroup",
+            "<init>",
+            "(Ljava/lang/ThreadGroup;Ljava/lang/String;)V",
+        )?;
+        let system_group = self.new_object(call_stack, "java/lang/ThreadGroup")?;
+        self.temporary_roots.push(system_group.clone());
+        let system_group_constructor =
+            self.resolve_class_method(call_stack, "java/lang/ThreadGroup", "<init>", "()V")?;
+        self.invoke(
+            call_stack,
+            system_group_constructor,
+            Some(system_group),
+            Vec::new(),
+        )?;
+
+        let main_group = self.new_object(call_stack, "java/lang/ThreadGroup")?;
+        self.temporary_roots.push(main_group.clone());
+        let name = self.intern_string(call_stack, "main")?;
+        let system_group = self.temporary_roots[base].clone();
+        self.invoke(
+            call_stack,
+            thread_group_constructor,
+            Some(main_group),
+            vec![Value::Object(system_group), Value::Object(name)],
+        )?;
+
+        let thread_id = call_stack.thread_id();
+        let thread_constructor = self.resolve_class_method(
+            call_stack,
+            "java/lang/Thread",
+            "<init>",
+            "(Ljava/lang/ThreadGroup;Ljava/lang/String;)V",
+        )?;
+        let java_thread = self.new_object(call_stack, "java/lang/Thread")?;
+        self.set_thread_field(&java_thread, "priority", THREAD_NORM_PRIORITY)?;
+        self.set_thread_field(&java_thread, "threadStatus", THREAD_STATUS_RUNNABLE)?;
+        self.scheduler.thread_mut(thread_id).java_thread = Some(java_thread.clone());
+        let name = self.intern_string(call_stack, "main")?;
+        let main_group = self.temporary_roots[base + 1].clone();
+        self.invoke(
+            call_stack,
+            thread_constructor,
+            Some(java_thread),
+            vec![Value::Object(main_group), Value::Object(name)],
+        )?;
+        Ok(self
+            .scheduler
+            .thread(thread_id)
+            .java_thread
+            .clone()
+            .expect("the thread was registered"))
+    }
+
+    fn get_thread_field(&self, java_thread: &AbstractObject<'a>, name: &str) -> Option<Value<'a>> {
+        let class = self.get_class_by_id(java_thread.class_id()).ok()?;
+        let (_, index, _) = class.find_field(name)?;
+        Some(java_thread.get_field(class, index))
+    }
+
+    fn set_thread_field(
+        &self,
+        java_thread: &AbstractObject<'a>,
+        name: &str,
+        value: i32,
+    ) -> Result<(), VmError> {
+        let class = self.get_class_by_id(java_thread.class_id())?;
+        let (_, index, _) = class
+            .find_field(name)
+            .ok_or(VmError::FieldNotFoundException(
+                class.name.clone(),
+                name.to_string(),
+            ))?;
+        java_thread.set_field(index, Value::Int(value));
+        Ok(())
+    }
+
+    /// Finds the green thread running a `java.lang.Thread`, if it was started
+    pub(crate) fn find_thread(&self, java_thread: &AbstractObject<'a>) -> Option<ThreadId> {
+        self.scheduler.find_by_java_thread(java_thread)
+    }
+
+    pub(crate) fn is_thread_alive(&self, thread_id: ThreadId) -> bool {
+        self.scheduler.thread(thread_id).state != ThreadState::Terminated
+    }
+
+    pub(crate) fn yield_thread(&mut self) {
+        self.scheduler.yield_now();
+    }
+
+    /// Blocks the current thread until the given time, unless it was interrupted
+    pub(crate) fn sleep(
+        &mut self,
+        call_stack: &CallStack<'a>,
+        wake_up_at: i64,
+    ) -> Result<(), VmError> {
+        let thread_id = call_stack.thread_id();
+        if self.scheduler.is_interrupted(thread_id, true) {
+            return Err(VmError::InterruptedException(Some(
+                "sleep interrupted".to_string(),
+            )));
+        }
+        self.scheduler
+            .set_state(thread_id, ThreadState::Sleeping { wake_up_at });
+        Ok(())
+    }
+
+    /// Implements `Object.notify` and `Object.notifyAll`, which require the current thread
+    /// to own the monitor of the object
+    pub(crate) fn notify(
+        &mut self,
+        call_stack: &CallStack<'a>,
+        object: &AbstractObject<'a>,
+        all: bool,
+    ) -> Result<(), VmError> {
+        if object.monitor_owner() != Some(call_stack.thread_id()) {
+            return Err(VmError::IllegalMonitorStateException);
         }
+        if all {
+            self.scheduler.notify_all(object);
+        } else {
+            self.scheduler.notify(object);
+        }
+        Ok(())
+    }
 
-        let mut frame = call_stack.add_frame(class_and_method, object, args)?;
-        let result = frame.as_mut().execute(self, call_stack);
-        call_stack
-            .pop_frame()
-            .expect("should be able to pop the frame we just pushed");
+    /// Interrupts the thread running a `java.lang.Thread`. Threads not started yet, or
+    /// terminated, are not affected.
+    pub(crate) fn interrupt(&mut self, java_thread: &AbstractObject<'a>) {
+        if let Some(thread_id) = self.find_thread(java_thread) {
+            self.scheduler.interrupt(thread_id);
+        }
+    }
+
+    pub(crate) fn is_interrupted(&mut self, java_thread: &AbstractObject<'a>, clear: bool) -> bool {
+        self.find_thread(java_thread)
+            .is_some_and(|thread_id| self.scheduler.is_interrupted(thread_id, clear))
+    }
+
+    /// Implements `Object.wait`: releases the monitor of the object, and blocks the current
+    /// thread until it is notified or until the given time
+    pub(crate) fn wait(
+        &mut self,
+        call_stack: &CallStack<'a>,
+        object: AbstractObject<'a>,
+        wake_up_at: Option<i64>,
+    ) -> Result<(), VmError> {
+        let thread_id = call_stack.thread_id();
+        if object.monitor_owner() != Some(thread_id) {
+            return Err(VmError::IllegalMonitorStateException);
+        }
+        if self.scheduler.is_interrupted(thread_id, true) {
+            return Err(VmError::InterruptedException(None));
+        }
+        let entry_count = object.release_monitor(thread_id)?;
+        self.scheduler.set_state(
+            thread_id,
+            ThreadState::Waiting {
+                monitor: object,
+                entry_count,
+                wake_up_at,
+            },
+        );
+        Ok(())
+    }
+
+    /// Invokes a synchronized native method while holding the given monitor
+    fn invoke_synchronized_native(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+        native_callback: NativeCallback<'a>,
+        monitor: AbstractObject<'a>,
+        object: Option<AbstractObject<'a>>,
+        args: Vec<Value<'a>>,
+    ) -> MethodCallResult<'a> {
+        // Other threads can run a garbage collection while this one waits for the
+        // monitor, so the monitor, receiver and arguments stay roots until the call
+        // returns, and are reloaded after the wait
+        let roots_length = self.temporary_roots.len();
+        self.push_temporary_root(monitor.clone());
+        let receiver_root = object
+            .clone()
+            .map(|object| self.push_temporary_root(object));
+        let arg_roots: Vec<Option<usize>> = args
+            .iter()
+            .map(|arg| match arg {
+                Value::Object(arg) => Some(self.push_temporary_root(arg.clone())),
+                _ => None,
+            })
+            .collect();
+        let (object, args) = if self.enter_monitor(call_stack, &monitor) {
+            (object, args)
+        } else {
+            self.wait_until_runnable(call_stack.thread_id())?;
+            let object = receiver_root.map(|index| self.temporary_root(index));
+            let args = args
+                .into_iter()
+                .zip(arg_roots)
+                .map(|(arg, root)| match root {
+                    Some(index) => Value::Object(self.temporary_root(index)),
+                    None => arg,
+                })
+                .collect();
+            (object, args)
+        };
+        let result = native_callback(self, call_stack, object, args);
+        let monitor = self.temporary_root(roots_length);
+        self.truncate_temporary_roots(roots_length);
+        monitor.exit_monitor(call_stack.thread_id())?;
         result
     }
 
@@ -123,7 +908,18 @@ impl<'a> Vm<'a> {
                 class_and_method.method.name,
                 class_and_method.method.type_descriptor
             );
-            native_callback(self, call_stack, object, args)
+            let result = match self.synchronized_on(class_and_method, &object)? {
+                Some(monitor) => self.invoke_synchronized_native(
+                    call_stack,
+                    native_callback,
+                    monitor,
+                    object,
+                    args,
+                ),
+                None => native_callback(self, call_stack, object, args),
+            }?;
+            CallFrame::validate_type_opt(self, class_and_method.return_type(), &result)?;
+            Ok(result)
         } else {
             error!(
                 "cannot resolve native method {}::{} {}",
@@ -136,36 +932,256 @@ impl<'a> Vm<'a> {
     }
 
     pub fn allocate_call_stack(&mut self) -> &'a mut CallStack<'a> {
-        let stack = self.call_stacks.alloc(CallStack::new());
-        unsafe {
-            let stack_ptr: *mut CallStack<'a> = stack;
-            &mut *stack_ptr
+        let thread_id = self.scheduler.next_thread_id();
+        let stack = self
+            .call_stacks
+            .alloc(CallStack::new(self.maximum_stack_depth, thread_id));
+        let stack_ptr: *mut CallStack<'a> = stack;
+        self.scheduler.add_thread(stack_ptr);
+        unsafe { &mut *stack_ptr }
+    }
+
+    /// Initializes a class on its first active use, following JVMS 5.5: the superclass and
+    /// the superinterfaces declaring default methods are initialized first, and then the
+    /// static initializer is executed. A request to initialize a class that is already being
+    /// initialized, which happens when static initializers depend on each other, returns
+    /// immediately.
+    pub fn initialize_class(
+        &mut self,
+        stack: &mut CallStack<'a>,
+        class: ClassRef<'a>,
+    ) -> Result<(), MethodCallFailed<'a>> {
+        let thread_id = stack.thread_id();
+        match class.initialization_state.get() {
+            InitializationState::Initialized => return Ok(()),
+            // A recursive request of the thread executing the static initializer
+            InitializationState::BeingInitialized(initializing_thread)
+                if initializing_thread == thread_id =>
+            {
+                return Ok(())
+            }
+            InitializationState::BeingInitialized(_) => {
+                // Wait for the other thread, then check whether its initialization succeeded
+                self.await_class_initialization(stack, class);
+                self.wait_until_runnable(thread_id)?;
+                return self.initialize_class(stack, class);
+            }
+            InitializationState::Erroneous => {
+                return Err(MethodCallFailed::InternalError(
+                    VmError::NoClassDefFoundError(format!(
+                        "Could not initialize class {}",
+                        class.name.replace('/', ".")
+                    )),
+                ))
+            }
+            InitializationState::NotInitialized => {}
         }
+
+        class
+            .initialization_state
+            .set(InitializationState::BeingInitialized(thread_id));
+        debug!("creating static instance of {}", class.name);
+        let static_instance = self.new_object_of_class(class);
+        self.statics.insert(class.id, static_instance);
+
+        let result = self
+            .get_class_mirror(stack, &class.name)
+            .and_then(|_| self.initialize_constant_fields(stack, class))
+            .and_then(|_| self.run_class_initializers(stack, class));
+        class.initialization_state.set(match result {
+            Ok(()) => InitializationState::Initialized,
+            Err(_) => InitializationState::Erroneous,
+        });
+        self.scheduler.class_initialized(class.id);
+        result
     }
 
-    fn init_class(
+    /// Checks whether the thread executing the given stack can use a class. If another
+    /// thread is initializing the class, blocks the thread and returns false, as in JVMS 5.5:
+    /// the thread will run again once the initialization has completed or failed.
+    pub(crate) fn await_class_initialization(
+        &mut self,
+        call_stack: &CallStack<'a>,
+        class: ClassRef<'a>,
+    ) -> bool {
+        let thread_id = call_stack.thread_id();
+        match class.initialization_state.get() {
+            InitializationState::BeingInitialized(initializing_thread)
+                if initializing_thread != thread_id =>
+            {
+                self.scheduler.set_state(
+                    thread_id,
+                    ThreadState::WaitingForInitialization { class_id: class.id },
+                );
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// Sets the static fields having a `ConstantValue` attribute, which javac does not
+    /// assign in the static initializer
+    fn initialize_constant_fields(
         &mut self,
         stack: &mut CallStack<'a>,
-        class_to_init: &ClassRef<'a>,
+        class: ClassRef<'a>,
     ) -> Result<(), MethodCallFailed<'a>> {
-        debug!("creating static instance of {}", class_to_init.name);
-        let static_instance = self.new_object_of_class(class_to_init);
-        self.statics.insert(class_to_init.id, static_instance);
-        if let Some(clinit_method) = class_to_init.find_method("<clinit>", "()V") {
-            debug!("invoking {}::<clinit>()", class_to_init.name);
-            self.invoke(
-                stack,
-                ClassAndMethod {
-                    class: class_to_init,
-                    method: clinit_method,
-                },
-                None,
-                Vec::new(),
-            )?;
+        for (index, field) in class.fields.iter().enumerate() {
+            if !field.flags.contains(FieldFlags::STATIC) {
+                continue;
+            }
+            let value = match &field.constant_value {
+                None => continue,
+                Some(FieldConstantValue::Int(value)) => Value::Int(*value),
+                Some(FieldConstantValue::Long(value)) => Value::Long(*value),
+                Some(FieldConstantValue::Float(value)) => Value::Float(*value),
+                Some(FieldConstantValue::Double(value)) => Value::Double(*value),
+                Some(FieldConstantValue::String(value)) => {
+                    Value::Object(self.intern_string(stack, value)?)
+                }
+            };
+            // Creating a string can move the static instance, so we get it only now
+            let static_instance = self
+                .get_static_instance(class.id)
+                .ok_or(VmError::ValidationException)?;
+            static_instance.set_field(class.first_field_index + index, value);
+        }
+        Ok(())
+    }
+
+    fn run_class_initializers(
+        &mut self,
+        stack: &mut CallStack<'a>,
+        class: ClassRef<'a>,
+    ) -> Result<(), MethodCallFailed<'a>> {
+        if !class.is_interface() {
+            if let Some(superclass) = class.superclass {
+                self.initialize_class(stack, superclass)?;
+            }
+            for interface in Self::superinterfaces_with_default_methods(class) {
+                self.initialize_class(stack, interface)?;
+            }
+        }
+
+        if let Some(clinit_method) = class.find_class_and_method("<clinit>", "()V") {
+            debug!("invoking {}::<clinit>()", class.name);
+            let result = self.invoke(stack, clinit_method, None, Vec::new());
+            match result {
+                Err(MethodCallFailed::ExceptionThrown(exception))
+                    if !self.is_error(&exception)? =>
+                {
+                    let error = self.new_wrapping_exception(
+                        stack,
+                        "java/lang/ExceptionInInitializerError",
+                        "(Ljava/lang/Throwable;)V",
+                        exception,
+                    )?;
+                    return Err(MethodCallFailed::ExceptionThrown(error));
+                }
+                result => {
+                    result?;
+                }
+            }
         }
         Ok(())
     }
 
+    /// Returns the superinterfaces of a class that need to be initialized with it, i.e. the
+    /// ones declaring default methods, in the order of JVMS 5.5
+    fn superinterfaces_with_default_methods(class: ClassRef<'a>) -> Vec<ClassRef<'a>> {
+        fn collect<'a>(interface: ClassRef<'a>, result: &mut Vec<ClassRef<'a>>) {
+            for superinterface in interface.interfaces.iter() {
+                collect(superinterface, result);
+            }
+            let declares_default_methods = interface
+                .methods
+                .iter()
+                .any(|method| !method.is_abstract() && !method.is_static());
+            if declares_default_methods && !result.iter().any(|other| other.id == interface.id) {
+                result.push(interface);
+            }
+        }
+
+        let mut result = Vec::new();
+        for interface in class.interfaces.iter() {
+            collect(interface, &mut result);
+        }
+        result
+    }
+
+    /// Returns whether a thrown object is a `java.lang.Error`, which static initializers
+    /// throw without wrapping it
+    fn is_error(&self, exception: &JavaException<'a>) -> Result<bool, VmError> {
+        self.is_thrown_instance_of(exception, "java/lang/Error")
+    }
+
+    /// Returns whether a thrown object is a checked exception, i.e. a `java.lang.Exception`
+    /// that is not a `java.lang.RuntimeException`
+    pub(crate) fn is_checked_exception(
+        &self,
+        exception: &JavaException<'a>,
+    ) -> Result<bool, VmError> {
+        let is_exception = self.is_thrown_instance_of(exception, "java/lang/Exception")?;
+        Ok(is_exception && !self.is_thrown_instance_of(exception, "java/lang/RuntimeException")?)
+    }
+
+    fn is_thrown_instance_of(
+        &self,
+        exception: &JavaException<'a>,
+        class_name: &str,
+    ) -> Result<bool, VmError> {
+        let mut class = Some(self.get_class_by_id(exception.0.class_id())?);
+        while let Some(curr_class) = class {
+            if curr_class.name == class_name {
+                return Ok(true);
+            }
+            class = curr_class.superclass;
+        }
+        Ok(false)
+    }
+
+    /// Wraps an exception thrown by a static initializer, by a method invoked through
+    /// reflection, or by a privileged action, in a new exception of the given class, created
+    /// with the constructor having the given descriptor and taking the exception as its only
+    /// argument. The exception and the new one are kept in the temporary gc roots while the
+    /// latter is created, since that can trigger a garbage collection and move them.
+    pub(crate) fn new_wrapping_exception(
+        &mut self,
+        stack: &mut CallStack<'a>,
+        wrapper_class_name: &str,
+        constructor_descriptor: &str,
+        exception: JavaException<'a>,
+    ) -> Result<JavaException<'a>, MethodCallFailed<'a>> {
+        let base = self.temporary_roots.len();
+        self.temporary_roots.push(exception.0);
+        let result =
+            self.create_wrapping_exception(stack, wrapper_class_name, constructor_descriptor, base);
+        self.temporary_roots.truncate(base);
+        result
+    }
+
+    fn create_wrapping_exception(
+        &mut self,
+        stack: &mut CallStack<'a>,
+        wrapper_class_name: &str,
+        constructor_descriptor: &str,
+        base: usize,
+    ) -> Result<JavaException<'a>, MethodCallFailed<'a>> {
+        let wrapper = self.new_object(stack, wrapper_class_name)?;
+        self.temporary_roots.push(wrapper);
+        let constructor =
+            self.resolve_class_method(stack, wrapper_class_name, "<init>", constructor_descriptor)?;
+        let exception = self.temporary_roots[base].clone();
+        let wrapper = self.temporary_roots[base + 1].clone();
+        self.invoke(
+            stack,
+            constructor,
+            Some(wrapper),
+            vec![Value::Object(exception)],
+        )?;
+        Ok(JavaException(self.temporary_roots[base + 1].clone()))
+    }
+
     pub fn get_class_by_id(&self, class_id: ClassId) -> Result<ClassRef<'a>, VmError> {
         self.find_class_by_id(class_id)
             .ok_or(VmError::ValidationException)
@@ -175,6 +1191,8 @@ impl<'a> Vm<'a> {
         self.class_manager.find_class_by_name(class_name)
     }
 
+    /// Finds a method declared by a class, initializing the class since the method is about
+    /// to be invoked
     pub fn resolve_class_method(
         &mut self,
         call_stack: &mut CallStack<'a>,
@@ -182,19 +1200,232 @@ impl<'a> Vm<'a> {
         method_name: &str,
         method_type_descriptor: &str,
     ) -> Result<ClassAndMethod<'a>, MethodCallFailed<'a>> {
-        self.get_or_resolve_class(call_stack, class_name)
-            .and_then(|class| {
-                class
-                    .find_method(method_name, method_type_descriptor)
-                    .map(|method| ClassAndMethod { class, method })
-                    .ok_or(MethodCallFailed::InternalError(
-                        VmError::MethodNotFoundException(
-                            class_name.to_string(),
-                            method_name.to_string(),
-                            method_type_descriptor.to_string(),
-                        ),
-                    ))
-            })
+        let class = self.get_or_resolve_class(class_name)?;
+        self.initialize_class(call_stack, class)?;
+        class
+            .find_class_and_method(method_name, method_type_descriptor)
+            .ok_or(MethodCallFailed::InternalError(
+                VmError::MethodNotFoundException(
+                    class_name.to_string(),
+                    method_name.to_string(),
+                    method_type_descriptor.to_string(),
+                ),
+            ))
+    }
+
+    /// Invokes a method on an object, selecting the implementation from the vtable of its
+    /// class like `invokevirtual` does
+    pub(crate) fn invoke_virtual(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+        receiver: AbstractObject<'a>,
+        method_name: &str,
+        method_type_descriptor: &str,
+    ) -> MethodCallResult<'a> {
+        self.invoke_virtual_with_args(
+            call_stack,
+            receiver,
+            method_name,
+            method_type_descriptor,
+            Vec::new(),
+        )
+    }
+
+    fn invoke_virtual_with_args(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+        receiver: AbstractObject<'a>,
+        method_name: &str,
+        method_type_descriptor: &str,
+        args: Vec<Value<'a>>,
+    ) -> MethodCallResult<'a> {
+        let class = self.get_class_by_id(receiver.class_id())?;
+        let method = class
+            .vtable_slot(method_name, method_type_descriptor)
+            .and_then(|slot| class.vtable_method(slot))
+            .ok_or_else(|| {
+                VmError::MethodNotFoundException(
+                    class.name.clone(),
+                    method_name.to_string(),
+                    method_type_descriptor.to_string(),
+                )
+            })?;
+        self.invoke(call_stack, method, Some(receiver), args)
+    }
+
+    /// Like HotSpot, runs `System.initializeSystemClass`, or `System.initPhase1` on newer
+    /// class libraries, which sets up the system properties and the standard streams.
+    /// Programs using `System.out` need this to happen before their `main` method.
+    pub fn initialize_system_class(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+    ) -> Result<(), MethodCallFailed<'a>> {
+        let class = self.get_or_resolve_class("java/lang/System")?;
+        self.initialize_class(call_stack, class)?;
+        let method = class
+            .find_class_and_method("initializeSystemClass", "()V")
+            .or_else(|| class.find_class_and_method("initPhase1", "()V"))
+            .ok_or_else(|| {
+                VmError::MethodNotFoundException(
+                    class.name.clone(),
+                    "initializeSystemClass".to_string(),
+                    "()V".to_string(),
+                )
+            })?;
+        self.invoke(call_stack, method, None, Vec::new())?;
+        Ok(())
+    }
+
+    /// Sets a static field of a class, as `putstatic` would, bypassing the `final` modifier
+    /// like the natives `System.setOut0` and friends need to
+    pub(crate) fn set_static_field(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+        class_name: &str,
+        field_name: &str,
+        value: Value<'a>,
+    ) -> Result<(), MethodCallFailed<'a>> {
+        let class = self.get_or_resolve_class(class_name)?;
+        self.initialize_class(call_stack, class)?;
+        let (field_class, index, _) = class.find_field(field_name).ok_or_else(|| {
+            VmError::FieldNotFoundException(class_name.to_string(), field_name.to_string())
+        })?;
+        let statics = self
+            .get_static_instance(field_class.id)
+            .ok_or(VmError::ValidationException)?;
+        statics.set_field(index, value);
+        Ok(())
+    }
+
+    /// Keeps an object reachable, and up to date when the garbage collector moves it, until
+    /// the temporary roots are truncated. Returns the index to retrieve it with.
+    pub(crate) fn push_temporary_root(&mut self, object: AbstractObject<'a>) -> usize {
+        self.temporary_roots.push(object);
+        self.temporary_roots.len() - 1
+    }
+
+    pub(crate) fn temporary_root(&self, index: usize) -> AbstractObject<'a> {
+        self.temporary_roots[index].clone()
+    }
+
+    pub(crate) fn truncate_temporary_roots(&mut self, length: usize) {
+        self.temporary_roots.truncate(length);
+    }
+
+    /// Invokes `Properties.setProperty` with each of the given entries. Returns the
+    /// properties object, which the garbage collector might have moved.
+    pub(crate) fn set_properties(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+        properties: AbstractObject<'a>,
+        entries: &[(String, String)],
+    ) -> Result<AbstractObject<'a>, MethodCallFailed<'a>> {
+        let base = self.temporary_roots.len();
+        self.temporary_roots.push(properties);
+        let result = self.set_rooted_properties(call_stack, base, entries);
+        self.temporary_roots.truncate(base);
+        result
+    }
+
+    fn set_rooted_properties(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+        base: usize,
+        entries: &[(String, String)],
+    ) -> Result<AbstractObject<'a>, MethodCallFailed<'a>> {
+        for (key, value) in entries {
+            let key = new_java_lang_string_object(self, call_stack, key)?;
+            self.temporary_roots.push(key);
+            let value = new_java_lang_string_object(self, call_stack, value)?;
+            let key = self.temporary_roots.pop().expect("the key was pushed");
+            let properties = self.temporary_roots[base].clone();
+            self.invoke_virtual_with_args(
+                call_stack,
+                properties,
+                "setProperty",
+                "(Ljava/lang/String;Ljava/lang/String;)Ljava/lang/Object;",
+                vec![Value::Object(key), Value::Object(value)],
+            )?;
+        }
+        Ok(self.temporary_roots[base].clone())
+    }
+
+    /// Allocates a `String[]` with the given contents
+    pub(crate) fn new_string_array(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+        strings: &[String],
+    ) -> Result<AbstractObject<'a>, MethodCallFailed<'a>> {
+        let string_class = self.get_or_resolve_class("java/lang/String")?;
+        let array = self.new_array(ArrayEntryType::Object(string_class.id), strings.len());
+        let base = self.temporary_roots.len();
+        self.temporary_roots.push(array);
+        let result = self.fill_rooted_string_array(call_stack, base, strings);
+        self.temporary_roots.truncate(base);
+        result
+    }
+
+    fn fill_rooted_string_array(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+        base: usize,
+        strings: &[String],
+    ) -> Result<AbstractObject<'a>, MethodCallFailed<'a>> {
+        for (index, content) in strings.iter().enumerate() {
+            let string = new_java_lang_string_object(self, call_stack, content)?;
+            self.temporary_roots[base].set_element(index, Value::Object(string))?;
+        }
+        Ok(self.temporary_roots[base].clone())
+    }
+
+    /// Returns the unique `java.lang.String` instance with the given content, creating it
+    /// on the first request
+    pub(crate) fn intern_string(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+        content: &str,
+    ) -> Result<AbstractObject<'a>, MethodCallFailed<'a>> {
+        let key: Vec<u16> = content.encode_utf16().collect();
+        if let Some(string) = self.interned_strings.get(&key) {
+            return Ok(string.clone());
+        }
+        let string = new_java_lang_string_object(self, call_stack, content)?;
+        self.interned_strings.insert(key, string.clone());
+        Ok(string)
+    }
+
+    /// Implements `String.intern`: returns the interned string with the same content as
+    /// the given one, which becomes the interned instance if there was none
+    pub(crate) fn intern_string_object(
+        &mut self,
+        string: AbstractObject<'a>,
+    ) -> Result<AbstractObject<'a>, VmError> {
+        let content = extract_utf16_from_java_lang_string(self, &string)?;
+        Ok(self
+            .interned_strings
+            .entry(content)
+            .or_insert(string)
+            .clone())
+    }
+
+    /// Returns the unique `java.lang.Class` instance of a class, array class or primitive
+    /// type, given its internal name, creating it on the first request
+    pub(crate) fn get_class_mirror(
+        &mut self,
+        call_stack: &mut CallStack<'a>,
+        class_name: &str,
+    ) -> Result<AbstractObject<'a>, MethodCallFailed<'a>> {
+        if let Some(mirror) = self.class_mirrors.get(class_name) {
+            return Ok(mirror.clone());
+        }
+        let mirror = new_java_lang_class_object(self, call_stack, class_name)?;
+        // Creating the mirror of `java.lang.Class` or `java.lang.String` initializes them,
+        // which creates their mirror before this one is stored
+        Ok(self
+            .class_mirrors
+            .entry(class_name.to_string())
+            .or_insert(mirror)
+            .clone())
     }
 
     pub fn new_object(
@@ -202,7 +1433,8 @@ impl<'a> Vm<'a> {
         call_stack: &mut CallStack<'a>,
         class_name: &str,
     ) -> Result<AbstractObject<'a>, MethodCallFailed<'a>> {
-        let class = self.get_or_resolve_class(call_stack, class_name)?;
+        let class = self.get_or_resolve_class(class_name)?;
+        self.initialize_class(call_stack, class)?;
         Ok(self.new_object_of_class(class))
     }
 
@@ -243,15 +1475,35 @@ impl<'a> Vm<'a> {
     pub fn clone_array(&mut self, value: Value<'a>) -> Result<Value<'a>, VmError> {
         match &value {
             Value::Object(array) if array.kind() == ObjectKind::Array => {
-                let new_array =
-                    self.new_array(array.elements_type(), array.len().into_usize_safe());
-                array_copy(array, 0, &new_array, 0, array.len().into_usize_safe())?;
+                let length = array.len().into_usize_safe();
+                // Allocating the copy can trigger a garbage collection, which would move the array
+                self.temporary_roots.push(array.clone());
+                let new_array = self.new_array(array.elements_type(), length);
+                let array = self.temporary_roots.pop().expect("the array was pushed");
+                array_copy(&array, 0, &new_array, 0, length)?;
                 Ok(Value::Object(new_array))
             }
             _ => Err(VmError::ValidationException),
         }
     }
 
+    /// Creates a shallow copy of an object, which is what `Object.clone` does for the
+    /// classes implementing `Cloneable`
+    pub(crate) fn clone_object(
+        &mut self,
+        object: &AbstractObject<'a>,
+    ) -> Result<AbstractObject<'a>, VmError> {
+        let class = self.get_class_by_id(object.class_id())?;
+        // Allocating the copy can trigger a garbage collection, which would move the object
+        self.temporary_roots.push(object.clone());
+        let copy = self.new_object_of_class(class);
+        let object = self.temporary_roots.pop().expect("the object was pushed");
+        for index in 0..class.num_total_fields {
+            copy.set_field(index, object.get_field(class, index));
+        }
+        Ok(copy)
+    }
+
     pub(crate) fn associate_stack_trace_with_throwable(
         &mut self,
         throwable: AbstractObject<'a>,
@@ -283,7 +1535,23 @@ impl<'a> Vm<'a> {
                 .iter_mut()
                 .map(|(_, object)| object as *mut AbstractObject<'a>),
         );
+        roots.extend(
+            self.temporary_roots
+                .iter_mut()
+                .map(|object| object as *mut AbstractObject<'a>),
+        );
+        roots.extend(
+            self.interned_strings
+                .values_mut()
+                .map(|object| object as *mut AbstractObject<'a>),
+        );
+        roots.extend(
+            self.class_mirrors
+                .values_mut()
+                .map(|object| object as *mut AbstractObject<'a>),
+        );
         roots.extend(self.call_stacks.iter_mut().flat_map(|s| s.gc_roots()));
+        roots.extend(self.scheduler.gc_roots());
 
         unsafe {
             self.object_allocator
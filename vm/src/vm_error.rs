@@ -13,6 +13,10 @@ pub enum VmError {
     #[error("class not found: {0}")]
     ClassNotFoundException(String),
 
+    /// Thrown by `Class.forName`, unlike a class missing while linking
+    #[error("class not found by name: {0}")]
+    ClassNotFoundByName(String),
+
     #[error("method not found: {0}.{1}#{2}")]
     MethodNotFoundException(String, String, String),
 
@@ -28,11 +32,122 @@ pub enum VmError {
     #[error("not yet implemented")]
     NotImplemented,
 
-    #[error("array index out of bounds")]
-    ArrayIndexOutOfBoundsException,
+    #[error("array index out of bounds: {0} for length {1}")]
+    ArrayIndexOutOfBoundsException(i64, u32),
+
+    #[error("class cast exception: {0} cannot be cast to {1}")]
+    ClassCastException(String, String),
+
+    #[error("negative array size: {0}")]
+    NegativeArraySizeException(i32),
+
+    #[error("stack overflow")]
+    StackOverflowError,
+
+    #[error("abstract method error: {0}")]
+    AbstractMethodError(String),
+
+    #[error("incompatible class change: {0}")]
+    IncompatibleClassChangeError(String),
+
+    #[error("no class definition found: {0}")]
+    NoClassDefFoundError(String),
+
+    #[error("illegal monitor state")]
+    IllegalMonitorStateException,
+
+    #[error("illegal argument: {0}")]
+    IllegalArgumentException(String),
 
-    #[error("class cast exception")]
-    ClassCastException,
+    #[error("cannot instantiate: {0}")]
+    InstantiationException(String),
+
+    #[error("cannot clone: {0}")]
+    CloneNotSupportedException(String),
+
+    #[error("interrupted")]
+    InterruptedException(Option<String>),
+
+    #[error("i/o error: {0}")]
+    IOException(String),
+
+    #[error("file not found: {0}")]
+    FileNotFoundException(String),
+
+    #[error("sync failed: {0}")]
+    SyncFailedException(String),
+
+    /// No thread can proceed: describes each of them
+    #[error("deadlock: {}", .0.join("; "))]
+    Deadlock(Vec<String>),
+}
+
+impl VmError {
+    /// Some errors are actually exceptions of the Java runtime, and should be thrown to the
+    /// executing code. In that case, returns the exception class and its message.
+    pub fn to_java_exception(&self) -> Option<(&'static str, Option<String>)> {
+        match self {
+            VmError::NullPointerException => Some(("java/lang/NullPointerException", None)),
+            VmError::ArithmeticException => Some((
+                "java/lang/ArithmeticException",
+                Some("/ by zero".to_string()),
+            )),
+            VmError::ArrayIndexOutOfBoundsException(index, length) => Some((
+                "java/lang/ArrayIndexOutOfBoundsException",
+                Some(format!("Index {index} out of bounds for length {length}")),
+            )),
+            VmError::ClassCastException(class_name, target_class_name) => Some((
+                "java/lang/ClassCastException",
+                Some(format!(
+                    "{} cannot be cast to {}",
+                    class_name.replace('/', "."),
+                    target_class_name.replace('/', ".")
+                )),
+            )),
+            VmError::NegativeArraySizeException(length) => Some((
+                "java/lang/NegativeArraySizeException",
+                Some(length.to_string()),
+            )),
+            VmError::StackOverflowError => Some(("java/lang/StackOverflowError", None)),
+            VmError::AbstractMethodError(message) => {
+                Some(("java/lang/AbstractMethodError", Some(message.clone())))
+            }
+            VmError::IncompatibleClassChangeError(message) => Some((
+                "java/lang/IncompatibleClassChangeError",
+                Some(message.clone()),
+            )),
+            VmError::NoClassDefFoundError(message) => {
+                Some(("java/lang/NoClassDefFoundError", Some(message.clone())))
+            }
+            VmError::IllegalArgumentException(message) => {
+                Some(("java/lang/IllegalArgumentException", Some(message.clone())))
+            }
+            VmError::ClassNotFoundByName(class_name) => {
+                Some(("java/lang/ClassNotFoundException", Some(class_name.clone())))
+            }
+            VmError::InstantiationException(class_name) => {
+                Some(("java/lang/InstantiationException", Some(class_name.clone())))
+            }
+            VmError::CloneNotSupportedException(class_name) => Some((
+                "java/lang/CloneNotSupportedException",
+                Some(class_name.clone()),
+            )),
+            VmError::InterruptedException(message) => {
+                Some(("java/lang/InterruptedException", message.clone()))
+            }
+            VmError::IOException(message) => Some(("java/io/IOException", Some(message.clone()))),
+            VmError::FileNotFoundException(message) => {
+                Some(("java/io/FileNotFoundException", Some(message.clone())))
+            }
+            VmError::SyncFailedException(message) => {
+                Some(("java/io/SyncFailedException", Some(message.clone())))
+            }
+            VmError::IllegalMonitorStateException => {
+                Some(("java/lang/IllegalMonitorStateException", None))
+            }
+            _ => None,
+        }
+    }
 }
 
 impl From<ValueStackError> for VmError {
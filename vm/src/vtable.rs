@@ -0,0 +1,164 @@
+use std::{collections::HashMap, fmt, fmt::Formatter};
+
+use rjvm_reader::{class_file_method::ClassFileMethod, method_flags::MethodFlags};
+
+use crate::{
+    class::{ClassId, ClassRef},
+    class_and_method::ClassAndMethod,
+    method_resolution::{all_superinterfaces, maximally_specific_methods, non_abstract_methods},
+};
+
+/// An entry of a virtual method table
+#[derive(Clone, Copy)]
+pub(crate) struct VtableEntry<'a> {
+    /// The class declaring the method, or `None` for the class owning the table, which does
+    /// not exist yet when the table is built
+    pub class: Option<ClassRef<'a>>,
+    pub method_index: usize,
+    /// Set when the class inherits several maximally-specific default methods, none of
+    /// which overrides the others, so that selecting this slot fails
+    pub conflicting: bool,
+}
+
+// The entries can point to the class owning the table, so we print only the names
+impl<'a> fmt::Debug for VtableEntry<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.class {
+            Some(class) => write!(
+                f,
+                "{}.{}",
+                class.name, class.methods[self.method_index].name
+            ),
+            None => write!(f, "#{}", self.method_index),
+        }
+    }
+}
+
+/// For each interface implemented by a class, maps the index of every method of the
+/// interface to the slot of the virtual method table implementing it
+pub(crate) type Itables = HashMap<ClassId, Vec<Option<usize>>>;
+
+/// Returns whether a method is selected by the class of the receiver, rather than being
+/// invoked directly
+pub(crate) fn is_overridable(method: &ClassFileMethod) -> bool {
+    !method
+        .flags
+        .intersects(MethodFlags::STATIC | MethodFlags::PRIVATE)
+        && !method.name.starts_with('<')
+}
+
+/// Builds the virtual method table of a class and its interface tables. The table starts
+/// with the slots of the superclass, whose methods can be overridden; then new methods get
+/// new slots, followed by the methods of the interfaces that are not implemented by the
+/// class or its superclasses. For those, the selection of JVMS 5.4.6 picks the only
+/// maximally-specific default method, or else an abstract one.
+pub(crate) fn build_tables<'a>(
+    superclass: Option<ClassRef<'a>>,
+    interfaces: &[ClassRef<'a>],
+    methods: &[ClassFileMethod],
+) -> (Vec<VtableEntry<'a>>, Itables) {
+    let mut vtable: Vec<VtableEntry<'a>> = Vec::new();
+    let mut slots: HashMap<(&str, &str), usize> = HashMap::new();
+    if let Some(superclass) = superclass {
+        for (slot, entry) in superclass.vtable.iter().enumerate() {
+            let class = entry.class.unwrap_or(superclass);
+            let method = &class.methods[entry.method_index];
+            vtable.push(VtableEntry {
+                class: Some(class),
+                ..*entry
+            });
+            slots.insert((&method.name, &method.type_descriptor), slot);
+        }
+    }
+
+    for (method_index, method) in methods.iter().enumerate() {
+        if !is_overridable(method) {
+            continue;
+        }
+        let entry = VtableEntry {
+            class: None,
+            method_index,
+            conflicting: false,
+        };
+        match slots.get(&(method.name.as_str(), method.type_descriptor.as_str())) {
+            Some(&slot) => vtable[slot] = entry,
+            None => {
+                slots.insert((&method.name, &method.type_descriptor), vtable.len());
+                vtable.push(entry);
+            }
+        }
+    }
+
+    // Interface methods are only used when no class declares the method. Slots already
+    // filled by interface methods of the superclass are chosen again, since this class can
+    // implement more specific interfaces.
+    let all_interfaces = all_superinterfaces(superclass, interfaces);
+    for (key, candidates) in group_interface_methods(&all_interfaces) {
+        let maximally_specific = maximally_specific_methods(&candidates);
+        let non_abstract = non_abstract_methods(&maximally_specific);
+        let selected = non_abstract.first().unwrap_or(&maximally_specific[0]);
+        let entry = VtableEntry {
+            class: Some(selected.class),
+            method_index: selected.method_index,
+            conflicting: non_abstract.len() > 1,
+        };
+        match slots.get(&key) {
+            Some(&slot) if vtable[slot].class.is_some_and(|class| class.is_interface()) => {
+                vtable[slot] = entry
+            }
+            Some(_) => {}
+            None => {
+                slots.insert(key, vtable.len());
+                vtable.push(entry);
+            }
+        }
+    }
+
+    let itables = all_interfaces
+        .iter()
+        .map(|interface| {
+            let interface_slots = interface
+                .methods
+                .iter()
+                .map(|method| {
+                    if is_overridable(method) {
+                        slots
+                            .get(&(method.name.as_str(), method.type_descriptor.as_str()))
+                            .cloned()
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            (interface.id, interface_slots)
+        })
+        .collect();
+
+    (vtable, itables)
+}
+
+/// Groups the overridable methods of the given interfaces by name and descriptor, in order
+/// of declaration
+fn group_interface_methods<'a>(
+    interfaces: &[ClassRef<'a>],
+) -> Vec<((&'a str, &'a str), Vec<ClassAndMethod<'a>>)> {
+    let mut groups: Vec<((&'a str, &'a str), Vec<ClassAndMethod<'a>>)> = Vec::new();
+    let mut group_indexes: HashMap<(&'a str, &'a str), usize> = HashMap::new();
+    for &interface in interfaces {
+        for (method_index, method) in interface.methods.iter().enumerate() {
+            if !is_overridable(method) {
+                continue;
+            }
+            let key = (method.name.as_str(), method.type_descriptor.as_str());
+            let class_and_method = ClassAndMethod::new(interface, method_index);
+            match group_indexes.get(&key) {
+                Some(&index) => groups[index].1.push(class_and_method),
+                None => {
+                    group_indexes.insert(key, groups.len());
+                    groups.push((key, vec![class_and_method]));
+                }
+            }
+        }
+    }
+    groups
+}
@@ -1,14 +1,150 @@
+use std::{
+    cell::RefCell,
+    io::Write,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rjvm_reader::instruction::Instruction;
 use rjvm_vm::{
     exceptions::MethodCallFailed,
-    java_objects_creation::extract_str_from_java_lang_string,
-    value::{expect_concrete_object_at, Value},
-    vm::{Vm, DEFAULT_MAX_MEMORY},
+    object::Object,
+    standard_streams::StandardStreams,
+    value::Value,
+    vm::{Vm, DEFAULT_MAX_MEMORY, DEFAULT_MAX_STACK_DEPTH},
+    vm_error::VmError,
 };
 
 // This file tests the real classes in ../resources/rjvm
 
-fn create_base_vm(max_memory: usize) -> Vm<'static> {
-    let mut vm = Vm::new(max_memory);
+/// Collects what the Java code writes to one of its standard streams
+#[derive(Clone, Default)]
+struct CapturedOutput(Rc<RefCell<Vec<u8>>>);
+
+impl Write for CapturedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl CapturedOutput {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.borrow().clone()).expect("should have written valid UTF-8")
+    }
+
+    fn lines(&self) -> Vec<String> {
+        self.contents().lines().map(str::to_string).collect()
+    }
+
+    /// Returns the values written by `tempPrint`
+    fn printed(&self) -> Vec<Printed> {
+        self.contents().lines().map(Printed::parse).collect()
+    }
+
+    fn printed_string(&self, index: usize) -> String {
+        match self.printed().into_iter().nth(index) {
+            Some(Printed::String(string)) => string,
+            printed => panic!("should have printed a string at position {index}, got {printed:?}"),
+        }
+    }
+}
+
+/// A value written by the `tempPrint` natives of the test classes, parsed back from the
+/// standard output
+#[derive(Debug, PartialEq)]
+enum Printed {
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+}
+
+impl Printed {
+    /// Parses a line formatted like a Java literal, as `tempPrint` does
+    fn parse(line: &str) -> Self {
+        if let Some(quoted) = line
+            .strip_prefix('"')
+            .and_then(|line| line.strip_suffix('"'))
+        {
+            return Printed::String(unescape(quoted));
+        }
+        if let Some(long) = line.strip_suffix('L').and_then(|long| long.parse().ok()) {
+            return Printed::Long(long);
+        }
+        if let Ok(int) = line.parse() {
+            return Printed::Int(int);
+        }
+        if let Some(float) = line.strip_suffix('f').and_then(|float| float.parse().ok()) {
+            return Printed::Float(float);
+        }
+        Printed::Double(
+            line.parse()
+                .unwrap_or_else(|_| panic!("should have printed a value, but got {line}")),
+        )
+    }
+}
+
+/// Reverts the escaping of the `Debug` format of strings
+fn unescape(escaped: &str) -> String {
+    let mut result = String::new();
+    let mut chars = escaped.chars();
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            result.push(char);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            Some('0') => result.push('\0'),
+            Some('u') => {
+                let code: String = chars
+                    .by_ref()
+                    .skip(1)
+                    .take_while(|char| *char != '}')
+                    .collect();
+                let code = u32::from_str_radix(&code, 16).expect("should be a hexadecimal code");
+                result.push(char::from_u32(code).expect("should be a valid char"));
+            }
+            Some(escaped) => result.push(escaped),
+            None => panic!("should not end with a backslash"),
+        }
+    }
+    result
+}
+
+fn create_base_vm(max_memory: usize) -> (Vm<'static>, CapturedOutput) {
+    create_base_vm_with_maximum_stack_depth(max_memory, DEFAULT_MAX_STACK_DEPTH)
+}
+
+/// Creates a vm whose standard output is captured, to check what `tempPrint` writes
+fn create_base_vm_with_maximum_stack_depth(
+    max_memory: usize,
+    maximum_stack_depth: usize,
+) -> (Vm<'static>, CapturedOutput) {
+    let stdout = CapturedOutput::default();
+    let standard_streams = StandardStreams::new(
+        Box::new(std::io::empty()),
+        Box::new(stdout.clone()),
+        Box::new(std::io::sink()),
+    );
+    let vm = create_vm_with_standard_streams(max_memory, maximum_stack_depth, standard_streams);
+    (vm, stdout)
+}
+
+fn create_vm_with_standard_streams(
+    max_memory: usize,
+    maximum_stack_depth: usize,
+    standard_streams: StandardStreams,
+) -> Vm<'static> {
+    let mut vm = Vm::with_standard_streams(max_memory, maximum_stack_depth, standard_streams);
 
     let src_dir = env!("CARGO_MANIFEST_DIR");
     vm.append_class_path(&format!("{src_dir}/rt.jar:{src_dir}/tests/resources",))
@@ -34,24 +170,47 @@ fn invoke<'a>(
     main_result
 }
 
-fn extract_printed_string(vm: &Vm, index: usize) -> String {
-    let string = expect_concrete_object_at(&vm.printed, index)
-        .unwrap_or_else(|_| panic!("should have printed an object at position {index}"));
-    extract_str_from_java_lang_string(vm, &string).expect("should have a valid string")
+/// Like `invoke`, but first runs `System.initializeSystemClass` on the same thread, like
+/// the command line launcher does
+fn invoke_main_with_initialized_system_class<'a>(
+    vm: &mut Vm<'a>,
+    class_name: &str,
+) -> Result<Option<Value<'a>>, MethodCallFailed<'a>> {
+    let call_stack = vm.allocate_call_stack();
+    vm.initialize_system_class(call_stack)
+        .expect("should be able to initialize the system class");
+    let main_method = vm
+        .resolve_class_method(call_stack, class_name, "main", "([Ljava/lang/String;)V")
+        .expect("should find main method");
+    vm.invoke(call_stack, main_method, None, vec![])
+}
+
+fn extract_thrown_exception_class_name<'a>(
+    vm: &Vm<'a>,
+    result: Result<Option<Value<'a>>, MethodCallFailed<'a>>,
+) -> String {
+    match result {
+        Err(MethodCallFailed::ExceptionThrown(exception)) => vm
+            .get_class_by_id(exception.0.class_id())
+            .expect("exception should have a valid class")
+            .name
+            .clone(),
+        _ => panic!("should have thrown an exception, but got {result:?}"),
+    }
 }
 
 #[test_log::test]
 fn simple_main() {
-    let mut vm = create_base_vm(DEFAULT_MAX_MEMORY);
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
     let main_result = invoke(&mut vm, "rjvm/SimpleMain", "main", "([Ljava/lang/String;)V");
     assert_eq!(Ok(None), main_result);
 
-    assert_eq!(vec![Value::Int(3), Value::Int(6)], vm.printed);
+    assert_eq!(vec![Printed::Int(3), Printed::Int(6)], stdout.printed());
 }
 
 #[test_log::test]
 fn superclasses() {
-    let mut vm = create_base_vm(DEFAULT_MAX_MEMORY);
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
     let main_result = invoke(
         &mut vm,
         "rjvm/SuperClasses",
@@ -60,12 +219,12 @@ fn superclasses() {
     );
     assert_eq!(Ok(None), main_result);
 
-    assert_eq!(vec![Value::Int(4)], vm.printed);
+    assert_eq!(vec![Printed::Int(4)], stdout.printed());
 }
 
 #[test_log::test]
 fn control_flow() {
-    let mut vm = create_base_vm(DEFAULT_MAX_MEMORY);
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
     let main_result = invoke(
         &mut vm,
         "rjvm/ControlFlow",
@@ -77,25 +236,25 @@ fn control_flow() {
     assert_eq!(
         vec![
             // Ints
-            Value::Int(241),
+            Printed::Int(241),
             // Objects
-            Value::Int(42),
-            Value::Int(43),
+            Printed::Int(42),
+            Printed::Int(43),
             // Double
-            Value::Int(1),
-            Value::Int(1),
-            Value::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
             // Arrays
-            Value::Int(51),
-            Value::Int(52),
+            Printed::Int(51),
+            Printed::Int(52),
         ],
-        vm.printed
+        stdout.printed()
     );
 }
 
 #[test_log::test]
 fn numeric_types() {
-    let mut vm = create_base_vm(DEFAULT_MAX_MEMORY);
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
     let main_result = invoke(
         &mut vm,
         "rjvm/NumericTypes",
@@ -106,37 +265,37 @@ fn numeric_types() {
 
     assert_eq!(
         vec![
-            Value::Int(3),
-            Value::Float(3.45f32),
-            Value::Int(3),
-            Value::Long(3),
-            Value::Double(3.45f32 as f64),
-            Value::Long(2),
-            Value::Int(2),
-            Value::Float(2f32),
-            Value::Double(2f64),
-            Value::Double(4.45),
-            Value::Int(4),
-            Value::Float(4.45),
-            Value::Long(4),
-            Value::Int(-1),
-            Value::Long(-1),
-            Value::Float(-1f32),
-            Value::Double(-1f64),
-            Value::Int(1),
-            Value::Int(((-1i32) as u32 >> 2) as i32),
-            Value::Int(8),
-            Value::Long(1),
-            Value::Long(((-1i64) as u64 >> 2) as i64),
-            Value::Long(8),
+            Printed::Int(3),
+            Printed::Float(3.45f32),
+            Printed::Int(3),
+            Printed::Long(3),
+            Printed::Double(3.45f32 as f64),
+            Printed::Long(2),
+            Printed::Int(2),
+            Printed::Float(2f32),
+            Printed::Double(2f64),
+            Printed::Double(4.45),
+            Printed::Int(4),
+            Printed::Float(4.45),
+            Printed::Long(4),
+            Printed::Int(-1),
+            Printed::Long(-1),
+            Printed::Float(-1f32),
+            Printed::Double(-1f64),
+            Printed::Int(1),
+            Printed::Int(((-1i32) as u32 >> 2) as i32),
+            Printed::Int(8),
+            Printed::Long(1),
+            Printed::Long(((-1i64) as u64 >> 2) as i64),
+            Printed::Long(8),
         ],
-        vm.printed
+        stdout.printed()
     );
 }
 
 #[test_log::test]
 fn numeric_arrays() {
-    let mut vm = create_base_vm(DEFAULT_MAX_MEMORY);
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
     let main_result = invoke(
         &mut vm,
         "rjvm/NumericArrays",
@@ -147,37 +306,60 @@ fn numeric_arrays() {
 
     assert_eq!(
         vec![
-            Value::Int(1),
-            Value::Int(2),
-            Value::Int(0x03),
-            Value::Int(2),
-            Value::Int('b' as i32),
-            Value::Int(2),
-            Value::Int(-1),
-            Value::Int(2),
-            Value::Int(12),
-            Value::Int(2),
-            Value::Long(2),
-            Value::Int(2),
-            Value::Float(1.2f32 + 0.2f32),
-            Value::Int(2),
-            Value::Double(0f64),
-            Value::Int(2),
+            Printed::Int(1),
+            Printed::Int(2),
+            Printed::Int(0x03),
+            Printed::Int(2),
+            Printed::Int('b' as i32),
+            Printed::Int(2),
+            Printed::Int(-1),
+            Printed::Int(2),
+            Printed::Int(12),
+            Printed::Int(2),
+            Printed::Long(2),
+            Printed::Int(2),
+            Printed::Float(1.2f32 + 0.2f32),
+            Printed::Int(2),
+            Printed::Double(0f64),
+            Printed::Int(2),
             // Arraycopy
-            Value::Int(0),
-            Value::Int(2),
-            Value::Int(3),
-            Value::Int(4),
-            Value::Int(0),
-            Value::Int(5),
+            Printed::Int(0),
+            Printed::Int(2),
+            Printed::Int(3),
+            Printed::Int(4),
+            Printed::Int(0),
+            Printed::Int(5),
+        ],
+        stdout.printed()
+    );
+}
+
+#[test_log::test]
+fn small_constants() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/SmallConstants",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![
+            Printed::Int(-2),
+            Printed::Int(-128),
+            Printed::Int(127),
+            Printed::Int(3),
+            Printed::Int(-129),
+            Printed::Int(-32768),
         ],
-        vm.printed
+        stdout.printed()
     );
 }
 
 #[test_log::test]
 fn object_arrays() {
-    let mut vm = create_base_vm(DEFAULT_MAX_MEMORY);
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
     let main_result = invoke(
         &mut vm,
         "rjvm/ObjectArrays",
@@ -186,61 +368,81 @@ fn object_arrays() {
     );
     assert_eq!(Ok(None), main_result);
 
-    assert_eq!(vec![Value::Int(5),], vm.printed);
+    assert_eq!(vec![Printed::Int(5),], stdout.printed());
 }
 
 #[test_log::test]
 fn statics() {
-    let mut vm = create_base_vm(DEFAULT_MAX_MEMORY);
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
     let main_result = invoke(&mut vm, "rjvm/Statics", "main", "([Ljava/lang/String;)V");
     assert_eq!(Ok(None), main_result);
 
-    assert_eq!(vec![Value::Int(311), Value::Int(322),], vm.printed);
+    assert_eq!(
+        vec![Printed::Int(311), Printed::Int(322),],
+        stdout.printed()
+    );
+}
+
+#[test_log::test]
+fn statics_of_other_classes() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/ForeignStatics",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+
+    assert_eq!(
+        vec![Printed::Int(3), Printed::Int(7), Printed::Int(7)],
+        stdout.printed()
+    );
 }
 
 #[test_log::test]
 fn instance_of() {
-    let mut vm = create_base_vm(DEFAULT_MAX_MEMORY);
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
     let main_result = invoke(&mut vm, "rjvm/InstanceOf", "main", "([Ljava/lang/String;)V");
     assert_eq!(Ok(None), main_result);
 
     assert_eq!(
         vec![
-            Value::Int(1),
-            Value::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
             // C1
-            Value::Int(0),
-            Value::Int(0),
-            Value::Int(0),
-            Value::Int(0),
+            Printed::Int(0),
+            Printed::Int(0),
+            Printed::Int(0),
+            Printed::Int(0),
             // C2
-            Value::Int(1),
-            Value::Int(0),
-            Value::Int(0),
-            Value::Int(0),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(0),
+            Printed::Int(0),
             // C3
-            Value::Int(1),
-            Value::Int(1),
-            Value::Int(0),
-            Value::Int(0),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(0),
             // C4
-            Value::Int(0),
-            Value::Int(0),
-            Value::Int(1),
-            Value::Int(1),
+            Printed::Int(0),
+            Printed::Int(0),
+            Printed::Int(1),
+            Printed::Int(1),
             // C5
-            Value::Int(1),
-            Value::Int(0),
-            Value::Int(1),
-            Value::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(1),
+            Printed::Int(1),
         ],
-        vm.printed
+        stdout.printed()
     );
 }
 
 #[test_log::test]
 fn instance_of_array() {
-    let mut vm = create_base_vm(DEFAULT_MAX_MEMORY);
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
     let main_result = invoke(
         &mut vm,
         "rjvm/InstanceOfArray",
@@ -252,36 +454,36 @@ fn instance_of_array() {
     assert_eq!(
         vec![
             // C0
-            Value::Int(1),
-            Value::Int(1),
-            Value::Int(0),
-            Value::Int(0),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(0),
             // C1
-            Value::Int(1),
-            Value::Int(0),
-            Value::Int(1),
-            Value::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(1),
+            Printed::Int(1),
         ],
-        vm.printed
+        stdout.printed()
     );
 }
 
 #[test_log::test]
 fn strings() {
-    let mut vm = create_base_vm(DEFAULT_MAX_MEMORY);
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
     let main_result = invoke(&mut vm, "rjvm/Strings", "main", "([Ljava/lang/String;)V");
     assert_eq!(Ok(None), main_result);
 
-    assert_eq!(1, vm.printed.len());
+    assert_eq!(1, stdout.printed().len());
     assert_eq!(
         "Hello, Andrea, you were born in 1985",
-        extract_printed_string(&vm, 0)
+        stdout.printed_string(0)
     );
 }
 
 #[test_log::test]
 fn invoke_interface() {
-    let mut vm = create_base_vm(DEFAULT_MAX_MEMORY);
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
     let main_result = invoke(
         &mut vm,
         "rjvm/InvokeInterface",
@@ -291,23 +493,23 @@ fn invoke_interface() {
     assert_eq!(Ok(None), main_result);
 
     assert_eq!(
-        vec![Value::Int(12), Value::Int(4), Value::Int(10)],
-        vm.printed
+        vec![Printed::Int(12), Printed::Int(4), Printed::Int(10)],
+        stdout.printed()
     );
 }
 
 #[test_log::test]
 fn check_cast() {
-    let mut vm = create_base_vm(DEFAULT_MAX_MEMORY);
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
     let main_result = invoke(&mut vm, "rjvm/CheckCast", "main", "([Ljava/lang/String;)V");
     assert_eq!(Ok(None), main_result);
 
-    assert_eq!(vec![Value::Int(1)], vm.printed);
+    assert_eq!(vec![Printed::Int(1)], stdout.printed());
 }
 
 #[test_log::test]
 fn stack_trace_printing() {
-    let mut vm = create_base_vm(DEFAULT_MAX_MEMORY);
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
     let main_result = invoke(
         &mut vm,
         "rjvm/StackTracePrinting",
@@ -316,28 +518,28 @@ fn stack_trace_printing() {
     );
     assert_eq!(Ok(None), main_result);
 
-    assert_eq!(4, vm.printed.len());
+    assert_eq!(4, stdout.printed().len());
     assert_eq!(
         "java/lang/Throwable::fillInStackTrace - Throwable.java:783",
-        extract_printed_string(&vm, 0)
+        stdout.printed_string(0)
     );
     assert_eq!(
         "java/lang/Throwable::<init> - Throwable.java:250",
-        extract_printed_string(&vm, 1)
+        stdout.printed_string(1)
     );
     assert_eq!(
         "java/lang/Exception::<init> - Exception.java:55",
-        extract_printed_string(&vm, 2)
+        stdout.printed_string(2)
     );
     assert_eq!(
         "rjvm/StackTracePrinting::main - StackTracePrinting.java:5",
-        extract_printed_string(&vm, 3)
+        stdout.printed_string(3)
     );
 }
 
 #[test_log::test]
 fn exceptions_throwing_and_catching() {
-    let mut vm = create_base_vm(DEFAULT_MAX_MEMORY);
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
     let main_result: Result<Option<Value<'_>>, MethodCallFailed<'_>> = invoke(
         &mut vm,
         "rjvm/ExceptionsThrowingAndCatching",
@@ -348,19 +550,19 @@ fn exceptions_throwing_and_catching() {
 
     assert_eq!(
         vec![
-            Value::Int(1),
-            Value::Int(2),
-            Value::Int(3),
-            Value::Int(5),
-            Value::Int(6)
+            Printed::Int(1),
+            Printed::Int(2),
+            Printed::Int(3),
+            Printed::Int(5),
+            Printed::Int(6)
         ],
-        vm.printed
+        stdout.printed()
     );
 }
 
 #[test_log::test]
 fn gabarge_collector() {
-    let mut vm = create_base_vm(10_000_000);
+    let (mut vm, _) = create_base_vm(10_000_000);
     let main_result = invoke(
         &mut vm,
         "rjvm/GarbageCollection",
@@ -372,7 +574,1242 @@ fn gabarge_collector() {
 
 #[test_log::test]
 fn generic() {
-    let mut vm = create_base_vm(10_000_000);
+    let (mut vm, _) = create_base_vm(10_000_000);
     let main_result = invoke(&mut vm, "rjvm/Generic", "main", "([Ljava/lang/String;)V");
     assert_eq!(Ok(None), main_result);
 }
+
+#[test_log::test]
+fn switch() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(&mut vm, "rjvm/Switch", "main", "([Ljava/lang/String;)V");
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![
+            Printed::Int(19),
+            Printed::Int(10),
+            Printed::Int(11),
+            Printed::Int(12),
+            Printed::Int(12),
+            Printed::Int(19),
+            Printed::Int(20),
+            Printed::Int(21),
+            Printed::Int(22),
+            Printed::Int(29),
+            Printed::Int(30),
+            Printed::Int(31),
+            Printed::Int(39),
+            Printed::Int(41),
+            Printed::Int(42),
+            Printed::Int(49),
+        ],
+        stdout.printed()
+    );
+}
+
+#[test_log::test]
+fn lambdas() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(&mut vm, "rjvm/Lambdas", "main", "([Ljava/lang/String;)V");
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![
+            Printed::Int(7),
+            Printed::Int(16),
+            Printed::Int(105),
+            Printed::Int(300),
+            Printed::Int(42),
+            Printed::Int(5),
+            Printed::Int(7),
+            Printed::Int(13),
+            Printed::Int(1),
+            Printed::Int(2),
+            Printed::Int(3),
+        ],
+        stdout.printed()
+    );
+}
+
+#[test_log::test]
+fn lambda_call_sites_at_the_same_address_of_different_methods() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/LambdaCallSites",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![Printed::Int(1), Printed::Int(2), Printed::Int(1)],
+        stdout.printed()
+    );
+}
+
+#[test_log::test]
+fn string_concat() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/modern/StringConcat",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(7, stdout.printed().len());
+    assert_eq!("Hello, rjvm!", stdout.printed_string(0));
+    assert_eq!("-42 12345678901 c true 3 -7", stdout.printed_string(1));
+    assert_eq!("null is null, point is (1, 2)", stdout.printed_string(2));
+    assert_eq!("constant:-42", stdout.printed_string(3));
+    assert_eq!(
+        "\u{1} and \u{2} are not tags in literals: rjvm",
+        stdout.printed_string(4)
+    );
+    assert_eq!("cc", stdout.printed_string(5));
+    assert_eq!("01234", stdout.printed_string(6));
+}
+
+#[test_log::test]
+fn multi_dimensional_arrays() {
+    let (mut vm, stdout) = create_base_vm(1_000_000);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/MultiDimensionalArrays",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![
+            Printed::Int(3),
+            Printed::Int(4),
+            Printed::Int(18),
+            Printed::Long(1234567890123),
+            Printed::Int(4),
+            Printed::Int(1),
+            Printed::Int(3),
+            Printed::Int(1),
+            Printed::Int('x' as i32),
+            Printed::Int(2),
+            Printed::Int(1),
+            Printed::Int(5),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(1),
+            Printed::Int(2),
+            Printed::Int(3),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(4),
+            Printed::Int(1),
+            Printed::Int(45),
+        ],
+        stdout.printed()
+    );
+}
+
+#[test_log::test]
+fn multi_dimensional_arrays_negative_size() {
+    let (mut vm, _) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/MultiDimensionalArrays",
+        "negativeSize",
+        "()V",
+    );
+    assert_eq!(
+        "java/lang/NegativeArraySizeException",
+        extract_thrown_exception_class_name(&vm, main_result)
+    );
+}
+
+#[test_log::test]
+fn legacy_subroutines() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/LegacySubroutines",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![
+            Printed::Int(11),
+            Printed::Int(12),
+            Printed::Int(12),
+            Printed::Int(21),
+            Printed::Int(22),
+            Printed::Int(22),
+            Printed::Int(42),
+        ],
+        stdout.printed()
+    );
+}
+
+#[test_log::test]
+fn runtime_exceptions() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/RuntimeExceptions",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(
+        "java/lang/ArithmeticException",
+        extract_thrown_exception_class_name(&vm, main_result)
+    );
+
+    let printed: Vec<String> = (0..stdout.printed().len())
+        .map(|index| stdout.printed_string(index))
+        .collect();
+    assert_eq!(
+        vec![
+            "npe on getfield, message null",
+            "npe on invoke",
+            "Index 5 out of bounds for length 3",
+            "Index -1 out of bounds for length 3",
+            "arraycopy Index 4 out of bounds for length 3",
+            "java.lang.String cannot be cast to java.lang.Integer",
+            "-2",
+            "/ by zero",
+            "divide:74",
+            "main:56",
+            "finally",
+        ],
+        printed
+    );
+}
+
+#[test_log::test]
+fn stack_overflow() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/StackOverflow",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+
+    // main is the first frame
+    let max_recursion_depth = DEFAULT_MAX_STACK_DEPTH as i32 - 1;
+    assert_eq!(
+        vec![
+            Printed::Int(12502500),
+            Printed::Int(max_recursion_depth),
+            Printed::Int(max_recursion_depth),
+            Printed::Int(12502500),
+        ],
+        stdout.printed()
+    );
+}
+
+#[test_log::test]
+fn stack_overflow_uncaught() {
+    let (mut vm, _) = create_base_vm_with_maximum_stack_depth(DEFAULT_MAX_MEMORY, 100);
+    let main_result = invoke(&mut vm, "rjvm/StackOverflow", "overflow", "()V");
+    assert_eq!(
+        "java/lang/StackOverflowError",
+        extract_thrown_exception_class_name(&vm, main_result)
+    );
+}
+
+#[test_log::test]
+fn hot_loops_dispatch_on_decoded_instructions() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(&mut vm, "rjvm/HotLoops", "main", "([Ljava/lang/String;)V");
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(vec![Printed::Int(-1795017296)], stdout.printed());
+
+    // The code of main is decoded on its first call and then reused
+    let class = vm
+        .find_class_by_name("rjvm/HotLoops")
+        .expect("class should be loaded");
+    let main_method = class
+        .find_class_and_method("main", "([Ljava/lang/String;)V")
+        .expect("should find main method");
+    let decoded_code = class
+        .decoded_code(main_method.method_index)
+        .expect("should decode the method");
+    assert!(std::ptr::eq(
+        decoded_code,
+        class.decoded_code(main_method.method_index).unwrap()
+    ));
+
+    // The decoded instructions are the ones found by parsing the bytecode, except that the
+    // jump targets are instruction indexes rather than addresses
+    let code = &main_method.method.code.as_ref().unwrap().code;
+    let mut address = 0;
+    let mut index = 0;
+    while address < code.len() {
+        let (instruction, next_address) =
+            Instruction::parse(code, address).expect("should parse the instruction");
+        let decoded_instruction = decoded_code
+            .instruction(index)
+            .expect("should have decoded the instruction");
+        assert_eq!(
+            std::mem::discriminant(&instruction),
+            std::mem::discriminant(decoded_instruction)
+        );
+        assert_eq!(address, decoded_code.address(index).0 as usize);
+        address = next_address;
+        index += 1;
+    }
+    assert_eq!(index, decoded_code.len());
+}
+
+/// Compares the time taken by the hot loops when the interpreter dispatches on the decoded
+/// instructions and when it parses each instruction again, as it used to.
+/// Run with `cargo test --release -- --ignored --nocapture hot_loops_speedup`.
+#[test]
+#[ignore]
+fn hot_loops_speedup() {
+    fn fastest_run(vm: &mut Vm, parse_every_step: bool) -> Duration {
+        vm.set_parse_every_step(parse_every_step);
+        (0..5)
+            .map(|_| {
+                let start = Instant::now();
+                let main_result = invoke(vm, "rjvm/HotLoops", "main", "([Ljava/lang/String;)V");
+                let elapsed = start.elapsed();
+                assert_eq!(Ok(None), main_result);
+                elapsed
+            })
+            .min()
+            .expect("should have run at least once")
+    }
+
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let parsing = fastest_run(&mut vm, true);
+    let decoded = fastest_run(&mut vm, false);
+    println!(
+        "parsing every step: {parsing:?}, decoded: {decoded:?}, speedup: {:.2}x",
+        parsing.as_secs_f64() / decoded.as_secs_f64()
+    );
+    assert!(stdout
+        .printed()
+        .iter()
+        .all(|value| *value == Printed::Int(-1795017296)));
+    assert!(decoded < parsing);
+}
+
+#[test_log::test]
+fn field_resolution() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/FieldResolution",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![
+            Printed::Int(1),
+            Printed::Int(2),
+            Printed::Int(1),
+            Printed::Int(10),
+            Printed::Int(1),
+            Printed::Int(2),
+            Printed::Int(1),
+            Printed::Int(20),
+        ],
+        stdout.printed()
+    );
+}
+
+#[test_log::test]
+fn virtual_dispatch() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/VirtualDispatch",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(5, stdout.printed().len());
+    assert_eq!(Printed::Int(693), stdout.printed()[0]);
+    assert_eq!("square", stdout.printed_string(1));
+    assert_eq!("circle", stdout.printed_string(2));
+    assert_eq!("colored square", stdout.printed_string(3));
+    assert_eq!(Printed::Int(3), stdout.printed()[4]);
+}
+
+#[test_log::test]
+fn super_and_virtual_calls_through_the_same_method_reference() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/SuperAndVirtualCalls",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![
+            Printed::Int(111),
+            Printed::Int(111),
+            Printed::Int(1011),
+            Printed::Int(1011)
+        ],
+        stdout.printed()
+    );
+}
+
+#[test_log::test]
+fn interface_methods() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/modern/InterfaceMethods",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    let printed: Vec<String> = (0..stdout.printed().len())
+        .map(|index| stdout.printed_string(index))
+        .collect();
+    assert_eq!(
+        vec![
+            "good morning",
+            "good morning",
+            "hi, and good morning",
+            "hello from greeter",
+            "good morning",
+            "hello from greeter",
+            "hey!",
+            "leaf of base",
+            "static of base",
+            "Conflicting default methods: greet()Ljava/lang/String;",
+            "rjvm.modern.EvolvingShape.area()I",
+            "Class rjvm.modern.EvolvingCircle does not implement the requested interface rjvm.modern.EvolvingShape",
+            "Expected static method rjvm.modern.EvolvingUtility.value()I",
+        ],
+        printed
+    );
+}
+
+#[test_log::test]
+fn lazy_initialization() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/LazyInitialization",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(15, stdout.printed().len());
+    assert_eq!("not yet", stdout.printed_string(0));
+    assert_eq!("Referenced", stdout.printed_string(1));
+    assert_eq!(Printed::Int(1), stdout.printed()[2]);
+    assert_eq!("Parent", stdout.printed_string(3));
+    assert_eq!(Printed::Int(10), stdout.printed()[4]);
+    assert_eq!("Child", stdout.printed_string(5));
+    assert_eq!("WithDefault", stdout.printed_string(6));
+    assert_eq!(Printed::Int(2), stdout.printed()[7]);
+    assert_eq!("WithoutDefault", stdout.printed_string(8));
+    assert_eq!(Printed::Int(14), stdout.printed()[9]);
+    assert_eq!("caused by boom", stdout.printed_string(10));
+    assert_eq!(
+        "Could not initialize class rjvm.LazyInitialization$Failing",
+        stdout.printed_string(11)
+    );
+    assert_eq!("errors are not wrapped", stdout.printed_string(12));
+    assert_eq!(Printed::Int(11), stdout.printed()[13]);
+    assert_eq!(Printed::Int(10), stdout.printed()[14]);
+}
+
+#[test_log::test]
+fn constant_values() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/modern/ConstantValues",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![
+            Printed::Int(42),
+            Printed::Long(1 << 40),
+            Printed::Float(1.5),
+            Printed::Double(2.25),
+            Printed::Int(1),
+            Printed::Int('x' as i32),
+        ],
+        stdout.printed()[0..6]
+    );
+    assert_eq!("constant", stdout.printed_string(6));
+    assert_eq!(Printed::Int(1), stdout.printed()[7]);
+}
+
+#[test_log::test]
+fn string_interning() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/StringInterning",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+        ],
+        stdout.printed()
+    );
+}
+
+#[test_log::test]
+fn synchronization() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/Synchronization",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![
+            Printed::Int(3),
+            Printed::Int(4),
+            Printed::Int(1),
+            Printed::Int(2)
+        ],
+        stdout.printed()[0..4]
+    );
+    assert_eq!("thrown inside block", stdout.printed_string(4));
+    assert_eq!(Printed::Int(5), stdout.printed()[5]);
+    assert_eq!("thrown from synchronized method", stdout.printed_string(6));
+    assert_eq!(Printed::Int(6), stdout.printed()[7]);
+    assert_eq!("cannot synchronize on null", stdout.printed_string(8));
+}
+
+#[test_log::test]
+fn unbalanced_monitors() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/UnbalancedMonitors",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![
+            Printed::Int(1),
+            Printed::Int(2),
+            Printed::Int(3),
+            Printed::Int(4),
+            Printed::Int(5),
+            Printed::Int(6),
+        ],
+        stdout.printed()
+    );
+}
+
+#[test_log::test]
+fn threads() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    vm.set_preemption_interval(50);
+    let main_result = invoke(&mut vm, "rjvm/Threads", "main", "([Ljava/lang/String;)V");
+    assert_eq!(Ok(None), main_result);
+    assert_eq!("main", stdout.printed_string(0));
+    assert_eq!(
+        vec![
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(3000),
+            Printed::Int(0)
+        ],
+        stdout.printed()[1..6]
+    );
+    assert_eq!(
+        vec![Printed::Int(1), Printed::Int(1)],
+        stdout.printed()[6..8]
+    );
+    assert_eq!("worker", stdout.printed_string(8));
+    assert_eq!(
+        vec![
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(0)
+        ],
+        stdout.printed()[9..13]
+    );
+    assert_eq!("failing", stdout.printed_string(13));
+    assert_eq!("uncaught", stdout.printed_string(14));
+    assert_eq!(Printed::Int(0), stdout.printed()[15]);
+    assert_eq!("timeout value is negative", stdout.printed_string(16));
+    assert_eq!("cannot start twice", stdout.printed_string(17));
+}
+
+#[test_log::test]
+fn wait_notify() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(&mut vm, "rjvm/WaitNotify", "main", "([Ljava/lang/String;)V");
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![
+            Printed::Int(5050),
+            Printed::Int(1),
+            Printed::Int(3),
+            Printed::Int(1)
+        ],
+        stdout.printed()[0..4]
+    );
+    assert_eq!("sleep interrupted", stdout.printed_string(4));
+    assert_eq!(
+        vec![
+            Printed::Int(0),
+            Printed::Int(0),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(0)
+        ],
+        stdout.printed()[5..11]
+    );
+    assert_eq!("sleep interrupted", stdout.printed_string(11));
+    assert_eq!(Printed::Int(0), stdout.printed()[12]);
+    assert_eq!("wait requires the monitor", stdout.printed_string(13));
+    assert_eq!("notify requires the monitor", stdout.printed_string(14));
+    assert_eq!("notifyAll requires the monitor", stdout.printed_string(15));
+    assert_eq!("timeout value is negative", stdout.printed_string(16));
+}
+
+#[test_log::test]
+fn deadlock() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(&mut vm, "rjvm/Deadlock", "main", "([Ljava/lang/String;)V");
+    assert_eq!(
+        Err(MethodCallFailed::InternalError(VmError::Deadlock(vec![
+            "thread 1 \"main\" is blocked on java/lang/Object owned by thread 2 \"other\""
+                .to_string(),
+            "thread 2 \"other\" is blocked on java/lang/Object owned by thread 1 \"main\""
+                .to_string(),
+        ]))),
+        main_result
+    );
+    assert!(stdout.printed().is_empty());
+}
+
+#[test_log::test]
+fn synchronized_natives() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/SynchronizedNatives",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!("from blocked thread", stdout.printed_string(0));
+}
+
+#[test_log::test]
+fn class_initialization_by_two_threads() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    vm.set_preemption_interval(50);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/ClassInitializationByTwoThreads",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(vec![Printed::Int(42042)], stdout.printed());
+}
+
+#[test_log::test]
+fn system_out() {
+    let stdout = CapturedOutput::default();
+    let stderr = CapturedOutput::default();
+    let standard_streams = StandardStreams::new(
+        Box::new("echo\nfrom stdin\n".as_bytes()),
+        Box::new(stdout.clone()),
+        Box::new(stderr.clone()),
+    );
+    let mut vm = create_vm_with_standard_streams(
+        DEFAULT_MAX_MEMORY,
+        DEFAULT_MAX_STACK_DEPTH,
+        standard_streams,
+    );
+    let main_result = invoke_main_with_initialized_system_class(&mut vm, "rjvm/SystemOut");
+    assert_eq!(Ok(None), main_result);
+    assert_eq!("to stderr\n", stderr.contents());
+    // What System.out writes, followed by what tempPrint writes
+    let lines = stdout.lines();
+    assert_eq!(
+        vec!["Hello, world!", "42", "x", "echo", "from stdin"],
+        lines[0..5]
+    );
+    let printed: Vec<Printed> = lines[5..].iter().map(|line| Printed::parse(line)).collect();
+    let line_separator_length = if cfg!(windows) { 2 } else { 1 };
+    assert_eq!(
+        vec![
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(line_separator_length),
+            Printed::Int(1),
+            Printed::String("UTF-8".to_string()),
+            Printed::String("1.8".to_string()),
+            Printed::String("default".to_string()),
+            Printed::Int(1),
+        ],
+        printed
+    );
+}
+
+#[test_log::test]
+fn privileged_actions() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/PrivilegedActions",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![
+            "action",
+            "exception action",
+            "with context",
+            "checked",
+            "unchecked"
+        ],
+        (0..5)
+            .map(|index| stdout.printed_string(index))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test_log::test]
+fn files() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke_main_with_initialized_system_class(&mut vm, "rjvm/Files");
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Long(12),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(12),
+            Printed::Int(104),
+            Printed::Long(5)
+        ],
+        stdout.printed()[0..9]
+    );
+    assert_eq!("world\n", stdout.printed_string(9));
+    assert_eq!(Printed::Int(-1), stdout.printed()[10]);
+    assert_eq!("Stream Closed", stdout.printed_string(11));
+    assert_eq!(
+        vec![Printed::Long(13), Printed::Int(119), Printed::Long(7)],
+        stdout.printed()[12..15]
+    );
+    assert_eq!("hello\nWorld\n!", stdout.printed_string(15));
+    assert_eq!(
+        vec![Printed::Long(5), Printed::Long(5), Printed::Int(1)],
+        stdout.printed()[16..19]
+    );
+    assert_eq!("data.txt", stdout.printed_string(19));
+    assert_eq!(
+        vec![
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(1)
+        ],
+        stdout.printed()[20..]
+    );
+}
+
+#[test_log::test]
+fn comparisons_and_overflows() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/ComparisonsAndOverflows",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    // NaN compared with 1.0f by >, >=, <, <=, ==, and != with 1.0f then with itself
+    assert_eq!(
+        vec![
+            Printed::Int(0),
+            Printed::Int(0),
+            Printed::Int(0),
+            Printed::Int(0),
+            Printed::Int(0),
+            Printed::Int(1),
+            Printed::Int(1)
+        ],
+        stdout.printed()[0..7]
+    );
+    // 2.0f compared with 1.0f
+    assert_eq!(
+        vec![
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(0),
+            Printed::Int(0),
+            Printed::Int(1),
+            Printed::Int(0)
+        ],
+        stdout.printed()[7..14]
+    );
+    // NaN compared with 1.0
+    assert_eq!(
+        vec![
+            Printed::Int(0),
+            Printed::Int(0),
+            Printed::Int(0),
+            Printed::Int(0),
+            Printed::Int(0),
+            Printed::Int(1),
+            Printed::Int(1)
+        ],
+        stdout.printed()[14..21]
+    );
+    // 1.0 compared with 2.0
+    assert_eq!(
+        vec![
+            Printed::Int(0),
+            Printed::Int(0),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(1),
+            Printed::Int(0)
+        ],
+        stdout.printed()[21..28]
+    );
+    // Long.MIN_VALUE compared with Long.MAX_VALUE
+    assert_eq!(
+        vec![Printed::Int(0), Printed::Int(1), Printed::Int(1)],
+        stdout.printed()[28..31]
+    );
+    // Long.MIN_VALUE / -1, Long.MIN_VALUE % -1, Long.MAX_VALUE + 1, Long.MIN_VALUE - 1 and
+    // Long.MAX_VALUE * 2
+    assert_eq!(
+        vec![
+            Printed::Long(i64::MIN),
+            Printed::Long(0),
+            Printed::Long(i64::MIN),
+            Printed::Long(i64::MAX),
+            Printed::Long(-2)
+        ],
+        stdout.printed()[31..36]
+    );
+    // -Integer.MIN_VALUE and -Long.MIN_VALUE
+    assert_eq!(
+        vec![Printed::Int(i32::MIN), Printed::Long(i64::MIN)],
+        stdout.printed()[36..38]
+    );
+    // 1L << 32, 1L << 40, -1L >>> 40, Long.MIN_VALUE >> 40, Long.MIN_VALUE >>> 32 and
+    // 1L << 104
+    assert_eq!(
+        vec![
+            Printed::Long(1 << 32),
+            Printed::Long(1 << 40),
+            Printed::Long(16777215),
+            Printed::Long(-8388608),
+            Printed::Long(2147483648),
+            Printed::Long(1 << 40)
+        ],
+        stdout.printed()[38..]
+    );
+}
+
+#[test_log::test]
+fn floating_point_math() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/FloatingPointMath",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![
+            Printed::Double(0.8414709848078965),
+            Printed::Double(-1.0),
+            Printed::Double(std::f64::consts::SQRT_2),
+            Printed::Double(1024.0),
+            Printed::Double(2.7182818284590455),
+            Printed::Double(1.0),
+            Printed::Double(std::f64::consts::FRAC_PI_4),
+            Printed::Double(-3.0),
+            Printed::Double(5.0),
+            Printed::Double(1.0),
+            Printed::Double(3.0),
+            Printed::Int(1),
+            Printed::Double(f64::INFINITY),
+            Printed::Double(-0.8522008497671888),
+            Printed::Long(9221120237041090851),
+            Printed::Long(9221120237041090560),
+            Printed::Double(1.5),
+            Printed::Long(i64::MIN),
+            Printed::Int(2143289635),
+            Printed::Int(2143289344),
+            Printed::Float(std::f32::consts::PI),
+            Printed::Int(1)
+        ],
+        stdout.printed()[0..22]
+    );
+    // Comparisons, including the unordered ones with NaN
+    assert_eq!(
+        vec![
+            Printed::Int(5),
+            Printed::Int(8),
+            Printed::Int(5),
+            Printed::Int(4)
+        ],
+        stdout.printed()[22..26]
+    );
+    // Checksums of the results of each StrictMath function on a thousand arguments
+    assert_eq!(
+        vec![
+            Printed::Long(-2334027130399851373),
+            Printed::Long(3518438762666998498),
+            Printed::Long(-8039268595454304980),
+            Printed::Long(-5353544544777796134),
+            Printed::Long(3301613162047605059),
+            Printed::Long(5449364087752916009),
+            Printed::Long(6847717640735134458),
+            Printed::Long(-3775989064875503465),
+            Printed::Long(-33939942539943869),
+            Printed::Long(-3175697696772503138),
+            Printed::Long(-4869391600042760707),
+            Printed::Long(4796337969549549020),
+            Printed::Long(1982458838023807833),
+            Printed::Long(-2326569743888729653),
+            Printed::Long(-2986780635795056725),
+            Printed::Long(6662934768556697938),
+            Printed::Long(4501483943564657923),
+            Printed::Long(4584063514917402025),
+            Printed::Long(2770847871474090742),
+            Printed::Long(2162480007511723316)
+        ],
+        stdout.printed()[26..]
+    );
+}
+
+#[test]
+fn reflection() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(&mut vm, "rjvm/Reflection", "main", "([Ljava/lang/String;)V");
+    assert_eq!(Ok(None), main_result);
+    assert_eq!("rjvm.Reflection$Lazy", stdout.printed_string(0));
+    assert_eq!(
+        vec![Printed::Int(0), Printed::Int(1)],
+        stdout.printed()[1..3]
+    );
+    assert_eq!(
+        vec![
+            "rjvm.Missing",
+            "x int 0",
+            "y long 4",
+            "label java.lang.String 1",
+            "count int 8",
+            "ratio double 26"
+        ],
+        (3..9)
+            .map(|index| stdout.printed_string(index))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![Printed::Int(3), Printed::Int(1)],
+        stdout.printed()[9..11]
+    );
+    assert_eq!(
+        vec!["sum", "long", "int", "java.lang.IllegalStateException"],
+        (11..15)
+            .map(|index| stdout.printed_string(index))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![
+            Printed::Int(2),
+            Printed::Int(2),
+            Printed::Int(1),
+            Printed::Int(42),
+            Printed::Int(43),
+            Printed::Long(2),
+            Printed::Long(1099511627776)
+        ],
+        stdout.printed()[15..22]
+    );
+    assert_eq!(
+        vec!["a", "b"],
+        (22..24)
+            .map(|index| stdout.printed_string(index))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![
+            Printed::Int(1),
+            Printed::Int(3),
+            Printed::Int(9),
+            Printed::Int(1),
+            Printed::Long(8)
+        ],
+        stdout.printed()[24..29]
+    );
+    assert_eq!(
+        vec!["shape", "circle"],
+        (29..31)
+            .map(|index| stdout.printed_string(index))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![
+            Printed::Long(42),
+            Printed::Int(1),
+            Printed::Int(114),
+            Printed::Int(1),
+            Printed::Int(0)
+        ],
+        stdout.printed()[31..36]
+    );
+    assert_eq!(
+        vec![
+            "boom",
+            "wrong number of arguments",
+            "argument type mismatch",
+            "not an instance",
+            "null receiver"
+        ],
+        (36..41)
+            .map(|index| stdout.printed_string(index))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![Printed::Int(3), Printed::Long(4)],
+        stdout.printed()[41..43]
+    );
+    assert_eq!("c", stdout.printed_string(43));
+    assert_eq!(Printed::Int(17), stdout.printed()[44]);
+    assert_eq!(
+        vec!["no", "abstract"],
+        (45..47)
+            .map(|index| stdout.printed_string(index))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn class_mirrors() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/ClassMirrors",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1)
+        ],
+        stdout.printed()[0..8]
+    );
+    assert_eq!(
+        vec![
+            "rjvm.ClassMirrors 0 0 0",
+            "rjvm.MirroredDrawable 0 0 1",
+            "int 0 1 0",
+            "void 0 1 0",
+            "[[J 1 0 0",
+            "[Ljava.lang.String; 1 0 0"
+        ],
+        (8..14)
+            .map(|index| stdout.printed_string(index))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1)
+        ],
+        stdout.printed()[14..24]
+    );
+    assert_eq!(
+        vec![
+            "rjvm.MirroredSquare: java.lang.Runnable java.io.Serializable",
+            "rjvm.MirroredDrawable: java.lang.Runnable",
+            "[I: java.lang.Cloneable java.io.Serializable",
+            "rjvm.MirroredShape:",
+            "int:"
+        ],
+        (24..29)
+            .map(|index| stdout.printed_string(index))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(0),
+            Printed::Int(0),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(1),
+            Printed::Int(0),
+            Printed::Int(0)
+        ],
+        stdout.printed()[29..51]
+    );
+    assert_eq!("null class", stdout.printed_string(51));
+    assert_eq!(
+        vec![
+            Printed::Int(1),
+            Printed::Int(1024),
+            Printed::Int(16),
+            Printed::Int(1536),
+            Printed::Int(1537),
+            Printed::Int(1041),
+            Printed::Int(1041),
+            Printed::Int(1040)
+        ],
+        stdout.printed()[52..60]
+    );
+    assert_eq!(
+        vec![
+            "static synchronized method",
+            "synchronized on the class literal"
+        ],
+        (60..62)
+            .map(|index| stdout.printed_string(index))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(Printed::Int(1), stdout.printed()[62]);
+    assert_eq!("rjvm.MirroredSquare", stdout.printed_string(63));
+}
+
+#[test]
+fn object_methods() {
+    let (mut vm, stdout) = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/ObjectMethods",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(5),
+            Printed::Int(1),
+            Printed::Long(1099511627776)
+        ],
+        stdout.printed()[0..5]
+    );
+    assert_eq!("a", stdout.printed_string(5));
+    assert_eq!(
+        vec![
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(3)
+        ],
+        stdout.printed()[6..10]
+    );
+    assert_eq!(
+        vec!["rjvm.ObjectMethods$Plain", "monitor not copied"],
+        (10..12)
+            .map(|index| stdout.printed_string(index))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1),
+            Printed::Int(1)
+        ],
+        stdout.printed()[12..]
+    );
+}
+
+#[test]
+fn array_clones_with_little_memory() {
+    let (mut vm, stdout) = create_base_vm(1_000_000);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/ArrayClones",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+    assert_eq!(
+        vec![Printed::Long(247500), Printed::Int(20000)],
+        stdout.printed()
+    );
+}
@@ -1,2 +1,7 @@
 #!/usr/bin/env sh
-javac -source 6 -target 6 rjvm/*.java
+javac -source 8 -target 8 rjvm/*.java
+javac --release 17 rjvm/modern/*.java
+# Replaces some classes compiled above, to simulate incompatible changes of their sources
+javac --release 17 -d . rjvm/modern/evolved/*.java
+java --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED generators/LegacySubroutines.java
+java --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED generators/UnbalancedMonitors.java
@@ -0,0 +1,117 @@
+import java.nio.file.Files;
+import java.nio.file.Paths;
+
+import jdk.internal.org.objectweb.asm.ClassWriter;
+import jdk.internal.org.objectweb.asm.Label;
+import jdk.internal.org.objectweb.asm.MethodVisitor;
+
+import static jdk.internal.org.objectweb.asm.Opcodes.*;
+
+/**
+ * Generates rjvm/LegacySubroutines.class, a Java 1.4 class using jsr/ret, goto_w and jsr_w.
+ * Modern versions of javac do not emit these instructions anymore, so we write the bytecode
+ * directly with the copy of ASM bundled in the JDK.
+ */
+public class LegacySubroutines {
+    // Not exposed by ASM, but accepted by visitJumpInsn
+    private static final int GOTO_W = 200;
+    private static final int JSR_W = 201;
+
+    private static final String CLASS_NAME = "rjvm/LegacySubroutines";
+
+    public static void main(String[] args) throws Exception {
+        ClassWriter cw = new ClassWriter(0);
+        cw.visit(V1_4, ACC_PUBLIC | ACC_SUPER, CLASS_NAME, null, "java/lang/Object", null);
+
+        cw.visitMethod(ACC_PRIVATE | ACC_STATIC | ACC_NATIVE, "tempPrint", "(I)V", null, null)
+                .visitEnd();
+        generateMain(cw);
+        generateWithFinally(cw);
+        generateWideLocals(cw);
+
+        cw.visitEnd();
+        Files.write(Paths.get(CLASS_NAME + ".class"), cw.toByteArray());
+    }
+
+    // public static void main(String[] args) {
+    //     tempPrint(withFinally(5));
+    //     tempPrint(withFinally(10));
+    //     goto_w skip;
+    //     tempPrint(-1);
+    //   skip:
+    //     tempPrint(wideLocals());
+    // }
+    private static void generateMain(ClassWriter cw) {
+        MethodVisitor mv = cw.visitMethod(ACC_PUBLIC | ACC_STATIC, "main", "([Ljava/lang/String;)V", null, null);
+        mv.visitCode();
+        mv.visitInsn(ICONST_5);
+        mv.visitMethodInsn(INVOKESTATIC, CLASS_NAME, "withFinally", "(I)I", false);
+        mv.visitMethodInsn(INVOKESTATIC, CLASS_NAME, "tempPrint", "(I)V", false);
+        mv.visitIntInsn(BIPUSH, 10);
+        mv.visitMethodInsn(INVOKESTATIC, CLASS_NAME, "withFinally", "(I)I", false);
+        mv.visitMethodInsn(INVOKESTATIC, CLASS_NAME, "tempPrint", "(I)V", false);
+
+        Label skip = new Label();
+        mv.visitJumpInsn(GOTO_W, skip);
+        mv.visitInsn(ICONST_M1);
+        mv.visitMethodInsn(INVOKESTATIC, CLASS_NAME, "tempPrint", "(I)V", false);
+        mv.visitLabel(skip);
+
+        mv.visitMethodInsn(INVOKESTATIC, CLASS_NAME, "wideLocals", "()I", false);
+        mv.visitMethodInsn(INVOKESTATIC, CLASS_NAME, "tempPrint", "(I)V", false);
+        mv.visitInsn(RETURN);
+        mv.visitMaxs(1, 1);
+        mv.visitEnd();
+    }
+
+    // Like the code generated by old javac for:
+    //   int result = x * 2;
+    //   try {
+    //       return result;
+    //   } finally {
+    //       result++;
+    //       tempPrint(result);
+    //   }
+    // except that the finally block is invoked twice, once via jsr and once via jsr_w
+    private static void generateWithFinally(ClassWriter cw) {
+        MethodVisitor mv = cw.visitMethod(ACC_STATIC, "withFinally", "(I)I", null, null);
+        mv.visitCode();
+        Label finallyBlock = new Label();
+        mv.visitVarInsn(ILOAD, 0);
+        mv.visitInsn(ICONST_2);
+        mv.visitInsn(IMUL);
+        mv.visitVarInsn(ISTORE, 1);
+        mv.visitJumpInsn(JSR, finallyBlock);
+        mv.visitJumpInsn(JSR_W, finallyBlock);
+        mv.visitVarInsn(ILOAD, 1);
+        mv.visitInsn(IRETURN);
+
+        mv.visitLabel(finallyBlock);
+        mv.visitVarInsn(ASTORE, 2);
+        mv.visitIincInsn(1, 1);
+        mv.visitVarInsn(ILOAD, 1);
+        mv.visitMethodInsn(INVOKESTATIC, CLASS_NAME, "tempPrint", "(I)V", false);
+        mv.visitVarInsn(RET, 2);
+        mv.visitMaxs(2, 3);
+        mv.visitEnd();
+    }
+
+    // A subroutine whose return address is kept in a local that requires wide astore and ret
+    private static void generateWideLocals(ClassWriter cw) {
+        MethodVisitor mv = cw.visitMethod(ACC_STATIC, "wideLocals", "()I", null, null);
+        mv.visitCode();
+        Label subroutine = new Label();
+        mv.visitIntInsn(BIPUSH, 7);
+        mv.visitVarInsn(ISTORE, 300);
+        mv.visitJumpInsn(JSR, subroutine);
+        mv.visitVarInsn(ILOAD, 300);
+        mv.visitInsn(IRETURN);
+
+        mv.visitLabel(subroutine);
+        mv.visitVarInsn(ASTORE, 301);
+        mv.visitIincInsn(300, 35);
+        mv.visitVarInsn(RET, 301);
+        mv.visitMaxs(1, 302);
+        mv.visitEnd();
+    }
+}
@@ -0,0 +1,178 @@
+import java.nio.file.Files;
+import java.nio.file.Paths;
+
+import jdk.internal.org.objectweb.asm.ClassWriter;
+import jdk.internal.org.objectweb.asm.Label;
+import jdk.internal.org.objectweb.asm.MethodVisitor;
+
+import static jdk.internal.org.objectweb.asm.Opcodes.*;
+
+/**
+ * Generates rjvm/UnbalancedMonitors.class, which exits monitors that it does not own.
+ * javac always pairs monitorenter and monitorexit, so we write the bytecode directly with
+ * the copy of ASM bundled in the JDK.
+ */
+public class UnbalancedMonitors {
+    private static final String CLASS_NAME = "rjvm/UnbalancedMonitors";
+    private static final String ILLEGAL_MONITOR_STATE = "java/lang/IllegalMonitorStateException";
+
+    public static void main(String[] args) throws Exception {
+        ClassWriter cw = new ClassWriter(0);
+        cw.visit(V1_4, ACC_PUBLIC | ACC_SUPER, CLASS_NAME, null, "java/lang/Object", null);
+
+        cw.visitMethod(ACC_PRIVATE | ACC_STATIC | ACC_NATIVE, "tempPrint", "(I)V", null, null)
+                .visitEnd();
+        generateConstructor(cw);
+        generateMain(cw);
+        generateExitWithoutEnter(cw);
+        generateExitInSynchronizedMethod(cw);
+        generateRecursiveEntries(cw);
+        generateReleasedOnException(cw);
+
+        cw.visitEnd();
+        Files.write(Paths.get(CLASS_NAME + ".class"), cw.toByteArray());
+    }
+
+    private static void generateConstructor(ClassWriter cw) {
+        MethodVisitor mv = cw.visitMethod(ACC_PUBLIC, "<init>", "()V", null, null);
+        mv.visitCode();
+        mv.visitVarInsn(ALOAD, 0);
+        mv.visitMethodInsn(INVOKESPECIAL, "java/lang/Object", "<init>", "()V", false);
+        mv.visitInsn(RETURN);
+        mv.visitMaxs(1, 1);
+        mv.visitEnd();
+    }
+
+    // public static void main(String[] args) {
+    //     try { exitWithoutEnter(); } catch (IllegalMonitorStateException e) { tempPrint(1); }
+    //     try { exitInSynchronizedMethod(); } catch (IllegalMonitorStateException e) { tempPrint(2); }
+    //     try { recursiveEntries(); } catch (IllegalMonitorStateException e) { tempPrint(4); }
+    //     try { releasedOnException(); } catch (IllegalMonitorStateException e) { tempPrint(6); }
+    // }
+    private static void generateMain(ClassWriter cw) {
+        MethodVisitor mv = cw.visitMethod(ACC_PUBLIC | ACC_STATIC, "main", "([Ljava/lang/String;)V", null, null);
+        mv.visitCode();
+        invokeCatchingIllegalMonitorState(mv, "exitWithoutEnter", 1);
+        invokeCatchingIllegalMonitorState(mv, "exitInSynchronizedMethod", 2);
+        invokeCatchingIllegalMonitorState(mv, "recursiveEntries", 4);
+        invokeCatchingIllegalMonitorState(mv, "releasedOnException", 6);
+        mv.visitInsn(RETURN);
+        mv.visitMaxs(1, 1);
+        mv.visitEnd();
+    }
+
+    private static void invokeCatchingIllegalMonitorState(MethodVisitor mv, String methodName, int printedIfThrown) {
+        Label start = new Label();
+        Label end = new Label();
+        Label handler = new Label();
+        Label after = new Label();
+        mv.visitTryCatchBlock(start, end, handler, ILLEGAL_MONITOR_STATE);
+        mv.visitLabel(start);
+        mv.visitMethodInsn(INVOKESTATIC, CLASS_NAME, methodName, "()V", false);
+        mv.visitLabel(end);
+        mv.visitJumpInsn(GOTO, after);
+        mv.visitLabel(handler);
+        mv.visitInsn(POP);
+        mv.visitIntInsn(BIPUSH, printedIfThrown);
+        mv.visitMethodInsn(INVOKESTATIC, CLASS_NAME, "tempPrint", "(I)V", false);
+        mv.visitLabel(after);
+    }
+
+    // Exits the monitor of a new object
+    private static void generateExitWithoutEnter(ClassWriter cw) {
+        MethodVisitor mv = cw.visitMethod(ACC_STATIC, "exitWithoutEnter", "()V", null, null);
+        mv.visitCode();
+        mv.visitTypeInsn(NEW, "java/lang/Object");
+        mv.visitInsn(DUP);
+        mv.visitMethodInsn(INVOKESPECIAL, "java/lang/Object", "<init>", "()V", false);
+        mv.visitInsn(MONITOREXIT);
+        mv.visitInsn(RETURN);
+        mv.visitMaxs(2, 0);
+        mv.visitEnd();
+    }
+
+    // Invokes a synchronized method that exits its own monitor, so that the implicit exit
+    // on return fails
+    private static void generateExitInSynchronizedMethod(ClassWriter cw) {
+        MethodVisitor mv = cw.visitMethod(ACC_STATIC, "exitInSynchronizedMethod", "()V", null, null);
+        mv.visitCode();
+        mv.visitTypeInsn(NEW, CLASS_NAME);
+        mv.visitInsn(DUP);
+        mv.visitMethodInsn(INVOKESPECIAL, CLASS_NAME, "<init>", "()V", false);
+        mv.visitMethodInsn(INVOKEVIRTUAL, CLASS_NAME, "releaseThis", "()V", false);
+        mv.visitInsn(RETURN);
+        mv.visitMaxs(2, 0);
+        mv.visitEnd();
+
+        mv = cw.visitMethod(ACC_SYNCHRONIZED, "releaseThis", "()V", null, null);
+        mv.visitCode();
+        mv.visitVarInsn(ALOAD, 0);
+        mv.visitInsn(MONITOREXIT);
+        mv.visitInsn(RETURN);
+        mv.visitMaxs(1, 1);
+        mv.visitEnd();
+    }
+
+    // Enters a monitor twice and exits it three times
+    private static void generateRecursiveEntries(ClassWriter cw) {
+        MethodVisitor mv = cw.visitMethod(ACC_STATIC, "recursiveEntries", "()V", null, null);
+        mv.visitCode();
+        mv.visitTypeInsn(NEW, "java/lang/Object");
+        mv.visitInsn(DUP);
+        mv.visitMethodInsn(INVOKESPECIAL, "java/lang/Object", "<init>", "()V", false);
+        mv.visitVarInsn(ASTORE, 0);
+        mv.visitVarInsn(ALOAD, 0);
+        mv.visitInsn(MONITORENTER);
+        mv.visitVarInsn(ALOAD, 0);
+        mv.visitInsn(MONITORENTER);
+        mv.visitVarInsn(ALOAD, 0);
+        mv.visitInsn(MONITOREXIT);
+        mv.visitVarInsn(ALOAD, 0);
+        mv.visitInsn(MONITOREXIT);
+        mv.visitInsn(ICONST_3);
+        mv.visitMethodInsn(INVOKESTATIC, CLASS_NAME, "tempPrint", "(I)V", false);
+        mv.visitVarInsn(ALOAD, 0);
+        mv.visitInsn(MONITOREXIT);
+        mv.visitInsn(RETURN);
+        mv.visitMaxs(2, 1);
+        mv.visitEnd();
+    }
+
+    // Invokes a synchronized method that throws, and then checks that its monitor was
+    // released by exiting it once more
+    private static void generateReleasedOnException(ClassWriter cw) {
+        MethodVisitor mv = cw.visitMethod(ACC_STATIC, "releasedOnException", "()V", null, null);
+        mv.visitCode();
+        Label start = new Label();
+        Label end = new Label();
+        Label handler = new Label();
+        Label after = new Label();
+        mv.visitTryCatchBlock(start, end, handler, "java/lang/NullPointerException");
+        mv.visitTypeInsn(NEW, CLASS_NAME);
+        mv.visitInsn(DUP);
+        mv.visitMethodInsn(INVOKESPECIAL, CLASS_NAME, "<init>", "()V", false);
+        mv.visitVarInsn(ASTORE, 0);
+        mv.visitLabel(start);
+        mv.visitVarInsn(ALOAD, 0);
+        mv.visitMethodInsn(INVOKEVIRTUAL, CLASS_NAME, "fail", "()V", false);
+        mv.visitLabel(end);
+        mv.visitJumpInsn(GOTO, after);
+        mv.visitLabel(handler);
+        mv.visitInsn(POP);
+        mv.visitInsn(ICONST_5);
+        mv.visitMethodInsn(INVOKESTATIC, CLASS_NAME, "tempPrint", "(I)V", false);
+        mv.visitLabel(after);
+        mv.visitVarInsn(ALOAD, 0);
+        mv.visitInsn(MONITOREXIT);
+        mv.visitInsn(RETURN);
+        mv.visitMaxs(2, 1);
+        mv.visitEnd();
+
+        mv = cw.visitMethod(ACC_SYNCHRONIZED, "fail", "()V", null, null);
+        mv.visitCode();
+        mv.visitInsn(ACONST_NULL);
+        mv.visitInsn(ATHROW);
+        mv.visitMaxs(1, 1);
+        mv.visitEnd();
+    }
+}
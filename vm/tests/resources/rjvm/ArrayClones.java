@@ -0,0 +1,30 @@
+package rjvm;
+
+public class ArrayClones {
+    public static void main(String[] args) {
+        // The clones are the only allocations, so the collections run while cloning
+        int[] ints = new int[100];
+        for (int i = 0; i < ints.length; ++i) {
+            ints[i] = i;
+        }
+        long sum = 0;
+        for (int i = 0; i < 5000; ++i) {
+            int[] copy = ints.clone();
+            sum += copy[i % copy.length];
+        }
+        tempPrint(sum);
+
+        String[] strings = new String[100];
+        strings[99] = "last";
+        int length = 0;
+        for (int i = 0; i < 5000; ++i) {
+            String[] copy = strings.clone();
+            length += copy[99].length();
+        }
+        tempPrint(length);
+    }
+
+    private static native void tempPrint(int value);
+
+    private static native void tempPrint(long value);
+}
@@ -0,0 +1,28 @@
+package rjvm;
+
+public class ClassInitializationByTwoThreads {
+    private static class Slow {
+        static int value;
+
+        static {
+            try {
+                Thread.sleep(20);
+            } catch (InterruptedException e) {
+                throw new RuntimeException(e);
+            }
+            value = 42;
+        }
+    }
+
+    private static int readByOther;
+
+    public static void main(String[] args) throws InterruptedException {
+        Thread other = new Thread(() -> readByOther = Slow.value);
+        other.start();
+        int readByMain = Slow.value;
+        other.join();
+        tempPrint(readByMain * 1000 + readByOther);
+    }
+
+    private static native void tempPrint(int value);
+}
@@ -0,0 +1,151 @@
+package rjvm;
+
+import java.io.Serializable;
+
+public class ClassMirrors {
+    public static void main(String[] args) throws Exception {
+        identity();
+        names();
+        hierarchy();
+        instances();
+        modifiers();
+        synchronization();
+        survivesGarbageCollection();
+    }
+
+    private static void identity() throws Exception {
+        tempPrint(String.class == "rjvm".getClass() ? 1 : 0);
+        tempPrint(Class.forName("java.lang.String") == String.class ? 1 : 0);
+        Object square = new MirroredSquare();
+        tempPrint(square.getClass() == MirroredSquare.class ? 1 : 0);
+        tempPrint(square.getClass() == MirroredShape.class ? 1 : 0);
+        tempPrint(new int[0].getClass() == int[].class ? 1 : 0);
+        tempPrint(new String[1][1].getClass() == String[][].class ? 1 : 0);
+        tempPrint(Class.forName("[Ljava.lang.String;") == String[].class ? 1 : 0);
+        tempPrint(Integer.TYPE == int.class ? 1 : 0);
+    }
+
+    private static void names() {
+        describe(ClassMirrors.class);
+        describe(MirroredDrawable.class);
+        describe(int.class);
+        describe(void.class);
+        describe(long[][].class);
+        describe(String[].class);
+    }
+
+    private static void describe(Class<?> type) {
+        tempPrint(type.getName() + " " + (type.isArray() ? 1 : 0) + " " + (type.isPrimitive() ? 1 : 0)
+                + " " + (type.isInterface() ? 1 : 0));
+    }
+
+    private static void hierarchy() {
+        tempPrint(long[][].class.getComponentType() == long[].class ? 1 : 0);
+        tempPrint(long[].class.getComponentType() == long.class ? 1 : 0);
+        tempPrint(String[].class.getComponentType() == String.class ? 1 : 0);
+        tempPrint(String.class.getComponentType() == null ? 1 : 0);
+
+        tempPrint(MirroredSquare.class.getSuperclass() == MirroredShape.class ? 1 : 0);
+        tempPrint(MirroredShape.class.getSuperclass() == Object.class ? 1 : 0);
+        tempPrint(String[].class.getSuperclass() == Object.class ? 1 : 0);
+        tempPrint(Object.class.getSuperclass() == null ? 1 : 0);
+        tempPrint(MirroredDrawable.class.getSuperclass() == null ? 1 : 0);
+        tempPrint(int.class.getSuperclass() == null ? 1 : 0);
+
+        printInterfaces(MirroredSquare.class);
+        printInterfaces(MirroredDrawable.class);
+        printInterfaces(int[].class);
+        printInterfaces(MirroredShape.class);
+        printInterfaces(int.class);
+        tempPrint(MirroredSquare.class.getInterfaces()[0] == Runnable.class ? 1 : 0);
+    }
+
+    private static void printInterfaces(Class<?> type) {
+        Class<?>[] interfaces = type.getInterfaces();
+        String names = type.getName() + ":";
+        for (Class<?> implemented : interfaces) {
+            names += " " + implemented.getName();
+        }
+        tempPrint(names);
+    }
+
+    private static void instances() {
+        tempPrint(MirroredShape.class.isInstance(new MirroredSquare()) ? 1 : 0);
+        tempPrint(MirroredSquare.class.isInstance(new MirroredCircle()) ? 1 : 0);
+        tempPrint(Runnable.class.isInstance(new MirroredSquare()) ? 1 : 0);
+        tempPrint(Object.class.isInstance(new int[0]) ? 1 : 0);
+        tempPrint(Serializable.class.isInstance(new int[0][0]) ? 1 : 0);
+        tempPrint(Object[].class.isInstance(new String[0][0]) ? 1 : 0);
+        tempPrint(Object[].class.isInstance(new int[0]) ? 1 : 0);
+        tempPrint(String.class.isInstance(null) ? 1 : 0);
+        tempPrint(int.class.isInstance(1) ? 1 : 0);
+
+        tempPrint(Object.class.isAssignableFrom(String.class) ? 1 : 0);
+        tempPrint(String.class.isAssignableFrom(Object.class) ? 1 : 0);
+        tempPrint(Runnable.class.isAssignableFrom(MirroredDrawable.class) ? 1 : 0);
+        tempPrint(Object.class.isAssignableFrom(MirroredDrawable.class) ? 1 : 0);
+        tempPrint(MirroredShape[].class.isAssignableFrom(MirroredSquare[].class) ? 1 : 0);
+        tempPrint(Object[].class.isAssignableFrom(String[][].class) ? 1 : 0);
+        tempPrint(Object[][].class.isAssignableFrom(String[].class) ? 1 : 0);
+        tempPrint(Cloneable.class.isAssignableFrom(int[].class) ? 1 : 0);
+        tempPrint(int[].class.isAssignableFrom(long[].class) ? 1 : 0);
+        tempPrint(int.class.isAssignableFrom(int.class) ? 1 : 0);
+        tempPrint(long.class.isAssignableFrom(int.class) ? 1 : 0);
+        tempPrint(Object.class.isAssignableFrom(int.class) ? 1 : 0);
+        try {
+            Object.class.isAssignableFrom(null);
+        } catch (NullPointerException e) {
+            tempPrint("null class");
+        }
+    }
+
+    private static void modifiers() {
+        tempPrint(ClassMirrors.class.getModifiers());
+        tempPrint(MirroredShape.class.getModifiers());
+        tempPrint(MirroredSquare.class.getModifiers());
+        tempPrint(MirroredDrawable.class.getModifiers());
+        tempPrint(Runnable.class.getModifiers());
+        tempPrint(int.class.getModifiers());
+        tempPrint(String[].class.getModifiers());
+        tempPrint(MirroredShape[][].class.getModifiers());
+    }
+
+    private static void synchronization() throws Exception {
+        lockedStatically();
+        synchronized (MirroredShape.class) {
+            // Throws IllegalMonitorStateException unless the monitor of the class is held
+            Class.forName("rjvm.MirroredShape").notifyAll();
+            tempPrint("synchronized on the class literal");
+        }
+    }
+
+    private static synchronized void lockedStatically() {
+        ClassMirrors.class.notifyAll();
+        tempPrint("static synchronized method");
+    }
+
+    private static void survivesGarbageCollection() throws Exception {
+        Class<?> square = MirroredSquare.class;
+        System.gc();
+        tempPrint(Class.forName("rjvm.MirroredSquare") == square ? 1 : 0);
+        tempPrint(square.getName());
+    }
+
+    private static native void tempPrint(int value);
+
+    private static native void tempPrint(String value);
+}
+
+abstract class MirroredShape {
+}
+
+final class MirroredSquare extends MirroredShape implements Runnable, Serializable {
+    public void run() {
+    }
+}
+
+class MirroredCircle extends MirroredShape {
+}
+
+interface MirroredDrawable extends Runnable {
+}
@@ -0,0 +1,68 @@
+package rjvm;
+
+public class ComparisonsAndOverflows {
+    public static void main(String[] args) {
+        compareFloats(Float.NaN, 1.0f);
+        compareFloats(2.0f, 1.0f);
+        compareDoubles(Double.NaN, 1.0);
+        compareDoubles(1.0, 2.0);
+        compareLongs(Long.MIN_VALUE, Long.MAX_VALUE);
+        overflowLongs(Long.MIN_VALUE, Long.MAX_VALUE, -1L);
+        negateMinimums(Integer.MIN_VALUE, Long.MIN_VALUE);
+        shiftLongs(1L, -1L, Long.MIN_VALUE, 32, 40);
+    }
+
+    // javac compiles > and >= to fcmpl, < and <= to fcmpg, so that NaN makes all of them false
+    private static void compareFloats(float a, float b) {
+        tempPrint(a > b ? 1 : 0);
+        tempPrint(a >= b ? 1 : 0);
+        tempPrint(a < b ? 1 : 0);
+        tempPrint(a <= b ? 1 : 0);
+        tempPrint(a == b ? 1 : 0);
+        tempPrint(a != b ? 1 : 0);
+        tempPrint(a != a ? 1 : 0);
+    }
+
+    private static void compareDoubles(double a, double b) {
+        tempPrint(a > b ? 1 : 0);
+        tempPrint(a >= b ? 1 : 0);
+        tempPrint(a < b ? 1 : 0);
+        tempPrint(a <= b ? 1 : 0);
+        tempPrint(a == b ? 1 : 0);
+        tempPrint(a != b ? 1 : 0);
+        tempPrint(a != a ? 1 : 0);
+    }
+
+    private static void compareLongs(long a, long b) {
+        tempPrint(a > b ? 1 : 0);
+        tempPrint(a < b ? 1 : 0);
+        tempPrint(a == a ? 1 : 0);
+    }
+
+    private static void overflowLongs(long min, long max, long minusOne) {
+        tempPrint(min / minusOne);
+        tempPrint(min % minusOne);
+        tempPrint(max + 1);
+        tempPrint(min - 1);
+        tempPrint(max * 2);
+    }
+
+    private static void negateMinimums(int minInt, long minLong) {
+        tempPrint(-minInt);
+        tempPrint(-minLong);
+    }
+
+    // Long shifts use the lowest six bits of the distance
+    private static void shiftLongs(long one, long minusOne, long min, int thirtyTwo, int forty) {
+        tempPrint(one << thirtyTwo);
+        tempPrint(one << forty);
+        tempPrint(minusOne >>> forty);
+        tempPrint(min >> forty);
+        tempPrint(min >>> thirtyTwo);
+        tempPrint(one << (forty + 64));
+    }
+
+    private static native void tempPrint(int value);
+
+    private static native void tempPrint(long value);
+}
@@ -0,0 +1,34 @@
+package rjvm;
+
+public class Deadlock {
+    public static void main(String[] args) throws InterruptedException {
+        Object first = new Object();
+        Object second = new Object();
+        Thread other = new Thread(() -> {
+            synchronized (second) {
+                sleep(10);
+                synchronized (first) {
+                    tempPrint(2);
+                }
+            }
+        }, "other");
+
+        synchronized (first) {
+            other.start();
+            sleep(10);
+            synchronized (second) {
+                tempPrint(1);
+            }
+        }
+    }
+
+    private static void sleep(long millis) {
+        try {
+            Thread.sleep(millis);
+        } catch (InterruptedException e) {
+            throw new RuntimeException(e);
+        }
+    }
+
+    private static native void tempPrint(int value);
+}
@@ -0,0 +1,35 @@
+package rjvm;
+
+public class FieldResolution {
+    static class Base {
+        int value = 1;
+
+        int baseValue() {
+            return value;
+        }
+    }
+
+    static class Derived extends Base {
+        int value = 2;
+
+        int derivedValue() {
+            return value;
+        }
+    }
+
+    private static int counter;
+
+    public static void main(String[] args) {
+        Derived derived = new Derived();
+        // The second iteration uses the references resolved by the first one
+        for (int i = 0; i < 2; ++i) {
+            tempPrint(derived.baseValue());
+            tempPrint(derived.derivedValue());
+            tempPrint(((Base) derived).value);
+            counter += 10;
+            tempPrint(counter);
+        }
+    }
+
+    private static native void tempPrint(int value);
+}
@@ -0,0 +1,105 @@
+package rjvm;
+
+import java.io.File;
+import java.io.FileInputStream;
+import java.io.FileNotFoundException;
+import java.io.FileOutputStream;
+import java.io.IOException;
+import java.io.RandomAccessFile;
+
+public class Files {
+    public static void main(String[] args) throws IOException {
+        File directory = new File(System.getProperty("java.io.tmpdir"), "rjvm-files-" + System.nanoTime());
+        tempPrint(directory.mkdir() ? 1 : 0);
+        tempPrint(directory.exists() ? 1 : 0);
+        tempPrint(directory.isDirectory() ? 1 : 0);
+
+        File file = new File(directory, "data.txt");
+        FileOutputStream output = new FileOutputStream(file);
+        output.write(toBytes("hello\nworld\n"));
+        output.close();
+        tempPrint(file.length());
+        tempPrint(file.isFile() ? 1 : 0);
+        tempPrint(file.lastModified() > 0 ? 1 : 0);
+
+        FileInputStream input = new FileInputStream(file);
+        tempPrint(input.available());
+        tempPrint(input.read());
+        tempPrint(input.skip(5));
+        byte[] buffer = new byte[16];
+        int read = input.read(buffer);
+        tempPrint(toString(buffer, read));
+        tempPrint(input.read());
+        input.close();
+        try {
+            input.read();
+        } catch (IOException e) {
+            tempPrint(e.getMessage());
+        }
+
+        output = new FileOutputStream(file, true);
+        output.write('!');
+        output.close();
+        tempPrint(file.length());
+
+        RandomAccessFile randomAccessFile = new RandomAccessFile(file, "rw");
+        randomAccessFile.seek(6);
+        tempPrint(randomAccessFile.read());
+        tempPrint(randomAccessFile.getFilePointer());
+        randomAccessFile.seek(6);
+        randomAccessFile.write('W');
+        randomAccessFile.seek(0);
+        read = randomAccessFile.read(buffer);
+        tempPrint(toString(buffer, read));
+        randomAccessFile.setLength(5);
+        tempPrint(randomAccessFile.length());
+        tempPrint(randomAccessFile.getFilePointer());
+        randomAccessFile.close();
+
+        String[] names = directory.list();
+        tempPrint(names.length);
+        tempPrint(names[0]);
+        File dotted = new File(directory, "./missing/../data.txt");
+        tempPrint(dotted.getCanonicalPath().equals(file.getCanonicalPath()) ? 1 : 0);
+
+        File missing = new File(directory, "missing.txt");
+        try {
+            new FileInputStream(missing);
+        } catch (FileNotFoundException e) {
+            tempPrint(e.getMessage().equals(missing.getPath() + " (No such file or directory)") ? 1 : 0);
+        }
+        try {
+            new FileInputStream(directory);
+        } catch (FileNotFoundException e) {
+            tempPrint(e.getMessage().equals(directory.getPath() + " (Is a directory)") ? 1 : 0);
+        }
+        tempPrint(missing.delete() ? 1 : 0);
+
+        tempPrint(file.delete() ? 1 : 0);
+        tempPrint(directory.delete() ? 1 : 0);
+        tempPrint(directory.exists() ? 1 : 0);
+        tempPrint(directory.list() == null ? 1 : 0);
+    }
+
+    private static byte[] toBytes(String string) {
+        byte[] bytes = new byte[string.length()];
+        for (int i = 0; i < bytes.length; ++i) {
+            bytes[i] = (byte) string.charAt(i);
+        }
+        return bytes;
+    }
+
+    private static String toString(byte[] bytes, int length) {
+        char[] chars = new char[length];
+        for (int i = 0; i < length; ++i) {
+            chars[i] = (char) bytes[i];
+        }
+        return new String(chars);
+    }
+
+    private static native void tempPrint(int value);
+
+    private static native void tempPrint(long value);
+
+    private static native void tempPrint(String value);
+}
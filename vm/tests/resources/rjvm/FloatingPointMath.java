@@ -0,0 +1,96 @@
+package rjvm;
+
+public class FloatingPointMath {
+    private static final int FUNCTIONS = 20;
+    private static final int SAMPLES = 1000;
+
+    private static long seed = 42;
+
+    public static void main(String[] args) {
+        tempPrint(StrictMath.sin(1.0));
+        tempPrint(StrictMath.cos(Math.PI));
+        tempPrint(StrictMath.sqrt(2.0));
+        tempPrint(StrictMath.pow(2.0, 10.0));
+        tempPrint(StrictMath.exp(1.0));
+        tempPrint(StrictMath.log(Math.E));
+        tempPrint(StrictMath.atan2(1.0, 1.0));
+        tempPrint(StrictMath.cbrt(-27.0));
+        tempPrint(StrictMath.hypot(3.0, 4.0));
+        tempPrint(StrictMath.IEEEremainder(10.0, 3.0));
+        tempPrint(StrictMath.log10(1000.0));
+        tempPrint(Double.isNaN(StrictMath.pow(-8.0, 1.0 / 3.0)) ? 1 : 0);
+        tempPrint(StrictMath.pow(0.0, -1.0));
+        tempPrint(StrictMath.sin(1e22));
+
+        tempPrint(Double.doubleToRawLongBits(Double.longBitsToDouble(0x7ff8000000000123L)));
+        tempPrint(Double.doubleToLongBits(Double.longBitsToDouble(0x7ff8000000000123L)));
+        tempPrint(Double.longBitsToDouble(0x3ff8000000000000L));
+        tempPrint(Double.doubleToRawLongBits(-0.0));
+        tempPrint(Float.floatToRawIntBits(Float.intBitsToFloat(0x7fc00123)));
+        tempPrint(Float.floatToIntBits(Float.intBitsToFloat(0x7fc00123)));
+        tempPrint(Float.intBitsToFloat(0x40490fdb));
+        tempPrint(Float.floatToRawIntBits(Float.MIN_VALUE));
+
+        double one = 1.0;
+        double nan = Double.NaN;
+        float oneFloat = 1.0f;
+        float nanFloat = Float.NaN;
+        tempPrint((one < 2.0 ? 1 : 0) + (one > 2.0 ? 2 : 0) + (one <= 1.0 ? 4 : 0) + (one >= 2.0 ? 8 : 0));
+        tempPrint((nan < one ? 1 : 0) + (nan > one ? 2 : 0) + (nan == nan ? 4 : 0) + (nan != nan ? 8 : 0));
+        tempPrint((oneFloat < 2.0f ? 1 : 0) + (oneFloat > 2.0f ? 2 : 0) + (oneFloat <= 1.0f ? 4 : 0));
+        tempPrint((nanFloat < oneFloat ? 1 : 0) + (nanFloat >= oneFloat ? 2 : 0) + (nanFloat != nanFloat ? 4 : 0));
+
+        for (int function = 0; function < FUNCTIONS; ++function) {
+            long checksum = 0;
+            for (int i = 0; i < SAMPLES; ++i) {
+                double x = nextDouble(i);
+                double y = nextDouble(i + 1);
+                checksum = checksum * 31 + Double.doubleToLongBits(apply(function, x, y));
+            }
+            tempPrint(checksum);
+        }
+    }
+
+    // Alternates between arbitrary bit patterns, which cover every exponent and the
+    // special values, and ordinary numbers in [-10, 10)
+    private static double nextDouble(int i) {
+        seed = seed * 6364136223846793005L + 1442695040888963407L;
+        if (i % 2 == 0) {
+            return Double.longBitsToDouble(seed);
+        }
+        return (seed >>> 11) * 0x1.0p-53 * 20.0 - 10.0;
+    }
+
+    private static double apply(int function, double x, double y) {
+        switch (function) {
+            case 0: return StrictMath.sin(x);
+            case 1: return StrictMath.cos(x);
+            case 2: return StrictMath.tan(x);
+            case 3: return StrictMath.asin(x);
+            case 4: return StrictMath.acos(x);
+            case 5: return StrictMath.atan(x);
+            case 6: return StrictMath.exp(x);
+            case 7: return StrictMath.log(x);
+            case 8: return StrictMath.log10(x);
+            case 9: return StrictMath.sqrt(x);
+            case 10: return StrictMath.cbrt(x);
+            case 11: return StrictMath.IEEEremainder(x, y);
+            case 12: return StrictMath.atan2(x, y);
+            case 13: return StrictMath.pow(x, y);
+            case 14: return StrictMath.sinh(x);
+            case 15: return StrictMath.cosh(x);
+            case 16: return StrictMath.tanh(x);
+            case 17: return StrictMath.hypot(x, y);
+            case 18: return StrictMath.expm1(x);
+            default: return StrictMath.log1p(x);
+        }
+    }
+
+    private static native void tempPrint(int value);
+
+    private static native void tempPrint(long value);
+
+    private static native void tempPrint(float value);
+
+    private static native void tempPrint(double value);
+}
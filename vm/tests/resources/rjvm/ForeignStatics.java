@@ -0,0 +1,21 @@
+package rjvm;
+
+public class ForeignStatics {
+    public static void main(String[] args) {
+        tempPrint(Counter.count);
+        Counter.count = 5;
+        Counter.count += 2;
+        tempPrint(Counter.count);
+        tempPrint(Counter.read());
+    }
+
+    private static native void tempPrint(int value);
+}
+
+class Counter {
+    static int count = 3;
+
+    static int read() {
+        return count;
+    }
+}
@@ -0,0 +1,22 @@
+package rjvm;
+
+public class HotLoops {
+    public static void main(String[] args) {
+        int sum = 0;
+        for (int i = 0; i < 200000; i++) {
+            switch (i % 4) {
+                case 0:
+                    sum += i;
+                    break;
+                case 1:
+                    sum -= i / 2;
+                    break;
+                default:
+                    sum ^= i;
+            }
+        }
+        tempPrint(sum);
+    }
+
+    private static native void tempPrint(int value);
+}
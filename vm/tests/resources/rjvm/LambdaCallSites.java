@@ -0,0 +1,24 @@
+package rjvm;
+
+public class LambdaCallSites {
+    interface IntProducer {
+        int produce();
+    }
+
+    // The invokedynamic instructions of these two methods are both at address 0
+    static IntProducer one() {
+        return () -> 1;
+    }
+
+    static IntProducer two() {
+        return () -> 2;
+    }
+
+    public static void main(String[] args) {
+        tempPrint(one().produce());
+        tempPrint(two().produce());
+        tempPrint(one().produce());
+    }
+
+    private static native void tempPrint(int value);
+}
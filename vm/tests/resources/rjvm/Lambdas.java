@@ -0,0 +1,78 @@
+package rjvm;
+
+public class Lambdas {
+    interface IntOperation {
+        int apply(int a, int b);
+    }
+
+    interface Transformer<T, R> {
+        R transform(T value);
+    }
+
+    interface Producer<T> {
+        T produce();
+    }
+
+    interface LongProducer {
+        long produce();
+    }
+
+    interface Action {
+        void run();
+    }
+
+    private final int base;
+
+    Lambdas(int base) {
+        this.base = base;
+    }
+
+    private int addBase(int value) {
+        return base + value;
+    }
+
+    private IntOperation multiplierOfBase() {
+        return (a, b) -> (a + b) * base;
+    }
+
+    static int twice(int value) {
+        return 2 * value;
+    }
+
+    public static void main(String[] args) {
+        IntOperation sum = (a, b) -> a + b;
+        tempPrint(sum.apply(3, 4));
+
+        int offset = 10;
+        IntOperation withOffset = (a, b) -> a * b + offset;
+        tempPrint(withOffset.apply(2, 3));
+
+        Lambdas instance = new Lambdas(100);
+        Transformer<Integer, Integer> boundMethodReference = instance::addBase;
+        tempPrint(boundMethodReference.transform(5));
+
+        tempPrint(instance.multiplierOfBase().apply(1, 2));
+
+        Transformer<Integer, Integer> staticMethodReference = Lambdas::twice;
+        tempPrint(staticMethodReference.transform(21));
+
+        Transformer<CharSequence, Integer> unboundMethodReference = CharSequence::length;
+        tempPrint(unboundMethodReference.transform("hello"));
+
+        Transformer<Integer, Lambdas> constructorReference = Lambdas::new;
+        tempPrint(constructorReference.transform(7).base);
+
+        LongProducer widening = () -> 13;
+        tempPrint((int) widening.produce());
+
+        final int[] counter = new int[1];
+        Action increment = () -> counter[0]++;
+        for (int i = 0; i < 3; ++i) {
+            Producer<Integer> producer = () -> counter[0];
+            increment.run();
+            tempPrint(producer.produce());
+        }
+    }
+
+    private static native void tempPrint(int value);
+}
@@ -0,0 +1,126 @@
+package rjvm;
+
+public class LazyInitialization {
+    static class Referenced {
+        static int value = 1;
+
+        static {
+            tempPrint("Referenced");
+        }
+    }
+
+    static class Parent {
+        static int parentValue = 10;
+
+        static {
+            tempPrint("Parent");
+        }
+    }
+
+    static class Child extends Parent {
+        static {
+            tempPrint("Child");
+        }
+
+        static void touch() {
+        }
+    }
+
+    interface WithDefault {
+        int VALUE = compute("WithDefault");
+
+        default int value() {
+            return VALUE;
+        }
+    }
+
+    interface WithoutDefault {
+        int VALUE = compute("WithoutDefault");
+
+        int other();
+    }
+
+    static class Implementor implements WithDefault, WithoutDefault {
+        public int other() {
+            return 2;
+        }
+    }
+
+    static class Failing {
+        static int value = fail();
+    }
+
+    static class CustomError extends Error {
+    }
+
+    static class ThrowingError {
+        static {
+            if (true) {
+                throw new CustomError();
+            }
+        }
+
+        static void touch() {
+        }
+    }
+
+    static class First {
+        static int value = Second.value + 1;
+    }
+
+    static class Second {
+        // First is being initialized, so its value is still 0
+        static int value = First.value + 10;
+    }
+
+    public static void main(String[] args) {
+        // Neither creating arrays nor using the class in a descriptor initializes it
+        Referenced[] array = new Referenced[2];
+        take(array[0]);
+        tempPrint("not yet");
+        tempPrint(Referenced.value);
+
+        // The field is declared by Parent, so Child is not initialized yet
+        tempPrint(Child.parentValue);
+        Child.touch();
+
+        // Only the superinterfaces declaring default methods are initialized with a class
+        tempPrint(new Implementor().other());
+        tempPrint(WithoutDefault.VALUE);
+
+        try {
+            tempPrint(Failing.value);
+        } catch (ExceptionInInitializerError e) {
+            tempPrint("caused by " + e.getCause().getMessage());
+        }
+        try {
+            tempPrint(Failing.value);
+        } catch (NoClassDefFoundError e) {
+            tempPrint(e.getMessage());
+        }
+        try {
+            ThrowingError.touch();
+        } catch (CustomError e) {
+            tempPrint("errors are not wrapped");
+        }
+
+        tempPrint(First.value);
+        tempPrint(Second.value);
+    }
+
+    private static void take(Referenced referenced) {
+    }
+
+    private static int compute(String name) {
+        tempPrint(name);
+        return name.length();
+    }
+
+    private static int fail() {
+        throw new IllegalStateException("boom");
+    }
+
+    private static native void tempPrint(int value);
+
+    private static native void tempPrint(String value);
+}
@@ -0,0 +1,114 @@
+package rjvm;
+
+public class MultiDimensionalArrays {
+    public static void main(String[] args) {
+        ints();
+        longs();
+        partialDimensions();
+        objects();
+        casts();
+        manyAllocations();
+    }
+
+    private static void ints() {
+        int[][] matrix = new int[3][4];
+        tempPrint(matrix.length);
+        tempPrint(matrix[2].length);
+        for (int i = 0; i < matrix.length; ++i) {
+            for (int j = 0; j < matrix[i].length; ++j) {
+                matrix[i][j] = i * j;
+            }
+        }
+        tempPrint(sum(matrix));
+    }
+
+    private static void longs() {
+        long[][][] cube = new long[2][3][4];
+        cube[1][2][3] = 1234567890123L;
+        tempPrint(cube[1][2][3] + cube[0][0][0]);
+        tempPrint(cube[1][2].length);
+    }
+
+    private static void partialDimensions() {
+        int[][] rows = new int[3][];
+        tempPrint(rows[0] == null);
+        rows[1] = new int[]{1, 2, 3};
+        tempPrint(rows[1][2]);
+
+        char[][][] chars = new char[2][2][];
+        tempPrint(chars[1][1] == null);
+        chars[1][1] = new char[]{'x'};
+        tempPrint(chars[1][1][0]);
+
+        Object[] asObjects = new int[2][2];
+        tempPrint(asObjects.length);
+    }
+
+    private static void objects() {
+        String[][] strings = new String[2][3];
+        tempPrint(strings[1][2] == null);
+        strings[1][2] = "hello";
+        tempPrint(strings[1][2].length());
+    }
+
+    private static void casts() {
+        Object ints = new int[2][3];
+        tempPrint(ints instanceof Object[]);
+        tempPrint(ints instanceof int[][]);
+        tempPrint(ints instanceof long[][]);
+        tempPrint(ints instanceof Cloneable);
+        Object[] rows = (Object[]) ints;
+        tempPrint(rows.length);
+        int[][] matrix = (int[][]) ints;
+        tempPrint(matrix[1].length);
+        int[][] copy = matrix.clone();
+        tempPrint(copy[1] == matrix[1]);
+
+        Object strings = new String[2][4];
+        tempPrint(strings instanceof Object[][]);
+        tempPrint(strings instanceof CharSequence[][]);
+        tempPrint(strings instanceof Integer[][]);
+        String[][] asStrings = (String[][]) strings;
+        tempPrint(asStrings[1].length);
+        try {
+            Integer[][] integers = (Integer[][]) strings;
+            tempPrint(integers.length);
+        } catch (ClassCastException e) {
+            tempPrint(true);
+        }
+    }
+
+    private static void manyAllocations() {
+        int[][] kept = new int[10][10];
+        for (int i = 0; i < 10; ++i) {
+            kept[i][i] = i;
+        }
+        for (int i = 0; i < 1000; ++i) {
+            int[][] garbage = new int[20][20];
+            garbage[19][19] = i;
+        }
+        tempPrint(sum(kept));
+    }
+
+    private static int sum(int[][] matrix) {
+        int sum = 0;
+        for (int[] row : matrix) {
+            for (int value : row) {
+                sum += value;
+            }
+        }
+        return sum;
+    }
+
+    public static void negativeSize() {
+        int[][] matrix = new int[2][-1];
+    }
+
+    private static native void tempPrint(int value);
+
+    private static native void tempPrint(long value);
+
+    private static native void tempPrint(boolean value);
+
+    private static native void tempPrint(char value);
+}
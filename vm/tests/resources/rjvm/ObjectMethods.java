@@ -0,0 +1,99 @@
+package rjvm;
+
+public class ObjectMethods {
+    public static void main(String[] args) throws Exception {
+        cloneObjects();
+        hashCodes();
+    }
+
+    private static void cloneObjects() throws Exception {
+        Point point = new Point(1, 1L << 40, "a", new int[] {7, 8});
+        point.id = 5;
+        Point copy = point.copy();
+        tempPrint(copy != point ? 1 : 0);
+        tempPrint(copy.getClass() == Point.class ? 1 : 0);
+        tempPrint(copy.id);
+        tempPrint(copy.x);
+        tempPrint(copy.y);
+        tempPrint(copy.label);
+        tempPrint(copy.values == point.values ? 1 : 0);
+
+        copy.x = 2;
+        tempPrint(point.x);
+
+        Point3D point3D = new Point3D(1, 2, 3);
+        Point copy3D = point3D.copy();
+        tempPrint(copy3D.getClass() == Point3D.class ? 1 : 0);
+        tempPrint(((Point3D) copy3D).z);
+
+        try {
+            new Plain().copy();
+        } catch (CloneNotSupportedException e) {
+            tempPrint(e.getMessage());
+        }
+
+        synchronized (point) {
+            try {
+                // The copy does not share the monitor of the original
+                copy.notify();
+            } catch (IllegalMonitorStateException e) {
+                tempPrint("monitor not copied");
+            }
+        }
+    }
+
+    private static void hashCodes() {
+        Object object = new Object();
+        int hashCode = object.hashCode();
+        tempPrint(hashCode == System.identityHashCode(object) ? 1 : 0);
+        tempPrint(hashCode == object.hashCode() ? 1 : 0);
+        System.gc();
+        tempPrint(hashCode == object.hashCode() ? 1 : 0);
+
+        Plain plain = new Plain();
+        tempPrint(plain.hashCode() == System.identityHashCode(plain) ? 1 : 0);
+    }
+
+    static class Base {
+        int id;
+    }
+
+    static class Point extends Base implements Cloneable {
+        int x;
+        long y;
+        String label;
+        int[] values;
+
+        Point(int x, long y, String label, int[] values) {
+            this.x = x;
+            this.y = y;
+            this.label = label;
+            this.values = values;
+        }
+
+        Point copy() throws CloneNotSupportedException {
+            return (Point) clone();
+        }
+    }
+
+    static class Point3D extends Point {
+        int z;
+
+        Point3D(int x, long y, int z) {
+            super(x, y, null, null);
+            this.z = z;
+        }
+    }
+
+    static class Plain {
+        Object copy() throws CloneNotSupportedException {
+            return clone();
+        }
+    }
+
+    private static native void tempPrint(int value);
+
+    private static native void tempPrint(long value);
+
+    private static native void tempPrint(String value);
+}
@@ -0,0 +1,36 @@
+package rjvm;
+
+import java.io.IOException;
+import java.security.AccessController;
+import java.security.PrivilegedAction;
+import java.security.PrivilegedActionException;
+import java.security.PrivilegedExceptionAction;
+
+public class PrivilegedActions {
+    public static void main(String[] args) throws PrivilegedActionException {
+        tempPrint(AccessController.doPrivileged((PrivilegedAction<String>) () -> "action"));
+        tempPrint(AccessController.doPrivileged(
+                (PrivilegedExceptionAction<String>) () -> "exception action"));
+        tempPrint(AccessController.doPrivileged(
+                (PrivilegedExceptionAction<String>) () -> "with context",
+                AccessController.getContext()));
+
+        try {
+            AccessController.doPrivileged((PrivilegedExceptionAction<String>) () -> {
+                throw new IOException("checked");
+            });
+        } catch (PrivilegedActionException e) {
+            tempPrint(e.getException().getMessage());
+        }
+
+        try {
+            AccessController.doPrivileged((PrivilegedExceptionAction<String>) () -> {
+                throw new IllegalStateException("unchecked");
+            });
+        } catch (IllegalStateException e) {
+            tempPrint(e.getMessage());
+        }
+    }
+
+    private static native void tempPrint(String value);
+}
@@ -0,0 +1,251 @@
+package rjvm;
+
+import java.lang.reflect.Constructor;
+import java.lang.reflect.Field;
+import java.lang.reflect.InvocationTargetException;
+import java.lang.reflect.Method;
+import java.lang.reflect.Modifier;
+
+public class Reflection {
+    static boolean lazyInitialized = false;
+
+    public static void main(String[] args) throws Exception {
+        loadClasses();
+        listMembers();
+        accessFields();
+        invokeMethods();
+        createInstances();
+    }
+
+    private static void loadClasses() throws Exception {
+        Class<?> lazy = Class.forName("rjvm.Reflection$Lazy", false, Reflection.class.getClassLoader());
+        tempPrint(lazy.getName());
+        tempPrint(lazyInitialized ? 1 : 0);
+        Class.forName("rjvm.Reflection$Lazy");
+        tempPrint(lazyInitialized ? 1 : 0);
+
+        try {
+            Class.forName("rjvm.Missing");
+        } catch (ClassNotFoundException e) {
+            tempPrint(e.getMessage());
+        }
+    }
+
+    private static void listMembers() throws Exception {
+        Class<?> pointClass = Class.forName("rjvm.Reflection$Point");
+        for (Field field : pointClass.getDeclaredFields()) {
+            tempPrint(field.getName() + " " + field.getType().getName() + " " + field.getModifiers());
+        }
+
+        Method[] methods = pointClass.getDeclaredMethods();
+        tempPrint(methods.length);
+        int staticMethods = 0;
+        for (Method method : methods) {
+            if (Modifier.isStatic(method.getModifiers())) {
+                staticMethods++;
+            }
+        }
+        tempPrint(staticMethods);
+
+        Method sum = pointClass.getDeclaredMethod("sum", int.class);
+        tempPrint(sum.getName());
+        tempPrint(sum.getReturnType().getName());
+        tempPrint(sum.getParameterTypes()[0].getName());
+        tempPrint(sum.getExceptionTypes()[0].getName());
+
+        tempPrint(pointClass.getDeclaredConstructors().length);
+        tempPrint(Public.class.getDeclaredConstructors().length);
+    }
+
+    private static void accessFields() throws Exception {
+        Point point = new Point(1, 2L, "a");
+
+        Field x = Point.class.getDeclaredField("x");
+        x.setAccessible(true);
+        tempPrint(((Integer) x.get(point)).intValue());
+        x.set(point, 42);
+        tempPrint(point.x);
+        x.setInt(point, 43);
+        tempPrint(x.getInt(point));
+
+        Field y = Point.class.getDeclaredField("y");
+        tempPrint(((Long) y.get(point)).longValue());
+        y.setLong(point, 1L << 40);
+        tempPrint(point.y);
+
+        Field label = Point.class.getDeclaredField("label");
+        tempPrint((String) label.get(point));
+        label.set(point, "b");
+        tempPrint(point.label);
+        label.set(point, null);
+        tempPrint(point.label == null ? 1 : 0);
+
+        Field count = Point.class.getDeclaredField("count");
+        tempPrint(((Integer) count.get(null)).intValue());
+        count.set(null, 9);
+        tempPrint(Point.count);
+
+        Field ratio = Point.class.getDeclaredField("ratio");
+        ratio.setAccessible(true);
+        tempPrint(((Double) ratio.get(null)).doubleValue() == 0.5 ? 1 : 0);
+    }
+
+    private static void invokeMethods() throws Exception {
+        Point point = new Point(1, 2L, "a");
+
+        Method sum = Point.class.getDeclaredMethod("sum", int.class);
+        tempPrint(((Long) sum.invoke(point, 5)).longValue());
+
+        Method describe = Shape.class.getDeclaredMethod("describe");
+        tempPrint((String) describe.invoke(new Shape()));
+        tempPrint((String) describe.invoke(new Circle()));
+
+        Method twice = Reflection.class.getDeclaredMethod("twice", long.class);
+        tempPrint(((Long) twice.invoke(null, 21)).longValue());
+
+        Method isPositive = Reflection.class.getDeclaredMethod("isPositive", double.class);
+        tempPrint(((Boolean) isPositive.invoke(null, 2.5f)).booleanValue() ? 1 : 0);
+
+        Method initial = Reflection.class.getDeclaredMethod("initial", String.class);
+        tempPrint(((Character) initial.invoke(null, "rjvm")).charValue());
+
+        Method reset = Point.class.getDeclaredMethod("reset");
+        tempPrint(reset.invoke(point) == null ? 1 : 0);
+        tempPrint(point.x);
+
+        Method fail = Reflection.class.getDeclaredMethod("fail", String.class);
+        try {
+            fail.invoke(null, "boom");
+        } catch (InvocationTargetException e) {
+            tempPrint(e.getCause().getMessage());
+        }
+
+        try {
+            sum.invoke(point);
+        } catch (IllegalArgumentException e) {
+            tempPrint("wrong number of arguments");
+        }
+        try {
+            sum.invoke(point, "5");
+        } catch (IllegalArgumentException e) {
+            tempPrint("argument type mismatch");
+        }
+        try {
+            sum.invoke(new Shape(), 5);
+        } catch (IllegalArgumentException e) {
+            tempPrint("not an instance");
+        }
+        try {
+            sum.invoke(null, 5);
+        } catch (NullPointerException e) {
+            tempPrint("null receiver");
+        }
+    }
+
+    private static void createInstances() throws Exception {
+        Constructor<Point> constructor = Point.class.getDeclaredConstructor(int.class, long.class, String.class);
+        Point point = constructor.newInstance(3, 4L, "c");
+        tempPrint(point.x);
+        tempPrint(point.y);
+        tempPrint(point.label);
+
+        Constructor<Public> empty = Public.class.getDeclaredConstructor();
+        tempPrint(empty.newInstance().value);
+
+        Constructor<Public> throwing = Public.class.getDeclaredConstructor(String.class);
+        try {
+            throwing.newInstance("no");
+        } catch (InvocationTargetException e) {
+            tempPrint(e.getCause().getMessage());
+        }
+
+        try {
+            Abstract.class.getDeclaredConstructor().newInstance();
+        } catch (InstantiationException e) {
+            tempPrint("abstract");
+        }
+    }
+
+    static long twice(long value) {
+        return 2 * value;
+    }
+
+    static boolean isPositive(double value) {
+        return value > 0;
+    }
+
+    static char initial(String value) {
+        return value.charAt(0);
+    }
+
+    static void fail(String message) {
+        throw new IllegalStateException(message);
+    }
+
+    static class Lazy {
+        static {
+            lazyInitialized = true;
+        }
+    }
+
+    static class Point {
+        int x;
+        protected long y;
+        public String label;
+        static int count = 3;
+        private static final double ratio = Math.sqrt(0.25);
+
+        Point(int x, long y, String label) {
+            this.x = x;
+            this.y = y;
+            this.label = label;
+        }
+
+        private Point() {
+        }
+
+        long sum(int other) throws IllegalStateException {
+            return x + y + other;
+        }
+
+        void reset() {
+            x = 0;
+        }
+
+        static Point origin() {
+            return new Point();
+        }
+    }
+
+    public static class Public {
+        int value = 17;
+
+        public Public() {
+        }
+
+        public Public(String message) {
+            throw new IllegalArgumentException(message);
+        }
+    }
+
+    static class Shape {
+        String describe() {
+            return "shape";
+        }
+    }
+
+    static class Circle extends Shape {
+        String describe() {
+            return "circle";
+        }
+    }
+
+    abstract static class Abstract {
+    }
+
+    private static native void tempPrint(int value);
+
+    private static native void tempPrint(long value);
+
+    private static native void tempPrint(String value);
+}
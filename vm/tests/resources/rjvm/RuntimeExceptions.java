@@ -0,0 +1,81 @@
+package rjvm;
+
+public class RuntimeExceptions {
+    static class Point {
+        int x;
+    }
+
+    public static void main(String[] args) {
+        try {
+            Point point = null;
+            tempPrint(point.x);
+        } catch (NullPointerException e) {
+            tempPrint("npe on getfield, message " + e.getMessage());
+        }
+
+        try {
+            Object object = null;
+            object.hashCode();
+        } catch (NullPointerException e) {
+            tempPrint("npe on invoke");
+        }
+
+        int[] array = new int[3];
+        try {
+            array[5] = 1;
+        } catch (ArrayIndexOutOfBoundsException e) {
+            tempPrint(e.getMessage());
+        }
+        try {
+            tempPrint(array[-1]);
+        } catch (IndexOutOfBoundsException e) {
+            tempPrint(e.getMessage());
+        }
+        try {
+            System.arraycopy(array, 1, new int[5], 0, 3);
+        } catch (ArrayIndexOutOfBoundsException e) {
+            tempPrint("arraycopy " + e.getMessage());
+        }
+
+        try {
+            Object string = "a string";
+            Integer integer = (Integer) string;
+            tempPrint(integer.intValue());
+        } catch (ClassCastException e) {
+            tempPrint(e.getMessage());
+        }
+        Object nothing = (Integer) null;
+
+        try {
+            tempPrint(new int[-2].length);
+        } catch (NegativeArraySizeException e) {
+            tempPrint(e.getMessage());
+        }
+
+        try {
+            divide(1, 0);
+        } catch (RuntimeException e) {
+            tempPrint(e.getMessage());
+            for (StackTraceElement element : e.getStackTrace()) {
+                if (element.getClassName().equals("rjvm/RuntimeExceptions")) {
+                    tempPrint(element.getMethodName() + ":" + element.getLineNumber());
+                }
+            }
+        }
+
+        try {
+            divide(1, 0);
+        } finally {
+            tempPrint("finally");
+        }
+    }
+
+    private static int divide(int a, int b) {
+        int result = a / b;
+        return result;
+    }
+
+    private static native void tempPrint(int value);
+
+    private static native void tempPrint(String value);
+}
@@ -0,0 +1,17 @@
+package rjvm;
+
+public class SmallConstants {
+    public static void main(String[] args) {
+        // Pushed with bipush, whose operand is a signed byte
+        tempPrint(-2);
+        tempPrint(-128);
+        tempPrint(127);
+        byte[] bytes = new byte[] {-7};
+        tempPrint(bytes[0] + 10);
+        // Pushed with sipush
+        tempPrint(-129);
+        tempPrint(-32768);
+    }
+
+    private static native void tempPrint(int value);
+}
@@ -0,0 +1,39 @@
+package rjvm;
+
+public class StackOverflow {
+    private static int depth = 0;
+
+    public static void main(String[] args) {
+        tempPrint(sum(5000));
+
+        try {
+            recurse();
+        } catch (StackOverflowError e) {
+            tempPrint(depth);
+        }
+
+        // The stack can be used again after the overflow
+        depth = 0;
+        try {
+            recurse();
+        } catch (StackOverflowError e) {
+            tempPrint(depth);
+        }
+        tempPrint(sum(5000));
+    }
+
+    public static void overflow() {
+        recurse();
+    }
+
+    private static int sum(int n) {
+        return n == 0 ? 0 : n + sum(n - 1);
+    }
+
+    private static void recurse() {
+        depth++;
+        recurse();
+    }
+
+    private static native void tempPrint(int value);
+}
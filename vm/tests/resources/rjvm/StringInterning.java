@@ -0,0 +1,43 @@
+package rjvm;
+
+public class StringInterning {
+    private static final String CONSTANT = "hello";
+
+    public static void main(String[] args) {
+        tempPrint("hello" == literal());
+        tempPrint(CONSTANT == literal());
+
+        String first = null;
+        for (int i = 0; i < 3; ++i) {
+            String current = literal();
+            if (first == null) {
+                first = current;
+            }
+            tempPrint(first == current);
+        }
+
+        String built = new String(new char[]{'h', 'e', 'l', 'l', 'o'});
+        tempPrint(built == "hello");
+        tempPrint(built.intern() == "hello");
+
+        // The first instance interned becomes the canonical one
+        String fresh = new String(new char[]{'f', 'r', 'e', 's', 'h'});
+        tempPrint(fresh.intern() == fresh);
+
+        System.gc();
+        tempPrint(literal() == "hello");
+        tempPrint(new String(new char[]{'f', 'r', 'e', 's', 'h'}).intern() == "fresh");
+
+        // Java strings can contain unpaired surrogates
+        String loneSurrogate = new String(new char[]{(char) 0xD800});
+        tempPrint(loneSurrogate.intern() == loneSurrogate);
+        tempPrint(new String(new char[]{(char) 0xD800}).intern() == loneSurrogate);
+        tempPrint(new String(new char[]{(char) 0xDC00}).intern() == loneSurrogate);
+    }
+
+    private static String literal() {
+        return "hello";
+    }
+
+    private static native void tempPrint(boolean value);
+}
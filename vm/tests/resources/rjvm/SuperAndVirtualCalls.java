@@ -0,0 +1,52 @@
+package rjvm;
+
+public class SuperAndVirtualCalls {
+    static class Base {
+        int value() {
+            return 1;
+        }
+    }
+
+    // super.value() and base.value() use the same method reference to Base.value
+    static class SuperCalledFirst extends Base {
+        int value() {
+            return 10 + super.value();
+        }
+
+        int valueOf(Base base) {
+            return base.value();
+        }
+    }
+
+    static class SuperCalledFirstLeaf extends SuperCalledFirst {
+        int value() {
+            return 100 + super.value();
+        }
+    }
+
+    static class VirtualCalledFirst extends Base {
+        int value() {
+            return 10 + super.value();
+        }
+
+        int valueOf(Base base) {
+            return base.value();
+        }
+    }
+
+    static class VirtualCalledFirstLeaf extends VirtualCalledFirst {
+        int value() {
+            return 1000 + super.value();
+        }
+    }
+
+    public static void main(String[] args) {
+        tempPrint(new SuperCalledFirstLeaf().value());
+        tempPrint(new SuperCalledFirst().valueOf(new SuperCalledFirstLeaf()));
+
+        tempPrint(new VirtualCalledFirst().valueOf(new VirtualCalledFirstLeaf()));
+        tempPrint(new VirtualCalledFirstLeaf().value());
+    }
+
+    private static native void tempPrint(int value);
+}
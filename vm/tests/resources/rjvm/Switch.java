@@ -0,0 +1,76 @@
+package rjvm;
+
+public class Switch {
+    public static void main(String[] args) {
+        for (int i = -1; i <= 4; ++i) {
+            tempPrint(denseSwitch(i));
+        }
+        tempPrint(sparseSwitch(-1000));
+        tempPrint(sparseSwitch(7));
+        tempPrint(sparseSwitch(1000000));
+        tempPrint(sparseSwitch(3));
+        tempPrint(enumSwitch(Color.RED));
+        tempPrint(enumSwitch(Color.GREEN));
+        tempPrint(enumSwitch(Color.BLUE));
+        tempPrint(stringSwitch("one"));
+        tempPrint(stringSwitch("two"));
+        tempPrint(stringSwitch("three"));
+    }
+
+    private static int denseSwitch(int value) {
+        switch (value) {
+            case 0:
+                return 10;
+            case 1:
+                return 11;
+            case 2:
+            case 3:
+                return 12;
+            default:
+                return 19;
+        }
+    }
+
+    private static int sparseSwitch(int value) {
+        switch (value) {
+            case -1000:
+                return 20;
+            case 7:
+                return 21;
+            case 1000000:
+                return 22;
+            default:
+                return 29;
+        }
+    }
+
+    enum Color {
+        RED,
+        GREEN,
+        BLUE
+    }
+
+    private static int enumSwitch(Color color) {
+        switch (color) {
+            case RED:
+                return 30;
+            case GREEN:
+                return 31;
+            default:
+                return 39;
+        }
+    }
+
+    private static int stringSwitch(String string) {
+        switch (string) {
+            case "one":
+                return 41;
+            case "two":
+                return 42;
+            default:
+                return 49;
+        }
+    }
+
+    private static native void tempPrint(int value);
+}
@@ -0,0 +1,78 @@
+package rjvm;
+
+public class Synchronization {
+    private static int staticCounter = 0;
+
+    private final Object lock = new Object();
+    private int counter = 0;
+
+    public static void main(String[] args) {
+        Synchronization synchronization = new Synchronization();
+        tempPrint(synchronization.recursive(3));
+        tempPrint(synchronization.nestedBlocks());
+        tempPrint(incrementStatic());
+        tempPrint(incrementStatic());
+
+        try {
+            synchronization.throwInsideBlock();
+        } catch (IllegalStateException e) {
+            tempPrint(e.getMessage());
+        }
+        // The monitors were released by the exception, and can be entered again
+        tempPrint(synchronization.nestedBlocks());
+
+        try {
+            synchronization.throwFromSynchronizedMethod();
+        } catch (IllegalStateException e) {
+            tempPrint(e.getMessage());
+        }
+        tempPrint(synchronization.recursive(1));
+
+        Object nothing = null;
+        try {
+            synchronized (nothing) {
+                tempPrint("unreachable");
+            }
+        } catch (NullPointerException e) {
+            tempPrint("cannot synchronize on null");
+        }
+    }
+
+    private synchronized int recursive(int depth) {
+        counter++;
+        if (depth > 1) {
+            return recursive(depth - 1);
+        }
+        return counter;
+    }
+
+    private int nestedBlocks() {
+        synchronized (lock) {
+            synchronized (this) {
+                synchronized (lock) {
+                    return ++counter;
+                }
+            }
+        }
+    }
+
+    private static synchronized int incrementStatic() {
+        return ++staticCounter;
+    }
+
+    private void throwInsideBlock() {
+        synchronized (lock) {
+            synchronized (this) {
+                throw new IllegalStateException("thrown inside block");
+            }
+        }
+    }
+
+    private synchronized void throwFromSynchronizedMethod() {
+        throw new IllegalStateException("thrown from synchronized method");
+    }
+
+    private static native void tempPrint(int value);
+
+    private static native void tempPrint(String value);
+}
@@ -0,0 +1,32 @@
+package rjvm;
+
+public class SynchronizedNatives {
+    private static volatile boolean started = false;
+    private static Thread blocked;
+
+    public static void main(String[] args) throws InterruptedException {
+        Thread owner = new Thread(SynchronizedNatives::collectGarbageWhileOwningMonitor);
+        owner.start();
+        owner.join();
+        blocked.join();
+    }
+
+    private static void collectGarbageWhileOwningMonitor() {
+        synchronized (SynchronizedNatives.class) {
+            // The other thread blocks on the class monitor with a string that is
+            // referenced only by the arguments of the native call
+            blocked = new Thread(() -> {
+                started = true;
+                tempPrint(new StringBuilder("from ").append("blocked thread").toString());
+            });
+            blocked.start();
+            while (!started) {
+                Thread.yield();
+            }
+            Thread.yield();
+            System.gc();
+        }
+    }
+
+    private static synchronized native void tempPrint(String value);
+}
@@ -0,0 +1,35 @@
+package rjvm;
+
+import java.io.IOException;
+
+public class SystemOut {
+    public static void main(String[] args) throws IOException {
+        System.out.println("Hello, world!");
+        System.out.println(42);
+        System.out.print('x');
+        System.out.println();
+        System.err.println("to stderr");
+
+        byte[] buffer = new byte[4];
+        int read;
+        while ((read = System.in.read(buffer)) != -1) {
+            System.out.write(buffer, 0, read);
+        }
+
+        tempPrint(System.in != null ? 1 : 0);
+        tempPrint(System.out != null ? 1 : 0);
+        tempPrint(System.err != null ? 1 : 0);
+        tempPrint(System.out.checkError() ? 1 : 0);
+
+        tempPrint(System.lineSeparator().length());
+        tempPrint(System.lineSeparator() == System.getProperty("line.separator") ? 1 : 0);
+        tempPrint(System.getProperty("file.encoding"));
+        tempPrint(System.getProperty("java.specification.version"));
+        tempPrint(System.getProperty("no.such.property", "default"));
+        tempPrint(System.getProperty("user.dir") != null ? 1 : 0);
+    }
+
+    private static native void tempPrint(int value);
+
+    private static native void tempPrint(String value);
+}
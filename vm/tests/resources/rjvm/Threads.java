@@ -0,0 +1,157 @@
+package rjvm;
+
+public class Threads {
+    private static final Object lock = new Object();
+    private static int counter = 0;
+
+    private static volatile boolean spinnerStarted = false;
+    private static volatile boolean spinnerStopped = false;
+
+    public static void main(String[] args) throws InterruptedException {
+        Thread main = Thread.currentThread();
+        tempPrint(main.getName());
+        tempPrint(main.isAlive());
+        tempPrint(main == Thread.currentThread());
+
+        incrementConcurrently();
+        sleepInAnotherThread();
+        runThreadSubclass();
+        joinWithTimeout();
+        preemptBusyThreads();
+        dispatchUncaughtException();
+        checkInvalidUsages();
+    }
+
+    private static void incrementConcurrently() throws InterruptedException {
+        Thread[] threads = new Thread[3];
+        for (int i = 0; i < threads.length; ++i) {
+            threads[i] = new Thread(() -> {
+                for (int j = 0; j < 1000; ++j) {
+                    synchronized (lock) {
+                        ++counter;
+                    }
+                    if (j % 100 == 0) {
+                        Thread.yield();
+                    }
+                }
+            });
+        }
+        tempPrint(threads[0].isAlive());
+        for (Thread thread : threads) {
+            thread.start();
+        }
+        for (Thread thread : threads) {
+            thread.join();
+        }
+        tempPrint(counter);
+        tempPrint(threads[0].isAlive());
+    }
+
+    private static void sleepInAnotherThread() throws InterruptedException {
+        long start = System.nanoTime();
+        Thread sleeper = new Thread(() -> {
+            try {
+                Thread.sleep(20);
+            } catch (InterruptedException e) {
+                throw new RuntimeException(e);
+            }
+        });
+        sleeper.start();
+        tempPrint(sleeper.isAlive());
+        sleeper.join();
+        tempPrint(System.nanoTime() - start >= 20_000_000);
+    }
+
+    private static class Worker extends Thread {
+        private String observedName;
+        private boolean observedItself;
+
+        Worker(String name) {
+            super(name);
+        }
+
+        @Override
+        public void run() {
+            observedName = Thread.currentThread().getName();
+            observedItself = Thread.currentThread() == this;
+        }
+    }
+
+    private static void runThreadSubclass() throws InterruptedException {
+        Worker worker = new Worker("worker");
+        worker.start();
+        worker.join();
+        tempPrint(worker.observedName);
+        tempPrint(worker.observedItself);
+    }
+
+    private static void joinWithTimeout() throws InterruptedException {
+        Thread sleeper = new Thread(() -> {
+            try {
+                Thread.sleep(200);
+            } catch (InterruptedException e) {
+                throw new RuntimeException(e);
+            }
+        });
+        sleeper.start();
+        sleeper.join(10);
+        tempPrint(sleeper.isAlive());
+        sleeper.join();
+        tempPrint(sleeper.isAlive());
+    }
+
+    // Neither thread blocks nor yields, so they progress only if preempted
+    private static void preemptBusyThreads() throws InterruptedException {
+        Thread spinner = new Thread(() -> {
+            spinnerStarted = true;
+            while (!spinnerStopped) {
+            }
+        });
+        spinner.start();
+        while (!spinnerStarted) {
+        }
+        spinnerStopped = true;
+        spinner.join();
+        tempPrint(spinner.isAlive());
+    }
+
+    private static void dispatchUncaughtException() throws InterruptedException {
+        ThreadGroup group = new ThreadGroup(Thread.currentThread().getThreadGroup(), "reporting") {
+            @Override
+            public void uncaughtException(Thread thread, Throwable throwable) {
+                tempPrint(thread.getName());
+                tempPrint(throwable.getMessage());
+            }
+        };
+        Thread failing = new Thread(group, () -> {
+            throw new IllegalStateException("uncaught");
+        }, "failing");
+        failing.start();
+        failing.join();
+        tempPrint(failing.isAlive());
+    }
+
+    private static void checkInvalidUsages() throws InterruptedException {
+        try {
+            Thread.sleep(-1);
+        } catch (IllegalArgumentException e) {
+            tempPrint(e.getMessage());
+        }
+
+        Thread thread = new Thread(() -> {
+        });
+        thread.start();
+        thread.join();
+        try {
+            thread.start();
+        } catch (IllegalThreadStateException e) {
+            tempPrint("cannot start twice");
+        }
+    }
+
+    private static native void tempPrint(String value);
+
+    private static native void tempPrint(int value);
+
+    private static native void tempPrint(boolean value);
+}
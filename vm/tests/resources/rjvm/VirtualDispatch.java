@@ -0,0 +1,117 @@
+package rjvm;
+
+public class VirtualDispatch {
+    interface Shape {
+        int area();
+
+        default int sides() {
+            return 0;
+        }
+    }
+
+    interface Named {
+        String name();
+    }
+
+    abstract static class Polygon implements Shape, Named {
+        private static int count;
+
+        Polygon() {
+            ++count;
+        }
+
+        int count() {
+            return count;
+        }
+    }
+
+    static class Square extends Polygon {
+        private final int side;
+
+        Square(int side) {
+            this.side = side;
+        }
+
+        public int area() {
+            return side * side;
+        }
+
+        public int sides() {
+            return 4;
+        }
+
+        public String name() {
+            return "square";
+        }
+    }
+
+    static class Triangle extends Polygon {
+        private final int base;
+        private final int height;
+
+        Triangle(int base, int height) {
+            this.base = base;
+            this.height = height;
+        }
+
+        public int area() {
+            return base * height / 2;
+        }
+
+        public int sides() {
+            return 3;
+        }
+
+        public String name() {
+            return "triangle";
+        }
+    }
+
+    static class Circle implements Shape, Named {
+        private final int radius;
+
+        Circle(int radius) {
+            this.radius = radius;
+        }
+
+        public int area() {
+            return 3 * radius * radius;
+        }
+
+        public String name() {
+            return "circle";
+        }
+    }
+
+    static class ColoredSquare extends Square {
+        ColoredSquare(int side) {
+            super(side);
+        }
+
+        public String name() {
+            return "colored " + super.name();
+        }
+    }
+
+    public static void main(String[] args) {
+        Shape[] shapes = {new Square(2), new Triangle(4, 3), new Circle(1), new ColoredSquare(3)};
+        // The call sites see several classes, and each round uses the cached methods
+        int total = 0;
+        for (int round = 0; round < 3; ++round) {
+            for (Shape shape : shapes) {
+                total += shape.area() * 10 + shape.sides();
+            }
+        }
+        tempPrint(total);
+
+        Named[] names = {(Named) shapes[0], (Named) shapes[2], (Named) shapes[3]};
+        for (Named named : names) {
+            tempPrint(named.name());
+        }
+        tempPrint(((Polygon) shapes[0]).count());
+    }
+
+    private static native void tempPrint(int value);
+
+    private static native void tempPrint(String value);
+}
@@ -0,0 +1,205 @@
+package rjvm;
+
+public class WaitNotify {
+    private static class BoundedBuffer {
+        private final int[] items = new int[2];
+        private int count = 0;
+        private int putIndex = 0;
+        private int takeIndex = 0;
+
+        synchronized void put(int item) throws InterruptedException {
+            while (count == items.length) {
+                wait();
+            }
+            items[putIndex] = item;
+            putIndex = (putIndex + 1) % items.length;
+            ++count;
+            notifyAll();
+        }
+
+        synchronized int take() throws InterruptedException {
+            while (count == 0) {
+                wait();
+            }
+            int item = items[takeIndex];
+            takeIndex = (takeIndex + 1) % items.length;
+            --count;
+            notifyAll();
+            return item;
+        }
+    }
+
+    private static final Object lock = new Object();
+    private static int ready = 0;
+    private static int woken = 0;
+    private static boolean interruptedWhileWaiting = false;
+
+    public static void main(String[] args) throws InterruptedException {
+        produceAndConsume();
+        notifyWakesUpOneThread();
+        timedWaitExpires();
+        interruptSleepingThread();
+        interruptWaitingThread();
+        interruptStatus();
+        checkInvalidUsages();
+    }
+
+    private static void produceAndConsume() throws InterruptedException {
+        BoundedBuffer buffer = new BoundedBuffer();
+        Thread producer = new Thread(() -> {
+            try {
+                for (int i = 1; i <= 100; ++i) {
+                    buffer.put(i);
+                }
+            } catch (InterruptedException e) {
+                throw new RuntimeException(e);
+            }
+        });
+        producer.start();
+        int sum = 0;
+        for (int i = 0; i < 100; ++i) {
+            sum += buffer.take();
+        }
+        producer.join();
+        tempPrint(sum);
+    }
+
+    private static void notifyWakesUpOneThread() throws InterruptedException {
+        Thread[] waiters = new Thread[3];
+        for (int i = 0; i < waiters.length; ++i) {
+            waiters[i] = new Thread(() -> {
+                synchronized (lock) {
+                    ++ready;
+                    try {
+                        lock.wait();
+                    } catch (InterruptedException e) {
+                        throw new RuntimeException(e);
+                    }
+                    ++woken;
+                }
+            });
+            waiters[i].start();
+        }
+        waitUntilReady(waiters.length);
+
+        synchronized (lock) {
+            lock.notify();
+        }
+        Thread.sleep(50);
+        synchronized (lock) {
+            tempPrint(woken);
+            lock.notifyAll();
+        }
+        for (Thread waiter : waiters) {
+            waiter.join();
+        }
+        tempPrint(woken);
+    }
+
+    private static void timedWaitExpires() throws InterruptedException {
+        synchronized (lock) {
+            long start = System.nanoTime();
+            lock.wait(20);
+            tempPrint(System.nanoTime() - start >= 20_000_000);
+        }
+    }
+
+    private static void interruptSleepingThread() throws InterruptedException {
+        Thread sleeper = new Thread(() -> {
+            try {
+                Thread.sleep(10_000);
+                tempPrint("not interrupted");
+            } catch (InterruptedException e) {
+                tempPrint(e.getMessage());
+                tempPrint(Thread.currentThread().isInterrupted());
+            }
+        });
+        sleeper.start();
+        Thread.sleep(10);
+        sleeper.interrupt();
+        sleeper.join();
+    }
+
+    // The interrupted thread must enter the monitor again before throwing
+    private static void interruptWaitingThread() throws InterruptedException {
+        ready = 0;
+        Thread waiter = new Thread(() -> {
+            synchronized (lock) {
+                ++ready;
+                try {
+                    lock.wait();
+                } catch (InterruptedException e) {
+                    interruptedWhileWaiting = e.getMessage() == null;
+                }
+            }
+        });
+        waiter.start();
+        waitUntilReady(1);
+
+        synchronized (lock) {
+            waiter.interrupt();
+            Thread.sleep(20);
+            tempPrint(interruptedWhileWaiting);
+        }
+        waiter.join();
+        tempPrint(interruptedWhileWaiting);
+    }
+
+    private static void interruptStatus() {
+        Thread.currentThread().interrupt();
+        tempPrint(Thread.currentThread().isInterrupted());
+        tempPrint(Thread.interrupted());
+        tempPrint(Thread.interrupted());
+
+        Thread.currentThread().interrupt();
+        try {
+            Thread.sleep(10_000);
+        } catch (InterruptedException e) {
+            tempPrint(e.getMessage());
+        }
+        tempPrint(Thread.currentThread().isInterrupted());
+    }
+
+    private static void checkInvalidUsages() throws InterruptedException {
+        Object object = new Object();
+        try {
+            object.wait();
+        } catch (IllegalMonitorStateException e) {
+            tempPrint("wait requires the monitor");
+        }
+        try {
+            object.notify();
+        } catch (IllegalMonitorStateException e) {
+            tempPrint("notify requires the monitor");
+        }
+        try {
+            object.notifyAll();
+        } catch (IllegalMonitorStateException e) {
+            tempPrint("notifyAll requires the monitor");
+        }
+        synchronized (object) {
+            try {
+                object.wait(-1);
+            } catch (IllegalArgumentException e) {
+                tempPrint(e.getMessage());
+            }
+        }
+    }
+
+    private static void waitUntilReady(int count) throws InterruptedException {
+        while (true) {
+            synchronized (lock) {
+                if (ready == count) {
+                    return;
+                }
+            }
+            Thread.sleep(1);
+        }
+    }
+
+    private static native void tempPrint(String value);
+
+    private static native void tempPrint(int value);
+
+    private static native void tempPrint(boolean value);
+}
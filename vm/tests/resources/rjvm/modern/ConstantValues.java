@@ -0,0 +1,40 @@
+package rjvm.modern;
+
+public class ConstantValues {
+    public static void main(String[] args) {
+        tempPrint(EvolvingConstants.INT);
+        tempPrint(EvolvingConstants.LONG);
+        tempPrint(EvolvingConstants.FLOAT);
+        tempPrint(EvolvingConstants.DOUBLE);
+        tempPrint(EvolvingConstants.BOOLEAN);
+        tempPrint(EvolvingConstants.CHAR);
+        tempPrint(EvolvingConstants.STRING);
+        tempPrint(EvolvingConstants.STRING == EvolvingConstants.OTHER_STRING);
+    }
+
+    private static native void tempPrint(int value);
+
+    private static native void tempPrint(long value);
+
+    private static native void tempPrint(float value);
+
+    private static native void tempPrint(double value);
+
+    private static native void tempPrint(boolean value);
+
+    private static native void tempPrint(char value);
+
+    private static native void tempPrint(String value);
+}
+
+// Not constants here, so that the fields are read with getstatic rather than inlined
+class EvolvingConstants {
+    static int INT;
+    static long LONG;
+    static float FLOAT;
+    static double DOUBLE;
+    static boolean BOOLEAN;
+    static char CHAR;
+    static String STRING;
+    static String OTHER_STRING;
+}
@@ -0,0 +1,142 @@
+package rjvm.modern;
+
+public class InterfaceMethods {
+    interface Greeter {
+        default String greet() {
+            return "hello from " + name();
+        }
+
+        private String name() {
+            return "greeter";
+        }
+
+        static String shout(String message) {
+            return message + "!";
+        }
+    }
+
+    interface PoliteGreeter extends Greeter {
+        default String greet() {
+            return "good morning";
+        }
+    }
+
+    interface CasualGreeter extends Greeter {
+    }
+
+    // PoliteGreeter.greet is more specific than Greeter.greet, whatever the order
+    static class Diamond implements PoliteGreeter, CasualGreeter {
+    }
+
+    static class ReversedDiamond implements CasualGreeter, PoliteGreeter {
+    }
+
+    static class Overriding implements PoliteGreeter {
+        public String greet() {
+            return "hi, and " + PoliteGreeter.super.greet();
+        }
+    }
+
+    static class Plain implements Greeter {
+    }
+
+    // The subclass implements a more specific interface than its superclass
+    static class MorePolite extends Plain implements PoliteGreeter {
+    }
+
+    abstract static class AbstractCasual implements CasualGreeter {
+    }
+
+    static class Concrete extends AbstractCasual {
+    }
+
+    static class Base {
+        static String describe() {
+            return "static of base";
+        }
+
+        String who() {
+            return "base";
+        }
+    }
+
+    static class Middle extends Base {
+    }
+
+    static class Leaf extends Middle {
+        String who() {
+            return "leaf of " + super.who();
+        }
+    }
+
+    public static void main(String[] args) {
+        Greeter[] greeters = {
+            new Diamond(), new ReversedDiamond(), new Overriding(), new Plain(), new MorePolite(), new Concrete()
+        };
+        for (Greeter greeter : greeters) {
+            tempPrint(greeter.greet());
+        }
+        tempPrint(Greeter.shout("hey"));
+        tempPrint(new Leaf().who());
+        tempPrint(Middle.describe());
+
+        // The following types are changed by the sources in the evolved directory after
+        // this class is compiled
+        try {
+            tempPrint(new EvolvingBoth().greet());
+        } catch (IncompatibleClassChangeError e) {
+            tempPrint(e.getMessage());
+        }
+        try {
+            EvolvingShape shape = new EvolvingSquare();
+            tempPrint(shape.area());
+        } catch (AbstractMethodError e) {
+            tempPrint(e.getMessage());
+        }
+        try {
+            EvolvingShape shape = new EvolvingCircle();
+            tempPrint(shape.area());
+        } catch (IncompatibleClassChangeError e) {
+            tempPrint(e.getMessage());
+        }
+        try {
+            tempPrint(EvolvingUtility.value());
+        } catch (IncompatibleClassChangeError e) {
+            tempPrint(e.getMessage());
+        }
+    }
+
+    private static native void tempPrint(int value);
+
+    private static native void tempPrint(String value);
+}
+
+interface EvolvingLeft {
+}
+
+interface EvolvingRight {
+    default String greet() {
+        return "right";
+    }
+}
+
+class EvolvingBoth implements EvolvingLeft, EvolvingRight {
+}
+
+interface EvolvingShape {
+    default int area() {
+        return 1;
+    }
+}
+
+class EvolvingSquare implements EvolvingShape {
+}
+
+class EvolvingCircle implements EvolvingShape {
+}
+
+class EvolvingUtility {
+    static int value() {
+        return 42;
+    }
+}
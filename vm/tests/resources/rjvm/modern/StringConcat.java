@@ -0,0 +1,47 @@
+package rjvm.modern;
+
+public class StringConcat {
+    static class Point {
+        private final int x;
+        private final int y;
+
+        Point(int x, int y) {
+            this.x = x;
+            this.y = y;
+        }
+
+        @Override
+        public String toString() {
+            return "(" + x + ", " + y + ")";
+        }
+    }
+
+    private static final String CONSTANT = "constant";
+
+    public static void main(String[] args) {
+        String name = "rjvm";
+        int anInt = -42;
+        long aLong = 12345678901L;
+        char aChar = 'c';
+        boolean aBoolean = true;
+        byte aByte = 3;
+        short aShort = -7;
+        Object aNull = null;
+        Point point = new Point(1, 2);
+
+        tempPrint("Hello, " + name + "!");
+        tempPrint(anInt + " " + aLong + " " + aChar + " " + aBoolean + " " + aByte + " " + aShort);
+        tempPrint("null is " + aNull + ", point is " + point);
+        tempPrint(CONSTANT + ":" + anInt);
+        tempPrint("\u0001 and \u0002 are not tags in literals: " + name);
+        tempPrint("" + aChar + aChar);
+
+        String counting = "";
+        for (int i = 0; i < 5; ++i) {
+            counting = counting + i;
+        }
+        tempPrint(counting);
+    }
+
+    private static native void tempPrint(Object value);
+}
@@ -0,0 +1,5 @@
+package rjvm.modern;
+
+// Not a shape anymore
+class EvolvingCircle {
+}
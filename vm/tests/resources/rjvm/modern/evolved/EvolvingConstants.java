@@ -0,0 +1,13 @@
+package rjvm.modern;
+
+// The fields became constants, initialized by their ConstantValue attribute
+class EvolvingConstants {
+    static final int INT = 42;
+    static final long LONG = 1L << 40;
+    static final float FLOAT = 1.5f;
+    static final double DOUBLE = 2.25;
+    static final boolean BOOLEAN = true;
+    static final char CHAR = 'x';
+    static final String STRING = "constant";
+    static final String OTHER_STRING = "constant";
+}
@@ -0,0 +1,8 @@
+package rjvm.modern;
+
+// Conflicts with the default method of EvolvingRight in EvolvingBoth
+interface EvolvingLeft {
+    default String greet() {
+        return "left";
+    }
+}
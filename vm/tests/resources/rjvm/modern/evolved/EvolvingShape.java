@@ -0,0 +1,6 @@
+package rjvm.modern;
+
+// EvolvingSquare does not implement the method anymore
+interface EvolvingShape {
+    int area();
+}
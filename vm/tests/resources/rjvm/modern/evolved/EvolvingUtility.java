@@ -0,0 +1,8 @@
+package rjvm.modern;
+
+// The static method became an instance one
+class EvolvingUtility {
+    int value() {
+        return 42;
+    }
+}
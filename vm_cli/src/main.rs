@@ -7,7 +7,7 @@ use rjvm_vm::{
     exceptions::MethodCallFailed,
     java_objects_creation::new_java_lang_string_object,
     value::Value,
-    vm::{Vm, DEFAULT_MAX_MEMORY_MB_STR, ONE_MEGABYTE},
+    vm::{Vm, DEFAULT_MAX_MEMORY_MB_STR, DEFAULT_MAX_STACK_DEPTH_STR, ONE_MEGABYTE},
     vm_error::VmError,
 };
 
@@ -22,6 +22,10 @@ struct Args {
     #[arg(short, long, default_value = DEFAULT_MAX_MEMORY_MB_STR)]
     maximum_mb_of_memory: usize,
 
+    /// Maximum number of nested Java method calls, like -Xss of the JVM
+    #[arg(short = 's', long, default_value = DEFAULT_MAX_STACK_DEPTH_STR)]
+    maximum_stack_depth: usize,
+
     java_program_arguments: Vec<String>,
 }
 
@@ -74,16 +78,23 @@ fn resolve_class_and_main_method<'a>(
 }
 
 fn run(args: Args) -> Result<i32, String> {
-    let mut vm = Vm::new(args.maximum_mb_of_memory * ONE_MEGABYTE);
+    let mut vm = Vm::with_maximum_stack_depth(
+        args.maximum_mb_of_memory * ONE_MEGABYTE,
+        args.maximum_stack_depth,
+    );
     append_classpath(&mut vm, &args)?;
 
     let (call_stack, main_method) = resolve_class_and_main_method(&mut vm, &args)?;
+    vm.initialize_system_class(call_stack)
+        .map_err(|err| format!("cannot initialize the system class: {err:?}"))?;
 
     let main_args = allocate_java_args(&mut vm, call_stack, &args.java_program_arguments)
         .map_err(|err| format!("{err:?}"))?;
     let main_result = vm
         .invoke(call_stack, main_method, None, vec![main_args])
         .map_err(|v| format!("execution error: {:?}", v))?;
+    vm.wait_for_non_daemon_threads(call_stack)
+        .map_err(|err| format!("execution error: {err:?}"))?;
 
     match main_result {
         None => Ok(0),
@@ -98,7 +109,7 @@ fn allocate_java_args<'a>(
     call_stack: &mut CallStack<'a>,
     command_line_args: &[String],
 ) -> Result<Value<'a>, MethodCallFailed<'a>> {
-    let class_id_java_lang_string = vm.get_or_resolve_class(call_stack, "java/lang/String")?.id;
+    let class_id_java_lang_string = vm.get_or_resolve_class("java/lang/String")?.id;
 
     let strings: Result<Vec<Value<'a>>, MethodCallFailed<'a>> = command_line_args
         .iter()